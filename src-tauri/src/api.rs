@@ -1,35 +1,249 @@
 mod claude;
+mod client;
 mod codex;
 mod ollama;
 
+#[cfg(test)]
+pub use client::MockUsageClient;
+pub use client::{ClientFuture, HttpUsageClient, UsageClient, UsageFuture};
+
 use crate::error::AppError;
-use crate::types::{ProviderKind, ProviderStatus, UsageSnapshot};
+use crate::types::{MemberUsage, Organization, ProviderKind, ProviderStatus, UsageSnapshot};
+use reqwest::header::{HeaderMap, HeaderValue};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+const APP_USER_AGENT: &str = "Claude-Monitor/0.1.0";
+const BROWSER_USER_AGENT: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/131.0.0.0 Safari/537.36";
+
+/// User-Agent sent with provider requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum UserAgentMode {
+    /// Identify as Claude Monitor.
+    #[default]
+    App,
+    /// A standard desktop browser string, for networks that block unknown clients.
+    Browser,
+}
+
+impl UserAgentMode {
+    fn header(self) -> HeaderValue {
+        match self {
+            Self::App => HeaderValue::from_static(APP_USER_AGENT),
+            Self::Browser => HeaderValue::from_static(BROWSER_USER_AGENT),
+        }
+    }
+}
+
+static BROWSER_UA: AtomicBool = AtomicBool::new(false);
+
+/// A session key the server rotated to, with the cookie's expiry when it had one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RotatedSessionKey {
+    pub session_key: crate::secret::SecretString,
+    /// RFC3339, from the cookie's `Max-Age` or `Expires`.
+    pub expires_at: Option<String>,
+}
+
+/// Apply a User-Agent preference. Called at startup and when preferences change.
+pub fn set_user_agent_mode(mode: UserAgentMode) {
+    BROWSER_UA.store(mode == UserAgentMode::Browser, Ordering::Relaxed);
+}
+
+pub(crate) fn user_agent() -> HeaderValue {
+    if BROWSER_UA.load(Ordering::Relaxed) {
+        UserAgentMode::Browser.header()
+    } else {
+        UserAgentMode::App.header()
+    }
+}
+
+/// HTTP client for provider requests, honouring the configured proxy.
+pub(crate) fn http_client() -> Result<reqwest::Client, AppError> {
+    let mut builder = reqwest::Client::builder();
+    if let Some(proxy) = &crate::config::get().proxy {
+        let proxy = reqwest::Proxy::all(proxy)
+            .map_err(|e| AppError::MissingConfig(format!("invalid proxy URL: {e}")))?;
+        builder = builder.proxy(proxy);
+    }
+    Ok(builder.build()?)
+}
+
+const SNIPPET_MAX_CHARS: usize = 200;
+
+/// Short single-line excerpt of a response body with secrets masked, so
+/// `Server` errors and logs show what came back without leaking credentials.
+pub(crate) fn response_snippet(body: &str) -> String {
+    let collapsed = body.split_whitespace().collect::<Vec<_>>().join(" ");
+    let scrubbed = crate::redact::redact(&collapsed);
+
+    if scrubbed.chars().count() > SNIPPET_MAX_CHARS {
+        let truncated: String = scrubbed.chars().take(SNIPPET_MAX_CHARS).collect();
+        format!("{truncated}…")
+    } else {
+        scrubbed.into_owned()
+    }
+}
+
+/// Deserialize an optional field, treating a malformed value like a missing one
+/// so a single bad sub-object doesn't discard the rest of the response.
+pub(crate) fn lenient<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeOwned,
+{
+    let value = serde_json::Value::deserialize(deserializer)?;
+    if value.is_null() {
+        return Ok(None);
+    }
+    Ok(serde_json::from_value(value)
+        .inspect_err(|e| log::warn!("Ignoring malformed field in provider response: {e}"))
+        .ok())
+}
+
+/// Cloudflare marks challenge responses with `cf-mitigated: challenge`.
+pub(crate) fn is_cloudflare_challenge(headers: &HeaderMap) -> bool {
+    headers
+        .get("cf-mitigated")
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("challenge"))
+}
+
+/// An HTML page where JSON was expected, e.g. a challenge or login page served with HTTP 200.
+pub(crate) fn is_html(body: &str) -> bool {
+    let start: String = body.trim_start().chars().take(14).collect();
+    let start = start.to_ascii_lowercase();
+    start.starts_with("<!doctype html") || start.starts_with("<html")
+}
+
+/// What a usage response said besides the usage itself, filled in by the
+/// provider modules as soon as a response arrives.
+#[derive(Debug, Default)]
+pub struct ResponseMeta {
+    /// None when the request failed before a response arrived.
+    pub http_status: Option<u16>,
+    /// A new Claude session key the server moved the request's session to, so
+    /// the caller can persist it before the old one expires.
+    pub rotated_session_key: Option<RotatedSessionKey>,
+}
+
+/// Outcome of one usage request.
+#[derive(Debug)]
+pub struct UsageResponse {
+    pub result: Result<UsageSnapshot, AppError>,
+    pub meta: ResponseMeta,
+}
 
 pub async fn fetch_usage_for_provider(
     provider: ProviderKind,
     org_id: Option<&str>,
     session_token: Option<&str>,
     ollama_session_token: Option<&str>,
-) -> Result<UsageSnapshot, AppError> {
+) -> UsageResponse {
+    let mut meta = ResponseMeta::default();
+    let result = match provider {
+        ProviderKind::Claude => claude::fetch_usage(org_id, session_token, &mut meta).await,
+        ProviderKind::Codex => codex::fetch_usage(&mut meta).await,
+        ProviderKind::Ollama => match ollama_session_token {
+            Some(token) => ollama::fetch_usage(token, &mut meta).await,
+            None => Err(AppError::MissingConfig("ollama_session_token".to_string())),
+        },
+    };
+    UsageResponse { result, meta }
+}
+
+/// Parse a usage response body recorded from `provider`, as if it had just
+/// been fetched. Rate-limit headers aren't recorded, so `rate_limit` is None.
+pub fn parse_usage_response(provider: ProviderKind, body: &str) -> Result<UsageSnapshot, AppError> {
     match provider {
-        ProviderKind::Claude => claude::fetch_usage(org_id, session_token).await,
-        ProviderKind::Codex => codex::fetch_usage().await,
-        ProviderKind::Ollama => {
-            let token = ollama_session_token
-                .ok_or_else(|| AppError::MissingConfig("ollama_session_token".to_string()))?;
-            ollama::fetch_usage(token).await
-        }
+        ProviderKind::Claude => claude::parse_usage(body, None),
+        ProviderKind::Codex => codex::parse_usage(body),
+        ProviderKind::Ollama => ollama::parse_usage(body),
     }
 }
 
+pub async fn fetch_claude_organizations(
+    session_token: &str,
+) -> Result<Vec<Organization>, AppError> {
+    claude::fetch_organizations(session_token).await
+}
+
+/// Usage of one Claude organization, for those monitored besides the primary one.
+pub async fn fetch_claude_usage(org_id: &str, session_token: &str) -> UsageResponse {
+    let mut meta = ResponseMeta::default();
+    let result = claude::fetch_usage(Some(org_id), Some(session_token), &mut meta).await;
+    UsageResponse { result, meta }
+}
+
+pub async fn fetch_claude_member_usage(
+    org_id: &str,
+    session_token: &str,
+) -> Result<Option<Vec<MemberUsage>>, AppError> {
+    claude::fetch_member_usage(org_id, session_token).await
+}
+
 pub fn get_provider_statuses(
     claude_org_id: Option<&str>,
     claude_session_token: Option<&str>,
     ollama_session_token: Option<&str>,
 ) -> Vec<ProviderStatus> {
+    if crate::demo::is_enabled() {
+        return crate::demo::provider_statuses();
+    }
     vec![
         claude::get_status(claude_org_id, claude_session_token),
         codex::get_status(),
         ollama::get_status(ollama_session_token),
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_challenge_pages() {
+        let mut headers = HeaderMap::new();
+        assert!(!is_cloudflare_challenge(&headers));
+        headers.insert("cf-mitigated", HeaderValue::from_static("challenge"));
+        assert!(is_cloudflare_challenge(&headers));
+
+        assert!(is_html(
+            "\n<!DOCTYPE html><html><head><title>Just a moment...</title>"
+        ));
+        assert!(is_html("<html lang=\"en\">"));
+        assert!(!is_html(r#"{"five_hour":null}"#));
+    }
+
+    #[test]
+    fn switches_user_agent() {
+        assert_eq!(UserAgentMode::App.header(), APP_USER_AGENT);
+        assert!(
+            UserAgentMode::Browser
+                .header()
+                .to_str()
+                .unwrap()
+                .starts_with("Mozilla/5.0")
+        );
+    }
+
+    #[test]
+    fn scrubs_and_truncates_snippets() {
+        let body = r#"{"error": {"message": "bad",
+            "sessionKey": "sk-ant-sid01-abc", "email": "me@example.com", "token=xyz"}}"#;
+        let snippet = response_snippet(body);
+        assert_eq!(
+            snippet,
+            r#"{"error": {"message": "bad", "sessionKey": "[redacted]", "email": "[redacted]", "token=[redacted]"}}"#
+        );
+
+        let long = "x".repeat(500);
+        assert_eq!(
+            response_snippet(&long).chars().count(),
+            SNIPPET_MAX_CHARS + 1
+        );
+    }
+}
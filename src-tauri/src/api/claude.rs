@@ -1,16 +1,23 @@
 use crate::error::AppError;
-use crate::types::{ProviderKind, ProviderStatus, UsageSnapshot, UsageWindow};
+use crate::types::{
+    MemberUsage, Organization, ProviderKind, ProviderStatus, RateLimitInfo, UsageSnapshot,
+    UsageWindow, reset_epoch_ms,
+};
 use crate::validation::{validate_org_id, validate_session_token};
-use reqwest::header::{COOKIE, HeaderMap, HeaderValue, USER_AGENT};
+use chrono::{DateTime, Utc};
+use reqwest::header::{COOKIE, HeaderMap, HeaderValue, RETRY_AFTER, SET_COOKIE, USER_AGENT};
 use serde::Deserialize;
+use std::collections::BTreeMap;
+use zeroize::Zeroizing;
 
-#[derive(Debug, Deserialize)]
-struct ClaudeUsageData {
-    five_hour: Option<ClaudeUsagePeriod>,
-    seven_day: Option<ClaudeUsagePeriod>,
-    seven_day_sonnet: Option<ClaudeUsagePeriod>,
-    seven_day_opus: Option<ClaudeUsagePeriod>,
-}
+/// Usage buckets with a friendly label. Anything else in the response that
+/// looks like a usage period is kept as a window labelled with its API name.
+const KNOWN_WINDOWS: [(&str, &str); 4] = [
+    ("five_hour", "5 Hour"),
+    ("seven_day", "7 Day"),
+    ("seven_day_sonnet", "Sonnet (7 Day)"),
+    ("seven_day_opus", "Opus (7 Day)"),
+];
 
 #[derive(Debug, Deserialize)]
 struct ClaudeUsagePeriod {
@@ -18,9 +25,231 @@ struct ClaudeUsagePeriod {
     resets_at: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+struct ClaudeOrganization {
+    uuid: String,
+    name: String,
+    #[serde(default)]
+    capabilities: Vec<String>,
+    rate_limit_tier: Option<String>,
+}
+
+/// An entry of the admin member usage list. `usage` has the same periods as
+/// the organization's own usage response.
+#[derive(Debug, Deserialize)]
+struct ClaudeMemberUsage {
+    account: ClaudeMemberAccount,
+    #[serde(default)]
+    usage: BTreeMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClaudeMemberAccount {
+    uuid: String,
+    full_name: Option<String>,
+    email_address: Option<String>,
+}
+
+fn request_headers(session_token: &str) -> Result<HeaderMap, AppError> {
+    let mut headers = HeaderMap::new();
+    headers.insert(USER_AGENT, super::user_agent());
+    let cookie = Zeroizing::new(format!("sessionKey={session_token}"));
+    let mut cookie = HeaderValue::from_str(&cookie).map_err(|_| AppError::InvalidToken)?;
+    cookie.set_sensitive(true);
+    headers.insert(COOKIE, cookie);
+    Ok(headers)
+}
+
+/// A new `sessionKey` the server set in place of `current`. Deletions (empty
+/// value or `Max-Age=0`) and malformed keys are ignored.
+fn rotated_session_key(
+    headers: &HeaderMap,
+    current: &str,
+    now: DateTime<Utc>,
+) -> Option<super::RotatedSessionKey> {
+    headers
+        .get_all(SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|cookie| {
+            let mut parts = cookie.split(';').map(str::trim);
+            let value = parts.next()?.strip_prefix("sessionKey=")?;
+            let attributes: Vec<&str> = parts.collect();
+            let deleted = attributes
+                .iter()
+                .any(|attribute| attribute.eq_ignore_ascii_case("max-age=0"));
+            (!deleted).then(|| super::RotatedSessionKey {
+                session_key: value.to_string().into(),
+                expires_at: cookie_expiry(&attributes, now),
+            })
+        })
+        .find(|rotated| {
+            rotated.session_key.expose() != current
+                && validate_session_token(rotated.session_key.expose()).is_ok()
+        })
+}
+
+/// Expiry from cookie attributes; `Max-Age` wins over `Expires` as in browsers.
+fn cookie_expiry(attributes: &[&str], now: DateTime<Utc>) -> Option<String> {
+    let attribute = |name: &str| {
+        attributes.iter().find_map(|attribute| {
+            let (key, value) = attribute.split_once('=')?;
+            key.trim().eq_ignore_ascii_case(name).then(|| value.trim())
+        })
+    };
+
+    if let Some(seconds) = attribute("max-age").and_then(|v| v.parse::<i64>().ok()) {
+        return Some((now + chrono::Duration::seconds(seconds)).to_rfc3339());
+    }
+    let expires = DateTime::parse_from_rfc2822(attribute("expires")?).ok()?;
+    Some(expires.with_timezone(&Utc).to_rfc3339())
+}
+
+/// List the organizations the session token belongs to.
+pub async fn fetch_organizations(session_token: &str) -> Result<Vec<Organization>, AppError> {
+    validate_session_token(session_token)?;
+
+    let client = super::http_client()?;
+    let url = format!(
+        "{}/api/organizations",
+        crate::config::get().claude_api_base_url()
+    );
+    let response = client
+        .get(&url)
+        .headers(request_headers(session_token)?)
+        .send()
+        .await?;
+
+    if super::is_cloudflare_challenge(response.headers()) {
+        log::error!("Claude organizations request was blocked by a Cloudflare challenge");
+        return Err(AppError::Blocked);
+    }
+
+    match response.status().as_u16() {
+        200 => {
+            let body = response.text().await?;
+            if super::is_html(&body) {
+                log::error!("Claude organizations request returned HTML instead of JSON");
+                return Err(AppError::Blocked);
+            }
+            let organizations: Vec<ClaudeOrganization> =
+                serde_json::from_str(&body).map_err(|e| {
+                    let snippet = super::response_snippet(&body);
+                    log::error!("Failed to parse Claude organizations response: {e}: {snippet}");
+                    AppError::Server(format!(
+                        "Failed to parse response (HTTP 200): {e}. Response: {snippet}"
+                    ))
+                })?;
+
+            Ok(organizations
+                .into_iter()
+                .map(|org| Organization {
+                    plan: plan_name(&org.capabilities).or(org.rate_limit_tier),
+                    id: org.uuid,
+                    name: org.name,
+                })
+                .collect())
+        }
+        401 => Err(AppError::InvalidToken),
+        403 => {
+            log::error!("Claude organizations request returned HTTP 403");
+            Err(AppError::Forbidden)
+        }
+        429 => Err(AppError::RateLimited(parse_rate_limit_headers(
+            response.headers(),
+            Utc::now(),
+        ))),
+        status => {
+            let snippet = super::response_snippet(&response.text().await.unwrap_or_default());
+            log::error!("Claude organizations request failed with HTTP {status}: {snippet}");
+            Err(AppError::Server(format!(
+                "Unexpected error (HTTP {status}). Please try again. Response: {snippet}"
+            )))
+        }
+    }
+}
+
+/// After a 403/404, check whether the stored organization is still one the
+/// token can access, so the user is told to pick another instead of a generic error.
+async fn organization_missing(org_id: &str, session_token: &str) -> bool {
+    match fetch_organizations(session_token).await {
+        Ok(organizations) => !organizations.iter().any(|org| org.id == org_id),
+        Err(e) => {
+            log::warn!("Could not re-validate Claude organization: {e}");
+            false
+        }
+    }
+}
+
+/// Header prefixes checked in order: Anthropic's own, then the common
+/// `x-ratelimit-*` and IETF draft `ratelimit-*` spellings.
+const RATE_LIMIT_HEADER_PREFIXES: [&str; 3] = [
+    "anthropic-ratelimit-requests-",
+    "x-ratelimit-",
+    "ratelimit-",
+];
+
+/// Read quota and retry hints from the response headers, or None if there are none.
+fn parse_rate_limit_headers(headers: &HeaderMap, now: DateTime<Utc>) -> Option<RateLimitInfo> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let prefixed = |suffix: &str| {
+        RATE_LIMIT_HEADER_PREFIXES
+            .iter()
+            .find_map(|prefix| header(&format!("{prefix}{suffix}")))
+    };
+
+    let info = RateLimitInfo {
+        limit: prefixed("limit").and_then(|v| v.trim().parse().ok()),
+        remaining: prefixed("remaining").and_then(|v| v.trim().parse().ok()),
+        reset_at: prefixed("reset").and_then(|v| parse_reset(v, now)),
+        retry_after_seconds: header(RETRY_AFTER.as_str()).and_then(|v| parse_retry_after(v, now)),
+    };
+
+    (info != RateLimitInfo::default()).then_some(info)
+}
+
+/// Reset values come as an RFC3339 timestamp, a Unix timestamp, or seconds from now.
+fn parse_reset(value: &str, now: DateTime<Utc>) -> Option<String> {
+    let value = value.trim();
+    if let Ok(reset) = DateTime::parse_from_rfc3339(value) {
+        return Some(reset.with_timezone(&Utc).to_rfc3339());
+    }
+
+    let seconds: i64 = value.parse().ok()?;
+    let reset = if seconds > 1_000_000_000 {
+        DateTime::from_timestamp(seconds, 0)?
+    } else {
+        now + chrono::Duration::seconds(seconds)
+    };
+    Some(reset.to_rfc3339())
+}
+
+/// `Retry-After` is either delay-seconds or an HTTP date.
+fn parse_retry_after(value: &str, now: DateTime<Utc>) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(seconds);
+    }
+
+    let retry_at = DateTime::parse_from_rfc2822(value).ok()?;
+    Some(retry_at.signed_duration_since(now).num_seconds().max(0) as u64)
+}
+
+fn plan_name(capabilities: &[String]) -> Option<String> {
+    let has = |capability: &str| capabilities.iter().any(|c| c == capability);
+    if has("claude_max") {
+        Some("Max".to_string())
+    } else if has("claude_pro") {
+        Some("Pro".to_string())
+    } else {
+        None
+    }
+}
+
 pub async fn fetch_usage(
     org_id: Option<&str>,
     session_token: Option<&str>,
+    meta: &mut super::ResponseMeta,
 ) -> Result<UsageSnapshot, AppError> {
     let org_id = org_id.ok_or_else(|| AppError::MissingConfig("organization_id".to_string()))?;
     let session_token =
@@ -29,55 +258,49 @@ pub async fn fetch_usage(
     validate_org_id(org_id)?;
     validate_session_token(session_token)?;
 
-    let client = reqwest::Client::new();
-    let mut headers = HeaderMap::new();
-    headers.insert(USER_AGENT, HeaderValue::from_static("Claude-Monitor/0.1.0"));
-    headers.insert(
-        COOKIE,
-        HeaderValue::from_str(&format!("sessionKey={session_token}"))
-            .map_err(|_| AppError::InvalidToken)?,
-    );
+    let client = super::http_client()?;
+    let headers = request_headers(session_token)?;
 
-    let url = format!("https://claude.ai/api/organizations/{org_id}/usage");
+    let url = format!(
+        "{}/api/organizations/{org_id}/usage",
+        crate::config::get().claude_api_base_url()
+    );
     let response = client.get(&url).headers(headers).send().await?;
     let status = response.status().as_u16();
+    meta.http_status = Some(status);
+    let rate_limit = parse_rate_limit_headers(response.headers(), Utc::now());
+    crate::clock::record_server_date(response.headers());
+    if let Some(rotated) = rotated_session_key(response.headers(), session_token, Utc::now()) {
+        log::info!("Claude rotated the session key");
+        meta.rotated_session_key = Some(rotated);
+    }
+
+    if super::is_cloudflare_challenge(response.headers()) {
+        log::error!("Claude usage request was blocked by a Cloudflare challenge (HTTP {status})");
+        return Err(AppError::Blocked);
+    }
 
     match status {
         200 => {
             let body = response.text().await?;
-            let usage: ClaudeUsageData = serde_json::from_str(&body).map_err(|e| {
-                log::error!("Failed to parse Claude usage response: {e}");
-                AppError::Server(format!("Failed to parse response: {e}"))
-            })?;
-
-            Ok(UsageSnapshot {
-                provider: ProviderKind::Claude,
-                windows: [
-                    map_window("five_hour", "5 Hour", usage.five_hour),
-                    map_window("seven_day", "7 Day", usage.seven_day),
-                    map_window("seven_day_sonnet", "Sonnet (7 Day)", usage.seven_day_sonnet),
-                    map_window("seven_day_opus", "Opus (7 Day)", usage.seven_day_opus),
-                ]
-                .into_iter()
-                .flatten()
-                .collect(),
-                account_email: None,
-                plan_type: None,
-            })
+            crate::capture::record(ProviderKind::Claude, &body);
+            parse_usage(&body, rate_limit)
         }
         401 => {
             log::error!("Claude usage request returned authentication failure (HTTP 401)");
             Err(AppError::InvalidToken)
         }
         429 => {
-            log::warn!("Claude usage request was rate limited (HTTP 429)");
-            Err(AppError::RateLimited)
+            log::warn!("Claude usage request was rate limited (HTTP 429): {rate_limit:?}");
+            Err(AppError::RateLimited(rate_limit))
+        }
+        403 | 404 if organization_missing(org_id, session_token).await => {
+            log::error!("Claude organization {org_id} is no longer available to this account");
+            Err(AppError::OrganizationNotFound(org_id.to_string()))
         }
         403 => {
             log::error!("Claude usage request returned HTTP 403 for org_id {org_id}");
-            Err(AppError::Server(
-                "Access denied. Check your organization ID.".to_string(),
-            ))
+            Err(AppError::Forbidden)
         }
         404 => {
             log::error!("Claude usage request returned HTTP 404 for org_id {org_id}");
@@ -86,20 +309,127 @@ pub async fn fetch_usage(
             ))
         }
         500..=599 => {
-            log::error!("Claude usage request failed with server error HTTP {status}");
-            Err(AppError::Server(
-                "Claude is experiencing issues. Please try again later.".to_string(),
-            ))
+            let snippet = super::response_snippet(&response.text().await.unwrap_or_default());
+            log::error!("Claude usage request failed with server error HTTP {status}: {snippet}");
+            Err(AppError::Server(format!(
+                "Claude is experiencing issues (HTTP {status}). Please try again later. Response: {snippet}"
+            )))
         }
         status => {
-            log::error!("Claude usage request failed with unexpected HTTP status {status}");
+            let snippet = super::response_snippet(&response.text().await.unwrap_or_default());
+            log::error!(
+                "Claude usage request failed with unexpected HTTP status {status}: {snippet}"
+            );
             Err(AppError::Server(format!(
-                "Unexpected error (HTTP {status}). Please try again."
+                "Unexpected error (HTTP {status}). Please try again. Response: {snippet}"
             )))
         }
     }
 }
 
+/// Parse the body of a successful usage response.
+pub(super) fn parse_usage(
+    body: &str,
+    rate_limit: Option<RateLimitInfo>,
+) -> Result<UsageSnapshot, AppError> {
+    if super::is_html(body) {
+        log::error!("Claude usage request returned HTML instead of JSON");
+        return Err(AppError::Blocked);
+    }
+    let usage: BTreeMap<String, serde_json::Value> = serde_json::from_str(body).map_err(|e| {
+        let snippet = super::response_snippet(body);
+        log::error!("Failed to parse Claude usage response: {e}: {snippet}");
+        AppError::Server(format!(
+            "Failed to parse response (HTTP 200): {e}. Response: {snippet}"
+        ))
+    })?;
+
+    Ok(UsageSnapshot {
+        provider: ProviderKind::Claude,
+        windows: map_windows(usage),
+        account_email: None,
+        plan_type: None,
+        rate_limit,
+    })
+}
+
+/// Per-member usage for an organization. Only Team/Enterprise admins may read
+/// it; anyone else gets `Forbidden`. None when the organization doesn't offer
+/// the endpoint at all (HTTP 404).
+///
+/// The endpoint isn't publicly documented and hasn't been verified against a
+/// Team/Enterprise organization, so a 404 means "unsupported", not an error.
+pub async fn fetch_member_usage(
+    org_id: &str,
+    session_token: &str,
+) -> Result<Option<Vec<MemberUsage>>, AppError> {
+    validate_org_id(org_id)?;
+    validate_session_token(session_token)?;
+
+    let client = super::http_client()?;
+    let url = format!(
+        "{}/api/organizations/{org_id}/members/usage",
+        crate::config::get().claude_api_base_url()
+    );
+    let response = client
+        .get(&url)
+        .headers(request_headers(session_token)?)
+        .send()
+        .await?;
+
+    if super::is_cloudflare_challenge(response.headers()) {
+        log::error!("Claude member usage request was blocked by a Cloudflare challenge");
+        return Err(AppError::Blocked);
+    }
+
+    match response.status().as_u16() {
+        200 => {
+            let body = response.text().await?;
+            if super::is_html(&body) {
+                log::error!("Claude member usage request returned HTML instead of JSON");
+                return Err(AppError::Blocked);
+            }
+            parse_member_usage(&body).map(Some).map_err(|e| {
+                let snippet = super::response_snippet(&body);
+                log::error!("Failed to parse Claude member usage response: {e}: {snippet}");
+                AppError::Server(format!(
+                    "Failed to parse response (HTTP 200): {e}. Response: {snippet}"
+                ))
+            })
+        }
+        401 => Err(AppError::InvalidToken),
+        403 => Err(AppError::Forbidden),
+        404 => {
+            log::warn!("Claude organization {org_id} doesn't offer member usage (HTTP 404)");
+            Ok(None)
+        }
+        429 => Err(AppError::RateLimited(parse_rate_limit_headers(
+            response.headers(),
+            Utc::now(),
+        ))),
+        status => {
+            let snippet = super::response_snippet(&response.text().await.unwrap_or_default());
+            log::error!("Claude member usage request failed with HTTP {status}: {snippet}");
+            Err(AppError::Server(format!(
+                "Unexpected error (HTTP {status}). Please try again. Response: {snippet}"
+            )))
+        }
+    }
+}
+
+fn parse_member_usage(body: &str) -> Result<Vec<MemberUsage>, serde_json::Error> {
+    let members: Vec<ClaudeMemberUsage> = serde_json::from_str(body)?;
+    Ok(members
+        .into_iter()
+        .map(|member| MemberUsage {
+            member_id: member.account.uuid,
+            name: member.account.full_name,
+            email: member.account.email_address,
+            windows: map_windows(member.usage),
+        })
+        .collect())
+}
+
 pub fn get_status(org_id: Option<&str>, session_token: Option<&str>) -> ProviderStatus {
     let configured = org_id.is_some() && session_token.is_some();
     ProviderStatus {
@@ -114,13 +444,217 @@ pub fn get_status(org_id: Option<&str>, session_token: Option<&str>) -> Provider
     }
 }
 
-fn map_window(key: &str, label: &str, period: Option<ClaudeUsagePeriod>) -> Option<UsageWindow> {
-    let period = period?;
+/// Known windows first in their usual order, then any other usage periods
+/// sorted by key. Entries that are null or not shaped like a period are skipped.
+fn map_windows(mut usage: BTreeMap<String, serde_json::Value>) -> Vec<UsageWindow> {
+    let mut windows: Vec<UsageWindow> = KNOWN_WINDOWS
+        .iter()
+        .filter_map(|(key, label)| map_window(key, label, usage.remove(*key)?))
+        .collect();
+
+    for (key, value) in usage {
+        if value.get("utilization").is_none() {
+            continue;
+        }
+        if let Some(window) = map_window(&key, &key, value) {
+            log::debug!("Including unrecognized Claude usage metric {key}");
+            windows.push(window);
+        }
+    }
+
+    windows
+}
+
+/// Each period is parsed on its own, so a malformed one is skipped instead of
+/// failing the whole response.
+fn map_window(key: &str, label: &str, value: serde_json::Value) -> Option<UsageWindow> {
+    if value.is_null() {
+        return None;
+    }
+    let period: ClaudeUsagePeriod = serde_json::from_value(value)
+        .inspect_err(|e| log::warn!("Skipping malformed Claude usage period {key}: {e}"))
+        .ok()?;
     Some(UsageWindow {
         key: key.to_string(),
         label: label.to_string(),
         utilization: period.utilization,
+        resets_at_epoch_ms: reset_epoch_ms(period.resets_at.as_deref()),
         resets_at: period.resets_at,
         window_duration_seconds: None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_plan_from_capabilities() {
+        let caps = |items: &[&str]| items.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            plan_name(&caps(&["chat", "claude_max"])).as_deref(),
+            Some("Max")
+        );
+        assert_eq!(
+            plan_name(&caps(&["chat", "claude_pro"])).as_deref(),
+            Some("Pro")
+        );
+        assert_eq!(plan_name(&caps(&["chat"])), None);
+    }
+
+    #[test]
+    fn parses_member_usage() {
+        let members = parse_member_usage(
+            r#"[
+                {
+                    "account": {"uuid": "u1", "full_name": "Ada", "email_address": "ada@example.com"},
+                    "usage": {
+                        "five_hour": {"utilization": 91.0, "resets_at": "2026-04-13T12:00:00Z"},
+                        "seven_day": null
+                    }
+                },
+                {"account": {"uuid": "u2"}}
+            ]"#,
+        )
+        .unwrap();
+
+        assert_eq!(members.len(), 2);
+        assert_eq!(members[0].member_id, "u1");
+        assert_eq!(members[0].email.as_deref(), Some("ada@example.com"));
+        assert_eq!(members[0].windows.len(), 1);
+        assert_eq!(members[0].windows[0].label, "5 Hour");
+        assert_eq!(members[0].windows[0].utilization, 91.0);
+        assert_eq!(members[1].name, None);
+        assert!(members[1].windows.is_empty());
+    }
+
+    #[test]
+    fn picks_up_rotated_session_key() {
+        let headers = |cookies: &[&str]| {
+            let mut headers = HeaderMap::new();
+            for cookie in cookies {
+                headers.append(SET_COOKIE, HeaderValue::from_str(cookie).unwrap());
+            }
+            headers
+        };
+
+        let now = DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            rotated_session_key(
+                &headers(&[
+                    "__cf_bm=abc; Path=/",
+                    "sessionKey=sk-ant-sid01-new; Path=/; Expires=Wed, 13 May 2026 10:00:00 GMT; Secure; HttpOnly",
+                ]),
+                "sk-ant-sid01-old",
+                now
+            ),
+            Some(crate::api::RotatedSessionKey {
+                session_key: "sk-ant-sid01-new".to_string().into(),
+                expires_at: Some("2026-05-13T10:00:00+00:00".to_string()),
+            })
+        );
+        assert_eq!(
+            rotated_session_key(
+                &headers(&["sessionKey=sk-ant-sid01-new; Max-Age=86400"]),
+                "sk-ant-sid01-old",
+                now
+            )
+            .and_then(|rotated| rotated.expires_at)
+            .as_deref(),
+            Some("2026-04-14T10:00:00+00:00")
+        );
+        assert_eq!(
+            rotated_session_key(
+                &headers(&["sessionKey=sk-ant-sid01-old; Path=/"]),
+                "sk-ant-sid01-old",
+                now
+            ),
+            None
+        );
+        assert_eq!(
+            rotated_session_key(
+                &headers(&["sessionKey=; Max-Age=0", "sessionKey=x; max-age=0"]),
+                "sk-ant-sid01-old",
+                now
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn keeps_unknown_usage_periods() {
+        let body = r#"{
+            "five_hour": {"utilization": 12.0, "resets_at": "2026-04-13T15:00:00Z"},
+            "seven_day_opus": null,
+            "seven_day_sonnet": {"utilization": "unknown"},
+            "seven_day_haiku": {"utilization": 3.5, "resets_at": null},
+            "extra_usage": {"is_enabled": false},
+            "seven_day": {"utilization": 40.0, "resets_at": null}
+        }"#;
+        let windows = map_windows(serde_json::from_str(body).unwrap());
+
+        let keys: Vec<&str> = windows.iter().map(|w| w.key.as_str()).collect();
+        assert_eq!(keys, vec!["five_hour", "seven_day", "seven_day_haiku"]);
+        assert_eq!(windows[1].label, "7 Day");
+        assert_eq!(windows[2].label, "seven_day_haiku");
+        assert_eq!(windows[2].utilization, 3.5);
+    }
+
+    #[test]
+    fn parses_rate_limit_headers() {
+        let now = DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            "anthropic-ratelimit-requests-remaining",
+            HeaderValue::from_static("0"),
+        );
+        headers.insert(
+            "anthropic-ratelimit-requests-reset",
+            HeaderValue::from_static("2026-04-13T10:05:00Z"),
+        );
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("60"));
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("120"));
+
+        let info = parse_rate_limit_headers(&headers, now).unwrap();
+        assert_eq!(info.limit, Some(60));
+        assert_eq!(info.remaining, Some(0));
+        assert_eq!(info.reset_at.as_deref(), Some("2026-04-13T10:05:00+00:00"));
+        assert_eq!(info.retry_after_seconds, Some(120));
+
+        assert_eq!(parse_rate_limit_headers(&HeaderMap::new(), now), None);
+    }
+
+    #[test]
+    fn parses_relative_reset_and_http_date_retry_after() {
+        let now = DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            parse_reset("30", now).as_deref(),
+            Some("2026-04-13T10:00:30+00:00")
+        );
+        assert_eq!(
+            parse_reset("1776074700", now).as_deref(),
+            Some("2026-04-13T10:05:00+00:00")
+        );
+        assert_eq!(
+            parse_retry_after("Mon, 13 Apr 2026 10:01:00 GMT", now),
+            Some(60)
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn parses_organizations_response() {
+        let body = r#"[{"uuid":"org-1","name":"Personal","capabilities":["chat","claude_pro"],"rate_limit_tier":"default_claude_pro"},{"uuid":"org-2","name":"Team"}]"#;
+        let organizations: Vec<ClaudeOrganization> = serde_json::from_str(body).unwrap();
+        assert_eq!(organizations.len(), 2);
+        assert_eq!(organizations[1].name, "Team");
+        assert!(organizations[1].capabilities.is_empty());
+    }
+}
@@ -0,0 +1,214 @@
+//! The provider fetches the refresh loop and commands make, behind
+//! `UsageClient` so they can run against a stand-in instead of the network:
+//! `MockUsageClient` in tests and `demo::DemoUsageClient` in demo mode.
+
+use super::{ResponseMeta, UsageResponse};
+use crate::error::AppError;
+use crate::types::{MemberUsage, Organization, ProviderKind, UsageSnapshot};
+use std::future::Future;
+use std::pin::Pin;
+
+/// Boxed so `UsageClient` can be used as `Arc<dyn UsageClient>`.
+pub type ClientFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, AppError>> + Send + 'a>>;
+
+/// A usage fetch, which reports the response's details even when it fails.
+pub type UsageFuture<'a> = Pin<Box<dyn Future<Output = UsageResponse> + Send + 'a>>;
+
+pub trait UsageClient: Send + Sync {
+    /// Usage of `provider`; Claude needs the organization and session token,
+    /// Ollama its session token.
+    fn fetch_usage<'a>(
+        &'a self,
+        provider: ProviderKind,
+        org_id: Option<&'a str>,
+        session_token: Option<&'a str>,
+        ollama_session_token: Option<&'a str>,
+    ) -> UsageFuture<'a>;
+
+    /// Usage of one additional Claude organization.
+    fn fetch_claude_usage<'a>(&'a self, org_id: &'a str, session_token: &'a str)
+    -> UsageFuture<'a>;
+
+    /// Per-member usage of an organization; None when it doesn't offer it.
+    fn fetch_claude_member_usage<'a>(
+        &'a self,
+        org_id: &'a str,
+        session_token: &'a str,
+    ) -> ClientFuture<'a, Option<Vec<MemberUsage>>>;
+
+    fn fetch_claude_organizations<'a>(
+        &'a self,
+        session_token: &'a str,
+    ) -> ClientFuture<'a, Vec<Organization>>;
+}
+
+/// The provider APIs over HTTP.
+pub struct HttpUsageClient;
+
+impl UsageClient for HttpUsageClient {
+    fn fetch_usage<'a>(
+        &'a self,
+        provider: ProviderKind,
+        org_id: Option<&'a str>,
+        session_token: Option<&'a str>,
+        ollama_session_token: Option<&'a str>,
+    ) -> UsageFuture<'a> {
+        Box::pin(super::fetch_usage_for_provider(
+            provider,
+            org_id,
+            session_token,
+            ollama_session_token,
+        ))
+    }
+
+    fn fetch_claude_usage<'a>(
+        &'a self,
+        org_id: &'a str,
+        session_token: &'a str,
+    ) -> UsageFuture<'a> {
+        Box::pin(super::fetch_claude_usage(org_id, session_token))
+    }
+
+    fn fetch_claude_member_usage<'a>(
+        &'a self,
+        org_id: &'a str,
+        session_token: &'a str,
+    ) -> ClientFuture<'a, Option<Vec<MemberUsage>>> {
+        Box::pin(super::fetch_claude_member_usage(org_id, session_token))
+    }
+
+    fn fetch_claude_organizations<'a>(
+        &'a self,
+        session_token: &'a str,
+    ) -> ClientFuture<'a, Vec<Organization>> {
+        Box::pin(super::fetch_claude_organizations(session_token))
+    }
+}
+
+/// Answers from canned snapshots; anything not set up fails with a `Server`
+/// error, like an unreachable provider.
+#[cfg(test)]
+#[derive(Default)]
+pub struct MockUsageClient {
+    usage: Vec<UsageSnapshot>,
+    /// Usage of additional organizations, by organization id.
+    organization_usage: Vec<(String, UsageSnapshot)>,
+    /// Answer member usage requests like an organization without the endpoint.
+    member_usage_unsupported: bool,
+}
+
+#[cfg(test)]
+impl MockUsageClient {
+    pub fn with_usage(mut self, usage: UsageSnapshot) -> Self {
+        self.usage.push(usage);
+        self
+    }
+
+    pub fn with_organization_usage(mut self, org_id: &str, usage: UsageSnapshot) -> Self {
+        self.organization_usage.push((org_id.to_string(), usage));
+        self
+    }
+
+    pub fn without_member_usage(mut self) -> Self {
+        self.member_usage_unsupported = true;
+        self
+    }
+
+    fn unavailable<T>(what: &str) -> Result<T, AppError> {
+        Err(AppError::Server(format!("No mock response for {what}")))
+    }
+
+    fn respond(result: Result<UsageSnapshot, AppError>) -> UsageResponse {
+        let meta = ResponseMeta {
+            http_status: result.is_ok().then_some(200),
+            rotated_session_key: None,
+        };
+        UsageResponse { result, meta }
+    }
+}
+
+#[cfg(test)]
+impl UsageClient for MockUsageClient {
+    fn fetch_usage<'a>(
+        &'a self,
+        provider: ProviderKind,
+        _org_id: Option<&'a str>,
+        _session_token: Option<&'a str>,
+        _ollama_session_token: Option<&'a str>,
+    ) -> UsageFuture<'a> {
+        let result = self
+            .usage
+            .iter()
+            .find(|usage| usage.provider == provider)
+            .cloned()
+            .map_or_else(|| Self::unavailable(provider.as_str()), Ok);
+        Box::pin(async move { Self::respond(result) })
+    }
+
+    fn fetch_claude_usage<'a>(
+        &'a self,
+        org_id: &'a str,
+        _session_token: &'a str,
+    ) -> UsageFuture<'a> {
+        let result = self
+            .organization_usage
+            .iter()
+            .find(|(id, _)| id == org_id)
+            .map(|(_, usage)| usage.clone())
+            .map_or_else(|| Self::unavailable(org_id), Ok);
+        Box::pin(async move { Self::respond(result) })
+    }
+
+    fn fetch_claude_member_usage<'a>(
+        &'a self,
+        _org_id: &'a str,
+        _session_token: &'a str,
+    ) -> ClientFuture<'a, Option<Vec<MemberUsage>>> {
+        let unsupported = self.member_usage_unsupported;
+        Box::pin(async move {
+            if unsupported {
+                Ok(None)
+            } else {
+                Err(AppError::Forbidden)
+            }
+        })
+    }
+
+    fn fetch_claude_organizations<'a>(
+        &'a self,
+        _session_token: &'a str,
+    ) -> ClientFuture<'a, Vec<Organization>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn mock_answers_only_what_it_was_given() {
+        let usage = UsageSnapshot {
+            provider: ProviderKind::Codex,
+            windows: Vec::new(),
+            account_email: None,
+            plan_type: Some("plus".to_string()),
+            rate_limit: None,
+        };
+        let client = MockUsageClient::default().with_usage(usage);
+
+        let fetched = client
+            .fetch_usage(ProviderKind::Codex, None, None, None)
+            .await
+            .result
+            .unwrap();
+        assert_eq!(fetched.plan_type.as_deref(), Some("plus"));
+        assert!(matches!(
+            client
+                .fetch_usage(ProviderKind::Claude, Some("org"), Some("token"), None)
+                .await
+                .result,
+            Err(AppError::Server(_))
+        ));
+    }
+}
@@ -1,5 +1,5 @@
 use crate::error::AppError;
-use crate::types::{ProviderKind, ProviderStatus, UsageSnapshot, UsageWindow};
+use crate::types::{ProviderKind, ProviderStatus, UsageSnapshot, UsageWindow, reset_epoch_ms};
 use chrono::{DateTime, Utc};
 use reqwest::header::{AUTHORIZATION, HeaderMap, HeaderValue, USER_AGENT};
 use serde::{Deserialize, Deserializer};
@@ -16,16 +16,22 @@ struct CodexTokens {
     access_token: Option<String>,
 }
 
+// Every field is parsed leniently so one malformed window doesn't lose the others.
 #[derive(Debug, Deserialize)]
 struct WhamUsageResponse {
+    #[serde(default, deserialize_with = "super::lenient")]
     email: Option<String>,
+    #[serde(default, deserialize_with = "super::lenient")]
     plan_type: Option<String>,
+    #[serde(default, deserialize_with = "super::lenient")]
     rate_limit: Option<WhamRateLimit>,
 }
 
 #[derive(Debug, Deserialize)]
 struct WhamRateLimit {
+    #[serde(default, deserialize_with = "super::lenient")]
     primary_window: Option<WhamRateLimitWindow>,
+    #[serde(default, deserialize_with = "super::lenient")]
     secondary_window: Option<WhamRateLimitWindow>,
 }
 
@@ -37,12 +43,12 @@ struct WhamRateLimitWindow {
     limit_window_seconds: Option<i64>,
 }
 
-pub async fn fetch_usage() -> Result<UsageSnapshot, AppError> {
+pub async fn fetch_usage(meta: &mut super::ResponseMeta) -> Result<UsageSnapshot, AppError> {
     let access_token = load_access_token()?;
 
-    let client = reqwest::Client::new();
+    let client = super::http_client()?;
     let mut headers = HeaderMap::new();
-    headers.insert(USER_AGENT, HeaderValue::from_static("Claude-Monitor/0.1.0"));
+    headers.insert(USER_AGENT, super::user_agent());
     headers.insert(
         AUTHORIZATION,
         HeaderValue::from_str(&format!("Bearer {access_token}"))
@@ -54,21 +60,14 @@ pub async fn fetch_usage() -> Result<UsageSnapshot, AppError> {
         .headers(headers)
         .send()
         .await?;
+    meta.http_status = Some(response.status().as_u16());
+    crate::clock::record_server_date(response.headers());
 
     match response.status().as_u16() {
         200 => {
             let body = response.text().await?;
-            let usage: WhamUsageResponse = serde_json::from_str(&body).map_err(|e| {
-                log::error!("Failed to parse Codex WHAM usage response: {e}");
-                AppError::Server(format!("Failed to parse Codex usage: {e}"))
-            })?;
-
-            Ok(UsageSnapshot {
-                provider: ProviderKind::Codex,
-                windows: usage.rate_limit.map(map_windows).unwrap_or_default(),
-                account_email: usage.email,
-                plan_type: usage.plan_type,
-            })
+            crate::capture::record(ProviderKind::Codex, &body);
+            parse_usage(&body)
         }
         status @ (401 | 403) => {
             log::error!("Codex usage request returned authentication failure (HTTP {status})");
@@ -76,23 +75,46 @@ pub async fn fetch_usage() -> Result<UsageSnapshot, AppError> {
         }
         429 => {
             log::warn!("Codex usage request was rate limited (HTTP 429)");
-            Err(AppError::RateLimited)
+            Err(AppError::RateLimited(None))
         }
         status @ 500..=599 => {
-            log::error!("Codex usage request failed with server error HTTP {status}");
-            Err(AppError::Server(
-                "OpenAI is experiencing issues. Please try again later.".to_string(),
-            ))
+            let snippet = super::response_snippet(&response.text().await.unwrap_or_default());
+            log::error!("Codex usage request failed with server error HTTP {status}: {snippet}");
+            Err(AppError::Server(format!(
+                "OpenAI is experiencing issues (HTTP {status}). Please try again later. Response: {snippet}"
+            )))
         }
         status => {
-            log::error!("Codex usage request failed with unexpected HTTP status {status}");
+            let snippet = super::response_snippet(&response.text().await.unwrap_or_default());
+            log::error!(
+                "Codex usage request failed with unexpected HTTP status {status}: {snippet}"
+            );
             Err(AppError::Server(format!(
-                "Unexpected Codex error (HTTP {status}). Please try again."
+                "Unexpected Codex error (HTTP {status}). Please try again. Response: {snippet}"
             )))
         }
     }
 }
 
+/// Parse the body of a successful WHAM usage response.
+pub(super) fn parse_usage(body: &str) -> Result<UsageSnapshot, AppError> {
+    let usage: WhamUsageResponse = serde_json::from_str(body).map_err(|e| {
+        let snippet = super::response_snippet(body);
+        log::error!("Failed to parse Codex WHAM usage response: {e}: {snippet}");
+        AppError::Server(format!(
+            "Failed to parse Codex usage (HTTP 200): {e}. Response: {snippet}"
+        ))
+    })?;
+
+    Ok(UsageSnapshot {
+        provider: ProviderKind::Codex,
+        windows: usage.rate_limit.map(map_windows).unwrap_or_default(),
+        account_email: usage.email,
+        plan_type: usage.plan_type,
+        rate_limit: None,
+    })
+}
+
 pub fn get_status() -> ProviderStatus {
     match load_access_token() {
         Ok(_) => ProviderStatus {
@@ -128,6 +150,7 @@ fn map_window(key: &str, window: Option<WhamRateLimitWindow>) -> Option<UsageWin
         key: key.to_string(),
         label,
         utilization: window.used_percent,
+        resets_at_epoch_ms: reset_epoch_ms(window.reset_at.as_deref()),
         resets_at: window.reset_at,
         window_duration_seconds: window.limit_window_seconds,
     })
@@ -232,6 +255,27 @@ mod tests {
         assert_eq!(label_for_window(Some(172_800), "secondary"), "2 Day");
     }
 
+    #[test]
+    fn keeps_valid_windows_when_one_is_malformed() {
+        let json = r#"{
+            "email": "me@example.com",
+            "plan_type": 3,
+            "rate_limit": {
+                "primary_window": {"used_percent": 42.0, "reset_at": null, "limit_window_seconds": 18000},
+                "secondary_window": {"used_percent": "n/a"}
+            }
+        }"#;
+
+        let parsed: WhamUsageResponse = serde_json::from_str(json).unwrap();
+        assert_eq!(parsed.email.as_deref(), Some("me@example.com"));
+        assert_eq!(parsed.plan_type, None);
+
+        let windows = map_windows(parsed.rate_limit.unwrap());
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].key, "primary");
+        assert_eq!(windows[0].utilization, 42.0);
+    }
+
     #[test]
     fn parses_numeric_reset_at() {
         let json = r#"{
@@ -1,8 +1,9 @@
 use crate::error::AppError;
-use crate::types::{ProviderKind, ProviderStatus, UsageSnapshot, UsageWindow};
+use crate::types::{ProviderKind, ProviderStatus, UsageSnapshot, UsageWindow, reset_epoch_ms};
 use regex::Regex;
 use reqwest::header::{COOKIE, HeaderMap, HeaderValue, USER_AGENT};
 use scraper::{Html, Selector};
+use zeroize::Zeroizing;
 
 /// Cookie name for Ollama session authentication.
 /// This may need to be updated if Ollama changes their cookie naming.
@@ -17,32 +18,31 @@ struct OllamaSettingsData {
     account_email: Option<String>,
 }
 
-pub async fn fetch_usage(session_token: &str) -> Result<UsageSnapshot, AppError> {
-    let client = reqwest::Client::new();
+pub async fn fetch_usage(
+    session_token: &str,
+    meta: &mut super::ResponseMeta,
+) -> Result<UsageSnapshot, AppError> {
+    let client = super::http_client()?;
     let mut headers = HeaderMap::new();
-    headers.insert(USER_AGENT, HeaderValue::from_static("Claude-Monitor/0.1.0"));
-    headers.insert(
-        COOKIE,
-        HeaderValue::from_str(&format!("{}={session_token}", OLLAMA_COOKIE_NAME))
-            .map_err(|_| AppError::InvalidToken)?,
-    );
+    headers.insert(USER_AGENT, super::user_agent());
+    let cookie = Zeroizing::new(format!("{}={session_token}", OLLAMA_COOKIE_NAME));
+    let mut cookie = HeaderValue::from_str(&cookie).map_err(|_| AppError::InvalidToken)?;
+    cookie.set_sensitive(true);
+    headers.insert(COOKIE, cookie);
 
     let response = client
         .get("https://ollama.com/settings")
         .headers(headers)
         .send()
         .await?;
+    meta.http_status = Some(response.status().as_u16());
+    crate::clock::record_server_date(response.headers());
 
     match response.status().as_u16() {
         200 => {
             let html = response.text().await?;
-            let data = parse_ollama_settings(&html)?;
-            Ok(UsageSnapshot {
-                provider: ProviderKind::Ollama,
-                windows: build_windows(&data),
-                account_email: data.account_email,
-                plan_type: data.plan_type,
-            })
+            crate::capture::record(ProviderKind::Ollama, &html);
+            parse_usage(&html)
         }
         401 | 403 => {
             log::error!(
@@ -53,23 +53,41 @@ pub async fn fetch_usage(session_token: &str) -> Result<UsageSnapshot, AppError>
         }
         429 => {
             log::warn!("Ollama settings request was rate limited (HTTP 429)");
-            Err(AppError::RateLimited)
+            Err(AppError::RateLimited(None))
         }
         status @ 500..=599 => {
-            log::error!("Ollama settings request failed with server error (HTTP {status})");
-            Err(AppError::Server(
-                "Ollama is experiencing issues. Please try again later.".to_string(),
-            ))
+            let snippet = super::response_snippet(&response.text().await.unwrap_or_default());
+            log::error!(
+                "Ollama settings request failed with server error (HTTP {status}): {snippet}"
+            );
+            Err(AppError::Server(format!(
+                "Ollama is experiencing issues (HTTP {status}). Please try again later. Response: {snippet}"
+            )))
         }
         status => {
-            log::error!("Ollama settings request failed with unexpected HTTP status {status}");
+            let snippet = super::response_snippet(&response.text().await.unwrap_or_default());
+            log::error!(
+                "Ollama settings request failed with unexpected HTTP status {status}: {snippet}"
+            );
             Err(AppError::Server(format!(
-                "Unexpected error (HTTP {status}). Please try again."
+                "Unexpected Ollama error (HTTP {status}). Please try again. Response: {snippet}"
             )))
         }
     }
 }
 
+/// Parse the settings page of a successful response.
+pub(super) fn parse_usage(html: &str) -> Result<UsageSnapshot, AppError> {
+    let data = parse_ollama_settings(html)?;
+    Ok(UsageSnapshot {
+        provider: ProviderKind::Ollama,
+        windows: build_windows(&data),
+        account_email: data.account_email,
+        plan_type: data.plan_type,
+        rate_limit: None,
+    })
+}
+
 pub fn get_status(session_token: Option<&str>) -> ProviderStatus {
     let configured = session_token.is_some();
     ProviderStatus {
@@ -222,6 +240,7 @@ fn build_windows(data: &OllamaSettingsData) -> Vec<UsageWindow> {
             label: "Session".to_string(),
             utilization,
             resets_at: data.session_resets_at.clone(),
+            resets_at_epoch_ms: reset_epoch_ms(data.session_resets_at.as_deref()),
             window_duration_seconds: None,
         });
     }
@@ -232,6 +251,7 @@ fn build_windows(data: &OllamaSettingsData) -> Vec<UsageWindow> {
             label: "Weekly".to_string(),
             utilization,
             resets_at: data.weekly_resets_at.clone(),
+            resets_at_epoch_ms: reset_epoch_ms(data.weekly_resets_at.as_deref()),
             window_duration_seconds: None,
         });
     }
@@ -0,0 +1,233 @@
+//! App Lock
+//!
+//! With a passphrase set, the app starts locked: credentials stay in the
+//! keychain and nothing is fetched until `unlock` checks the passphrase against
+//! its Argon2 hash. Locking again, by hand or after `auto_lock_minutes` away
+//! from the window, drops the session tokens from memory.
+
+use crate::commands::run_blocking;
+use crate::credentials;
+use crate::error::AppError;
+use crate::types::{AppLockChanged, AppState};
+use argon2::Argon2;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use rand::RngExt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use tauri_specta::Event;
+use zeroize::Zeroizing;
+
+/// Settings store keys.
+pub const PASSPHRASE_HASH_KEY: &str = "app_lock_hash";
+pub const AUTO_LOCK_MINUTES_KEY: &str = "app_lock_auto_lock_minutes";
+
+const AUTO_LOCK_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Wrong passphrases allowed before each check waits, doubling per failure.
+const FREE_ATTEMPTS: u32 = 3;
+const MAX_ATTEMPT_DELAY: Duration = Duration::from_secs(60);
+
+/// Consecutive wrong passphrases. Held for the whole check, so attempts run
+/// one at a time and each waits out the delay of the failures before it.
+static FAILED_ATTEMPTS: tokio::sync::Mutex<u32> = tokio::sync::Mutex::const_new(0);
+
+/// Last time the window gained or lost focus, or the app was unlocked.
+static LAST_ACTIVITY: LazyLock<Mutex<Instant>> = LazyLock::new(|| Mutex::new(Instant::now()));
+static WINDOW_FOCUSED: AtomicBool = AtomicBool::new(false);
+
+/// PHC string (`$argon2id$...`) for `passphrase` with a fresh random salt.
+pub fn hash_passphrase(passphrase: &str) -> Result<String, AppError> {
+    let salt_bytes: [u8; 16] = rand::rng().random();
+    let salt = SaltString::encode_b64(&salt_bytes)
+        .map_err(|e| AppError::Storage(format!("Failed to encode passphrase salt: {e}")))?;
+    Argon2::default()
+        .hash_password(passphrase.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| AppError::Storage(format!("Failed to hash passphrase: {e}")))
+}
+
+/// False for a wrong passphrase and for a hash that doesn't parse.
+pub fn verify_passphrase(hash: &str, passphrase: &str) -> bool {
+    PasswordHash::new(hash).is_ok_and(|hash| {
+        Argon2::default()
+            .verify_password(passphrase.as_bytes(), &hash)
+            .is_ok()
+    })
+}
+
+/// How long a passphrase check waits after `failures` wrong passphrases in a row.
+fn attempt_delay(failures: u32) -> Duration {
+    match failures.checked_sub(FREE_ATTEMPTS) {
+        None => Duration::ZERO,
+        Some(excess) => Duration::from_secs(1u64 << excess.min(6)).min(MAX_ATTEMPT_DELAY),
+    }
+}
+
+/// Check `passphrase` against `hash` off the async runtime, throttled after
+/// repeated failures.
+pub async fn check_passphrase(hash: String, passphrase: &str) -> Result<(), AppError> {
+    let mut failures = FAILED_ATTEMPTS.lock().await;
+    tokio::time::sleep(attempt_delay(*failures)).await;
+
+    let passphrase = Zeroizing::new(passphrase.to_string());
+    let valid = run_blocking(move || Ok(verify_passphrase(&hash, &passphrase))).await?;
+    if valid {
+        *failures = 0;
+        Ok(())
+    } else {
+        *failures = failures.saturating_add(1);
+        log::warn!("Wrong app lock passphrase ({} in a row)", *failures);
+        Err(AppError::WrongPassphrase)
+    }
+}
+
+/// Track window focus; the auto-lock timer runs while the window isn't focused.
+pub fn note_window_focus(focused: bool) {
+    WINDOW_FOCUSED.store(focused, Ordering::Relaxed);
+    note_activity();
+}
+
+fn note_activity() {
+    *LAST_ACTIVITY.lock().unwrap_or_else(|e| e.into_inner()) = Instant::now();
+}
+
+/// True when the window has been away for at least `auto_lock_minutes`.
+pub fn auto_lock_due(auto_lock_minutes: Option<u32>, focused: bool, idle: Duration) -> bool {
+    match auto_lock_minutes {
+        Some(minutes) if minutes > 0 => {
+            !focused && idle >= Duration::from_secs(u64::from(minutes) * 60)
+        }
+        _ => false,
+    }
+}
+
+/// Drop the session tokens from memory and stop polling.
+pub async fn lock<R: tauri::Runtime>(app: &tauri::AppHandle<R>, state: &AppState) {
+    let mut config = state.config.lock().await;
+    if config.locked {
+        return;
+    }
+    config.locked = true;
+    config.session_token = None;
+    config.ollama_session_token = None;
+    drop(config);
+
+    log::info!("App locked");
+    let _ = state.restart_tx.send(());
+    let _ = AppLockChanged { locked: true }.emit(app);
+}
+
+/// Check `passphrase`, then load the credentials from the keychain and resume
+/// polling.
+pub async fn unlock<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &AppState,
+    passphrase: &str,
+) -> Result<(), AppError> {
+    // Neither the passphrase check nor the keychain reads hold the config
+    // lock, so the refresh loop and other commands aren't blocked meanwhile
+    let Some(hash) = state.config.lock().await.app_lock_hash.clone() else {
+        return Ok(());
+    };
+    check_passphrase(hash, passphrase).await?;
+    if !state.config.lock().await.locked {
+        return Ok(());
+    }
+
+    let ((organization_id, session_token, session_expires_at), ollama_session_token) =
+        run_blocking(|| {
+            Ok((
+                credentials::load_credentials()
+                    .map(|(id, token, expires_at)| (Some(id), Some(token), expires_at))
+                    .unwrap_or_default(),
+                credentials::load_ollama_credentials(),
+            ))
+        })
+        .await?;
+
+    let mut config = state.config.lock().await;
+    if !config.locked {
+        return Ok(());
+    }
+    if let Some(org_id) = &organization_id {
+        crate::commands::set_history_organization(app, org_id);
+    }
+    config.organization_id = organization_id;
+    config.session_token = session_token;
+    config.session_expires_at = session_expires_at;
+    config.ollama_session_token = ollama_session_token;
+    config.locked = false;
+    crate::tray::update_organization_menu(app, &config);
+    drop(config);
+
+    if let Some(issue) = credentials::keychain_issue() {
+        let _ = issue.emit(app);
+    }
+
+    note_activity();
+    log::info!("App unlocked");
+    let _ = state.restart_tx.send(());
+    let _ = AppLockChanged { locked: false }.emit(app);
+    Ok(())
+}
+
+/// Lock the app once the window has been away for the auto-lock timeout.
+pub async fn auto_lock_loop<R: tauri::Runtime>(app: tauri::AppHandle<R>, state: Arc<AppState>) {
+    loop {
+        tokio::time::sleep(AUTO_LOCK_CHECK_INTERVAL).await;
+
+        let config = state.config.lock().await;
+        let armed = config.app_lock_hash.is_some() && !config.locked;
+        let auto_lock_minutes = config.auto_lock_minutes;
+        drop(config);
+
+        let idle = LAST_ACTIVITY
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .elapsed();
+        if armed
+            && auto_lock_due(
+                auto_lock_minutes,
+                WINDOW_FOCUSED.load(Ordering::Relaxed),
+                idle,
+            )
+        {
+            lock(&app, &state).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn verifies_only_the_hashed_passphrase() {
+        let hash = hash_passphrase("correct horse").unwrap();
+        assert!(hash.starts_with("$argon2id$"));
+        assert!(verify_passphrase(&hash, "correct horse"));
+        assert!(!verify_passphrase(&hash, "battery staple"));
+        assert!(!verify_passphrase("not a hash", "correct horse"));
+        assert_ne!(hash, hash_passphrase("correct horse").unwrap());
+    }
+
+    #[test]
+    fn delays_checks_after_repeated_failures() {
+        assert_eq!(attempt_delay(0), Duration::ZERO);
+        assert_eq!(attempt_delay(FREE_ATTEMPTS - 1), Duration::ZERO);
+        assert_eq!(attempt_delay(FREE_ATTEMPTS), Duration::from_secs(1));
+        assert_eq!(attempt_delay(FREE_ATTEMPTS + 2), Duration::from_secs(4));
+        assert_eq!(attempt_delay(u32::MAX), MAX_ATTEMPT_DELAY);
+    }
+
+    #[test]
+    fn auto_locks_after_timeout_away_from_window() {
+        let minutes = Duration::from_secs(60);
+        assert!(auto_lock_due(Some(5), false, minutes * 5));
+        assert!(!auto_lock_due(Some(5), false, minutes * 4));
+        assert!(!auto_lock_due(Some(5), true, minutes * 60));
+        assert!(!auto_lock_due(None, false, minutes * 60));
+        assert!(!auto_lock_due(Some(0), false, minutes * 60));
+    }
+}
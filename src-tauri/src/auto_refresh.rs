@@ -1,31 +1,102 @@
-use crate::api::fetch_usage_for_provider;
+use crate::clock::clock_jumped;
+use crate::commands::run_blocking;
 use crate::error::AppError;
-use crate::history::save_usage_snapshot;
-use crate::notifications::{process_notifications, reset_notification_state_if_needed};
-use crate::tray::update_tray_tooltip;
-use crate::types::{AppState, UsageErrorEvent, UsageUpdateEvent};
-use chrono::{Timelike, Utc};
+use crate::history::{
+    get_burn_rate, latest_resets_at, sample_due, save_member_usage, save_organization_snapshot,
+    save_usage_snapshot, sync_local_usage,
+};
+use crate::local_usage::{ActiveSessions, get_active_sessions};
+use crate::notifications::{
+    next_reset, notify_access_denied, notify_budgets, notify_model_advisory, notify_recoveries,
+    notify_stale_data, notify_token_expiring, notify_weekly_summary, process_notifications,
+    reset_notification_state_if_needed,
+};
+use crate::pricing::get_cost_today;
+use crate::secret::SecretString;
+use crate::tray::{flash_on_critical_usage, update_tray_tooltip};
+use crate::types::{
+    ActiveSessionsChanged, AppState, FetchSource, HistoryAppended, HistoryChanged, LastFetchResult,
+    OrganizationUsage, PausedReason, ProviderKind, RateLimitInfo, RateLimitUpdated,
+    RefreshLoopRestarted, StaleDataReason, StaleDataSuspected, UsageError, UsageSnapshot,
+    UsageUpdated, monitoring_enabled,
+};
+use chrono::{DateTime, Timelike, Utc};
 use rand::RngExt;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::sync::Arc;
-use tauri::Emitter;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri_specta::Event;
 
 /// Result of a fetch attempt for backoff handling
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FetchResult {
     Success,
     RateLimited,
+    Forbidden,
     OtherError,
     NoCredentials,
 }
 
+/// Wait before respawning a refresh loop that panicked, so a panic on every
+/// fetch doesn't spin.
+const LOOP_RESTART_DELAY: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// The supervisor running `auto_refresh_loop`, so shutdown can stop it.
+static REFRESH_LOOP: std::sync::Mutex<Option<tauri::async_runtime::JoinHandle<()>>> =
+    std::sync::Mutex::new(None);
+
+/// Set by the wake monitor before it restarts the loop, so the next fetch is
+/// recorded as a wake rather than a manual refresh.
+static WAKE_PENDING: AtomicBool = AtomicBool::new(false);
+
+/// Mark the next restart signal as coming from a system resume.
+pub fn note_wake() {
+    WAKE_PENDING.store(true, Ordering::Relaxed);
+}
+
+/// The source of a fetch started by a restart signal.
+fn restart_source() -> FetchSource {
+    if WAKE_PENDING.swap(false, Ordering::Relaxed) {
+        FetchSource::Wake
+    } else {
+        FetchSource::Manual
+    }
+}
+
+/// A utilization rise of at least this many points in one fetch is treated as a
+/// possible API glitch when `Preferences.reject_usage_spikes` is on.
+const SPIKE_JUMP_PERCENT: f64 = 40.0;
+
+/// Unchanged usage for this long while Claude Code is in use looks stale...
+const STALE_UNCHANGED_MINUTES: i64 = 60;
+/// ...once Claude Code has been seen active for at least this long of it.
+const STALE_ACTIVITY_MINUTES: i64 = 30;
+/// A window still reporting usage this long after its reset looks stale.
+const STALE_RESET_GRACE_MINUTES: i64 = 30;
+
 /// Backoff configuration
 pub const INITIAL_BACKOFF_SECS: u64 = 30; // Start with 30 seconds
 pub const MAX_BACKOFF_SECS: u64 = 300; // Cap at 5 minutes
 pub const BACKOFF_MULTIPLIER: u64 = 2; // Double each time
 
-/// Hourly refresh configuration
-pub const HOURLY_REFRESH_INITIAL_GAP_SECS: u64 = 5; // Wait 5 seconds after hour starts
-pub const HOURLY_REFRESH_JITTER_MAX_SECS: u64 = 55; // Add up to 55 seconds of jitter
+/// Polling interval during the near-reset window
+pub const NEAR_RESET_INTERVAL_MINUTES: i64 = 1;
+
+/// Idle suspension: how often to look for local sessions while paused, and how
+/// often to still fetch so usage from the web app is noticed
+pub const IDLE_CHECK_SECS: u64 = 60;
+pub const IDLE_PROBE_SECS: i64 = 3600;
+
+/// Hourly refresh defaults; the bounds accepted from settings are in `validation`
+pub const HOURLY_REFRESH_INITIAL_GAP_SECS: u32 = 5; // Wait 5 seconds after hour starts
+pub const HOURLY_REFRESH_JITTER_MAX_SECS: u32 = 55; // Add up to 55 seconds of jitter
+
+/// Startup jitter default; the bound accepted from settings is in `validation`
+pub const STARTUP_JITTER_MAX_SECS: u32 = 30; // Wait up to 30 seconds before the first fetch
+
+/// Settings store key of the backoff in force, so a restart doesn't retry early.
+const BACKOFF_STATE_KEY: &str = "backoff_state";
 
 /// Calculate the next backoff duration based on the current backoff and fetch result.
 /// Returns the new backoff value in seconds (0 means no backoff active).
@@ -43,6 +114,10 @@ pub fn calculate_next_backoff(current_backoff: u64, result: FetchResult) -> u64
                 (current_backoff * BACKOFF_MULTIPLIER).min(MAX_BACKOFF_SECS)
             }
         }
+        FetchResult::Forbidden => {
+            // Retrying sooner won't fix permissions, so fall back to the regular interval
+            0
+        }
         FetchResult::OtherError | FetchResult::NoCredentials => {
             // Don't change backoff for other errors
             current_backoff
@@ -50,18 +125,231 @@ pub fn calculate_next_backoff(current_backoff: u64, result: FetchResult) -> u64
     }
 }
 
+/// Delay the server asked for: `Retry-After`, or the time until the quota
+/// resets once it is exhausted. Never shortens the exponential backoff.
+pub fn server_requested_delay(rate_limit: &RateLimitInfo, now: DateTime<Utc>) -> Option<u64> {
+    if let Some(seconds) = rate_limit.retry_after_seconds {
+        return Some(seconds);
+    }
+    if rate_limit.remaining != Some(0) {
+        return None;
+    }
+    let reset_at = DateTime::parse_from_rfc3339(rate_limit.reset_at.as_deref()?).ok()?;
+    Some(reset_at.signed_duration_since(now).num_seconds().max(0) as u64)
+}
+
+/// The next backoff after a 429, honouring any delay the server asked for.
+pub fn rate_limited_backoff(
+    current_backoff: u64,
+    rate_limit: Option<&RateLimitInfo>,
+    now: DateTime<Utc>,
+) -> u64 {
+    let backoff = calculate_next_backoff(current_backoff, FetchResult::RateLimited);
+    rate_limit
+        .and_then(|info| server_requested_delay(info, now))
+        .map_or(backoff, |delay| backoff.max(delay))
+}
+
 /// Check if the auto-refresh loop should be active based on config.
 pub fn should_refresh(enabled: bool, has_credentials: bool) -> bool {
     enabled && has_credentials
 }
 
+/// True when idle suspension is on and nothing happened for `idle_hours`.
+pub fn is_idle(idle_hours: Option<u32>, last_activity_ms: i64, now_ms: i64) -> bool {
+    idle_hours
+        .is_some_and(|hours| hours > 0 && now_ms - last_activity_ms >= hours as i64 * 3_600_000)
+}
+
+/// Whether any window's utilization went up, i.e. the provider was used.
+/// Drops are resets rather than activity.
+pub fn usage_increased(previous: &UsageSnapshot, current: &UsageSnapshot) -> bool {
+    previous.provider == current.provider
+        && current.windows.iter().any(|window| {
+            previous
+                .windows
+                .iter()
+                .find(|previous_window| previous_window.key == window.key)
+                .is_some_and(|previous_window| window.utilization > previous_window.utilization)
+        })
+}
+
+/// Last accepted snapshot, and whether the latest fetch was held back as a spike.
+#[derive(Debug, Default)]
+pub struct SpikeFilter {
+    baseline: Option<UsageSnapshot>,
+    holding: bool,
+}
+
+impl SpikeFilter {
+    /// Returns the last accepted snapshot to use instead of `usage` when a window
+    /// jumped by `SPIKE_JUMP_PERCENT` or more, or None to accept `usage`. Only one
+    /// fetch in a row is held back: the next one is accepted whether the jump
+    /// reverted or persisted.
+    pub fn check(&mut self, usage: &UsageSnapshot, enabled: bool) -> Option<UsageSnapshot> {
+        let baseline = self
+            .baseline
+            .as_ref()
+            .filter(|baseline| enabled && baseline.provider == usage.provider);
+
+        if let Some(baseline) = baseline {
+            if self.holding {
+                log::info!(
+                    "Usage after a held-back spike: {}",
+                    if spiking_window(baseline, usage).is_some() {
+                        "jump confirmed"
+                    } else {
+                        "reverted"
+                    }
+                );
+            } else if let Some((window, previous)) = spiking_window(baseline, usage) {
+                log::warn!(
+                    "Holding back implausible jump for provider={} window={}: {:.1}% -> {:.1}%",
+                    usage.provider.as_str(),
+                    window.key,
+                    previous,
+                    window.utilization
+                );
+                self.holding = true;
+                return Some(baseline.clone());
+            }
+        }
+
+        self.holding = false;
+        self.baseline = Some(usage.clone());
+        None
+    }
+}
+
+/// Notices usage that stops changing when it shouldn't: while Claude Code is
+/// in use, or past a window's reset. Either hints at a token serving cached
+/// data or belonging to another account. Warns once per unchanged stretch.
+#[derive(Debug, Default)]
+pub struct StaleDataWatchdog {
+    /// Provider and each window's utilization, as last seen.
+    fingerprint: Option<(ProviderKind, Vec<(String, f64)>)>,
+    unchanged_since: Option<DateTime<Utc>>,
+    /// First time Claude Code was seen active since usage last changed.
+    active_since: Option<DateTime<Utc>>,
+    warned: bool,
+}
+
+impl StaleDataWatchdog {
+    pub fn observe(
+        &mut self,
+        usage: &UsageSnapshot,
+        claude_code_active: bool,
+        now: DateTime<Utc>,
+    ) -> Option<StaleDataSuspected> {
+        let fingerprint = (
+            usage.provider,
+            usage
+                .windows
+                .iter()
+                .map(|window| (window.key.clone(), window.utilization))
+                .collect::<Vec<_>>(),
+        );
+        if self.fingerprint.as_ref() != Some(&fingerprint) {
+            *self = Self {
+                fingerprint: Some(fingerprint),
+                unchanged_since: Some(now),
+                ..Self::default()
+            };
+            return None;
+        }
+        let unchanged_since = self.unchanged_since?;
+        if claude_code_active && usage.provider == ProviderKind::Claude {
+            self.active_since.get_or_insert(now);
+        }
+        if self.warned {
+            return None;
+        }
+
+        let reset_passed = usage.windows.iter().find(|window| {
+            window.utilization > 0.0
+                && window
+                    .resets_at
+                    .as_deref()
+                    .and_then(|resets_at| DateTime::parse_from_rfc3339(resets_at).ok())
+                    .is_some_and(|reset| {
+                        now - reset.with_timezone(&Utc)
+                            > chrono::Duration::minutes(STALE_RESET_GRACE_MINUTES)
+                    })
+        });
+        let active_while_unchanged = now - unchanged_since
+            >= chrono::Duration::minutes(STALE_UNCHANGED_MINUTES)
+            && self.active_since.is_some_and(|since| {
+                now - since >= chrono::Duration::minutes(STALE_ACTIVITY_MINUTES)
+            });
+
+        let (reason, window) = match reset_passed {
+            Some(window) => (StaleDataReason::ResetPassed, Some(window.label.clone())),
+            None if active_while_unchanged => (StaleDataReason::ActiveSessions, None),
+            None => return None,
+        };
+        self.warned = true;
+        Some(StaleDataSuspected {
+            provider: usage.provider,
+            reason,
+            window,
+            unchanged_since: unchanged_since.to_rfc3339(),
+        })
+    }
+}
+
+/// The first window that rose by `SPIKE_JUMP_PERCENT` or more, with its
+/// previous utilization. Drops are resets and never count.
+fn spiking_window<'a>(
+    previous: &UsageSnapshot,
+    current: &'a UsageSnapshot,
+) -> Option<(&'a crate::types::UsageWindow, f64)> {
+    current.windows.iter().find_map(|window| {
+        let before = previous.windows.iter().find(|w| w.key == window.key)?;
+        (window.utilization - before.utilization >= SPIKE_JUMP_PERCENT)
+            .then_some((window, before.utilization))
+    })
+}
+
+/// Change of each window's utilization since `previous`, keyed by window key.
+pub fn usage_deltas(
+    previous: Option<&UsageSnapshot>,
+    current: &UsageSnapshot,
+) -> BTreeMap<String, f64> {
+    let Some(previous) = previous else {
+        return BTreeMap::new();
+    };
+    current
+        .windows
+        .iter()
+        .filter_map(|window| {
+            let before = previous.windows.iter().find(|w| w.key == window.key)?;
+            Some((window.key.clone(), window.utilization - before.utilization))
+        })
+        .collect()
+}
+
+/// Pick the refresh interval, tightening it while Claude Code sessions are active
+/// if the user configured a separate active-session interval.
+pub fn effective_interval_minutes(
+    interval_minutes: u32,
+    active_session_interval_minutes: Option<u32>,
+    active_sessions: u32,
+) -> u32 {
+    match active_session_interval_minutes {
+        Some(active_interval) if active_sessions > 0 => interval_minutes.min(active_interval),
+        _ => interval_minutes,
+    }
+}
+
 /// Calculate seconds until the next hour starts, plus initial gap and jitter.
 /// Returns None if hourly refresh is disabled.
 /// `seconds_into_hour` is the number of seconds elapsed since the current hour started (0-3599).
-/// `jitter` is the random jitter to add (0-55 seconds typically).
+/// `gap_secs` is the configured wait after the hour starts (5 seconds by default).
+/// `jitter` is the random jitter to add (0-55 seconds by default).
 pub fn calculate_hourly_refresh_delay_with_params(
     hourly_refresh_enabled: bool,
     seconds_into_hour: u64,
+    gap_secs: u32,
     jitter: u64,
 ) -> Option<u64> {
     if !hourly_refresh_enabled {
@@ -69,40 +357,76 @@ pub fn calculate_hourly_refresh_delay_with_params(
     }
 
     let seconds_until_next_hour = 3600 - seconds_into_hour;
-    let total_delay = seconds_until_next_hour + HOURLY_REFRESH_INITIAL_GAP_SECS + jitter;
+    let total_delay = seconds_until_next_hour + gap_secs as u64 + jitter;
 
     Some(total_delay)
 }
 
 /// Calculate seconds until the next hour starts, plus initial gap and random jitter.
 /// Returns None if hourly refresh is disabled.
-pub fn calculate_hourly_refresh_delay(hourly_refresh_enabled: bool) -> Option<u64> {
+pub fn calculate_hourly_refresh_delay(
+    hourly_refresh_enabled: bool,
+    gap_secs: u32,
+    jitter_max_secs: u32,
+) -> Option<u64> {
     if !hourly_refresh_enabled {
         return None;
     }
 
     let now = Utc::now();
     let seconds_into_hour = now.minute() as u64 * 60 + now.second() as u64;
-    let jitter = rand::rng().random_range(0..=HOURLY_REFRESH_JITTER_MAX_SECS);
+    let jitter = rand::rng().random_range(0..=jitter_max_secs as u64);
 
-    calculate_hourly_refresh_delay_with_params(true, seconds_into_hour, jitter)
+    calculate_hourly_refresh_delay_with_params(true, seconds_into_hour, gap_secs, jitter)
+}
+
+/// Random delay of up to `jitter_max_secs` before the first fetch after launch
+/// or a wake, so machines that start together don't hit the API at once.
+fn startup_jitter(jitter_max_secs: u32) -> std::time::Duration {
+    std::time::Duration::from_millis(
+        rand::rng().random_range(0..=u64::from(jitter_max_secs) * 1000),
+    )
+}
+
+/// Upcoming reset of the shortest usage window, polled more often as it nears.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NearReset {
+    /// When the window resets, in milliseconds.
+    pub reset_at_ms: i64,
+    /// How many minutes before the reset to switch to `NEAR_RESET_INTERVAL_MINUTES`.
+    pub window_minutes: u32,
 }
 
 /// Calculate the next refresh timestamp in milliseconds.
 /// Takes into account both regular interval and hourly refresh (whichever is sooner).
 /// `now_ms` is the current timestamp in milliseconds.
 /// `hourly_delay_secs` is the pre-calculated hourly refresh delay (if any).
+/// `near_reset` tightens polling in the last minutes before a reset; once the
+/// reset has passed the regular interval applies again.
 pub fn calculate_next_refresh_at(
     enabled: bool,
     interval_minutes: u32,
     now_ms: i64,
     hourly_delay_secs: Option<u64>,
+    near_reset: Option<NearReset>,
 ) -> Option<i64> {
     if !enabled {
         return None;
     }
 
-    let regular_next = now_ms + (interval_minutes as i64 * 60 * 1000);
+    let mut regular_next = now_ms + (interval_minutes as i64 * 60 * 1000);
+
+    // A reset in the past means the window already rolled over; relax again
+    if let Some(near_reset) = near_reset.filter(|near_reset| now_ms < near_reset.reset_at_ms) {
+        let turbo_start = near_reset.reset_at_ms - near_reset.window_minutes as i64 * 60 * 1000;
+        let near_reset_next = if now_ms >= turbo_start {
+            now_ms + NEAR_RESET_INTERVAL_MINUTES * 60 * 1000
+        } else {
+            // Wake up in time to start polling faster
+            turbo_start
+        };
+        regular_next = regular_next.min(near_reset_next);
+    }
 
     // If hourly refresh delay is provided, use whichever is sooner
     if let Some(delay_secs) = hourly_delay_secs {
@@ -113,16 +437,579 @@ pub fn calculate_next_refresh_at(
     }
 }
 
+/// The source of the fetch scheduled for `next_refresh_at`: hourly when the
+/// hourly refresh comes before the regular interval.
+fn scheduled_source(
+    next_refresh_at: Option<i64>,
+    now_ms: i64,
+    hourly_delay_secs: Option<u64>,
+) -> FetchSource {
+    match (next_refresh_at, hourly_delay_secs) {
+        (Some(next_at), Some(delay_secs)) if next_at == now_ms + delay_secs as i64 * 1000 => {
+            FetchSource::Hourly
+        }
+        _ => FetchSource::Scheduled,
+    }
+}
+
+/// The soonest stored reset for the provider, when near-reset polling is on.
+fn near_reset(
+    provider: crate::types::ProviderKind,
+    window_minutes: Option<u32>,
+) -> Option<NearReset> {
+    let window_minutes = window_minutes.filter(|minutes| *minutes > 0)?;
+    let resets_at = latest_resets_at(provider)
+        .inspect_err(|e| log::debug!("Failed to read stored reset times: {e}"))
+        .ok()?;
+    let next_reset = next_reset(resets_at.iter().map(String::as_str), crate::clock::now())?;
+    let reset_at = DateTime::parse_from_rfc3339(&next_reset).ok()?;
+    // Reset times are on the provider's clock; the schedule runs on the local one
+    let skew_ms = (crate::clock::now() - Utc::now()).num_milliseconds();
+    Some(NearReset {
+        reset_at_ms: reset_at.timestamp_millis() - skew_ms,
+        window_minutes,
+    })
+}
+
 /// Result of a fetch operation, including the next refresh timestamp
 pub struct FetchOutput {
     pub result: FetchResult,
+    pub usage: Option<UsageSnapshot>,
     pub next_refresh_at: Option<i64>,
+    pub rate_limit: Option<RateLimitInfo>,
+    /// What the fetch at `next_refresh_at` will be recorded as.
+    pub next_source: FetchSource,
+    /// Why scheduled fetching is paused after this fetch, before backoff.
+    pub paused_reason: Option<PausedReason>,
+}
+
+/// Why scheduled fetching is paused after a fetch that failed with `error`
+/// (None on success), given the reason before it. An error that doesn't
+/// change backoff keeps a backoff going.
+fn paused_reason_after_fetch(
+    enabled: bool,
+    previous: Option<PausedReason>,
+    error: Option<&AppError>,
+) -> Option<PausedReason> {
+    if !enabled {
+        return Some(PausedReason::UserPause);
+    }
+    match error {
+        None => None,
+        Some(AppError::Forbidden | AppError::InvalidToken) => Some(PausedReason::AuthExpired),
+        Some(AppError::RateLimited(_)) => Some(PausedReason::Backoff),
+        Some(_) => previous.filter(|reason| *reason == PausedReason::Backoff),
+    }
+}
+
+/// A backoff as persisted in the settings store.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct PersistedBackoff {
+    /// Current step of the exponential backoff, so the next 429 escalates it.
+    backoff_secs: u64,
+    until: DateTime<Utc>,
+}
+
+/// Publish the loop's backoff as `AppState.backoff_until`, so manual refreshes
+/// can respect it, and persist it so a restart doesn't retry early. Returns
+/// when it ends.
+async fn record_backoff(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    backoff_secs: u64,
+) -> Option<DateTime<Utc>> {
+    let until =
+        (backoff_secs > 0).then(|| Utc::now() + chrono::Duration::seconds(backoff_secs as i64));
+    let previous = std::mem::replace(&mut *state.backoff_until.lock().await, until);
+    if previous.is_none() && until.is_none() {
+        return until;
+    }
+    match crate::commands::settings_store(app) {
+        Ok(store) => match until {
+            Some(until) => store.set(
+                BACKOFF_STATE_KEY,
+                serde_json::json!(PersistedBackoff {
+                    backoff_secs,
+                    until
+                }),
+            ),
+            None => {
+                store.delete(BACKOFF_STATE_KEY);
+            }
+        },
+        Err(e) => log::warn!("Failed to persist backoff: {e}"),
+    }
+    until
+}
+
+/// Back off after a manual refresh was rate limited, as the loop would have,
+/// so the restart that follows doesn't fetch again straight away. Returns the
+/// backoff in seconds.
+pub async fn record_manual_rate_limit(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    rate_limit: Option<&RateLimitInfo>,
+) -> u64 {
+    let backoff_secs = rate_limited_backoff(0, rate_limit, crate::clock::now());
+    record_backoff(app, state, backoff_secs).await;
+    *state.paused_reason.lock().await = Some(PausedReason::Backoff);
+    backoff_secs
+}
+
+/// User action clears the loop's backoff, except one set by someone else since
+/// the loop last recorded its own (a rate-limited manual refresh): that one is
+/// resumed. `loop_until` is the end the loop last recorded.
+async fn backoff_after_restart(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    loop_until: Option<DateTime<Utc>>,
+) -> Option<PersistedBackoff> {
+    let current = *state.backoff_until.lock().await;
+    if set_outside_loop(current, loop_until) {
+        return load_backoff(app, Utc::now());
+    }
+    record_backoff(app, state, 0).await;
+    None
+}
+
+fn set_outside_loop(current: Option<DateTime<Utc>>, loop_until: Option<DateTime<Utc>>) -> bool {
+    current.is_some() && current != loop_until
+}
+
+/// The backoff persisted by a previous run, if it is still in force.
+fn load_backoff(app: &tauri::AppHandle, now: DateTime<Utc>) -> Option<PersistedBackoff> {
+    let persisted = crate::commands::settings_store(app)
+        .ok()?
+        .get(BACKOFF_STATE_KEY)
+        .and_then(|value| serde_json::from_value(value).ok());
+    restorable_backoff(persisted, now)
+}
+
+/// `persisted` if it hasn't ended by `now`. The end is capped at `now` plus the
+/// backoff step, so a clock set back while the app was closed can't stretch it.
+fn restorable_backoff(
+    persisted: Option<PersistedBackoff>,
+    now: DateTime<Utc>,
+) -> Option<PersistedBackoff> {
+    let persisted = persisted.filter(|backoff| backoff.backoff_secs > 0 && backoff.until > now)?;
+    let longest = now + chrono::Duration::seconds(persisted.backoff_secs as i64);
+    Some(PersistedBackoff {
+        until: persisted.until.min(longest),
+        ..persisted
+    })
+}
+
+/// Whole seconds left of a backoff ending at `backoff_until`, if it hasn't ended.
+pub fn backoff_remaining(backoff_until: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Option<u64> {
+    let remaining_ms = (backoff_until? - now).num_milliseconds();
+    (remaining_ms > 0).then(|| (remaining_ms as u64).div_ceil(1000))
+}
+
+/// The refresh loop's current paused reason, for events emitted outside it.
+pub async fn paused_reason(state: &AppState) -> Option<PausedReason> {
+    *state.paused_reason.lock().await
+}
+
+/// Update the tray, history and notifications for a new snapshot.
+pub async fn apply_usage(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    usage: &UsageSnapshot,
+    source: FetchSource,
+) {
+    *state.last_success_at.lock().await = Some(Utc::now());
+    *state.last_applied_usage.lock().await = Some(usage.clone());
+
+    // Save usage snapshot for analytics, unless too recent or unchanged, and hand
+    // the new points to open charts (ignore errors silently). Saved before the
+    // tray update so the burn rate includes this sample.
+    let config = state.config.lock().await;
+    let (sample_interval, skip_unchanged) = (
+        config.history_sample_interval_minutes,
+        config.skip_unchanged_samples,
+    );
+    // Demo data stays out of the sync folder
+    let history_sync_folder = config
+        .history_sync_folder
+        .clone()
+        .filter(|_| !crate::demo::is_enabled());
+    let machine_id = config.machine_id.clone();
+    let org_id = match usage.provider {
+        ProviderKind::Claude => config.organization_id.clone(),
+        ProviderKind::Codex | ProviderKind::Ollama => None,
+    };
+    drop(config);
+    let points = if sample_due(usage, sample_interval, skip_unchanged).unwrap_or(true) {
+        save_usage_snapshot(usage, org_id.as_deref(), source).unwrap_or_default()
+    } else {
+        Vec::new()
+    };
+    // Share the new points through the sync folder and pick up other machines'
+    if let Some(folder) = history_sync_folder {
+        let synced = points.clone();
+        let organization_id = org_id.unwrap_or_default();
+        let sync = run_blocking(move || {
+            crate::history_sync::sync(
+                std::path::Path::new(&folder),
+                &machine_id,
+                &organization_id,
+                &synced,
+            )
+        });
+        match sync.await {
+            Ok(0) => {}
+            Ok(imported) => {
+                log::info!("Imported {imported} history points from the sync folder");
+                let _ = HistoryChanged.emit(app);
+            }
+            Err(e) => log::warn!("History sync failed: {e}"),
+        }
+    }
+    if !points.is_empty() {
+        let _ = HistoryAppended { points }.emit(app);
+    }
+
+    refresh_tray_tooltip(app, state).await;
+    let preferences = state.preferences.lock().await;
+    let severity = preferences.severity.clone();
+    let reduce_motion = preferences.reduce_motion;
+    drop(preferences);
+    flash_on_critical_usage(app, usage, &severity, !reduce_motion);
+
+    let config = state.config.lock().await;
+    let (model_advisory, weekly_summary_enabled) =
+        (config.model_advisory, config.weekly_summary_enabled);
+    let budgets = config.budgets.clone();
+    drop(config);
+
+    // Process notifications
+    let notification_settings = state.notification_settings.lock().await;
+    let mut notification_state = state.notification_state.lock().await;
+
+    // Announce recoveries before a reset clears the fired thresholds
+    let recovered_state = notify_recoveries(
+        app,
+        usage,
+        &notification_settings,
+        &severity,
+        &notification_state,
+    );
+    *notification_state = recovered_state;
+
+    // Check for usage resets and clear notification state if needed
+    let reset_state = reset_notification_state_if_needed(usage, &notification_state);
+    *notification_state = reset_state;
+
+    // Process notifications and update state
+    let new_state = process_notifications(
+        app,
+        usage,
+        &notification_settings,
+        &severity,
+        &notification_state,
+    );
+    *notification_state = new_state;
+
+    *notification_state = notify_model_advisory(
+        app,
+        usage,
+        &model_advisory,
+        &notification_settings,
+        &notification_state,
+    );
+
+    *notification_state = notify_budgets(
+        app,
+        usage,
+        &budgets,
+        &notification_settings,
+        &notification_state,
+    );
+
+    *notification_state = notify_weekly_summary(
+        app,
+        usage,
+        weekly_summary_enabled,
+        &notification_settings,
+        &notification_state,
+    );
+}
+
+/// Keep a session key claude.ai rotated `used_token` to, so polling survives
+/// the old one expiring without the user pasting a new token. Skipped when the
+/// stored credential no longer is `used_token`: the user saved another token,
+/// or a concurrent request already persisted its rotation.
+async fn persist_rotated_session_key(
+    state: &AppState,
+    used_token: &SecretString,
+    rotated: crate::api::RotatedSessionKey,
+) {
+    let config = state.config.lock().await;
+    if config.session_token.as_ref() != Some(used_token) {
+        log::info!(
+            "Ignoring a rotated Claude session key for a credential that is no longer stored"
+        );
+        return;
+    }
+    let Some(org_id) = config.organization_id.clone() else {
+        return;
+    };
+    drop(config);
+
+    let session_key = rotated.session_key.clone();
+    let expires_at = rotated.expires_at.clone();
+    let saved = run_blocking(move || {
+        crate::credentials::save_credentials(&org_id, session_key.expose(), expires_at.as_deref())
+    })
+    .await;
+    if let Err(e) = saved {
+        log::warn!("Failed to store rotated Claude session key: {e}");
+        return;
+    }
+    let mut config = state.config.lock().await;
+    if config.session_token.as_ref() == Some(used_token) {
+        config.session_token = Some(rotated.session_key);
+        config.session_expires_at = rotated.expires_at;
+    }
+}
+
+/// Fetch every additional organization concurrently, one task each, and keep
+/// the latest usage of those that answered. A failing organization is only
+/// logged; its last usage stays until it answers again. Skipped while an
+/// earlier run is still going.
+async fn fetch_additional_organizations(state: &AppState, source: FetchSource) {
+    let Ok(_running) = state.organization_fetch.try_lock() else {
+        log::debug!("Additional organizations are already being fetched");
+        return;
+    };
+    let config = state.config.lock().await;
+    let primary_org_id = config.organization_id.clone();
+    let organizations = config.additional_organizations.clone();
+    let session_token = config.session_token.clone();
+    drop(config);
+
+    let Some(session_token) = session_token else {
+        return;
+    };
+    let mut tasks = tokio::task::JoinSet::new();
+    for organization in organizations {
+        if primary_org_id.as_deref() == Some(organization.id.as_str()) {
+            continue;
+        }
+        let session_token = session_token.clone();
+        let client = state.usage_client.clone();
+        tasks.spawn(async move {
+            let response = client
+                .fetch_claude_usage(&organization.id, session_token.expose())
+                .await;
+            (organization, response)
+        });
+    }
+
+    let mut fetched = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        let (organization, response) = match joined {
+            Ok(joined) => joined,
+            Err(e) => {
+                log::warn!("Organization fetch task failed: {e}");
+                continue;
+            }
+        };
+        // Additional organizations share the primary session, so a rotation
+        // seen here moves the stored credential too
+        if let Some(rotated) = response.meta.rotated_session_key {
+            persist_rotated_session_key(state, &session_token, rotated).await;
+        }
+        match response.result {
+            Ok(usage) => {
+                if let Err(e) = save_organization_snapshot(&organization.id, &usage, source) {
+                    log::warn!(
+                        "Failed to save usage of organization {}: {e}",
+                        organization.id
+                    );
+                }
+                fetched.push(OrganizationUsage {
+                    organization,
+                    usage,
+                });
+            }
+            Err(e) => {
+                log::warn!(
+                    "Failed to fetch usage of organization {}: {e}",
+                    organization.id
+                )
+            }
+        }
+    }
+
+    // Keep configured order; fall back to the previous usage of failed ones
+    let configured = state.config.lock().await.additional_organizations.clone();
+    let mut organization_usage = state.organization_usage.lock().await;
+    let updated: Vec<OrganizationUsage> = configured
+        .iter()
+        .filter_map(|organization| {
+            fetched
+                .iter()
+                .chain(organization_usage.iter())
+                .find(|entry| entry.organization.id == organization.id)
+                .cloned()
+        })
+        .collect();
+    *organization_usage = updated;
+}
+
+/// Record the organization's per-member usage. Failures (e.g. a non-admin
+/// session) are only logged, so they never fail the fetch itself. Returns the
+/// organization when it turned out not to offer member usage, which is then
+/// no longer asked.
+async fn sync_member_usage(state: &AppState) -> Option<String> {
+    let config = state.config.lock().await;
+    let org_id = config.organization_id.clone();
+    let session_token = config.session_token.clone();
+    let unsupported = org_id.is_some() && config.member_usage_unsupported_org == org_id;
+    drop(config);
+
+    let (Some(org_id), Some(session_token)) = (org_id, session_token) else {
+        return None;
+    };
+    if unsupported {
+        return None;
+    }
+    match state
+        .usage_client
+        .fetch_claude_member_usage(&org_id, session_token.expose())
+        .await
+    {
+        Ok(Some(members)) => {
+            if let Err(e) = save_member_usage(&org_id, &members) {
+                log::warn!("Failed to save member usage: {e}");
+            }
+            None
+        }
+        Ok(None) => {
+            state.config.lock().await.member_usage_unsupported_org = Some(org_id.clone());
+            Some(org_id)
+        }
+        Err(AppError::Forbidden) => {
+            log::debug!("Member usage is only available to organization admins");
+            None
+        }
+        Err(e) => {
+            log::warn!("Failed to fetch member usage: {e}");
+            None
+        }
+    }
+}
+
+/// Remember that `org_id` has no member usage, so it isn't polled again after a
+/// restart, and tell the user why the member view stays empty.
+fn member_usage_unsupported(app: &tauri::AppHandle, org_id: &str) {
+    match crate::commands::settings_store(app) {
+        Ok(store) => store.set("member_usage_unsupported_org", org_id),
+        Err(e) => log::warn!("Failed to remember unsupported member usage: {e}"),
+    }
+    crate::notifications::notify_member_usage_unsupported(app);
+}
+
+/// Redraw the tray tooltip from the last applied usage, with today's local
+/// cost estimate and the burn rate if enabled, and its age once stale.
+async fn refresh_tray_tooltip(app: &tauri::AppHandle, state: &AppState) {
+    let Some(usage) = state.last_applied_usage.lock().await.clone() else {
+        return;
+    };
+    let preferences = state.preferences.lock().await.clone();
+    let cost_today = if preferences.show_cost_in_tray {
+        let pricing_overrides = preferences.pricing_overrides.clone();
+        run_blocking(move || get_cost_today(&pricing_overrides))
+            .await
+            .inspect_err(|e| log::warn!("Failed to estimate today's cost: {e}"))
+            .ok()
+    } else {
+        None
+    };
+    let burn_rate = match usage.windows.first() {
+        Some(window) if preferences.show_burn_rate_in_tray => {
+            get_burn_rate(usage.provider, &window.key)
+                .inspect_err(|e| log::warn!("Failed to compute burn rate: {e}"))
+                .ok()
+                .flatten()
+        }
+        _ => None,
+    };
+    let interval_minutes = state.config.lock().await.interval_minutes;
+    let updated_minutes_ago = stale_minutes(
+        *state.last_success_at.lock().await,
+        interval_minutes,
+        Utc::now(),
+    );
+    let organizations = state.organization_usage.lock().await.clone();
+    update_tray_tooltip(
+        app,
+        Some(&usage),
+        &organizations,
+        &preferences,
+        burn_rate,
+        cost_today,
+        updated_minutes_ago,
+    );
+}
+
+/// Minutes since the last successful update, once that is more than twice
+/// `interval_minutes` ago.
+pub(crate) fn stale_minutes(
+    last_success_at: Option<DateTime<Utc>>,
+    interval_minutes: u32,
+    now: DateTime<Utc>,
+) -> Option<i64> {
+    let age = (now - last_success_at?).num_minutes();
+    (age > 2 * i64::from(interval_minutes)).then_some(age)
+}
+
+/// When usage was last applied, for `usage-updated` (RFC3339).
+pub async fn last_success_at(state: &AppState) -> Option<String> {
+    state.last_success_at.lock().await.map(|at| at.to_rfc3339())
+}
+
+/// The snapshot of the last fetch, if it succeeded for `provider`.
+pub async fn last_fetched_usage(
+    state: &AppState,
+    provider: crate::types::ProviderKind,
+) -> Option<UsageSnapshot> {
+    state
+        .last_fetch
+        .lock()
+        .await
+        .as_ref()
+        .filter(|fetch| fetch.provider == provider)
+        .and_then(|fetch| fetch.usage.clone())
+}
+
+/// Warn ahead of a known Claude session expiry.
+async fn check_token_expiry(app: &tauri::AppHandle, state: &AppState) {
+    let config = state.config.lock().await;
+    let expires_at = config.session_expires_at.clone();
+    let warning_days = config.token_expiry_warning_days;
+    drop(config);
+
+    let Some(expires_at) = expires_at else {
+        return;
+    };
+    let notification_settings = state.notification_settings.lock().await;
+    let mut notification_state = state.notification_state.lock().await;
+    *notification_state = notify_token_expiring(
+        app,
+        &expires_at,
+        warning_days,
+        &notification_settings,
+        &notification_state,
+    );
 }
 
 pub async fn do_fetch_and_emit(
     app: &tauri::AppHandle,
     state: &AppState,
     interval_minutes: u32,
+    source: FetchSource,
 ) -> FetchOutput {
     let config = state.config.lock().await;
     let provider = config.active_provider;
@@ -130,78 +1017,157 @@ pub async fn do_fetch_and_emit(
     let session_token = config.session_token.clone();
     let ollama_session_token = config.ollama_session_token.clone();
     let enabled = config.enabled;
+    let locked = config.locked;
     let hourly_refresh_enabled = config.hourly_refresh_enabled;
+    let hourly_refresh_gap_secs = config.hourly_refresh_gap_secs;
+    let hourly_refresh_jitter_secs = config.hourly_refresh_jitter_secs;
+    let near_reset_minutes = config.near_reset_minutes;
+    let member_usage_enabled = config.member_usage_enabled;
     drop(config);
 
-    let has_provider_config = match provider {
-        crate::types::ProviderKind::Claude => org_id.is_some() && session_token.is_some(),
-        crate::types::ProviderKind::Codex => true,
-        crate::types::ProviderKind::Ollama => ollama_session_token.is_some(),
-    };
+    let has_provider_config = crate::demo::is_enabled()
+        || match provider {
+            crate::types::ProviderKind::Claude => org_id.is_some() && session_token.is_some(),
+            crate::types::ProviderKind::Codex => true,
+            crate::types::ProviderKind::Ollama => ollama_session_token.is_some(),
+        };
 
-    if !has_provider_config {
-        log::warn!(
-            "Skipping usage refresh for provider={} because configuration is incomplete",
-            provider.as_str()
-        );
+    if locked || !has_provider_config {
+        if locked {
+            log::info!("Skipping usage refresh because the app is locked");
+        } else {
+            log::warn!(
+                "Skipping usage refresh for provider={} because configuration is incomplete",
+                provider.as_str()
+            );
+        }
         return FetchOutput {
             result: FetchResult::NoCredentials,
+            usage: None,
             next_refresh_at: None,
+            rate_limit: None,
+            next_source: FetchSource::Scheduled,
+            paused_reason: Some(if locked {
+                PausedReason::Locked
+            } else {
+                PausedReason::NoCredentials
+            }),
         };
     }
 
-    match fetch_usage_for_provider(
+    let _activity = crate::power::begin_activity("Refreshing usage");
+    let previous_usage = last_fetched_usage(state, provider).await;
+    let started = std::time::Instant::now();
+    let response = state
+        .usage_client
+        .fetch_usage(
+            provider,
+            org_id.as_deref(),
+            session_token.as_ref().map(SecretString::expose),
+            ollama_session_token.as_ref().map(SecretString::expose),
+        )
+        .await;
+    let result = response.result;
+
+    // A held-back spike is replaced by the last accepted snapshot everywhere
+    let mut spike_held = false;
+    let result = match result {
+        Ok(usage) => {
+            let reject_spikes = state.preferences.lock().await.reject_usage_spikes;
+            match state.spike_filter.lock().await.check(&usage, reject_spikes) {
+                Some(baseline) => {
+                    spike_held = true;
+                    Ok(baseline)
+                }
+                None => Ok(usage),
+            }
+        }
+        Err(e) => Err(e),
+    };
+    let paused_reason =
+        paused_reason_after_fetch(enabled, paused_reason(state).await, result.as_ref().err());
+    *state.last_fetch.lock().await = Some(LastFetchResult {
         provider,
-        org_id.as_deref(),
-        session_token.as_deref(),
-        ollama_session_token.as_deref(),
-    )
-    .await
+        success: result.is_ok(),
+        error: result.as_ref().err().map(AppError::to_payload),
+        finished_at: Utc::now().to_rfc3339(),
+        http_status: response.meta.http_status,
+        duration_ms: started.elapsed().as_millis() as u64,
+        usage: result.as_ref().ok().cloned(),
+    });
+    if let (Some(rotated), Some(used_token)) =
+        (response.meta.rotated_session_key, session_token.as_ref())
     {
-        Ok(usage) => {
-            // Update tray tooltip
-            update_tray_tooltip(app, Some(&usage));
-
-            // Save usage snapshot for analytics (ignore errors silently)
-            let _ = save_usage_snapshot(&usage);
-
-            // Process notifications
-            {
-                let notification_settings = state.notification_settings.lock().await;
-                let mut notification_state = state.notification_state.lock().await;
-
-                // Check for usage resets and clear notification state if needed
-                let reset_state = reset_notification_state_if_needed(&usage, &notification_state);
-                *notification_state = reset_state;
+        persist_rotated_session_key(state, used_token, rotated).await;
+    }
+    if provider == crate::types::ProviderKind::Claude {
+        check_token_expiry(app, state).await;
+    }
 
-                // Process notifications and update state
-                let new_state =
-                    process_notifications(app, &usage, &notification_settings, &notification_state);
-                *notification_state = new_state;
+    match result {
+        Ok(usage) => {
+            if provider == crate::types::ProviderKind::Claude {
+                fetch_additional_organizations(state, source).await;
+            }
+            if !spike_held {
+                apply_usage(app, state, &usage, source).await;
+            }
+            // Incognito keeps everything in memory, so there is nothing to record
+            if !crate::history::is_incognito() {
+                if let Err(e) = run_blocking(sync_local_usage).await {
+                    log::debug!("Failed to sync local token history: {e}");
+                }
+                if member_usage_enabled && provider == crate::types::ProviderKind::Claude {
+                    if let Some(org_id) = sync_member_usage(state).await {
+                        member_usage_unsupported(app, &org_id);
+                    }
+                }
             }
 
             // Calculate next refresh time (considers both regular interval and hourly refresh)
             let now_ms = Utc::now().timestamp_millis();
-            let hourly_delay = calculate_hourly_refresh_delay(hourly_refresh_enabled);
-            let next_refresh_at =
-                calculate_next_refresh_at(enabled, interval_minutes, now_ms, hourly_delay);
+            let hourly_delay = calculate_hourly_refresh_delay(
+                hourly_refresh_enabled,
+                hourly_refresh_gap_secs,
+                hourly_refresh_jitter_secs,
+            );
+            let next_refresh_at = calculate_next_refresh_at(
+                enabled,
+                interval_minutes,
+                now_ms,
+                hourly_delay,
+                near_reset(provider, near_reset_minutes),
+            );
+            let next_source = scheduled_source(next_refresh_at, now_ms, hourly_delay);
+
+            let rate_limit = usage.rate_limit.clone();
 
             // Emit usage update event
-            let _ = app.emit(
-                "usage-updated",
-                UsageUpdateEvent {
-                    usage,
-                    next_refresh_at,
-                },
-            );
+            let _ = UsageUpdated {
+                usage: usage.clone(),
+                next_refresh_at,
+                deltas: usage_deltas(previous_usage.as_ref(), &usage),
+                last_success_at: last_success_at(state).await,
+                monitoring_enabled: monitoring_enabled(paused_reason),
+                paused_reason,
+            }
+            .emit(app);
 
             FetchOutput {
                 result: FetchResult::Success,
+                usage: Some(usage),
                 next_refresh_at,
+                rate_limit,
+                next_source,
+                paused_reason,
             }
         }
         Err(e) => {
-            let is_rate_limited = matches!(e, AppError::RateLimited);
+            let rate_limit = match &e {
+                AppError::RateLimited(info) => info.clone(),
+                _ => None,
+            };
+            let is_rate_limited = matches!(e, AppError::RateLimited(_));
             if is_rate_limited {
                 log::warn!(
                     "Usage refresh failed for provider={} due to rate limiting: {}",
@@ -218,61 +1184,388 @@ pub async fn do_fetch_and_emit(
 
             // Calculate next refresh time even on error (for retry countdown)
             let now_ms = Utc::now().timestamp_millis();
-            let hourly_delay = calculate_hourly_refresh_delay(hourly_refresh_enabled);
-            let next_refresh_at =
-                calculate_next_refresh_at(enabled, interval_minutes, now_ms, hourly_delay);
+            let hourly_delay = calculate_hourly_refresh_delay(
+                hourly_refresh_enabled,
+                hourly_refresh_gap_secs,
+                hourly_refresh_jitter_secs,
+            );
+            let next_refresh_at = calculate_next_refresh_at(
+                enabled,
+                interval_minutes,
+                now_ms,
+                hourly_delay,
+                near_reset(provider, near_reset_minutes),
+            );
+            let next_source = scheduled_source(next_refresh_at, now_ms, hourly_delay);
+
+            let _ = UsageError {
+                provider,
+                error: e.message(),
+                code: e.code(),
+                monitoring_enabled: monitoring_enabled(paused_reason),
+                paused_reason,
+            }
+            .emit(app);
+            // Keep showing the last usage, marked with its age once stale
+            refresh_tray_tooltip(app, state).await;
 
-            let _ = app.emit(
-                "usage-error",
-                UsageErrorEvent {
+            let is_forbidden = matches!(e, AppError::Forbidden);
+            if is_forbidden {
+                let notification_settings = state.notification_settings.lock().await;
+                let mut notification_state = state.notification_state.lock().await;
+                *notification_state = notify_access_denied(
+                    app,
                     provider,
-                    error: e.to_string(),
-                },
-            );
+                    &notification_settings,
+                    &notification_state,
+                );
+            }
 
             FetchOutput {
                 result: if is_rate_limited {
                     FetchResult::RateLimited
+                } else if is_forbidden {
+                    FetchResult::Forbidden
                 } else {
                     FetchResult::OtherError
                 },
+                usage: None,
                 next_refresh_at,
+                rate_limit,
+                next_source,
+                paused_reason,
             }
         }
     }
 }
 
+/// How often a long wait checks whether the wall clock jumped.
+const CLOCK_CHECK_INTERVAL_SECS: u64 = 30;
+
+/// Sleep for `duration` of monotonic time, waking early if the wall clock jumps
+/// (NTP sync after wake, manual change) so the next fetch isn't left stale.
+/// Ticks are checked against the monotonic deadline, so a tick that fires late
+/// only shortens the next one. Returns true when it woke for a clock jump.
+async fn sleep_or_clock_jump(duration: std::time::Duration) -> bool {
+    let deadline = tokio::time::Instant::now() + duration;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        if remaining.is_zero() {
+            return false;
+        }
+
+        let tick = remaining.min(std::time::Duration::from_secs(CLOCK_CHECK_INTERVAL_SECS));
+        // Earlier ticks may be coalesced under App Nap; the last one must not
+        // be, or the fetch lands late
+        let _activity = (tick == remaining)
+            .then(|| crate::power::begin_activity("Waiting for scheduled usage refresh"));
+        let monotonic_start = std::time::Instant::now();
+        let wall_start = Utc::now();
+        tokio::time::sleep(tick).await;
+
+        if clock_jumped(monotonic_start.elapsed(), Utc::now() - wall_start) {
+            log::info!("System clock jumped; refreshing early");
+            return true;
+        }
+    }
+}
+
+/// Spawn `auto_refresh_loop` under a supervisor that respawns it, with fresh
+/// loop state, when it panics. Replaces a previous loop.
+pub fn start_auto_refresh(app: tauri::AppHandle, state: Arc<AppState>) {
+    let handle = tauri::async_runtime::spawn(async move {
+        let loop_app = app.clone();
+        supervise(
+            move || auto_refresh_loop(loop_app.clone(), state.clone()),
+            LOOP_RESTART_DELAY,
+            |message, restarts| {
+                log::error!(
+                    "Refresh loop panicked ({message}); restarting in {}s",
+                    LOOP_RESTART_DELAY.as_secs()
+                );
+                let _ = RefreshLoopRestarted {
+                    message: crate::redact::redact(&message).into_owned(),
+                    restarts,
+                }
+                .emit(&app);
+            },
+        )
+        .await;
+    });
+    let previous = REFRESH_LOOP
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace(handle);
+    if let Some(previous) = previous {
+        previous.abort();
+    }
+}
+
+/// Aborts the task it guards when dropped, so aborting the supervisor also
+/// stops the loop it is waiting on.
+struct AbortOnDrop(tokio::task::AbortHandle);
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
+
+/// Run the task `spawn_loop` returns until it finishes, respawning it after
+/// `restart_delay` each time it panics. `on_panic` gets the panic message and
+/// the number of restarts so far.
+async fn supervise<F, Fut>(
+    mut spawn_loop: F,
+    restart_delay: std::time::Duration,
+    mut on_panic: impl FnMut(String, u32),
+) where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = ()> + Send + 'static,
+{
+    let mut restarts = 0;
+    loop {
+        let task = tokio::spawn(spawn_loop());
+        let _abort = AbortOnDrop(task.abort_handle());
+        let payload = match task.await {
+            Ok(()) => return,
+            Err(e) if e.is_panic() => e.into_panic(),
+            Err(_) => return,
+        };
+        restarts += 1;
+        on_panic(panic_message(payload.as_ref()), restarts);
+        tokio::time::sleep(restart_delay).await;
+    }
+}
+
+/// The message a panic was raised with.
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}
+
+/// Stop the refresh loop at its next await. History and notification state
+/// are written between awaits, so no write is cut short.
+pub fn stop_auto_refresh() {
+    let handle = REFRESH_LOOP
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take();
+    if let Some(handle) = handle {
+        handle.abort();
+    }
+}
+
 pub async fn auto_refresh_loop(app: tauri::AppHandle, state: Arc<AppState>) {
     let mut restart_rx = state.restart_tx.subscribe();
     let mut backoff_secs: u64 = 0; // 0 means no backoff active
+    // A backoff still in force from before a restart is waited out before the
+    // first fetch
+    let mut resumed_backoff = load_backoff(&app, Utc::now());
+    if let Some(backoff) = resumed_backoff {
+        log::info!(
+            "Resuming rate-limit backoff until {}",
+            backoff.until.to_rfc3339()
+        );
+        backoff_secs = backoff.backoff_secs;
+        *state.backoff_until.lock().await = Some(backoff.until);
+        *state.paused_reason.lock().await = Some(PausedReason::Backoff);
+    }
+    // The backoff end the loop last recorded, to tell it from one a manual
+    // refresh recorded
+    let mut loop_backoff_until = resumed_backoff.map(|backoff| backoff.until);
+    let mut last_active_sessions: Option<ActiveSessions> = None;
+    let mut last_usage: Option<UsageSnapshot> = None;
+    let mut stale_watchdog = StaleDataWatchdog::default();
+    let mut last_activity_ms = Utc::now().timestamp_millis();
+    let mut last_fetch_ms = 0;
+    let mut suspended = false;
+    let mut source = FetchSource::Scheduled;
+    let mut first_fetch = true;
 
     loop {
         // Get current config
         let config = state.config.lock().await;
-        let enabled = config.enabled;
-        let interval_minutes = config.interval_minutes;
-        let has_credentials = match config.active_provider {
-            crate::types::ProviderKind::Claude => {
-                config.organization_id.is_some() && config.session_token.is_some()
-            }
-            crate::types::ProviderKind::Codex => true,
-            crate::types::ProviderKind::Ollama => config.ollama_session_token.is_some(),
-        };
+        // Nothing is fetched while the app is locked
+        let enabled = config.enabled && !config.locked;
+        let (user_enabled, locked) = (config.enabled, config.locked);
+        let provider = config.active_provider;
+        let configured_interval_minutes = config.interval_minutes;
+        let active_session_interval_minutes = config.active_session_interval_minutes;
+        let idle_suspend_hours = config.idle_suspend_hours;
+        let startup_jitter_secs = config.startup_jitter_secs;
+        let has_credentials = crate::demo::is_enabled()
+            || match config.active_provider {
+                crate::types::ProviderKind::Claude => {
+                    config.organization_id.is_some() && config.session_token.is_some()
+                }
+                crate::types::ProviderKind::Codex => true,
+                crate::types::ProviderKind::Ollama => config.ollama_session_token.is_some(),
+            };
         drop(config);
 
         if !should_refresh(enabled, has_credentials) {
             // Reset backoff when disabled or no credentials
             backoff_secs = 0;
+            resumed_backoff = None;
+            loop_backoff_until = record_backoff(&app, &state, backoff_secs).await;
+            *state.paused_reason.lock().await = Some(if !user_enabled {
+                PausedReason::UserPause
+            } else if locked {
+                PausedReason::Locked
+            } else {
+                PausedReason::NoCredentials
+            });
             // Wait for restart signal
             let _ = restart_rx.changed().await;
+            source = restart_source();
+            continue;
+        }
+
+        // Notify listeners when the set of active Claude Code sessions changes
+        let active_sessions = run_blocking(get_active_sessions).await.unwrap_or_else(|e| {
+            log::warn!("Failed to detect active Claude Code sessions: {e}");
+            ActiveSessions::default()
+        });
+        if last_active_sessions.as_ref() != Some(&active_sessions) {
+            let _ = ActiveSessionsChanged(active_sessions.clone()).emit(&app);
+        }
+        let interval_minutes = effective_interval_minutes(
+            configured_interval_minutes,
+            active_session_interval_minutes,
+            active_sessions.count,
+        );
+        let now_ms = Utc::now().timestamp_millis();
+        if active_sessions.count > 0 {
+            last_activity_ms = now_ms;
+        }
+        last_active_sessions = Some(active_sessions);
+
+        // Pause while neither Claude Code nor the web app has been used for a
+        // while, still fetching occasionally to notice web usage
+        if is_idle(idle_suspend_hours, last_activity_ms, now_ms)
+            && now_ms - last_fetch_ms < IDLE_PROBE_SECS * 1000
+        {
+            if !suspended {
+                log::info!("No Claude activity for a while; pausing usage polling");
+                suspended = true;
+                *state.paused_reason.lock().await = Some(PausedReason::Idle);
+            }
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_secs(IDLE_CHECK_SECS)) => {
+                    source = FetchSource::Scheduled;
+                }
+                _ = restart_rx.changed() => {
+                    // Opening the window or changing settings counts as activity
+                    last_activity_ms = Utc::now().timestamp_millis();
+                    resumed_backoff = backoff_after_restart(&app, &state, loop_backoff_until).await;
+                    backoff_secs = resumed_backoff.map_or(0, |backoff| backoff.backoff_secs);
+                    loop_backoff_until = resumed_backoff.map(|backoff| backoff.until);
+                    source = restart_source();
+                }
+            }
             continue;
         }
+        if suspended && !is_idle(idle_suspend_hours, last_activity_ms, now_ms) {
+            log::info!("Claude activity detected; resuming usage polling");
+            suspended = false;
+        }
+
+        if let Some(backoff) = resumed_backoff.take() {
+            let remaining = backoff_remaining(Some(backoff.until), Utc::now()).unwrap_or(0);
+            let _ = RateLimitUpdated {
+                provider,
+                rate_limit: None,
+                backoff_seconds: remaining,
+            }
+            .emit(&app);
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_secs(remaining)) => {}
+                _ = restart_rx.changed() => {
+                    // Reset backoff since user took action
+                    resumed_backoff = backoff_after_restart(&app, &state, loop_backoff_until).await;
+                    backoff_secs = resumed_backoff.map_or(0, |backoff| backoff.backoff_secs);
+                    loop_backoff_until = resumed_backoff.map(|backoff| backoff.until);
+                    source = restart_source();
+                    continue;
+                }
+            }
+        }
+
+        // Spread the first scheduled fetch after launch, and fetches after a
+        // wake, over the startup jitter. Anything the user does skips the wait.
+        let jitter_due =
+            (first_fetch && source == FetchSource::Scheduled) || source == FetchSource::Wake;
+        first_fetch = false;
+        if jitter_due {
+            let jitter = startup_jitter(startup_jitter_secs);
+            log::debug!("Waiting {}ms before fetching", jitter.as_millis());
+            tokio::select! {
+                _ = tokio::time::sleep(jitter) => {}
+                _ = restart_rx.changed() => {
+                    source = restart_source();
+                    continue;
+                }
+            }
+        }
 
         // Fetch immediately and get the next refresh timestamp
-        let fetch_output = do_fetch_and_emit(&app, &state, interval_minutes).await;
+        let fetch_output = do_fetch_and_emit(&app, &state, interval_minutes, source).await;
+        last_fetch_ms = Utc::now().timestamp_millis();
+        if let Some(usage) = fetch_output.usage.as_ref() {
+            if last_usage
+                .as_ref()
+                .is_some_and(|previous| usage_increased(previous, usage))
+            {
+                last_activity_ms = last_fetch_ms;
+            }
+            last_usage = Some(usage.clone());
 
-        // Update backoff based on result
-        backoff_secs = calculate_next_backoff(backoff_secs, fetch_output.result);
+            let claude_code_active = last_active_sessions
+                .as_ref()
+                .is_some_and(|sessions| sessions.count > 0);
+            if let Some(stale) =
+                stale_watchdog.observe(usage, claude_code_active, crate::clock::now())
+            {
+                log::warn!(
+                    "Usage for provider={} unchanged since {} ({:?}); the token may return cached data",
+                    provider.as_str(),
+                    stale.unchanged_since,
+                    stale.reason
+                );
+                notify_stale_data(&app, &stale);
+                let _ = stale.emit(&app);
+            }
+        }
+
+        // Update backoff based on result, honouring any delay the server asked for
+        let previous_backoff_secs = backoff_secs;
+        backoff_secs = if fetch_output.result == FetchResult::RateLimited {
+            rate_limited_backoff(
+                backoff_secs,
+                fetch_output.rate_limit.as_ref(),
+                crate::clock::now(),
+            )
+        } else {
+            calculate_next_backoff(backoff_secs, fetch_output.result)
+        };
+
+        loop_backoff_until = record_backoff(&app, &state, backoff_secs).await;
+        *state.paused_reason.lock().await = fetch_output
+            .paused_reason
+            .or((backoff_secs > 0).then_some(PausedReason::Backoff));
+
+        if fetch_output.rate_limit.is_some() || backoff_secs != previous_backoff_secs {
+            let _ = RateLimitUpdated {
+                provider,
+                rate_limit: fetch_output.rate_limit.clone(),
+                backoff_seconds: backoff_secs,
+            }
+            .emit(&app);
+        }
 
         // Calculate wait duration based on the same next_refresh_at that was sent to frontend
         let wait_duration = if backoff_secs > 0 {
@@ -289,13 +1582,24 @@ pub async fn auto_refresh_loop(app: tauri::AppHandle, state: Arc<AppState>) {
         };
 
         tokio::select! {
-            _ = tokio::time::sleep(wait_duration) => {
-                // Wait elapsed, continue to next iteration
+            jumped = sleep_or_clock_jump(wait_duration) => {
+                // Wait elapsed (or the clock jumped), continue to next iteration
+                source = if jumped {
+                    FetchSource::Wake
+                } else if backoff_secs > 0 {
+                    FetchSource::Scheduled
+                } else {
+                    fetch_output.next_source
+                };
             }
             _ = restart_rx.changed() => {
                 // Restart signal received (e.g., new credentials)
                 // Reset backoff since user took action
-                backoff_secs = 0;
+                resumed_backoff = backoff_after_restart(&app, &state, loop_backoff_until).await;
+                backoff_secs = resumed_backoff.map_or(0, |backoff| backoff.backoff_secs);
+                loop_backoff_until = resumed_backoff.map(|backoff| backoff.until);
+                last_activity_ms = Utc::now().timestamp_millis();
+                source = restart_source();
             }
         }
     }
@@ -304,6 +1608,25 @@ pub async fn auto_refresh_loop(app: tauri::AppHandle, state: Arc<AppState>) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::UsageWindow;
+
+    /// A Claude snapshot with only the 5-hour window at `utilization`.
+    fn snapshot(utilization: f64) -> UsageSnapshot {
+        UsageSnapshot {
+            provider: ProviderKind::Claude,
+            windows: vec![UsageWindow {
+                key: "five_hour".to_string(),
+                label: "5 Hour".to_string(),
+                utilization,
+                resets_at: None,
+                resets_at_epoch_ms: None,
+                window_duration_seconds: None,
+            }],
+            account_email: None,
+            plan_type: None,
+            rate_limit: None,
+        }
+    }
 
     mod fetch_result_tests {
         use super::*;
@@ -391,6 +1714,12 @@ mod tests {
             assert_eq!(calculate_next_backoff(60, FetchResult::OtherError), 60);
         }
 
+        #[test]
+        fn forbidden_clears_backoff() {
+            assert_eq!(calculate_next_backoff(0, FetchResult::Forbidden), 0);
+            assert_eq!(calculate_next_backoff(120, FetchResult::Forbidden), 0);
+        }
+
         #[test]
         fn no_credentials_preserves_backoff() {
             assert_eq!(calculate_next_backoff(0, FetchResult::NoCredentials), 0);
@@ -441,6 +1770,66 @@ mod tests {
         }
     }
 
+    mod server_requested_delay_tests {
+        use super::*;
+
+        fn now() -> DateTime<Utc> {
+            DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc)
+        }
+
+        #[test]
+        fn prefers_retry_after() {
+            let info = RateLimitInfo {
+                remaining: Some(0),
+                reset_at: Some("2026-04-13T10:10:00Z".to_string()),
+                retry_after_seconds: Some(90),
+                ..RateLimitInfo::default()
+            };
+            assert_eq!(server_requested_delay(&info, now()), Some(90));
+        }
+
+        #[test]
+        fn backs_off_at_least_as_long_as_the_server_asks() {
+            let info = RateLimitInfo {
+                retry_after_seconds: Some(90),
+                ..RateLimitInfo::default()
+            };
+            assert_eq!(rate_limited_backoff(0, None, now()), INITIAL_BACKOFF_SECS);
+            assert_eq!(rate_limited_backoff(0, Some(&info), now()), 90);
+            assert_eq!(
+                rate_limited_backoff(MAX_BACKOFF_SECS, Some(&info), now()),
+                MAX_BACKOFF_SECS
+            );
+        }
+
+        #[test]
+        fn resumes_only_a_backoff_set_outside_the_loop() {
+            let later = now() + chrono::Duration::seconds(60);
+            assert!(set_outside_loop(Some(later), None));
+            assert!(set_outside_loop(Some(later), Some(now())));
+            assert!(!set_outside_loop(Some(later), Some(later)));
+            assert!(!set_outside_loop(None, Some(later)));
+        }
+
+        #[test]
+        fn waits_for_reset_only_when_quota_exhausted() {
+            let exhausted = RateLimitInfo {
+                remaining: Some(0),
+                reset_at: Some("2026-04-13T10:10:00Z".to_string()),
+                ..RateLimitInfo::default()
+            };
+            assert_eq!(server_requested_delay(&exhausted, now()), Some(600));
+
+            let available = RateLimitInfo {
+                remaining: Some(5),
+                ..exhausted
+            };
+            assert_eq!(server_requested_delay(&available, now()), None);
+        }
+    }
+
     mod should_refresh_tests {
         use super::*;
 
@@ -462,19 +1851,322 @@ mod tests {
         }
     }
 
+    mod effective_interval_minutes_tests {
+        use super::*;
+
+        #[test]
+        fn uses_configured_interval_without_active_sessions() {
+            assert_eq!(effective_interval_minutes(10, Some(1), 0), 10);
+        }
+
+        #[test]
+        fn uses_active_interval_while_sessions_are_active() {
+            assert_eq!(effective_interval_minutes(10, Some(1), 2), 1);
+        }
+
+        #[test]
+        fn never_slows_down_polling() {
+            assert_eq!(effective_interval_minutes(2, Some(5), 1), 2);
+        }
+
+        #[test]
+        fn ignores_sessions_when_not_configured() {
+            assert_eq!(effective_interval_minutes(10, None, 3), 10);
+        }
+    }
+
+    mod idle_suspend_tests {
+        use super::*;
+
+        const HOUR_MS: i64 = 3_600_000;
+
+        #[test]
+        fn idles_only_after_configured_hours() {
+            assert!(!is_idle(None, 0, 100 * HOUR_MS));
+            assert!(!is_idle(Some(0), 0, 100 * HOUR_MS));
+            assert!(!is_idle(Some(3), 0, 2 * HOUR_MS));
+            assert!(is_idle(Some(3), 0, 3 * HOUR_MS));
+        }
+
+        #[test]
+        fn counts_only_rising_usage_as_activity() {
+            assert!(usage_increased(&snapshot(10.0), &snapshot(12.0)));
+            assert!(!usage_increased(&snapshot(12.0), &snapshot(12.0)));
+            // A reset drops utilization without anyone using Claude
+            assert!(!usage_increased(&snapshot(80.0), &snapshot(0.0)));
+        }
+    }
+
+    mod usage_deltas_tests {
+        use super::*;
+
+        #[test]
+        fn computes_deltas_against_previous_fetch() {
+            let deltas = usage_deltas(Some(&snapshot(10.0)), &snapshot(12.5));
+            assert_eq!(deltas.get("five_hour"), Some(&2.5));
+            assert!(usage_deltas(None, &snapshot(12.5)).is_empty());
+        }
+    }
+
+    mod spike_filter_tests {
+        use super::*;
+
+        #[test]
+        fn holds_back_one_fetch_after_a_spike() {
+            let mut filter = SpikeFilter::default();
+            assert!(filter.check(&snapshot(10.0), true).is_none());
+
+            // The jump is replaced by the last accepted snapshot once...
+            let held = filter.check(&snapshot(95.0), true).unwrap();
+            assert_eq!(held.windows[0].utilization, 10.0);
+            // ...and whatever comes next is accepted
+            assert!(filter.check(&snapshot(12.0), true).is_none());
+            assert!(filter.check(&snapshot(30.0), true).is_none());
+
+            // Resets and small rises pass, and so does everything when disabled
+            assert!(filter.check(&snapshot(0.0), true).is_none());
+            assert!(filter.check(&snapshot(39.0), true).is_none());
+            assert!(filter.check(&snapshot(95.0), false).is_none());
+        }
+    }
+
+    mod stale_data_tests {
+        use super::*;
+
+        fn at(minutes: i64) -> DateTime<Utc> {
+            DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+                .unwrap()
+                .with_timezone(&Utc)
+                + chrono::Duration::minutes(minutes)
+        }
+
+        fn snapshot(utilization: f64, resets_at: Option<&str>) -> UsageSnapshot {
+            UsageSnapshot {
+                provider: ProviderKind::Claude,
+                windows: vec![UsageWindow {
+                    key: "five_hour".to_string(),
+                    label: "5 Hour".to_string(),
+                    utilization,
+                    resets_at: resets_at.map(str::to_string),
+                    resets_at_epoch_ms: None,
+                    window_duration_seconds: None,
+                }],
+                account_email: None,
+                plan_type: None,
+                rate_limit: None,
+            }
+        }
+
+        #[test]
+        fn warns_once_when_usage_stays_flat_during_activity() {
+            let mut watchdog = StaleDataWatchdog::default();
+            let usage = snapshot(40.0, None);
+            assert!(watchdog.observe(&usage, false, at(0)).is_none());
+            // Idle time doesn't count, however long
+            assert!(watchdog.observe(&usage, false, at(120)).is_none());
+            assert!(watchdog.observe(&usage, true, at(125)).is_none());
+
+            let stale = watchdog.observe(&usage, true, at(155)).unwrap();
+            assert_eq!(stale.reason, StaleDataReason::ActiveSessions);
+            assert_eq!(stale.unchanged_since, at(0).to_rfc3339());
+            assert!(watchdog.observe(&usage, true, at(240)).is_none());
+
+            // A change starts over
+            assert!(
+                watchdog
+                    .observe(&snapshot(41.0, None), true, at(241))
+                    .is_none()
+            );
+            assert!(
+                watchdog
+                    .observe(&snapshot(41.0, None), true, at(280))
+                    .is_none()
+            );
+        }
+
+        #[test]
+        fn warns_when_usage_outlives_its_reset() {
+            let mut watchdog = StaleDataWatchdog::default();
+            let usage = snapshot(40.0, Some("2026-04-13T10:10:00Z"));
+            assert!(watchdog.observe(&usage, false, at(0)).is_none());
+            assert!(watchdog.observe(&usage, false, at(30)).is_none());
+
+            let stale = watchdog.observe(&usage, false, at(45)).unwrap();
+            assert_eq!(stale.reason, StaleDataReason::ResetPassed);
+            assert_eq!(stale.window.as_deref(), Some("5 Hour"));
+
+            // An unused window has nothing to reset
+            let mut watchdog = StaleDataWatchdog::default();
+            let unused = snapshot(0.0, Some("2026-04-13T10:10:00Z"));
+            assert!(watchdog.observe(&unused, false, at(0)).is_none());
+            assert!(watchdog.observe(&unused, false, at(45)).is_none());
+        }
+    }
+
+    mod supervise_tests {
+        use super::*;
+        use std::sync::atomic::AtomicU32;
+
+        #[tokio::test]
+        async fn respawns_a_panicked_loop() {
+            let runs = Arc::new(AtomicU32::new(0));
+            let mut panics = Vec::new();
+            let loop_runs = runs.clone();
+            supervise(
+                move || {
+                    let runs = loop_runs.clone();
+                    async move {
+                        if runs.fetch_add(1, Ordering::SeqCst) < 2 {
+                            panic!("fetch failed");
+                        }
+                    }
+                },
+                std::time::Duration::ZERO,
+                |message, restarts| panics.push((message, restarts)),
+            )
+            .await;
+
+            assert_eq!(runs.load(Ordering::SeqCst), 3);
+            assert_eq!(
+                panics,
+                vec![
+                    ("fetch failed".to_string(), 1),
+                    ("fetch failed".to_string(), 2)
+                ]
+            );
+        }
+
+        #[test]
+        fn reads_formatted_panic_messages() {
+            let payload: Box<dyn std::any::Any + Send> = Box::new(format!("code {}", 42));
+            assert_eq!(panic_message(payload.as_ref()), "code 42");
+            let payload: Box<dyn std::any::Any + Send> = Box::new(7);
+            assert_eq!(panic_message(payload.as_ref()), "unknown panic");
+        }
+    }
+
+    mod paused_reason_tests {
+        use super::*;
+
+        #[test]
+        fn names_why_polling_is_paused() {
+            let backoff = Some(PausedReason::Backoff);
+            assert_eq!(paused_reason_after_fetch(true, backoff, None), None);
+            assert_eq!(
+                paused_reason_after_fetch(false, None, None),
+                Some(PausedReason::UserPause)
+            );
+            assert_eq!(
+                paused_reason_after_fetch(true, None, Some(&AppError::InvalidToken)),
+                Some(PausedReason::AuthExpired)
+            );
+            assert_eq!(
+                paused_reason_after_fetch(true, None, Some(&AppError::RateLimited(None))),
+                backoff
+            );
+            // Other errors leave backoff as it was
+            let network = AppError::Server("unavailable".to_string());
+            assert_eq!(
+                paused_reason_after_fetch(true, backoff, Some(&network)),
+                backoff
+            );
+            assert_eq!(
+                paused_reason_after_fetch(true, Some(PausedReason::Idle), Some(&network)),
+                None
+            );
+            assert!(!monitoring_enabled(Some(PausedReason::NoCredentials)));
+            assert!(monitoring_enabled(backoff));
+        }
+    }
+
+    mod backoff_remaining_tests {
+        use super::*;
+
+        #[test]
+        fn rounds_up_until_the_backoff_ends() {
+            let now = Utc::now();
+            let until = |ms| Some(now + chrono::Duration::milliseconds(ms));
+            assert_eq!(backoff_remaining(None, now), None);
+            assert_eq!(backoff_remaining(until(0), now), None);
+            assert_eq!(backoff_remaining(until(-5_000), now), None);
+            assert_eq!(backoff_remaining(until(1), now), Some(1));
+            assert_eq!(backoff_remaining(until(60_000), now), Some(60));
+        }
+    }
+
+    mod restorable_backoff_tests {
+        use super::*;
+
+        #[test]
+        fn restores_only_a_backoff_still_in_force() {
+            let now = Utc::now();
+            let backoff = |backoff_secs, until_secs| {
+                Some(PersistedBackoff {
+                    backoff_secs,
+                    until: now + chrono::Duration::seconds(until_secs),
+                })
+            };
+            assert_eq!(restorable_backoff(None, now), None);
+            assert_eq!(restorable_backoff(backoff(120, -1), now), None);
+            assert_eq!(restorable_backoff(backoff(0, 60), now), None);
+            assert_eq!(restorable_backoff(backoff(120, 60), now), backoff(120, 60));
+            // The clock went back while the app was closed
+            assert_eq!(
+                restorable_backoff(backoff(120, 3600), now),
+                backoff(120, 120)
+            );
+        }
+    }
+
+    mod stale_minutes_tests {
+        use super::*;
+
+        #[test]
+        fn stale_after_twice_the_interval() {
+            let now = Utc::now();
+            let ago = |minutes| Some(now - chrono::Duration::minutes(minutes));
+            assert_eq!(stale_minutes(None, 5, now), None);
+            assert_eq!(stale_minutes(ago(10), 5, now), None);
+            assert_eq!(stale_minutes(ago(11), 5, now), Some(11));
+            assert_eq!(stale_minutes(ago(180), 60, now), Some(180));
+        }
+    }
+
     mod calculate_hourly_refresh_delay_tests {
         use super::*;
 
         #[test]
         fn returns_none_when_disabled() {
-            assert!(calculate_hourly_refresh_delay_with_params(false, 0, 0).is_none());
-            assert!(calculate_hourly_refresh_delay_with_params(false, 1800, 30).is_none());
+            assert!(
+                calculate_hourly_refresh_delay_with_params(
+                    false,
+                    0,
+                    HOURLY_REFRESH_INITIAL_GAP_SECS,
+                    0
+                )
+                .is_none()
+            );
+            assert!(
+                calculate_hourly_refresh_delay_with_params(
+                    false,
+                    1800,
+                    HOURLY_REFRESH_INITIAL_GAP_SECS,
+                    30
+                )
+                .is_none()
+            );
         }
 
         #[test]
         fn calculates_delay_at_start_of_hour() {
             // At 00:00 of the hour, with 0 jitter
-            let delay = calculate_hourly_refresh_delay_with_params(true, 0, 0).unwrap();
+            let delay = calculate_hourly_refresh_delay_with_params(
+                true,
+                0,
+                HOURLY_REFRESH_INITIAL_GAP_SECS,
+                0,
+            )
+            .unwrap();
             // Should be 3600 (full hour) + 5 (initial gap) = 3605 seconds
             assert_eq!(delay, 3605);
         }
@@ -482,7 +2174,13 @@ mod tests {
         #[test]
         fn calculates_delay_at_middle_of_hour() {
             // At 30:00 of the hour (1800 seconds in), with 0 jitter
-            let delay = calculate_hourly_refresh_delay_with_params(true, 1800, 0).unwrap();
+            let delay = calculate_hourly_refresh_delay_with_params(
+                true,
+                1800,
+                HOURLY_REFRESH_INITIAL_GAP_SECS,
+                0,
+            )
+            .unwrap();
             // Should be 1800 (remaining) + 5 (initial gap) = 1805 seconds
             assert_eq!(delay, 1805);
         }
@@ -490,7 +2188,13 @@ mod tests {
         #[test]
         fn calculates_delay_near_end_of_hour() {
             // At 59:00 of the hour (3540 seconds in), with 0 jitter
-            let delay = calculate_hourly_refresh_delay_with_params(true, 3540, 0).unwrap();
+            let delay = calculate_hourly_refresh_delay_with_params(
+                true,
+                3540,
+                HOURLY_REFRESH_INITIAL_GAP_SECS,
+                0,
+            )
+            .unwrap();
             // Should be 60 (remaining) + 5 (initial gap) = 65 seconds
             assert_eq!(delay, 65);
         }
@@ -498,15 +2202,41 @@ mod tests {
         #[test]
         fn adds_jitter_to_delay() {
             // At 30:00 of the hour, with 30 seconds jitter
-            let delay = calculate_hourly_refresh_delay_with_params(true, 1800, 30).unwrap();
+            let delay = calculate_hourly_refresh_delay_with_params(
+                true,
+                1800,
+                HOURLY_REFRESH_INITIAL_GAP_SECS,
+                30,
+            )
+            .unwrap();
             // Should be 1800 (remaining) + 5 (initial gap) + 30 (jitter) = 1835 seconds
             assert_eq!(delay, 1835);
         }
 
+        #[test]
+        fn uses_configured_gap() {
+            let delay = calculate_hourly_refresh_delay_with_params(true, 1800, 120, 0).unwrap();
+            assert_eq!(delay, 1920);
+        }
+
+        #[test]
+        fn startup_jitter_stays_within_bounds() {
+            assert_eq!(startup_jitter(0), std::time::Duration::ZERO);
+            for _ in 0..100 {
+                assert!(startup_jitter(2) <= std::time::Duration::from_secs(2));
+            }
+        }
+
         #[test]
         fn adds_max_jitter() {
             // With maximum jitter (55 seconds)
-            let delay = calculate_hourly_refresh_delay_with_params(true, 0, 55).unwrap();
+            let delay = calculate_hourly_refresh_delay_with_params(
+                true,
+                0,
+                HOURLY_REFRESH_INITIAL_GAP_SECS,
+                55,
+            )
+            .unwrap();
             // Should be 3600 + 5 + 55 = 3660 seconds
             assert_eq!(delay, 3660);
         }
@@ -519,7 +2249,7 @@ mod tests {
 
         #[test]
         fn returns_some_when_enabled() {
-            let result = calculate_next_refresh_at(true, 5, NOW_MS, None);
+            let result = calculate_next_refresh_at(true, 5, NOW_MS, None, None);
             assert!(result.is_some());
 
             let timestamp = result.unwrap();
@@ -530,15 +2260,15 @@ mod tests {
 
         #[test]
         fn returns_none_when_disabled() {
-            assert!(calculate_next_refresh_at(false, 5, NOW_MS, None).is_none());
-            assert!(calculate_next_refresh_at(false, 10, NOW_MS, None).is_none());
+            assert!(calculate_next_refresh_at(false, 5, NOW_MS, None, None).is_none());
+            assert!(calculate_next_refresh_at(false, 10, NOW_MS, None, None).is_none());
         }
 
         #[test]
         fn different_intervals_produce_different_timestamps() {
-            let result_1min = calculate_next_refresh_at(true, 1, NOW_MS, None).unwrap();
-            let result_5min = calculate_next_refresh_at(true, 5, NOW_MS, None).unwrap();
-            let result_10min = calculate_next_refresh_at(true, 10, NOW_MS, None).unwrap();
+            let result_1min = calculate_next_refresh_at(true, 1, NOW_MS, None, None).unwrap();
+            let result_5min = calculate_next_refresh_at(true, 5, NOW_MS, None, None).unwrap();
+            let result_10min = calculate_next_refresh_at(true, 10, NOW_MS, None, None).unwrap();
 
             assert_eq!(result_1min, NOW_MS + 60_000);
             assert_eq!(result_5min, NOW_MS + 300_000);
@@ -554,7 +2284,7 @@ mod tests {
             // Regular interval is 30 minutes (1800 seconds)
             // Hourly delay is 10 minutes (600 seconds) - sooner
             let hourly_delay = Some(600u64);
-            let result = calculate_next_refresh_at(true, 30, NOW_MS, hourly_delay).unwrap();
+            let result = calculate_next_refresh_at(true, 30, NOW_MS, hourly_delay, None).unwrap();
 
             // Should use the hourly delay since it's sooner
             assert_eq!(result, NOW_MS + 600_000);
@@ -565,7 +2295,7 @@ mod tests {
             // Regular interval is 5 minutes (300 seconds)
             // Hourly delay is 50 minutes (3000 seconds) - later
             let hourly_delay = Some(3000u64);
-            let result = calculate_next_refresh_at(true, 5, NOW_MS, hourly_delay).unwrap();
+            let result = calculate_next_refresh_at(true, 5, NOW_MS, hourly_delay, None).unwrap();
 
             // Should use the regular interval since it's sooner
             assert_eq!(result, NOW_MS + 300_000);
@@ -573,9 +2303,52 @@ mod tests {
 
         #[test]
         fn ignores_hourly_delay_when_none() {
-            let result = calculate_next_refresh_at(true, 5, NOW_MS, None).unwrap();
+            let result = calculate_next_refresh_at(true, 5, NOW_MS, None, None).unwrap();
             assert_eq!(result, NOW_MS + 300_000);
         }
+
+        #[test]
+        fn tightens_interval_near_reset() {
+            let near_reset = |minutes_away: i64| {
+                Some(NearReset {
+                    reset_at_ms: NOW_MS + minutes_away * 60_000,
+                    window_minutes: 15,
+                })
+            };
+
+            // Inside the last 15 minutes: poll every minute
+            let result = calculate_next_refresh_at(true, 10, NOW_MS, None, near_reset(10));
+            assert_eq!(result, Some(NOW_MS + 60_000));
+
+            // Approaching: wake up when the near-reset window starts
+            let result = calculate_next_refresh_at(true, 10, NOW_MS, None, near_reset(20));
+            assert_eq!(result, Some(NOW_MS + 5 * 60_000));
+
+            // Far away or already reset: regular interval
+            let result = calculate_next_refresh_at(true, 10, NOW_MS, None, near_reset(120));
+            assert_eq!(result, Some(NOW_MS + 600_000));
+            let result = calculate_next_refresh_at(true, 10, NOW_MS, None, near_reset(-1));
+            assert_eq!(result, Some(NOW_MS + 600_000));
+        }
+
+        #[test]
+        fn records_hourly_fetch_only_when_it_comes_first() {
+            let hourly = calculate_next_refresh_at(true, 10, NOW_MS, Some(120), None);
+            assert_eq!(
+                scheduled_source(hourly, NOW_MS, Some(120)),
+                FetchSource::Hourly
+            );
+
+            let regular = calculate_next_refresh_at(true, 10, NOW_MS, Some(1800), None);
+            assert_eq!(
+                scheduled_source(regular, NOW_MS, Some(1800)),
+                FetchSource::Scheduled
+            );
+            assert_eq!(
+                scheduled_source(regular, NOW_MS, None),
+                FetchSource::Scheduled
+            );
+        }
     }
 
     mod integration_tests {
@@ -613,7 +2386,7 @@ mod tests {
 
             // Next refresh should be None
             let now_ms = 1704067200000i64;
-            assert!(calculate_next_refresh_at(false, 5, now_ms, None).is_none());
+            assert!(calculate_next_refresh_at(false, 5, now_ms, None, None).is_none());
         }
 
         #[test]
@@ -623,7 +2396,122 @@ mod tests {
 
             // But next refresh timestamp is still calculated (frontend handles display)
             let now_ms = 1704067200000i64;
-            assert!(calculate_next_refresh_at(true, 5, now_ms, None).is_some());
+            assert!(calculate_next_refresh_at(true, 5, now_ms, None, None).is_some());
+        }
+    }
+
+    mod additional_organizations_tests {
+        use super::*;
+        use crate::api::MockUsageClient;
+        use crate::types::{
+            AutoRefreshConfig, NotificationSettings, NotificationState, Organization, Preferences,
+            ProviderKind,
+        };
+        use std::sync::Arc;
+        use tokio::sync::{Mutex, watch};
+
+        fn organization(id: &str) -> Organization {
+            Organization {
+                id: id.to_string(),
+                name: id.to_uppercase(),
+                plan: None,
+            }
+        }
+
+        fn usage(utilization: f64) -> UsageSnapshot {
+            UsageSnapshot {
+                provider: ProviderKind::Claude,
+                windows: vec![crate::types::UsageWindow {
+                    key: "five_hour".to_string(),
+                    label: "5 Hour".to_string(),
+                    utilization,
+                    resets_at: None,
+                    resets_at_epoch_ms: None,
+                    window_duration_seconds: None,
+                }],
+                account_email: None,
+                plan_type: None,
+                rate_limit: None,
+            }
+        }
+
+        fn create_state(client: MockUsageClient) -> AppState {
+            let (restart_tx, _) = watch::channel(());
+            AppState {
+                config: Mutex::new(AutoRefreshConfig {
+                    organization_id: Some("primary".to_string()),
+                    session_token: Some(SecretString::from("token".to_string())),
+                    additional_organizations: vec![
+                        organization("primary"),
+                        organization("beta"),
+                        organization("alpha"),
+                    ],
+                    ..AutoRefreshConfig::default()
+                }),
+                restart_tx,
+                notification_settings: Mutex::new(NotificationSettings::default()),
+                notification_state: Mutex::new(NotificationState::default()),
+                preferences: Mutex::new(Preferences::default()),
+                last_fetch: Mutex::new(None),
+                organization_usage: Mutex::new(Vec::new()),
+                organization_fetch: Mutex::new(()),
+                spike_filter: Mutex::new(SpikeFilter::default()),
+                last_success_at: Mutex::new(None),
+                last_applied_usage: Mutex::new(None),
+                paused_reason: Mutex::new(None),
+                backoff_until: Mutex::new(None),
+                usage_client: Arc::new(client),
+                #[cfg(feature = "transcript-watcher")]
+                transcript_watcher: Mutex::new(None),
+                #[cfg(target_os = "macos")]
+                wake_observer: Mutex::new(None),
+            }
+        }
+
+        #[tokio::test]
+        async fn keeps_configured_order_and_last_usage_of_failed_ones() {
+            let state = create_state(
+                MockUsageClient::default()
+                    .with_organization_usage("alpha", usage(10.0))
+                    .with_organization_usage("primary", usage(99.0)),
+            );
+            *state.organization_usage.lock().await = vec![OrganizationUsage {
+                organization: organization("beta"),
+                usage: usage(42.0),
+            }];
+
+            fetch_additional_organizations(&state, FetchSource::Scheduled).await;
+
+            let organization_usage = state.organization_usage.lock().await;
+            let fetched: Vec<(&str, f64)> = organization_usage
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.organization.id.as_str(),
+                        entry.usage.windows[0].utilization,
+                    )
+                })
+                .collect();
+            // The primary organization is fetched on its own, not here
+            assert_eq!(fetched, vec![("beta", 42.0), ("alpha", 10.0)]);
+        }
+
+        #[tokio::test]
+        async fn stops_asking_for_member_usage_after_a_404() {
+            let state = create_state(MockUsageClient::default().without_member_usage());
+
+            assert_eq!(sync_member_usage(&state).await.as_deref(), Some("primary"));
+            assert_eq!(
+                state
+                    .config
+                    .lock()
+                    .await
+                    .member_usage_unsupported_org
+                    .as_deref(),
+                Some("primary")
+            );
+            // Reported once; later fetches skip the request
+            assert_eq!(sync_member_usage(&state).await, None);
         }
     }
 }
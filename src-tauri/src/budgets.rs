@@ -0,0 +1,241 @@
+//! Usage Budgets
+//!
+//! A budget is a personal target for one window, e.g. "stay under 60% of the
+//! 7-day window by Wednesday". The plan rises linearly from 0% at the start of
+//! the window's period to the target at the deadline: the end of the chosen
+//! local weekday, or the reset when no weekday is set or the weekday falls
+//! after it. Running more than `AHEAD_TOLERANCE` points above the plan counts
+//! as ahead of it and alerts once per period.
+
+use crate::types::{ProviderKind, UsageWindow};
+use chrono::{DateTime, Datelike, Duration, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+
+/// Settings store key.
+pub const BUDGETS_KEY: &str = "usage_budgets";
+
+/// Points above the plan tolerated before a budget counts as ahead of it.
+const AHEAD_TOLERANCE: f64 = 5.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetWeekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl BudgetWeekday {
+    fn to_chrono(self) -> chrono::Weekday {
+        match self {
+            Self::Monday => chrono::Weekday::Mon,
+            Self::Tuesday => chrono::Weekday::Tue,
+            Self::Wednesday => chrono::Weekday::Wed,
+            Self::Thursday => chrono::Weekday::Thu,
+            Self::Friday => chrono::Weekday::Fri,
+            Self::Saturday => chrono::Weekday::Sat,
+            Self::Sunday => chrono::Weekday::Sun,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageBudget {
+    pub provider: ProviderKind,
+    /// Window key, e.g. `seven_day`.
+    pub window_key: String,
+    /// Utilization to stay under by the deadline (1-100).
+    pub target_percent: u32,
+    /// Local weekday whose end is the deadline; None means the window's reset.
+    pub deadline: Option<BudgetWeekday>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetStatus {
+    OnTrack,
+    /// Above the planned burn curve by more than the tolerance.
+    Ahead,
+    /// At or above the target already.
+    Exceeded,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetProgress {
+    pub budget: UsageBudget,
+    pub label: String,
+    pub utilization: f64,
+    /// Utilization the plan allows by now.
+    pub planned: f64,
+    /// Utilization above the plan; negative while under it.
+    pub ahead_by: f64,
+    pub status: BudgetStatus,
+    /// When the target applies (RFC3339).
+    pub deadline_at: String,
+    /// Reset of the period this progress belongs to (RFC3339).
+    pub resets_at: String,
+}
+
+/// Length of a window's period: reported by the provider, or known from its key.
+fn period_length(window: &UsageWindow) -> Option<Duration> {
+    if let Some(seconds) = window.window_duration_seconds {
+        return Some(Duration::seconds(seconds));
+    }
+    match window.key.as_str() {
+        "five_hour" | "primary" | "session" => Some(Duration::hours(5)),
+        "secondary" | "weekly" => Some(Duration::days(7)),
+        key if key.starts_with("seven_day") => Some(Duration::days(7)),
+        _ => None,
+    }
+}
+
+/// End of the first `weekday` after `period_start` in `tz`, capped at `reset`.
+fn deadline_in<Tz: TimeZone>(
+    weekday: Option<BudgetWeekday>,
+    period_start: DateTime<Utc>,
+    reset: DateTime<Utc>,
+    tz: &Tz,
+) -> DateTime<Utc> {
+    let Some(weekday) = weekday else {
+        return reset;
+    };
+    let start_date = period_start.with_timezone(tz).date_naive();
+    (0..8)
+        .filter_map(|days| start_date.checked_add_days(chrono::Days::new(days)))
+        .filter(|date| date.weekday() == weekday.to_chrono())
+        .filter_map(|date| {
+            let next_midnight = date.succ_opt()?.and_hms_opt(0, 0, 0)?;
+            tz.from_local_datetime(&next_midnight)
+                .earliest()
+                .map(|end| end.with_timezone(&Utc))
+        })
+        .find(|end| *end > period_start)
+        .map_or(reset, |end| end.min(reset))
+}
+
+/// Progress of `budget` given its window's latest utilization. None when the
+/// window has no reset time or its period length isn't known.
+pub fn progress(
+    budget: &UsageBudget,
+    window: &UsageWindow,
+    now: DateTime<Utc>,
+) -> Option<BudgetProgress> {
+    progress_in(budget, window, now, &Local)
+}
+
+fn progress_in<Tz: TimeZone>(
+    budget: &UsageBudget,
+    window: &UsageWindow,
+    now: DateTime<Utc>,
+    tz: &Tz,
+) -> Option<BudgetProgress> {
+    let reset = DateTime::parse_from_rfc3339(window.resets_at.as_deref()?)
+        .ok()?
+        .with_timezone(&Utc);
+    let period_start = reset - period_length(window)?;
+    let deadline = deadline_in(budget.deadline, period_start, reset, tz);
+
+    let target = budget.target_percent as f64;
+    let elapsed = (now - period_start).num_seconds() as f64;
+    let planned_span = (deadline - period_start).num_seconds().max(1) as f64;
+    let planned = target * (elapsed / planned_span).clamp(0.0, 1.0);
+    let ahead_by = window.utilization - planned;
+    let status = if window.utilization >= target {
+        BudgetStatus::Exceeded
+    } else if ahead_by > AHEAD_TOLERANCE {
+        BudgetStatus::Ahead
+    } else {
+        BudgetStatus::OnTrack
+    };
+
+    Some(BudgetProgress {
+        budget: budget.clone(),
+        label: window.label.clone(),
+        utilization: window.utilization,
+        planned,
+        ahead_by,
+        status,
+        deadline_at: deadline.to_rfc3339(),
+        resets_at: reset.to_rfc3339(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(timestamp: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(timestamp)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn seven_day(utilization: f64) -> UsageWindow {
+        UsageWindow {
+            key: "seven_day".to_string(),
+            label: "7 Day".to_string(),
+            utilization,
+            // Period from Monday 2026-04-13 00:00 to the next Monday
+            resets_at: Some("2026-04-20T00:00:00+00:00".to_string()),
+            resets_at_epoch_ms: None,
+            window_duration_seconds: None,
+        }
+    }
+
+    #[test]
+    fn plans_a_linear_burn_until_the_weekday_ends() {
+        let budget = UsageBudget {
+            provider: ProviderKind::Claude,
+            window_key: "seven_day".to_string(),
+            target_percent: 60,
+            deadline: Some(BudgetWeekday::Wednesday),
+        };
+        // Halfway from Monday 00:00 to the end of Wednesday
+        let now = at("2026-04-14T12:00:00Z");
+
+        let on_track = progress_in(&budget, &seven_day(28.0), now, &Utc).unwrap();
+        assert_eq!(on_track.deadline_at, "2026-04-16T00:00:00+00:00");
+        assert_eq!(on_track.planned, 30.0);
+        assert_eq!(on_track.status, BudgetStatus::OnTrack);
+
+        let ahead = progress_in(&budget, &seven_day(40.0), now, &Utc).unwrap();
+        assert_eq!(ahead.ahead_by, 10.0);
+        assert_eq!(ahead.status, BudgetStatus::Ahead);
+
+        let exceeded = progress_in(&budget, &seven_day(61.0), now, &Utc).unwrap();
+        assert_eq!(exceeded.status, BudgetStatus::Exceeded);
+    }
+
+    #[test]
+    fn falls_back_to_the_reset_as_deadline() {
+        let period_start = at("2026-04-13T00:00:00Z");
+        let reset = at("2026-04-20T00:00:00Z");
+        assert_eq!(deadline_in(None, period_start, reset, &Utc), reset);
+        // Sunday ends right at the reset; Monday ends a day into the period
+        assert_eq!(
+            deadline_in(Some(BudgetWeekday::Sunday), period_start, reset, &Utc),
+            reset
+        );
+        assert_eq!(
+            deadline_in(Some(BudgetWeekday::Monday), period_start, reset, &Utc),
+            at("2026-04-14T00:00:00Z")
+        );
+
+        let mut window = seven_day(10.0);
+        window.resets_at = None;
+        let budget = UsageBudget {
+            provider: ProviderKind::Claude,
+            window_key: "seven_day".to_string(),
+            target_percent: 60,
+            deadline: None,
+        };
+        assert_eq!(progress_in(&budget, &window, period_start, &Utc), None);
+    }
+}
@@ -0,0 +1,234 @@
+//! Response Capture and Replay
+//!
+//! While a capture is running, the body of every successful usage response is
+//! appended, redacted, to an NDJSON file the user can attach to a bug report.
+//! Replaying a capture (debug builds only) parses each body again and feeds
+//! the snapshot through `apply_usage` — notifications, tray and history — with
+//! the original gaps between responses shortened by `speed`.
+
+use crate::api::parse_usage_response;
+use crate::auto_refresh::{
+    apply_usage, last_fetched_usage, last_success_at, paused_reason, usage_deltas,
+};
+use crate::error::AppError;
+use crate::types::{
+    AppState, FetchSource, ProviderKind, UsageSnapshot, UsageUpdated, monitoring_enabled,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri_specta::Event;
+
+/// Longest wait between two replayed responses, however slow `speed` is.
+const MAX_REPLAY_GAP: Duration = Duration::from_secs(10);
+
+/// File responses are appended to while a capture runs.
+static CAPTURE_PATH: Mutex<Option<PathBuf>> = Mutex::new(None);
+
+/// One recorded response, as a line of a capture file.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CapturedResponse {
+    pub provider: ProviderKind,
+    pub captured_at: String,
+    pub body: String,
+}
+
+/// Start appending usage responses to `path`.
+pub fn start(path: PathBuf) -> Result<(), AppError> {
+    // Fail now rather than on the first response
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| AppError::Storage(format!("Failed to open {}: {e}", path.display())))?;
+    log::info!("Capturing usage responses to {}", path.display());
+    *CAPTURE_PATH.lock().unwrap_or_else(|e| e.into_inner()) = Some(path);
+    Ok(())
+}
+
+/// Stop capturing. Returns the file that was written, if a capture was running.
+pub fn stop() -> Option<PathBuf> {
+    CAPTURE_PATH
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .take()
+}
+
+/// Append a usage response body to the running capture, if any.
+pub fn record(provider: ProviderKind, body: &str) {
+    let Some(path) = CAPTURE_PATH
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+    else {
+        return;
+    };
+
+    let response = CapturedResponse {
+        provider,
+        captured_at: crate::clock::now().to_rfc3339(),
+        body: crate::redact::redact(body).into_owned(),
+    };
+    let result = serde_json::to_string(&response)
+        .map_err(std::io::Error::other)
+        .and_then(|line| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| writeln!(file, "{line}"))
+        });
+    if let Err(e) = result {
+        log::warn!("Failed to record response to {}: {e}", path.display());
+    }
+}
+
+fn read_capture(path: &Path) -> Result<Vec<CapturedResponse>, AppError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| AppError::Storage(format!("Failed to read {}: {e}", path.display())))?;
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .enumerate()
+        .map(|(index, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| AppError::Storage(format!("Invalid capture line {}: {e}", index + 1)))
+        })
+        .collect()
+}
+
+/// How long to wait before replaying a response captured `gap` after the
+/// previous one.
+fn replay_delay(gap: chrono::Duration, speed: f64) -> Duration {
+    let gap = gap.to_std().unwrap_or_default();
+    if speed <= 0.0 || !speed.is_finite() {
+        return MAX_REPLAY_GAP.min(gap);
+    }
+    gap.div_f64(speed).min(MAX_REPLAY_GAP)
+}
+
+/// Move reset times by `offset`, so a replayed window is as far from its
+/// reset as it was when captured.
+fn shift_resets(usage: &mut UsageSnapshot, offset: chrono::Duration) {
+    for window in &mut usage.windows {
+        let shifted = window
+            .resets_at
+            .as_deref()
+            .and_then(|resets_at| DateTime::parse_from_rfc3339(resets_at).ok())
+            .map(|resets_at| resets_at.with_timezone(&Utc) + offset);
+        if let Some(shifted) = shifted {
+            window.resets_at = Some(shifted.to_rfc3339());
+            window.resets_at_epoch_ms = Some(shifted.timestamp_millis());
+        }
+    }
+}
+
+/// Replay a capture file. Returns how many responses parsed and were applied;
+/// ones that no longer parse are logged and skipped.
+pub async fn replay(
+    app: &tauri::AppHandle,
+    state: &AppState,
+    path: &Path,
+    speed: f64,
+) -> Result<usize, AppError> {
+    let responses = read_capture(path)?;
+    log::info!(
+        "Replaying {} captured responses from {} at {speed}x",
+        responses.len(),
+        path.display()
+    );
+
+    let mut applied = 0;
+    let mut previous_at: Option<DateTime<Utc>> = None;
+    for (index, response) in responses.iter().enumerate() {
+        let captured_at = DateTime::parse_from_rfc3339(&response.captured_at)
+            .map(|at| at.with_timezone(&Utc))
+            .ok();
+        if let (Some(previous_at), Some(captured_at)) = (previous_at, captured_at) {
+            tokio::time::sleep(replay_delay(captured_at - previous_at, speed)).await;
+        }
+        previous_at = captured_at.or(previous_at);
+
+        let mut usage = match parse_usage_response(response.provider, &response.body) {
+            Ok(usage) => usage,
+            Err(e) => {
+                log::warn!("Captured response {} failed to parse: {e}", index + 1);
+                continue;
+            }
+        };
+        if let Some(captured_at) = captured_at {
+            shift_resets(&mut usage, crate::clock::now() - captured_at);
+        }
+
+        let previous = last_fetched_usage(state, usage.provider).await;
+        apply_usage(app, state, &usage, FetchSource::Manual).await;
+        let paused_reason = paused_reason(state).await;
+        let _ = UsageUpdated {
+            deltas: usage_deltas(previous.as_ref(), &usage),
+            usage,
+            next_refresh_at: None,
+            last_success_at: last_success_at(state).await,
+            monitoring_enabled: monitoring_enabled(paused_reason),
+            paused_reason,
+        }
+        .emit(app);
+        applied += 1;
+    }
+    Ok(applied)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::UsageWindow;
+
+    #[test]
+    fn shortens_gaps_by_speed() {
+        let minutes = |m: i64| chrono::Duration::minutes(m);
+        assert_eq!(replay_delay(minutes(5), 60.0), Duration::from_secs(5));
+        assert_eq!(replay_delay(minutes(60), 60.0), MAX_REPLAY_GAP);
+        assert_eq!(replay_delay(minutes(-5), 60.0), Duration::ZERO);
+        assert_eq!(replay_delay(minutes(5), 0.0), MAX_REPLAY_GAP);
+    }
+
+    #[test]
+    fn shifts_reset_times() {
+        let mut usage = UsageSnapshot {
+            provider: ProviderKind::Claude,
+            windows: vec![UsageWindow {
+                key: "five_hour".to_string(),
+                label: "5 Hour".to_string(),
+                utilization: 42.0,
+                resets_at: Some("2026-04-13T12:00:00+00:00".to_string()),
+                resets_at_epoch_ms: None,
+                window_duration_seconds: None,
+            }],
+            account_email: None,
+            plan_type: None,
+            rate_limit: None,
+        };
+
+        shift_resets(&mut usage, chrono::Duration::hours(2));
+        assert_eq!(
+            usage.windows[0].resets_at.as_deref(),
+            Some("2026-04-13T14:00:00+00:00")
+        );
+        assert_eq!(
+            usage.windows[0].resets_at_epoch_ms,
+            crate::types::reset_epoch_ms(Some("2026-04-13T14:00:00+00:00"))
+        );
+    }
+
+    #[test]
+    fn replays_recorded_claude_body() {
+        let line = r#"{"provider":"claude","capturedAt":"2026-04-13T10:00:00+00:00","body":"{\"five_hour\":{\"utilization\":91.0,\"resets_at\":\"2026-04-13T12:00:00Z\"}}"}"#;
+        let response: CapturedResponse = serde_json::from_str(line).unwrap();
+        let usage = parse_usage_response(response.provider, &response.body).unwrap();
+        assert_eq!(usage.windows[0].key, "five_hour");
+        assert_eq!(usage.windows[0].utilization, 91.0);
+    }
+}
@@ -0,0 +1,226 @@
+//! Command-Line Interface
+//!
+//! `claude-monitor query [provider]` prints the latest stored usage as JSON and
+//! exits without starting the GUI, so launchers like Raycast or Alfred can show
+//! usage without scraping the window. `claude-monitor prompt [provider]` prints
+//! a short colored segment such as `⚡5h 72%` for shell prompts. Both read the
+//! history database read-only, so they work alongside a running instance and
+//! never touch the network. `claude-monitor --refresh` asks a running instance
+//! to fetch now (or starts the app when none is running).
+
+use crate::history::{DB_FILE_NAME, LatestSnapshot, query_latest_snapshots};
+use crate::tray::short_label;
+use crate::types::{Severity, SeverityConfig, UsageWindow};
+use std::path::PathBuf;
+
+/// Must match `identifier` in `tauri.conf.json`; Tauri's app data directory is
+/// the platform data directory joined with it.
+const APP_IDENTIFIER: &str = "dev.xikxp1.claude-monitor";
+
+const QUERY_COMMAND: &str = "query";
+const PROMPT_COMMAND: &str = "prompt";
+
+/// The prompt segment is marked stale when the newest sample is older than this.
+const PROMPT_STALE_MINUTES: i64 = 30;
+
+/// Handle a CLI command and return the exit code, or None to start the app.
+pub fn run(args: &[String]) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    match command.as_str() {
+        QUERY_COMMAND => Some(query(rest.first().map(String::as_str))),
+        PROMPT_COMMAND => Some(prompt(rest)),
+        _ => None,
+    }
+}
+
+fn query(provider: Option<&str>) -> i32 {
+    let snapshots = match read_snapshots(provider) {
+        Ok(snapshots) => snapshots,
+        Err(message) => {
+            eprintln!("{message}");
+            return 1;
+        }
+    };
+
+    match serde_json::to_string_pretty(&snapshots) {
+        Ok(json) => {
+            println!("{json}");
+            0
+        }
+        Err(e) => {
+            eprintln!("Failed to encode usage: {e}");
+            1
+        }
+    }
+}
+
+/// Print the active provider's first window. Prints nothing (and still exits 0)
+/// when there is no data, so a prompt never shows an error.
+fn prompt(args: &[String]) -> i32 {
+    let color =
+        !args.iter().any(|arg| arg == "--no-color") && std::env::var_os("NO_COLOR").is_none();
+    let provider = args
+        .iter()
+        .find(|arg| !arg.starts_with("--"))
+        .cloned()
+        .or_else(active_provider)
+        .unwrap_or_else(|| "claude".to_string());
+
+    let segment = read_snapshots(Some(&provider)).ok().and_then(|snapshots| {
+        let snapshot = snapshots.into_iter().next()?;
+        let stale = is_stale(&snapshot, chrono::Utc::now());
+        let window = snapshot.windows.into_iter().next()?;
+        Some(prompt_segment(&window, &severity_config(), stale, color))
+    });
+    if let Some(segment) = segment {
+        println!("{segment}");
+    }
+    0
+}
+
+fn read_snapshots(provider: Option<&str>) -> Result<Vec<LatestSnapshot>, String> {
+    let db_path = data_dir()
+        .ok_or("Could not determine the app data directory")?
+        .join(DB_FILE_NAME);
+    query_latest_snapshots(&db_path, provider, primary_organization().as_deref())
+        .map_err(|e| format!("Failed to read {}: {e}", db_path.display()))
+}
+
+/// The settings store file the app writes.
+fn settings() -> Option<serde_json::Value> {
+    let contents = std::fs::read_to_string(data_dir()?.join("settings.json")).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+/// The provider selected in the app.
+fn active_provider() -> Option<String> {
+    settings()?
+        .get("active_provider")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// The primary Claude organization, whose history the app shows.
+fn primary_organization() -> Option<String> {
+    settings()?
+        .get("organization_id")?
+        .as_str()
+        .map(str::to_string)
+}
+
+/// The app's severity cutoffs (`Preferences.severity`), or the defaults.
+fn severity_config() -> SeverityConfig {
+    settings()
+        .and_then(|settings| settings.get("preferences")?.get("severity").cloned())
+        .and_then(|severity| serde_json::from_value(severity).ok())
+        .unwrap_or_default()
+}
+
+fn data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+fn is_stale(snapshot: &LatestSnapshot, now: chrono::DateTime<chrono::Utc>) -> bool {
+    chrono::DateTime::parse_from_rfc3339(&snapshot.timestamp)
+        .map(|timestamp| now.signed_duration_since(timestamp).num_minutes() > PROMPT_STALE_MINUTES)
+        .unwrap_or(true)
+}
+
+/// `⚡5h 72%`, green/yellow/red by severity; stale data is dimmed with a `?`.
+fn prompt_segment(
+    window: &UsageWindow,
+    severity: &SeverityConfig,
+    stale: bool,
+    color: bool,
+) -> String {
+    let text = format!(
+        "⚡{} {:.0}%{}",
+        short_label(&window.label),
+        window.utilization,
+        if stale { "?" } else { "" }
+    );
+    if !color {
+        return text;
+    }
+
+    let code = match severity.severity(&window.key, window.utilization) {
+        _ if stale => "2",
+        Severity::Critical => "31",
+        Severity::Warning => "33",
+        Severity::Normal => "32",
+    };
+    format!("\x1b[{code}m{text}\x1b[0m")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(label: &str, utilization: f64) -> UsageWindow {
+        UsageWindow {
+            key: "five_hour".to_string(),
+            label: label.to_string(),
+            utilization,
+            resets_at: None,
+            resets_at_epoch_ms: None,
+            window_duration_seconds: None,
+        }
+    }
+
+    #[test]
+    fn starts_the_app_for_other_arguments() {
+        assert_eq!(run(&[]), None);
+        assert_eq!(run(&["--autostart".to_string()]), None);
+    }
+
+    #[test]
+    fn formats_prompt_segment() {
+        assert_eq!(short_label("5 Hour"), "5h");
+        assert_eq!(short_label("7 Day"), "7d");
+        assert_eq!(short_label("Session"), "session");
+
+        assert_eq!(
+            prompt_segment(
+                &window("5 Hour", 72.4),
+                &SeverityConfig::default(),
+                false,
+                false
+            ),
+            "⚡5h 72%"
+        );
+        assert_eq!(
+            prompt_segment(
+                &window("5 Hour", 72.4),
+                &SeverityConfig::default(),
+                true,
+                false
+            ),
+            "⚡5h 72%?"
+        );
+        assert_eq!(
+            prompt_segment(
+                &window("5 Hour", 95.0),
+                &SeverityConfig::default(),
+                false,
+                true
+            ),
+            "\x1b[31m⚡5h 95%\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn marks_old_snapshots_stale() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        let snapshot = |timestamp: &str| LatestSnapshot {
+            provider: "claude".to_string(),
+            timestamp: timestamp.to_string(),
+            windows: Vec::new(),
+        };
+
+        assert!(!is_stale(&snapshot("2026-04-13T09:45:00+00:00"), now));
+        assert!(is_stale(&snapshot("2026-04-13T09:00:00+00:00"), now));
+        assert!(is_stale(&snapshot("garbage"), now));
+    }
+}
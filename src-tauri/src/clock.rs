@@ -0,0 +1,85 @@
+//! Clock skew handling.
+//!
+//! Reset timestamps come from the provider's clock, so a wrong or freshly
+//! corrected local clock makes time-remaining maths misfire. The offset seen in
+//! the last response's `Date` header is applied once it exceeds a tolerance, and
+//! the refresh loop watches for wall-clock jumps while it sleeps.
+
+use chrono::{DateTime, Utc};
+use reqwest::header::{DATE, HeaderMap};
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::time::Duration;
+
+/// Offsets below this are network latency or `Date` header rounding, not skew.
+const SKEW_TOLERANCE_SECS: i64 = 120;
+
+/// A wall clock that drifts this far from monotonic time during one sleep has jumped.
+pub const CLOCK_JUMP_THRESHOLD_SECS: i64 = 60;
+
+/// The local clock's error, so it is shared by all providers and refreshed by
+/// whichever one answered last.
+static SKEW_SECS: AtomicI64 = AtomicI64::new(0);
+
+/// Compare the server's `Date` header with the local clock.
+pub fn record_server_date(headers: &HeaderMap) {
+    let Some(server_now) = headers
+        .get(DATE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| DateTime::parse_from_rfc2822(value).ok())
+    else {
+        return;
+    };
+
+    let skew = significant_skew(server_now.with_timezone(&Utc), Utc::now());
+    if skew != SKEW_SECS.swap(skew, Ordering::Relaxed) && skew != 0 {
+        log::warn!("Local clock differs from the server by {skew}s; correcting reset times");
+    }
+}
+
+fn significant_skew(server_now: DateTime<Utc>, local_now: DateTime<Utc>) -> i64 {
+    let skew = server_now.signed_duration_since(local_now).num_seconds();
+    if skew.abs() > SKEW_TOLERANCE_SECS {
+        skew
+    } else {
+        0
+    }
+}
+
+/// Current time on the provider's clock, for comparing against `resets_at`.
+pub fn now() -> DateTime<Utc> {
+    Utc::now() + chrono::Duration::seconds(SKEW_SECS.load(Ordering::Relaxed))
+}
+
+/// True when the wall clock moved noticeably more or less than monotonic time,
+/// e.g. an NTP correction or a suspend that paused the monotonic clock.
+pub fn clock_jumped(monotonic_elapsed: Duration, wall_elapsed: chrono::Duration) -> bool {
+    let monotonic_secs = monotonic_elapsed.as_secs() as i64;
+    (wall_elapsed.num_seconds() - monotonic_secs).abs() > CLOCK_JUMP_THRESHOLD_SECS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn ignores_small_offsets() {
+        let local = at("2026-04-13T10:00:00Z");
+        assert_eq!(significant_skew(at("2026-04-13T10:01:00Z"), local), 0);
+        assert_eq!(significant_skew(at("2026-04-13T10:10:00Z"), local), 600);
+        assert_eq!(significant_skew(at("2026-04-13T09:50:00Z"), local), -600);
+    }
+
+    #[test]
+    fn detects_clock_jumps() {
+        let thirty_secs = Duration::from_secs(30);
+        assert!(!clock_jumped(thirty_secs, chrono::Duration::seconds(31)));
+        assert!(clock_jumped(thirty_secs, chrono::Duration::seconds(3600)));
+        assert!(clock_jumped(thirty_secs, chrono::Duration::seconds(-300)));
+    }
+}
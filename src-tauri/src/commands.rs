@@ -1,57 +1,280 @@
-use crate::api::{fetch_usage_for_provider, get_provider_statuses as collect_provider_statuses};
-use crate::auto_refresh::do_fetch_and_emit;
+use crate::api::{self, get_provider_statuses as collect_provider_statuses};
+use crate::app_lock;
+use crate::auto_refresh::{
+    self, FetchResult, apply_usage, backoff_remaining, do_fetch_and_emit, last_fetched_usage,
+    last_success_at, paused_reason, stale_minutes, usage_deltas,
+};
+use crate::budgets::{self, BudgetProgress, UsageBudget};
+use crate::capture;
+use crate::crash_reporter;
 use crate::credentials;
 use crate::error::AppError;
-use crate::history::{self, UsageHistoryPoint, UsageStats};
+use crate::history::{
+    self, ChartSeries, ClearScope, CombinedHistory, HistoryDbInfo, LimitProjection,
+    MemberUsageSummary, TimeToThreshold, UsageHistogram, UsageHistoryPoint, UsageStats,
+};
+use crate::i18n;
+use crate::local_usage::{self, ActiveSessions, ModelUsage, ProjectUsage};
+use crate::notifications;
+use crate::pricing::{self, CostEstimate};
+use crate::report::{self, ReportFormat};
+use crate::secret::SecretString;
+use crate::time_format;
+use crate::tray;
 use crate::types::{
-    AppState, NotificationSettings, ProviderKind, ProviderStatus, Settings, UsageSnapshot,
+    AppLockStatus, AppState, AutoRefreshConfig, CredentialFallback, FetchSource, HistoryChanged,
+    KeychainIssue, LastFetchResult, LocalUsageChanged, ManualRefresh, ModelAdvisorySettings,
+    NotificationSettings, Organization, OrganizationUsage, Preferences, ProviderKind,
+    ProviderStatus, Settings, Staleness, UsageSnapshot, UsageUpdated, monitoring_enabled,
+    reset_epoch_ms,
 };
-use crate::validation::{validate_org_id, validate_session_token};
+use crate::usage_import::UsageImportSummary;
+use crate::validation::{
+    validate_hourly_refresh_gap, validate_hourly_refresh_jitter, validate_idle_hours,
+    validate_interval_minutes, validate_model_advisory, validate_notification_settings,
+    validate_org_id, validate_retention_days, validate_retention_policy, validate_session_expiry,
+    validate_session_token, validate_severity_config, validate_startup_jitter, validate_threshold,
+    validate_token_expiry_warning_days,
+};
+use crate::window_state;
 use std::sync::Arc;
+use tauri_plugin_autostart::ManagerExt;
+use tauri_plugin_store::StoreExt;
+use tauri_specta::Event;
+use zeroize::Zeroizing;
+
+pub(crate) fn settings_store<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Result<Arc<tauri_plugin_store::Store<R>>, AppError> {
+    app.store("settings.json")
+        .map_err(|e| AppError::Storage(format!("Failed to open settings store: {e}")))
+}
+
+/// Point history reads at the primary organization and remember it for the
+/// CLI, which can't reach the keychain.
+pub(crate) fn set_history_organization<R: tauri::Runtime>(app: &tauri::AppHandle<R>, org_id: &str) {
+    history::set_active_organization(org_id);
+    if let Ok(store) = settings_store(app) {
+        store.set("organization_id", org_id);
+    }
+}
+
+/// Run blocking filesystem or database work off the async runtime.
+pub(crate) async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> Result<T, AppError> + Send + 'static,
+) -> Result<T, AppError> {
+    tokio::task::spawn_blocking(task)
+        .await
+        .map_err(|e| AppError::Server(format!("Background task failed: {e}")))?
+}
 
 #[tauri::command]
 #[specta::specta]
 pub async fn get_usage(
+    state: tauri::State<'_, Arc<AppState>>,
     provider: ProviderKind,
     org_id: Option<String>,
     session_token: Option<String>,
     ollama_session_token: Option<String>,
 ) -> Result<UsageSnapshot, AppError> {
-    fetch_usage_for_provider(
-        provider,
-        org_id.as_deref(),
-        session_token.as_deref(),
-        ollama_session_token.as_deref(),
-    )
-    .await
+    let session_token = session_token.map(SecretString::from);
+    let ollama_session_token = ollama_session_token.map(SecretString::from);
+    state
+        .usage_client
+        .fetch_usage(
+            provider,
+            org_id.as_deref(),
+            session_token.as_ref().map(SecretString::expose),
+            ollama_session_token.as_ref().map(SecretString::expose),
+        )
+        .await
+        .result
 }
 
 #[tauri::command]
 #[specta::specta]
 pub fn get_default_settings() -> Settings {
-    Settings::default()
+    Settings::from_config(crate::config::get())
 }
 
 #[tauri::command]
 #[specta::specta]
 pub async fn save_credentials(
+    app: tauri::AppHandle,
     state: tauri::State<'_, Arc<AppState>>,
     org_id: String,
     session_token: String,
+    session_expires_at: Option<String>,
 ) -> Result<(), AppError> {
+    let session_token = SecretString::from(session_token);
+    ensure_unlocked(&state).await?;
     validate_org_id(&org_id)?;
-    validate_session_token(&session_token)?;
-    credentials::save_credentials(&org_id, &session_token)?;
+    validate_session_token(session_token.expose())?;
+    if let Some(expires_at) = &session_expires_at {
+        validate_session_expiry(expires_at)?;
+    }
+    keep_if_keychain_unusable(
+        &app,
+        credentials::save_credentials(
+            &org_id,
+            session_token.expose(),
+            session_expires_at.as_deref(),
+        ),
+    )?;
 
     let mut config = state.config.lock().await;
+    if config.organization_id.as_deref() != Some(org_id.as_str()) {
+        settings_store(&app)?.delete("organization_name");
+        config.organization_name = None;
+    }
+    set_history_organization(&app, &org_id);
     config.organization_id = Some(org_id);
     config.session_token = Some(session_token);
+    config.session_expires_at = session_expires_at;
+    tray::update_organization_menu(&app, &config);
     drop(config);
 
     let _ = state.restart_tx.send(());
     Ok(())
 }
 
+/// Organizations available to a session token, for the organization picker.
+#[tauri::command]
+#[specta::specta]
+pub async fn list_claude_organizations(
+    state: tauri::State<'_, Arc<AppState>>,
+    session_token: String,
+) -> Result<Vec<Organization>, AppError> {
+    let session_token = SecretString::from(session_token);
+    state
+        .usage_client
+        .fetch_claude_organizations(session_token.expose())
+        .await
+}
+
+/// Monitor these organizations alongside the primary one, using the same
+/// session token. Each is fetched in its own task on every refresh.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_additional_organizations(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    organizations: Vec<Organization>,
+) -> Result<(), AppError> {
+    for organization in &organizations {
+        validate_org_id(&organization.id)?;
+    }
+    let value = serde_json::to_value(&organizations)
+        .map_err(|e| AppError::Storage(format!("Failed to serialize organizations: {e}")))?;
+    settings_store(&app)?.set("additional_organizations", value);
+
+    state
+        .organization_usage
+        .lock()
+        .await
+        .retain(|entry| organizations.iter().any(|o| o.id == entry.organization.id));
+    let mut config = state.config.lock().await;
+    config.additional_organizations = organizations;
+    tray::update_organization_menu(&app, &config);
+    drop(config);
+
+    let _ = state.restart_tx.send(());
+    Ok(())
+}
+
+/// Make one of the additional organizations the primary one, keeping the
+/// session token; the previous primary becomes an additional organization.
+#[tauri::command]
+#[specta::specta]
+pub async fn switch_organization(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    org_id: String,
+) -> Result<(), AppError> {
+    apply_primary_organization(&app, &state, &org_id).await
+}
+
+/// Shared by `switch_organization` and the tray's organization submenu.
+pub(crate) async fn apply_primary_organization<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &AppState,
+    org_id: &str,
+) -> Result<(), AppError> {
+    let mut config = state.config.lock().await;
+    if config.locked {
+        return Err(AppError::Locked);
+    }
+    if config.organization_id.as_deref() == Some(org_id) {
+        tray::update_organization_menu(app, &config);
+        return Ok(());
+    }
+    let Some(index) = config
+        .additional_organizations
+        .iter()
+        .position(|organization| organization.id == org_id)
+    else {
+        return Err(AppError::OrganizationNotFound(org_id.to_string()));
+    };
+    let Some(session_token) = config.session_token.as_ref() else {
+        return Err(AppError::MissingConfig("Claude session token".to_string()));
+    };
+    keep_if_keychain_unusable(
+        app,
+        credentials::save_credentials(
+            org_id,
+            session_token.expose(),
+            config.session_expires_at.as_deref(),
+        ),
+    )?;
+
+    let mut organizations = config.additional_organizations.clone();
+    let selected = organizations.remove(index);
+    if let Some(previous_id) = config.organization_id.clone() {
+        let name = config
+            .organization_name
+            .clone()
+            .unwrap_or_else(|| previous_id.clone());
+        organizations.insert(
+            index,
+            Organization {
+                id: previous_id,
+                name,
+                plan: None,
+            },
+        );
+    }
+    let value = serde_json::to_value(&organizations)
+        .map_err(|e| AppError::Storage(format!("Failed to serialize organizations: {e}")))?;
+    let store = settings_store(app)?;
+    store.set("additional_organizations", value);
+    store.set("organization_name", selected.name.clone());
+
+    log::info!("Switched primary organization to {}", selected.id);
+    set_history_organization(app, &selected.id);
+    config.organization_id = Some(selected.id);
+    config.organization_name = Some(selected.name);
+    config.additional_organizations = organizations;
+    tray::update_organization_menu(app, &config);
+    drop(config);
+
+    state
+        .organization_usage
+        .lock()
+        .await
+        .retain(|entry| entry.organization.id != org_id);
+    let _ = state.restart_tx.send(());
+    Ok(())
+}
+
+/// Latest usage of each additional organization.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_organization_usage(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<OrganizationUsage>, ()> {
+    Ok(state.organization_usage.lock().await.clone())
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn clear_credentials(state: tauri::State<'_, Arc<AppState>>) -> Result<(), AppError> {
@@ -60,6 +283,7 @@ pub async fn clear_credentials(state: tauri::State<'_, Arc<AppState>>) -> Result
     let mut config = state.config.lock().await;
     config.organization_id = None;
     config.session_token = None;
+    config.session_expires_at = None;
     drop(config);
 
     let _ = state.restart_tx.send(());
@@ -69,11 +293,17 @@ pub async fn clear_credentials(state: tauri::State<'_, Arc<AppState>>) -> Result
 #[tauri::command]
 #[specta::specta]
 pub async fn save_ollama_credentials(
+    app: tauri::AppHandle,
     state: tauri::State<'_, Arc<AppState>>,
     session_token: String,
 ) -> Result<(), AppError> {
-    validate_session_token(&session_token)?;
-    credentials::save_ollama_credentials(&session_token)?;
+    let session_token = SecretString::from(session_token);
+    ensure_unlocked(&state).await?;
+    validate_session_token(session_token.expose())?;
+    keep_if_keychain_unusable(
+        &app,
+        credentials::save_ollama_credentials(session_token.expose()),
+    )?;
 
     let mut config = state.config.lock().await;
     config.ollama_session_token = Some(session_token);
@@ -98,20 +328,172 @@ pub async fn clear_ollama_credentials(
     Ok(())
 }
 
+/// A keychain that can't be used at all doesn't stop credentials from being
+/// saved: they are kept in memory for this session and the UI is told why.
+fn keep_if_keychain_unusable<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    result: Result<(), AppError>,
+) -> Result<(), AppError> {
+    let Err(e) = result else {
+        return Ok(());
+    };
+    match credentials::keychain_issue() {
+        Some(issue) if issue.fallback == CredentialFallback::MemoryOnly => {
+            log::warn!("Keeping credentials in memory only: {e}");
+            let _ = issue.emit(app);
+            Ok(())
+        }
+        _ => Err(e),
+    }
+}
+
+/// Why saved credentials couldn't be loaded or saved, if they couldn't.
+#[tauri::command]
+#[specta::specta]
+pub fn get_keychain_status() -> Option<KeychainIssue> {
+    credentials::keychain_issue()
+}
+
+/// Credentials can't be changed while the app is locked.
+async fn ensure_unlocked(state: &AppState) -> Result<(), AppError> {
+    if state.config.lock().await.locked {
+        return Err(AppError::Locked);
+    }
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_app_lock_status(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<AppLockStatus, ()> {
+    let config = state.config.lock().await;
+    Ok(AppLockStatus {
+        enabled: config.app_lock_hash.is_some(),
+        locked: config.locked,
+        auto_lock_minutes: config.auto_lock_minutes,
+    })
+}
+
+/// Set, change (`current_passphrase` must match) or remove (None) the app lock
+/// passphrase. Only possible while unlocked.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_app_passphrase(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    passphrase: Option<String>,
+    current_passphrase: Option<String>,
+) -> Result<(), AppError> {
+    let passphrase = passphrase.map(Zeroizing::new);
+    let current_passphrase = current_passphrase.map(Zeroizing::new);
+    let config = state.config.lock().await;
+    if config.locked {
+        return Err(AppError::Locked);
+    }
+    let current_hash = config.app_lock_hash.clone();
+    drop(config);
+    if let Some(hash) = current_hash {
+        let current = current_passphrase.as_deref().map_or("", String::as_str);
+        app_lock::check_passphrase(hash, current).await?;
+    }
+
+    let store = settings_store(&app)?;
+    let hash = match passphrase {
+        Some(passphrase) if passphrase.is_empty() => {
+            return Err(AppError::MissingConfig("passphrase".to_string()));
+        }
+        Some(passphrase) => {
+            let hash = run_blocking(move || app_lock::hash_passphrase(&passphrase)).await?;
+            store.set(app_lock::PASSPHRASE_HASH_KEY, hash.clone());
+            Some(hash)
+        }
+        None => {
+            store.delete(app_lock::PASSPHRASE_HASH_KEY);
+            None
+        }
+    };
+    state.config.lock().await.app_lock_hash = hash;
+    Ok(())
+}
+
+/// Lock after `minutes` away from the window. None disables auto-lock.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_auto_lock_timeout(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    minutes: Option<u32>,
+) -> Result<(), AppError> {
+    let store = settings_store(&app)?;
+    match minutes {
+        Some(minutes) => store.set(app_lock::AUTO_LOCK_MINUTES_KEY, minutes),
+        None => {
+            store.delete(app_lock::AUTO_LOCK_MINUTES_KEY);
+        }
+    }
+
+    state.config.lock().await.auto_lock_minutes = minutes;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn lock_app(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<(), AppError> {
+    if state.config.lock().await.app_lock_hash.is_none() {
+        return Err(AppError::MissingConfig("app lock passphrase".to_string()));
+    }
+    app_lock::lock(&app, &state).await;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn unlock_app(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    passphrase: String,
+) -> Result<(), AppError> {
+    let passphrase = Zeroizing::new(passphrase);
+    app_lock::unlock(&app, &state, &passphrase).await
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn set_active_provider(
     state: tauri::State<'_, Arc<AppState>>,
     provider: ProviderKind,
 ) -> Result<(), ()> {
-    let mut config = state.config.lock().await;
-    config.active_provider = provider;
-    drop(config);
-
-    let _ = state.restart_tx.send(());
+    update_refresh_config(&state, |config| {
+        replace_setting(&mut config.active_provider, provider)
+    })
+    .await;
     Ok(())
 }
 
+/// Apply `update` to the refresh config and restart the loop only if it
+/// reports a change. The frontend re-sends its settings at startup; restarting
+/// then would cut the startup jitter and a resumed backoff short, and record
+/// the first fetch as manual.
+async fn update_refresh_config(
+    state: &AppState,
+    update: impl FnOnce(&mut AutoRefreshConfig) -> bool,
+) {
+    let changed = update(&mut *state.config.lock().await);
+    if changed {
+        let _ = state.restart_tx.send(());
+    }
+}
+
+/// Store `value` in `slot`; true when that changed it.
+fn replace_setting<T: PartialEq>(slot: &mut T, value: T) -> bool {
+    let previous = std::mem::replace(slot, value);
+    previous != *slot
+}
+
 #[tauri::command]
 #[specta::specta]
 pub async fn get_provider_statuses(
@@ -120,8 +502,11 @@ pub async fn get_provider_statuses(
     let config = state.config.lock().await;
     Ok(collect_provider_statuses(
         config.organization_id.as_deref(),
-        config.session_token.as_deref(),
-        config.ollama_session_token.as_deref(),
+        config.session_token.as_ref().map(SecretString::expose),
+        config
+            .ollama_session_token
+            .as_ref()
+            .map(SecretString::expose),
     ))
 }
 
@@ -131,13 +516,13 @@ pub async fn set_auto_refresh(
     state: tauri::State<'_, Arc<AppState>>,
     enabled: bool,
     interval_minutes: u32,
-) -> Result<(), ()> {
-    let mut config = state.config.lock().await;
-    config.enabled = enabled;
-    config.interval_minutes = interval_minutes;
-    drop(config);
-
-    let _ = state.restart_tx.send(());
+) -> Result<(), AppError> {
+    validate_interval_minutes("interval_minutes", interval_minutes)?;
+    update_refresh_config(&state, |config| {
+        replace_setting(&mut config.enabled, enabled)
+            | replace_setting(&mut config.interval_minutes, interval_minutes)
+    })
+    .await;
     Ok(())
 }
 
@@ -147,27 +532,445 @@ pub async fn set_hourly_refresh(
     state: tauri::State<'_, Arc<AppState>>,
     enabled: bool,
 ) -> Result<(), ()> {
+    update_refresh_config(&state, |config| {
+        replace_setting(&mut config.hourly_refresh_enabled, enabled)
+    })
+    .await;
+    Ok(())
+}
+
+/// Wait up to `jitter_secs` at random before the first fetch after launch or a
+/// wake. At most 10 minutes; 0 fetches right away.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_startup_jitter(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    jitter_secs: u32,
+) -> Result<(), AppError> {
+    validate_startup_jitter("jitter_secs", jitter_secs)?;
+    settings_store(&app)?.set("startup_jitter_secs", jitter_secs);
+    state.config.lock().await.startup_jitter_secs = jitter_secs;
+    Ok(())
+}
+
+/// Tune the hourly fetch: wait `gap_secs` after the top of the hour plus up to
+/// `jitter_secs` at random, at most 15 and 5 minutes.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_hourly_refresh_timing(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    gap_secs: u32,
+    jitter_secs: u32,
+) -> Result<(), AppError> {
+    validate_hourly_refresh_gap("gap_secs", gap_secs)?;
+    validate_hourly_refresh_jitter("jitter_secs", jitter_secs)?;
+    let store = settings_store(&app)?;
+    store.set("hourly_refresh_gap_secs", gap_secs);
+    store.set("hourly_refresh_jitter_secs", jitter_secs);
+
+    update_refresh_config(&state, |config| {
+        replace_setting(&mut config.hourly_refresh_gap_secs, gap_secs)
+            | replace_setting(&mut config.hourly_refresh_jitter_secs, jitter_secs)
+    })
+    .await;
+    Ok(())
+}
+
+/// Enable or disable launch at login. The window is shown when the OS starts
+/// the app at login, unless `start_hidden` keeps it in the tray.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_autostart(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    enabled: bool,
+    start_hidden: bool,
+) -> Result<(), AppError> {
+    let autolaunch = app.autolaunch();
+    let result = if enabled {
+        autolaunch.enable()
+    } else {
+        autolaunch.disable()
+    };
+    result.map_err(|e| AppError::Storage(format!("Failed to update launch at login: {e}")))?;
+
+    let mut preferences = state.preferences.lock().await;
+    preferences.start_hidden_at_login = start_hidden;
+    persist_preferences(&app, &preferences)
+}
+
+/// Shared by `set_compact_mode` and the tray menu toggle.
+pub(crate) async fn apply_compact_mode<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    state: &AppState,
+    compact: bool,
+) -> Result<(), AppError> {
+    {
+        let mut preferences = state.preferences.lock().await;
+        preferences.compact_mode = compact;
+        persist_preferences(app, &preferences)?;
+    }
+    window_state::set_compact(app, compact);
+    Ok(())
+}
+
+/// Switch between the full window and a minimal strip of usage bars.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_compact_mode(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    compact: bool,
+) -> Result<(), AppError> {
+    apply_compact_mode(&app, &state, compact).await
+}
+
+/// Keep the window visible when it loses focus instead of hiding it.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_window_pinned(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    pinned: bool,
+) -> Result<(), AppError> {
+    let mut preferences = state.preferences.lock().await;
+    preferences.window_pinned = pinned;
+    persist_preferences(&app, &preferences)?;
+    window_state::set_pinned(pinned);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_active_session_interval(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    interval_minutes: Option<u32>,
+) -> Result<(), AppError> {
+    if let Some(minutes) = interval_minutes {
+        validate_interval_minutes("active_session_interval_minutes", minutes)?;
+    }
+    let store = settings_store(&app)?;
+    match interval_minutes {
+        Some(minutes) => store.set("active_session_interval_minutes", minutes),
+        None => {
+            store.delete("active_session_interval_minutes");
+        }
+    }
+
+    update_refresh_config(&state, |config| {
+        replace_setting(
+            &mut config.active_session_interval_minutes,
+            interval_minutes,
+        )
+    })
+    .await;
+    Ok(())
+}
+
+/// Poll every minute during the last `minutes` before the next reset, so the
+/// rollover shows up promptly. None turns it off.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_near_reset_polling(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    minutes: Option<u32>,
+) -> Result<(), AppError> {
+    if let Some(minutes) = minutes {
+        validate_interval_minutes("near_reset_minutes", minutes)?;
+    }
+    let store = settings_store(&app)?;
+    match minutes {
+        Some(minutes) => store.set("near_reset_minutes", minutes),
+        None => {
+            store.delete("near_reset_minutes");
+        }
+    }
+
+    update_refresh_config(&state, |config| {
+        replace_setting(&mut config.near_reset_minutes, minutes)
+    })
+    .await;
+    Ok(())
+}
+
+/// Warn this many days (at most 90) before a known session expiry; 0 turns the
+/// warning off.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_token_expiry_warning(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    days: u32,
+) -> Result<(), AppError> {
+    validate_token_expiry_warning_days("days", days)?;
+    let store = settings_store(&app)?;
+    store.set("token_expiry_warning_days", days);
+
+    state.config.lock().await.token_expiry_warning_days = days;
+    Ok(())
+}
+
+/// Configure when to suggest Sonnet over Opus (see `ModelAdvisorySettings`).
+#[tauri::command]
+#[specta::specta]
+pub async fn set_model_advisory(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    settings: ModelAdvisorySettings,
+) -> Result<(), AppError> {
+    validate_model_advisory(&settings)?;
+    let value = serde_json::to_value(settings)
+        .map_err(|e| AppError::Storage(format!("Failed to serialize model advisory: {e}")))?;
+    settings_store(&app)?.set("model_advisory", value);
+
+    state.config.lock().await.model_advisory = settings;
+    Ok(())
+}
+
+/// Record per-member usage alongside each Claude fetch. Needs an organization
+/// admin's session; for anyone else the member requests are refused and skipped.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_member_usage_enabled(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let store = settings_store(&app)?;
+    store.set("member_usage_enabled", enabled);
+    // Re-enabling asks an organization that answered 404 again
+    if enabled {
+        store.delete("member_usage_unsupported_org");
+    }
+
     let mut config = state.config.lock().await;
-    config.hourly_refresh_enabled = enabled;
-    drop(config);
+    config.member_usage_enabled = enabled;
+    if enabled {
+        config.member_usage_unsupported_org = None;
+    }
+    Ok(())
+}
 
-    let _ = state.restart_tx.send(());
+/// Notify with last week's peaks and capped short windows whenever a 7-day
+/// window resets.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_weekly_summary_enabled(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    settings_store(&app)?.set("weekly_summary_enabled", enabled);
+
+    state.config.lock().await.weekly_summary_enabled = enabled;
+    Ok(())
+}
+
+/// Replace the usage budgets; each needs a window key and a target of 1-100%.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_usage_budgets(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    budgets: Vec<UsageBudget>,
+) -> Result<(), AppError> {
+    for budget in &budgets {
+        if budget.window_key.is_empty() {
+            return Err(AppError::MissingConfig(format!(
+                "invalid budget for {}",
+                budget.provider.as_str()
+            )));
+        }
+        validate_threshold("target_percent", budget.target_percent)?;
+    }
+    let value = serde_json::to_value(&budgets)
+        .map_err(|e| AppError::Storage(format!("Failed to serialize budgets: {e}")))?;
+    settings_store(&app)?.set(budgets::BUDGETS_KEY, value);
+
+    state.config.lock().await.budgets = budgets;
+    Ok(())
+}
+
+/// Progress of each budget against its planned burn, from the latest stored
+/// snapshot of its provider. Budgets whose window has no data are left out.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_budget_progress(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Vec<BudgetProgress>, AppError> {
+    let budgets = state.config.lock().await.budgets.clone();
+    let snapshots = history::get_latest_snapshots()
+        .map_err(|e| AppError::Storage(format!("Failed to read latest usage: {e}")))?;
+    let now = crate::clock::now();
+    Ok(budgets
+        .iter()
+        .filter_map(|budget| {
+            let window = snapshots
+                .iter()
+                .find(|snapshot| snapshot.provider == budget.provider.as_str())?
+                .windows
+                .iter()
+                .find(|window| window.key == budget.window_key)?;
+            budgets::progress(budget, window, now)
+        })
+        .collect())
+}
+
+/// Pause polling after `hours` without a local Claude Code session or a rise in
+/// usage, resuming on activity. None polls all the time.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_idle_suspend(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    hours: Option<u32>,
+) -> Result<(), AppError> {
+    if let Some(hours) = hours {
+        validate_idle_hours("idle_suspend_hours", hours)?;
+    }
+    let store = settings_store(&app)?;
+    match hours {
+        Some(hours) => store.set("idle_suspend_hours", hours),
+        None => {
+            store.delete("idle_suspend_hours");
+        }
+    }
+
+    update_refresh_config(&state, |config| {
+        replace_setting(&mut config.idle_suspend_hours, hours)
+    })
+    .await;
     Ok(())
 }
 
+/// Store at most one history sample per `minutes`, however often usage is
+/// polled. None stores every fetch.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_history_sample_interval(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    minutes: Option<u32>,
+) -> Result<(), AppError> {
+    if let Some(minutes) = minutes {
+        validate_interval_minutes("history_sample_interval_minutes", minutes)?;
+    }
+    let store = settings_store(&app)?;
+    match minutes {
+        Some(minutes) => store.set("history_sample_interval_minutes", minutes),
+        None => {
+            store.delete("history_sample_interval_minutes");
+        }
+    }
+
+    state.config.lock().await.history_sample_interval_minutes = minutes;
+    Ok(())
+}
+
+/// Skip storing snapshots identical to the previous one, keeping one per hour.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_skip_unchanged_samples(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    settings_store(&app)?.set("skip_unchanged_samples", enabled);
+
+    state.config.lock().await.skip_unchanged_samples = enabled;
+    Ok(())
+}
+
+/// Share history with other machines through `folder` (e.g. a Dropbox or
+/// Syncthing folder). None stops syncing; logs already written stay.
+#[tauri::command]
+#[specta::specta]
+pub async fn set_history_sync_folder(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    folder: Option<String>,
+) -> Result<(), AppError> {
+    let store = settings_store(&app)?;
+    match &folder {
+        Some(folder) => {
+            if !std::path::Path::new(folder).is_dir() {
+                return Err(AppError::MissingConfig(format!(
+                    "history sync folder {folder} is not a directory"
+                )));
+            }
+            store.set("history_sync_folder", folder.clone());
+        }
+        None => {
+            store.delete("history_sync_folder");
+        }
+    }
+
+    state.config.lock().await.history_sync_folder = folder;
+    Ok(())
+}
+
+/// Import what other machines appended to the sync folder. Returns how many
+/// points were new.
+#[tauri::command]
+#[specta::specta]
+pub async fn sync_history_now(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<usize, String> {
+    let config = state.config.lock().await;
+    let folder = config.history_sync_folder.clone();
+    let machine_id = config.machine_id.clone();
+    drop(config);
+
+    let Some(folder) = folder else {
+        return Err("No history sync folder is set".to_string());
+    };
+    let imported = run_blocking(move || {
+        crate::history_sync::import_logs(std::path::Path::new(&folder), &machine_id)
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+    if imported > 0 {
+        let _ = HistoryChanged.emit(&app);
+    }
+    Ok(imported)
+}
+
+/// Fetch now, unless the refresh loop is backing off after a rate limit: then
+/// nothing is sent, and the loop fetches once the backoff ends.
 #[tauri::command]
 #[specta::specta]
 pub async fn refresh_now(
     app: tauri::AppHandle,
     state: tauri::State<'_, Arc<AppState>>,
-) -> Result<(), ()> {
+) -> Result<ManualRefresh, ()> {
+    let backoff_until = *state.backoff_until.lock().await;
+    if let Some(retry_in_seconds) = backoff_remaining(backoff_until, chrono::Utc::now()) {
+        log::info!("Manual refresh skipped while rate limited; retry in {retry_in_seconds}s");
+        return Ok(ManualRefresh::RateLimited { retry_in_seconds });
+    }
+
     let config = state.config.lock().await;
     let interval_minutes = config.interval_minutes;
     drop(config);
 
-    do_fetch_and_emit(&app, &state, interval_minutes).await;
+    let output = do_fetch_and_emit(&app, &state, interval_minutes, FetchSource::Manual).await;
+    // Record the backoff before the restart, so the loop waits it out instead
+    // of fetching again at once
+    let rate_limited = if output.result == FetchResult::RateLimited {
+        Some(auto_refresh::record_manual_rate_limit(&app, &state, output.rate_limit.as_ref()).await)
+    } else {
+        None
+    };
     let _ = state.restart_tx.send(());
-    Ok(())
+    Ok(match rate_limited {
+        Some(retry_in_seconds) => ManualRefresh::RateLimited { retry_in_seconds },
+        None => ManualRefresh::Refreshed,
+    })
 }
 
 #[tauri::command]
@@ -175,19 +978,165 @@ pub async fn refresh_now(
 pub async fn set_notification_settings(
     state: tauri::State<'_, Arc<AppState>>,
     settings: NotificationSettings,
-) -> Result<(), ()> {
+) -> Result<(), AppError> {
+    validate_notification_settings(&settings)?;
     let mut notification_settings = state.notification_settings.lock().await;
     *notification_settings = settings;
     Ok(())
 }
 
+/// Outcome of the most recent usage fetch, or None before the first one.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_last_fetch_result(
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Option<LastFetchResult>, ()> {
+    Ok(state.last_fetch.lock().await.clone())
+}
+
+/// How old the displayed usage is. Stale means the last successful update was
+/// more than twice the refresh interval ago.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_staleness(state: tauri::State<'_, Arc<AppState>>) -> Result<Staleness, ()> {
+    let last_success_at = *state.last_success_at.lock().await;
+    let interval_minutes = state.config.lock().await.interval_minutes;
+    let now = crate::clock::now();
+    Ok(Staleness {
+        last_success_at: last_success_at.map(|at| at.to_rfc3339()),
+        age_seconds: last_success_at.map(|at| (now - at).num_seconds()),
+        stale: stale_minutes(last_success_at, interval_minutes, now).is_some(),
+    })
+}
+
+/// Development aid: run a synthetic snapshot through notifications, the tray
+/// and history as if it had just been fetched. Refused in release builds.
+#[tauri::command]
+#[specta::specta]
+pub async fn simulate_usage(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    mut usage: UsageSnapshot,
+) -> Result<(), String> {
+    if !cfg!(debug_assertions) {
+        return Err("simulate_usage is only available in debug builds".to_string());
+    }
+
+    log::info!(
+        "Simulating usage for provider={} with {} windows",
+        usage.provider.as_str(),
+        usage.windows.len()
+    );
+    for window in &mut usage.windows {
+        window.resets_at_epoch_ms = reset_epoch_ms(window.resets_at.as_deref());
+    }
+    let previous = last_fetched_usage(&state, usage.provider).await;
+    apply_usage(&app, &state, &usage, FetchSource::Manual).await;
+    let paused_reason = paused_reason(&state).await;
+    let _ = UsageUpdated {
+        deltas: usage_deltas(previous.as_ref(), &usage),
+        usage,
+        next_refresh_at: None,
+        last_success_at: last_success_at(&state).await,
+        monitoring_enabled: monitoring_enabled(paused_reason),
+        paused_reason,
+    }
+    .emit(&app);
+    Ok(())
+}
+
+/// Append the body of every successful usage response to `path` (NDJSON,
+/// redacted) until `stop_response_capture`, for attaching to bug reports.
+#[tauri::command]
+#[specta::specta]
+pub fn start_response_capture(path: String) -> Result<(), AppError> {
+    capture::start(std::path::PathBuf::from(path))
+}
+
+/// Stop capturing. Returns the capture file, if one was being written.
+#[tauri::command]
+#[specta::specta]
+pub fn stop_response_capture() -> Option<String> {
+    capture::stop().map(|path| path.display().to_string())
+}
+
+/// Development aid: replay a capture file through parsing, notifications, the
+/// tray and history, with the gaps between responses divided by `speed`.
+/// Returns how many responses were applied. Refused in release builds.
+#[tauri::command]
+#[specta::specta]
+pub async fn replay_response_capture(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    path: String,
+    speed: f64,
+) -> Result<usize, String> {
+    if !cfg!(debug_assertions) {
+        return Err("replay_response_capture is only available in debug builds".to_string());
+    }
+    capture::replay(&app, &state, std::path::Path::new(&path), speed)
+        .await
+        .map_err(|e| e.message())
+}
+
+/// Silence all alerts until the current short window (e.g. Claude's 5-hour)
+/// resets. Returns the deadline, or None when no upcoming reset is known yet.
+#[tauri::command]
+#[specta::specta]
+pub async fn snooze_until_reset(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+) -> Result<Option<String>, AppError> {
+    let provider = state.config.lock().await.active_provider;
+    let resets_at =
+        history::latest_resets_at(provider).map_err(|e| AppError::Storage(e.to_string()))?;
+    let Some(until) =
+        notifications::next_reset(resets_at.iter().map(String::as_str), crate::clock::now())
+    else {
+        return Ok(None);
+    };
+
+    let mut notification_state = state.notification_state.lock().await;
+    notification_state.snoozed_until = Some(until.clone());
+    if history::is_incognito() {
+        return Ok(Some(until));
+    }
+    persist_notification_state(&app, &notification_state)?;
+    Ok(Some(until))
+}
+
+/// Write the notification state to the settings store.
+pub(crate) fn persist_notification_state<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    notification_state: &NotificationState,
+) -> Result<(), AppError> {
+    let value = serde_json::to_value(notification_state)
+        .map_err(|e| AppError::Storage(format!("Failed to serialize notification state: {e}")))?;
+    settings_store(app)?.set("notification_state", value);
+    Ok(())
+}
+
+/// Samples over `range`; `metrics` limits them to those window keys (empty for all).
 #[tauri::command]
 #[specta::specta]
 pub fn get_usage_history_by_range(
     provider: ProviderKind,
     range: String,
+    metrics: Vec<String>,
 ) -> Result<Vec<UsageHistoryPoint>, String> {
-    history::get_usage_history_by_range(provider, &range).map_err(|e| e.to_string())
+    history::get_usage_history_by_range(provider, &range, &metrics).map_err(|e| e.to_string())
+}
+
+/// One plot-ready series per window over `range`: epoch-ms x values,
+/// downsampled like `get_usage_history_by_range`, with null y at gaps.
+#[tauri::command]
+#[specta::specta]
+pub fn get_chart_series(
+    provider: ProviderKind,
+    range: String,
+    metrics: Vec<String>,
+) -> Result<Vec<ChartSeries>, String> {
+    history::get_chart_series(provider, &range, &metrics).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -196,16 +1145,222 @@ pub fn get_usage_stats(provider: ProviderKind, range: String) -> Result<UsageSta
     history::get_usage_stats(provider, &range).map_err(|e| e.to_string())
 }
 
+/// Consumption of the `metric` window over `range`, by local weekday and hour.
+#[tauri::command]
+#[specta::specta]
+pub fn get_usage_histogram(
+    provider: ProviderKind,
+    range: String,
+    metric: String,
+) -> Result<UsageHistogram, String> {
+    history::get_usage_histogram(provider, &range, &metric).map_err(|e| e.to_string())
+}
+
+/// Average time from the start of 5-hour periods in `range` until they
+/// reached `threshold` percent.
+#[tauri::command]
+#[specta::specta]
+pub fn get_time_to_threshold(
+    provider: ProviderKind,
+    range: String,
+    threshold: f64,
+) -> Result<TimeToThreshold, String> {
+    if !(threshold > 0.0 && threshold <= 100.0) {
+        return Err("Threshold must be between 0 and 100".to_string());
+    }
+    history::get_time_to_threshold(provider, &range, threshold).map_err(|e| e.to_string())
+}
+
+/// Per window, when 100% would be reached at the current pace (null when the
+/// window resets first or usage isn't climbing).
+#[tauri::command]
+#[specta::specta]
+pub fn get_projected_limit_time(provider: ProviderKind) -> Result<Vec<LimitProjection>, String> {
+    history::get_projected_limits(provider).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_combined_history(
+    provider: ProviderKind,
+    range: String,
+    metrics: Vec<String>,
+) -> Result<CombinedHistory, String> {
+    history::get_combined_history(provider, &range, &metrics).map_err(|e| e.to_string())
+}
+
+/// Per-member usage of the current Claude organization over `range`, recorded
+/// while member usage is enabled. Empty when no organization is configured;
+/// an error when the organization doesn't offer member usage and nothing was
+/// recorded, so the UI can say why.
+#[tauri::command]
+#[specta::specta]
+pub async fn get_member_usage(
+    state: tauri::State<'_, Arc<AppState>>,
+    range: String,
+) -> Result<Vec<MemberUsageSummary>, String> {
+    let config = state.config.lock().await;
+    let Some(org_id) = config.organization_id.clone() else {
+        return Ok(Vec::new());
+    };
+    let unsupported = config.member_usage_unsupported_org.as_ref() == Some(&org_id);
+    drop(config);
+
+    let members = history::get_member_usage(&org_id, &range).map_err(|e| e.to_string())?;
+    if members.is_empty() && unsupported {
+        return Err(i18n::t(i18n::Text::MemberUsageUnsupportedBody).to_string());
+    }
+    Ok(members)
+}
+
 #[tauri::command]
 #[specta::specta]
 pub fn cleanup_history(retention_days: u32) -> Result<usize, String> {
+    validate_retention_days("retention_days", retention_days).map_err(|e| e.to_string())?;
     history::cleanup_old_data(retention_days).map_err(|e| e.to_string())
 }
 
+/// Delete history for `scope`. `confirmation` must be the phrase the user typed
+/// into the confirmation dialog (`DELETE`).
+#[tauri::command]
+#[specta::specta]
+pub fn clear_history(
+    app: tauri::AppHandle,
+    scope: ClearScope,
+    confirmation: String,
+) -> Result<usize, String> {
+    if confirmation != history::CLEAR_HISTORY_CONFIRMATION {
+        return Err("History was not cleared: confirmation does not match".to_string());
+    }
+    let deleted = history::clear_history(&scope).map_err(|e| e.to_string())?;
+    let _ = HistoryChanged.emit(&app);
+    Ok(deleted)
+}
+
+/// Import token history exported by another tool (`ccusage daily --json`).
+#[tauri::command]
+#[specta::specta]
+pub fn import_usage_data(
+    app: tauri::AppHandle,
+    path: String,
+) -> Result<UsageImportSummary, String> {
+    let summary = crate::usage_import::import_usage_file(std::path::Path::new(&path))
+        .map_err(|e| e.to_string())?;
+    if summary.imported > 0 {
+        let _ = LocalUsageChanged.emit(&app);
+    }
+    Ok(summary)
+}
+
+#[tauri::command]
+#[specta::specta]
+pub fn get_history_db_info() -> Result<HistoryDbInfo, String> {
+    history::get_db_info().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_preferences(state: tauri::State<'_, Arc<AppState>>) -> Result<Preferences, ()> {
+    Ok(state.preferences.lock().await.clone())
+}
+
+fn persist_preferences<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    preferences: &Preferences,
+) -> Result<(), AppError> {
+    let store = settings_store(app)?;
+    let value = serde_json::to_value(preferences)
+        .map_err(|e| AppError::Storage(format!("Failed to serialize preferences: {e}")))?;
+    store.set("preferences", value);
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn set_preferences(
+    app: tauri::AppHandle,
+    state: tauri::State<'_, Arc<AppState>>,
+    preferences: Preferences,
+) -> Result<(), AppError> {
+    validate_severity_config(&preferences.severity)?;
+    validate_retention_policy(&preferences.history_retention)?;
+    persist_preferences(&app, &preferences)?;
+    if preferences.incognito {
+        // Drop what was persisted before; the in-memory state carries on
+        settings_store(&app)?.delete("notification_state");
+    }
+
+    crash_reporter::set_enabled(preferences.crash_reports_enabled);
+    i18n::set_language(preferences.language);
+    time_format::set_time_format(preferences.time_format);
+    api::set_user_agent_mode(preferences.user_agent);
+    window_state::set_window_placement(preferences.window_placement);
+    window_state::set_pinned(preferences.window_pinned);
+    notifications::set_time_sensitive_alerts(preferences.time_sensitive_alerts);
+    history::set_incognito(preferences.incognito);
+    history::set_retention_policy(&preferences.history_retention);
+    *state.preferences.lock().await = preferences;
+    Ok(())
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_project_usage(
+    state: tauri::State<'_, Arc<AppState>>,
+    range: String,
+) -> Result<Vec<ProjectUsage>, AppError> {
+    let pricing_overrides = state.preferences.lock().await.pricing_overrides.clone();
+    run_blocking(move || local_usage::get_project_usage(&range, &pricing_overrides)).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_model_usage(
+    state: tauri::State<'_, Arc<AppState>>,
+    range: String,
+) -> Result<Vec<ModelUsage>, AppError> {
+    let pricing_overrides = state.preferences.lock().await.pricing_overrides.clone();
+    run_blocking(move || local_usage::get_model_usage(&range, &pricing_overrides)).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_active_sessions() -> Result<ActiveSessions, AppError> {
+    run_blocking(local_usage::get_active_sessions).await
+}
+
+#[tauri::command]
+#[specta::specta]
+pub async fn get_cost_estimate(
+    state: tauri::State<'_, Arc<AppState>>,
+    range: String,
+) -> Result<CostEstimate, AppError> {
+    let pricing_overrides = state.preferences.lock().await.pricing_overrides.clone();
+    run_blocking(move || pricing::get_cost_estimate(&range, &pricing_overrides)).await
+}
+
+/// Write a Markdown or HTML usage report for `range` to `path`.
+#[tauri::command]
+#[specta::specta]
+pub async fn generate_report(
+    state: tauri::State<'_, Arc<AppState>>,
+    range: String,
+    format: ReportFormat,
+    path: String,
+) -> Result<(), AppError> {
+    let pricing_overrides = state.preferences.lock().await.pricing_overrides.clone();
+    report::generate_report(
+        &range,
+        format,
+        &pricing_overrides,
+        std::path::Path::new(&path),
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::{AutoRefreshConfig, NotificationState};
+    use crate::types::NotificationState;
     use tokio::sync::watch;
 
     fn create_test_state() -> Arc<AppState> {
@@ -215,6 +1370,18 @@ mod tests {
             restart_tx,
             notification_settings: tokio::sync::Mutex::new(NotificationSettings::default()),
             notification_state: tokio::sync::Mutex::new(NotificationState::default()),
+            preferences: tokio::sync::Mutex::new(Preferences::default()),
+            last_fetch: tokio::sync::Mutex::new(None),
+            organization_usage: tokio::sync::Mutex::new(Vec::new()),
+            organization_fetch: tokio::sync::Mutex::new(()),
+            spike_filter: tokio::sync::Mutex::new(Default::default()),
+            last_success_at: tokio::sync::Mutex::new(None),
+            last_applied_usage: tokio::sync::Mutex::new(None),
+            paused_reason: tokio::sync::Mutex::new(None),
+            backoff_until: tokio::sync::Mutex::new(None),
+            usage_client: Arc::new(crate::api::MockUsageClient::default()),
+            #[cfg(feature = "transcript-watcher")]
+            transcript_watcher: tokio::sync::Mutex::new(None),
             #[cfg(target_os = "macos")]
             wake_observer: tokio::sync::Mutex::new(None),
         })
@@ -237,6 +1404,57 @@ mod tests {
         assert_eq!(config.active_provider, ProviderKind::Codex);
     }
 
+    #[tokio::test]
+    async fn restarts_only_when_a_setting_changes() {
+        let state = create_test_state();
+        let mut restart_rx = state.restart_tx.subscribe();
+
+        update_refresh_config(&state, |config| {
+            replace_setting(&mut config.active_provider, ProviderKind::Claude)
+                | replace_setting(&mut config.enabled, true)
+        })
+        .await;
+        assert!(!restart_rx.has_changed().unwrap());
+
+        update_refresh_config(&state, |config| {
+            replace_setting(&mut config.active_provider, ProviderKind::Codex)
+        })
+        .await;
+        assert!(restart_rx.has_changed().unwrap());
+        assert_eq!(
+            state.config.lock().await.active_provider,
+            ProviderKind::Codex
+        );
+    }
+
+    #[tokio::test]
+    async fn resumed_backoff_survives_the_startup_settings_sync() {
+        let state = create_test_state();
+        // As restored by the refresh loop from the previous run
+        let until = chrono::Utc::now() + chrono::Duration::minutes(4);
+        *state.backoff_until.lock().await = Some(until);
+        let mut restart_rx = state.restart_tx.subscribe();
+
+        // What the frontend re-sends at startup, with the values already loaded
+        let config = state.config.lock().await.clone();
+        update_refresh_config(&state, |c| {
+            replace_setting(&mut c.active_provider, config.active_provider)
+        })
+        .await;
+        update_refresh_config(&state, |c| {
+            replace_setting(&mut c.enabled, config.enabled)
+                | replace_setting(&mut c.interval_minutes, config.interval_minutes)
+        })
+        .await;
+        update_refresh_config(&state, |c| {
+            replace_setting(&mut c.hourly_refresh_enabled, config.hourly_refresh_enabled)
+        })
+        .await;
+
+        assert!(!restart_rx.has_changed().unwrap());
+        assert_eq!(*state.backoff_until.lock().await, Some(until));
+    }
+
     #[test]
     fn default_settings_use_claude() {
         let settings = get_default_settings();
@@ -0,0 +1,267 @@
+//! Deployment Configuration
+//!
+//! Optional `config.toml` in the app data directory, with `CLAUDE_MONITOR_*`
+//! environment variables taking precedence over the file. Loaded once at startup
+//! before `AppState` is created, so headless and managed installs can be set up
+//! without the settings UI.
+
+use crate::error::AppError;
+use crate::validation::{
+    validate_hourly_refresh_gap, validate_hourly_refresh_jitter, validate_interval_minutes,
+    validate_retention_days, validate_startup_jitter,
+};
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::Manager;
+
+const CONFIG_FILE_NAME: &str = "config.toml";
+const ENV_PREFIX: &str = "CLAUDE_MONITOR_";
+const DEFAULT_CLAUDE_API_BASE_URL: &str = "https://claude.ai";
+
+static CONFIG: OnceLock<AppConfig> = OnceLock::new();
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct AppConfig {
+    /// Initial refresh interval; the UI setting still takes over once changed.
+    pub refresh_interval_minutes: Option<u32>,
+    /// Initial upper bound of the random delay before the first fetch; the UI
+    /// setting takes over once changed.
+    pub startup_jitter_secs: Option<u32>,
+    /// Initial wait after the top of the hour before the hourly fetch.
+    pub hourly_refresh_gap_secs: Option<u32>,
+    /// Initial upper bound of the random delay added to the hourly fetch.
+    pub hourly_refresh_jitter_secs: Option<u32>,
+    /// History retention for tables without one in `Preferences.history_retention`.
+    pub retention_days: Option<u32>,
+    /// Base URL for the Claude usage API, e.g. for a corporate gateway.
+    pub api_base_url: Option<String>,
+    /// Proxy for all provider requests (`http://`, `https://` or `socks5://`).
+    pub proxy: Option<String>,
+    /// Sentry DSN for crash uploads (requires the `crash-reporting` feature and user opt-in).
+    pub sentry_dsn: Option<String>,
+    pub integrations: IntegrationsConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct IntegrationsConfig {
+    /// Claude Code config directory, overriding `$CLAUDE_CONFIG_DIR` and `~/.claude`.
+    pub claude_config_dir: Option<PathBuf>,
+    /// Read Claude Code transcripts for local token usage and cost estimates.
+    pub local_usage: bool,
+    /// Watch Claude Code transcripts for changes (requires the `transcript-watcher` feature).
+    pub watch_transcripts: bool,
+    /// Serve the reporting views as JSON on this localhost port; off when unset.
+    pub reporting_port: Option<u16>,
+}
+
+impl Default for IntegrationsConfig {
+    fn default() -> Self {
+        Self {
+            claude_config_dir: None,
+            local_usage: true,
+            watch_transcripts: true,
+            reporting_port: None,
+        }
+    }
+}
+
+impl AppConfig {
+    pub fn claude_api_base_url(&self) -> &str {
+        self.api_base_url
+            .as_deref()
+            .map(|url| url.trim_end_matches('/'))
+            .unwrap_or(DEFAULT_CLAUDE_API_BASE_URL)
+    }
+}
+
+/// Load the config file and environment overrides. Must be called before
+/// anything reads `get()`: the first read fixes the config for the process,
+/// so a late call logs an error and keeps the defaults already handed out.
+pub fn init<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> &'static AppConfig {
+    let mut config = app
+        .path()
+        .app_data_dir()
+        .ok()
+        .and_then(|dir| load_file(&dir.join(CONFIG_FILE_NAME)))
+        .unwrap_or_default();
+
+    apply_env_overrides(&mut config, |key| std::env::var(key).ok());
+    discard_invalid_values(&mut config);
+    if CONFIG.set(config).is_err() {
+        log::error!(
+            "Configuration was read before config.toml was loaded; its values are ignored for this run"
+        );
+    }
+    get()
+}
+
+/// The loaded configuration, or defaults when `init` has not run (e.g. in tests).
+pub fn get() -> &'static AppConfig {
+    CONFIG.get_or_init(AppConfig::default)
+}
+
+fn load_file(path: &Path) -> Option<AppConfig> {
+    let contents = std::fs::read_to_string(path).ok()?;
+    toml::from_str(&contents)
+        .inspect_err(|e| log::warn!("Ignoring invalid {}: {e}", path.display()))
+        .ok()
+}
+
+fn apply_env_overrides(config: &mut AppConfig, lookup: impl Fn(&str) -> Option<String>) {
+    let var = |name: &str| {
+        lookup(&format!("{ENV_PREFIX}{name}"))
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    };
+
+    if let Some(value) = var("REFRESH_INTERVAL_MINUTES").and_then(|v| v.parse().ok()) {
+        config.refresh_interval_minutes = Some(value);
+    }
+    if let Some(value) = var("STARTUP_JITTER_SECS").and_then(|v| v.parse().ok()) {
+        config.startup_jitter_secs = Some(value);
+    }
+    if let Some(value) = var("HOURLY_REFRESH_GAP_SECS").and_then(|v| v.parse().ok()) {
+        config.hourly_refresh_gap_secs = Some(value);
+    }
+    if let Some(value) = var("HOURLY_REFRESH_JITTER_SECS").and_then(|v| v.parse().ok()) {
+        config.hourly_refresh_jitter_secs = Some(value);
+    }
+    if let Some(value) = var("RETENTION_DAYS").and_then(|v| v.parse().ok()) {
+        config.retention_days = Some(value);
+    }
+    if let Some(value) = var("API_BASE_URL") {
+        config.api_base_url = Some(value);
+    }
+    if let Some(value) = var("PROXY") {
+        config.proxy = Some(value);
+    }
+    if let Some(value) = var("SENTRY_DSN") {
+        config.sentry_dsn = Some(value);
+    }
+    if let Some(value) = var("CLAUDE_CONFIG_DIR") {
+        config.integrations.claude_config_dir = Some(PathBuf::from(value));
+    }
+    if let Some(value) = var("LOCAL_USAGE").and_then(|v| parse_bool(&v)) {
+        config.integrations.local_usage = value;
+    }
+    if let Some(value) = var("WATCH_TRANSCRIPTS").and_then(|v| parse_bool(&v)) {
+        config.integrations.watch_transcripts = value;
+    }
+    if let Some(value) = var("REPORTING_PORT").and_then(|v| v.parse().ok()) {
+        config.integrations.reporting_port = Some(value);
+    }
+}
+
+/// Drop numeric values the settings commands would refuse, so a bad managed
+/// config can't make the refresh loop spin or prune all history.
+fn discard_invalid_values(config: &mut AppConfig) {
+    discard_invalid(&mut config.refresh_interval_minutes, |minutes| {
+        validate_interval_minutes("refresh_interval_minutes", minutes)
+    });
+    discard_invalid(&mut config.startup_jitter_secs, |secs| {
+        validate_startup_jitter("startup_jitter_secs", secs)
+    });
+    discard_invalid(&mut config.hourly_refresh_gap_secs, |secs| {
+        validate_hourly_refresh_gap("hourly_refresh_gap_secs", secs)
+    });
+    discard_invalid(&mut config.hourly_refresh_jitter_secs, |secs| {
+        validate_hourly_refresh_jitter("hourly_refresh_jitter_secs", secs)
+    });
+    discard_invalid(&mut config.retention_days, |days| {
+        validate_retention_days("retention_days", days)
+    });
+}
+
+fn discard_invalid(value: &mut Option<u32>, validate: impl Fn(u32) -> Result<(), AppError>) {
+    if let Some(Err(e)) = value.map(validate) {
+        log::warn!("Ignoring config value: {e}");
+        *value = None;
+    }
+}
+
+fn parse_bool(value: &str) -> Option<bool> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Some(true),
+        "0" | "false" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn parses_config_file() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            refresh_interval_minutes = 10
+            proxy = "http://proxy.local:3128"
+
+            [integrations]
+            watch_transcripts = false
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.refresh_interval_minutes, Some(10));
+        assert_eq!(config.proxy.as_deref(), Some("http://proxy.local:3128"));
+        assert_eq!(config.retention_days, None);
+        assert!(!config.integrations.watch_transcripts);
+    }
+
+    #[test]
+    fn environment_overrides_file_values() {
+        let mut config = AppConfig {
+            refresh_interval_minutes: Some(10),
+            ..AppConfig::default()
+        };
+        let env: HashMap<&str, &str> = HashMap::from([
+            ("CLAUDE_MONITOR_REFRESH_INTERVAL_MINUTES", "3"),
+            ("CLAUDE_MONITOR_STARTUP_JITTER_SECS", "120"),
+            ("CLAUDE_MONITOR_API_BASE_URL", "https://gateway.example/"),
+            ("CLAUDE_MONITOR_WATCH_TRANSCRIPTS", "off"),
+            ("CLAUDE_MONITOR_REPORTING_PORT", "9184"),
+            ("CLAUDE_MONITOR_RETENTION_DAYS", "not-a-number"),
+        ]);
+
+        apply_env_overrides(&mut config, |key| env.get(key).map(|v| v.to_string()));
+
+        assert_eq!(config.refresh_interval_minutes, Some(3));
+        assert_eq!(config.startup_jitter_secs, Some(120));
+        assert_eq!(config.retention_days, None);
+        assert_eq!(config.claude_api_base_url(), "https://gateway.example");
+        assert!(!config.integrations.watch_transcripts);
+        assert_eq!(config.integrations.reporting_port, Some(9184));
+    }
+
+    #[test]
+    fn discards_out_of_range_values() {
+        let mut config = AppConfig {
+            refresh_interval_minutes: Some(0),
+            startup_jitter_secs: Some(3600),
+            hourly_refresh_gap_secs: Some(60),
+            hourly_refresh_jitter_secs: Some(301),
+            retention_days: Some(30),
+            ..AppConfig::default()
+        };
+        discard_invalid_values(&mut config);
+        assert_eq!(config.refresh_interval_minutes, None);
+        assert_eq!(config.startup_jitter_secs, None);
+        assert_eq!(config.hourly_refresh_gap_secs, Some(60));
+        assert_eq!(config.hourly_refresh_jitter_secs, None);
+        assert_eq!(config.retention_days, Some(30));
+    }
+
+    #[test]
+    fn defaults_to_claude_api() {
+        assert_eq!(
+            AppConfig::default().claude_api_base_url(),
+            "https://claude.ai"
+        );
+    }
+}
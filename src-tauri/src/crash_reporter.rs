@@ -0,0 +1,116 @@
+//! Crash Reporting
+//!
+//! Every panic is logged. When the user opts in via `Preferences.crash_reports_enabled`,
+//! panics are also written to a crash file in the app log directory and, in builds
+//! with the `crash-reporting` feature and a configured `sentry_dsn`, uploaded to Sentry.
+
+use std::backtrace::Backtrace;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::Manager;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[cfg(feature = "crash-reporting")]
+static SENTRY_GUARD: OnceLock<sentry::ClientInitGuard> = OnceLock::new();
+
+/// Install the panic hook. Call once, first thing in `setup`; crash files
+/// stay off until `set_enabled`.
+pub fn install<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    if let Ok(log_dir) = app.path().app_log_dir() {
+        let _ = CRASH_DIR.set(log_dir.join("crashes"));
+    }
+
+    let default_panic = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let backtrace = Backtrace::force_capture();
+        log::error!("Panic: {info}\n{backtrace}");
+
+        if ENABLED.load(Ordering::Relaxed) {
+            if let Some(dir) = CRASH_DIR.get() {
+                let panic = info.to_string();
+                let report =
+                    format_crash_report(&crate::redact::redact(&panic), &backtrace.to_string());
+                let _ = write_crash_file(dir, &report);
+            }
+        }
+
+        default_panic(info);
+    }));
+}
+
+/// Start Sentry uploads when `sentry_dsn` is configured. Call after
+/// `config::init`, since reading the config earlier would fix its defaults.
+/// Sentry chains onto the panic hook, so `install` must have run first.
+pub fn init_upload() {
+    #[cfg(feature = "crash-reporting")]
+    if let Some(dsn) = crate::config::get().sentry_dsn.clone() {
+        let guard = sentry::init((
+            dsn,
+            sentry::ClientOptions {
+                release: sentry::release_name!(),
+                before_send: Some(std::sync::Arc::new(
+                    |mut event: sentry::protocol::Event<'static>| {
+                        if !ENABLED.load(Ordering::Relaxed) {
+                            return None;
+                        }
+                        // Panic messages may quote a token
+                        for exception in &mut event.exception.values {
+                            if let Some(value) = &mut exception.value {
+                                *value = crate::redact::redact(value).into_owned();
+                            }
+                        }
+                        Some(event)
+                    },
+                )),
+                ..Default::default()
+            },
+        ));
+        let _ = SENTRY_GUARD.set(guard);
+    }
+}
+
+/// Toggle reporting at runtime (the hook stays installed either way).
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+fn format_crash_report(panic: &str, backtrace: &str) -> String {
+    format!(
+        "Claude Monitor {version} crashed\nTime: {time}\nOS: {os} ({arch})\n\n{panic}\n\nBacktrace:\n{backtrace}\n",
+        version = env!("CARGO_PKG_VERSION"),
+        time = chrono::Utc::now().to_rfc3339(),
+        os = std::env::consts::OS,
+        arch = std::env::consts::ARCH,
+    )
+}
+
+fn write_crash_file(dir: &Path, report: &str) -> std::io::Result<PathBuf> {
+    std::fs::create_dir_all(dir)?;
+    let path = dir.join(format!(
+        "crash-{}.txt",
+        chrono::Utc::now().format("%Y%m%dT%H%M%S%.3fZ")
+    ));
+    std::fs::write(&path, report)?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn writes_crash_report_file() {
+        let dir = std::env::temp_dir().join(format!("claude-monitor-crash-{}", std::process::id()));
+        let report = format_crash_report("panicked at src/lib.rs:1:1: boom", "0: main");
+
+        let path = write_crash_file(&dir, &report).unwrap();
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert!(contents.contains("boom"));
+        assert!(contents.contains(env!("CARGO_PKG_VERSION")));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -1,50 +1,169 @@
 use crate::error::AppError;
+use crate::secret::SecretString;
+use crate::types::{CredentialFallback, KeychainIssue, KeychainProblem};
 use keyring::Entry;
 use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use zeroize::{Zeroize, Zeroizing};
 
 const SERVICE_NAME: &str = "dev.xikxp1.claude-monitor";
 const CREDENTIALS_KEY: &str = "credentials";
 const OLLAMA_CREDENTIALS_KEY: &str = "ollama_credentials";
 
+/// The last keychain failure, until credentials are saved successfully.
+static KEYCHAIN_ISSUE: Mutex<Option<KeychainIssue>> = Mutex::new(None);
+
 #[derive(Serialize, Deserialize)]
 struct StoredCredentials {
     organization_id: String,
     session_token: String,
+    /// When the session cookie expires (RFC3339), if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    session_expires_at: Option<String>,
+}
+
+impl Drop for StoredCredentials {
+    fn drop(&mut self) {
+        self.session_token.zeroize();
+    }
+}
+
+/// Why credentials couldn't be loaded or saved, if the last attempt failed.
+pub fn keychain_issue() -> Option<KeychainIssue> {
+    KEYCHAIN_ISSUE
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .clone()
+}
+
+fn set_keychain_issue(issue: Option<KeychainIssue>) {
+    *KEYCHAIN_ISSUE.lock().unwrap_or_else(|e| e.into_inner()) = issue;
+}
+
+fn record_keychain_error(error: &keyring::Error) {
+    let problem = classify_keychain_error(error);
+    let issue = KeychainIssue {
+        problem,
+        fallback: fallback_for(problem),
+        detail: crate::redact::redact(&error.to_string()).into_owned(),
+    };
+    log::warn!("Keychain access failed ({problem:?}): {}", issue.detail);
+    set_keychain_issue(Some(issue));
+}
+
+/// The platform only hands back a boxed error, so Secret Service failures are
+/// told apart by their messages.
+fn classify_keychain_error(error: &keyring::Error) -> KeychainProblem {
+    match error {
+        keyring::Error::NoStorageAccess(e) => {
+            let message = e.to_string().to_lowercase();
+            if message.contains("locked") || message.contains("prompt") {
+                KeychainProblem::Locked
+            } else {
+                KeychainProblem::AccessDenied
+            }
+        }
+        keyring::Error::PlatformFailure(e) => {
+            let message = e.to_string().to_lowercase();
+            if message.contains("secret service provider")
+                || message.contains("org.freedesktop.secrets")
+                || message.contains("dbus")
+            {
+                KeychainProblem::Unavailable
+            } else if message.contains("locked") {
+                KeychainProblem::Locked
+            } else {
+                KeychainProblem::Failed
+            }
+        }
+        keyring::Error::BadEncoding(_) => KeychainProblem::Unreadable,
+        _ => KeychainProblem::Failed,
+    }
+}
+
+fn fallback_for(problem: KeychainProblem) -> CredentialFallback {
+    match problem {
+        KeychainProblem::Unreadable => CredentialFallback::ReplaceOnSave,
+        _ => CredentialFallback::MemoryOnly,
+    }
+}
+
+/// The stored secret, or None if there is none. Failures are recorded for
+/// `keychain_issue`.
+fn read_entry(key: &str) -> Option<Zeroizing<String>> {
+    match Entry::new(SERVICE_NAME, key).and_then(|entry| entry.get_password()) {
+        Ok(secret) => Some(Zeroizing::new(secret)),
+        Err(keyring::Error::NoEntry) => None,
+        Err(e) => {
+            record_keychain_error(&e);
+            None
+        }
+    }
+}
+
+fn write_entry(key: &str, secret: &str) -> Result<(), AppError> {
+    Entry::new(SERVICE_NAME, key)
+        .and_then(|entry| entry.set_password(secret))
+        .map_err(|e| {
+            record_keychain_error(&e);
+            AppError::Storage(format!("Failed to store credentials: {e}"))
+        })?;
+    set_keychain_issue(None);
+    Ok(())
+}
+
+fn load_stored_credentials() -> Option<StoredCredentials> {
+    let json = read_entry(CREDENTIALS_KEY)?;
+    match serde_json::from_str(&json) {
+        Ok(creds) => Some(creds),
+        Err(_) => {
+            // The serde error may quote the token, so it isn't kept
+            set_keychain_issue(Some(KeychainIssue {
+                problem: KeychainProblem::Unreadable,
+                fallback: CredentialFallback::ReplaceOnSave,
+                detail: "Stored credentials are not valid JSON".to_string(),
+            }));
+            None
+        }
+    }
 }
 
 /// Load credentials from OS keychain.
-/// Returns None if credentials don't exist or on any error.
-pub fn load_credentials() -> Option<(String, String)> {
-    let entry = Entry::new(SERVICE_NAME, CREDENTIALS_KEY).ok()?;
-    let json = entry.get_password().ok()?;
-    let creds: StoredCredentials = serde_json::from_str(&json).ok()?;
-    Some((creds.organization_id, creds.session_token))
+/// Returns None if credentials don't exist or on any error (see `keychain_issue`).
+/// The third value is the session token's expiry (RFC3339), if it was known when saved.
+pub fn load_credentials() -> Option<(String, SecretString, Option<String>)> {
+    let mut creds = load_stored_credentials()?;
+    Some((
+        std::mem::take(&mut creds.organization_id),
+        std::mem::take(&mut creds.session_token).into(),
+        creds.session_expires_at.take(),
+    ))
 }
 
 /// Save credentials to OS keychain.
-pub fn save_credentials(org_id: &str, session_token: &str) -> Result<(), AppError> {
-    let entry = Entry::new(SERVICE_NAME, CREDENTIALS_KEY)
-        .map_err(|e| AppError::Storage(format!("Failed to create keyring entry: {:?}", e)))?;
-
+pub fn save_credentials(
+    org_id: &str,
+    session_token: &str,
+    session_expires_at: Option<&str>,
+) -> Result<(), AppError> {
     let creds = StoredCredentials {
         organization_id: org_id.to_string(),
         session_token: session_token.to_string(),
+        session_expires_at: session_expires_at.map(str::to_string),
     };
 
-    let json = serde_json::to_string(&creds)
-        .map_err(|e| AppError::Storage(format!("Failed to serialize credentials: {:?}", e)))?;
-
-    entry
-        .set_password(&json)
-        .map_err(|e| AppError::Storage(format!("Failed to store credentials: {:?}", e)))?;
+    let json = Zeroizing::new(
+        serde_json::to_string(&creds)
+            .map_err(|e| AppError::Storage(format!("Failed to serialize credentials: {e}")))?,
+    );
 
-    Ok(())
+    write_entry(CREDENTIALS_KEY, &json)
 }
 
 /// Delete credentials from OS keychain.
 pub fn delete_credentials() -> Result<(), AppError> {
     let entry = Entry::new(SERVICE_NAME, CREDENTIALS_KEY)
-        .map_err(|e| AppError::Storage(format!("Failed to create keyring entry: {:?}", e)))?;
+        .map_err(|e| AppError::Storage(format!("Failed to create keyring entry: {e}")))?;
 
     // Ignore NoEntry errors - credential might not exist
     let _ = entry.delete_credential();
@@ -57,31 +176,73 @@ pub fn delete_credentials() -> Result<(), AppError> {
 // ============================================================================
 
 /// Load Ollama session token from OS keychain.
-/// Returns None if credentials don't exist or on any error.
-pub fn load_ollama_credentials() -> Option<String> {
-    let entry = Entry::new(SERVICE_NAME, OLLAMA_CREDENTIALS_KEY).ok()?;
-    entry.get_password().ok()
+/// Returns None if credentials don't exist or on any error (see `keychain_issue`).
+pub fn load_ollama_credentials() -> Option<SecretString> {
+    read_entry(OLLAMA_CREDENTIALS_KEY)
+        .map(|mut token| SecretString::from(std::mem::take(&mut *token)))
 }
 
 /// Save Ollama session token to OS keychain.
 pub fn save_ollama_credentials(session_token: &str) -> Result<(), AppError> {
-    let entry = Entry::new(SERVICE_NAME, OLLAMA_CREDENTIALS_KEY)
-        .map_err(|e| AppError::Storage(format!("Failed to create keyring entry: {:?}", e)))?;
-
-    entry
-        .set_password(session_token)
-        .map_err(|e| AppError::Storage(format!("Failed to store Ollama credentials: {:?}", e)))?;
-
-    Ok(())
+    write_entry(OLLAMA_CREDENTIALS_KEY, session_token)
 }
 
 /// Delete Ollama session token from OS keychain.
 pub fn delete_ollama_credentials() -> Result<(), AppError> {
     let entry = Entry::new(SERVICE_NAME, OLLAMA_CREDENTIALS_KEY)
-        .map_err(|e| AppError::Storage(format!("Failed to create keyring entry: {:?}", e)))?;
+        .map_err(|e| AppError::Storage(format!("Failed to create keyring entry: {e}")))?;
 
     // Ignore NoEntry errors - credential might not exist
     let _ = entry.delete_credential();
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn platform_error(message: &str) -> Box<dyn std::error::Error + Send + Sync> {
+        message.into()
+    }
+
+    #[test]
+    fn classifies_secret_service_failures() {
+        let unavailable = keyring::Error::PlatformFailure(platform_error(
+            "No DBus session or Secret Service provider found",
+        ));
+        assert_eq!(
+            classify_keychain_error(&unavailable),
+            KeychainProblem::Unavailable
+        );
+
+        let locked =
+            keyring::Error::NoStorageAccess(platform_error("Secret Service: object locked"));
+        assert_eq!(classify_keychain_error(&locked), KeychainProblem::Locked);
+
+        let dismissed = keyring::Error::NoStorageAccess(platform_error(
+            "Secret Service: unlock prompt was dismissed",
+        ));
+        assert_eq!(classify_keychain_error(&dismissed), KeychainProblem::Locked);
+
+        let denied = keyring::Error::NoStorageAccess(platform_error("User canceled the operation"));
+        assert_eq!(
+            classify_keychain_error(&denied),
+            KeychainProblem::AccessDenied
+        );
+
+        let unreadable = keyring::Error::BadEncoding(vec![0xff]);
+        assert_eq!(
+            classify_keychain_error(&unreadable),
+            KeychainProblem::Unreadable
+        );
+        assert_eq!(
+            fallback_for(KeychainProblem::Unreadable),
+            CredentialFallback::ReplaceOnSave
+        );
+        assert_eq!(
+            fallback_for(KeychainProblem::Locked),
+            CredentialFallback::MemoryOnly
+        );
+    }
+}
@@ -0,0 +1,242 @@
+//! Demo Mode
+//!
+//! Started with `--demo` (or the `demo_mode` preference), `DemoUsageClient`
+//! answers every usage fetch with a synthetic snapshot, so the tray,
+//! notifications and history can be exercised without credentials. Each
+//! window rises linearly from 0 to its peak and drops back at its reset, with
+//! periods aligned to the Unix epoch: the same instant always gives the same
+//! numbers. History goes to a separate database so demo data never mixes with
+//! real usage.
+
+use crate::api::{ClientFuture, ResponseMeta, UsageClient, UsageFuture, UsageResponse};
+use crate::types::{
+    MemberUsage, Organization, ProviderKind, ProviderStatus, UsageSnapshot, UsageWindow,
+};
+use chrono::{DateTime, TimeZone, Utc};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Command-line flag enabling demo mode for this launch.
+pub const DEMO_ARG: &str = "--demo";
+
+/// History database used instead of `history::DB_FILE_NAME`.
+pub const DEMO_DB_FILE_NAME: &str = "usage_history_demo.db";
+
+static DEMO_MODE: AtomicBool = AtomicBool::new(false);
+
+const HOUR_SECS: i64 = 3600;
+const DAY_SECS: i64 = 24 * HOUR_SECS;
+
+struct DemoWindow {
+    key: &'static str,
+    label: &'static str,
+    period_secs: i64,
+    /// Utilization reached just before the reset.
+    peak: f64,
+}
+
+const CLAUDE_WINDOWS: [DemoWindow; 4] = [
+    DemoWindow {
+        key: "five_hour",
+        label: "5 Hour",
+        period_secs: 5 * HOUR_SECS,
+        peak: 100.0,
+    },
+    DemoWindow {
+        key: "seven_day",
+        label: "7 Day",
+        period_secs: 7 * DAY_SECS,
+        peak: 85.0,
+    },
+    DemoWindow {
+        key: "seven_day_sonnet",
+        label: "Sonnet (7 Day)",
+        period_secs: 7 * DAY_SECS,
+        peak: 60.0,
+    },
+    DemoWindow {
+        key: "seven_day_opus",
+        label: "Opus (7 Day)",
+        period_secs: 7 * DAY_SECS,
+        peak: 95.0,
+    },
+];
+
+const CODEX_WINDOWS: [DemoWindow; 2] = [
+    DemoWindow {
+        key: "primary",
+        label: "5 Hour",
+        period_secs: 5 * HOUR_SECS,
+        peak: 90.0,
+    },
+    DemoWindow {
+        key: "secondary",
+        label: "7 Day",
+        period_secs: 7 * DAY_SECS,
+        peak: 70.0,
+    },
+];
+
+const OLLAMA_WINDOWS: [DemoWindow; 2] = [
+    DemoWindow {
+        key: "session",
+        label: "Session",
+        period_secs: 5 * HOUR_SECS,
+        peak: 80.0,
+    },
+    DemoWindow {
+        key: "weekly",
+        label: "Weekly",
+        period_secs: 7 * DAY_SECS,
+        peak: 65.0,
+    },
+];
+
+/// Called once at startup; the history database is chosen from it.
+pub fn set_enabled(enabled: bool) {
+    DEMO_MODE.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    DEMO_MODE.load(Ordering::Relaxed)
+}
+
+/// The synthetic snapshot `provider` reports at `now`.
+pub fn usage(provider: ProviderKind, now: DateTime<Utc>) -> UsageSnapshot {
+    let windows: &[DemoWindow] = match provider {
+        ProviderKind::Claude => &CLAUDE_WINDOWS,
+        ProviderKind::Codex => &CODEX_WINDOWS,
+        ProviderKind::Ollama => &OLLAMA_WINDOWS,
+    };
+    UsageSnapshot {
+        provider,
+        windows: windows
+            .iter()
+            .map(|window| sawtooth_window(window, now))
+            .collect(),
+        account_email: Some("demo@example.com".to_string()),
+        plan_type: Some("Demo".to_string()),
+        rate_limit: None,
+    }
+}
+
+fn sawtooth_window(window: &DemoWindow, now: DateTime<Utc>) -> UsageWindow {
+    let now_secs = now.timestamp();
+    let elapsed = now_secs.rem_euclid(window.period_secs);
+    let utilization = window.peak * elapsed as f64 / window.period_secs as f64;
+    let resets_at = Utc
+        .timestamp_opt(now_secs - elapsed + window.period_secs, 0)
+        .single()
+        .unwrap_or(now);
+    UsageWindow {
+        key: window.key.to_string(),
+        label: window.label.to_string(),
+        // One decimal, like the real APIs
+        utilization: (utilization * 10.0).round() / 10.0,
+        resets_at: Some(resets_at.to_rfc3339()),
+        resets_at_epoch_ms: Some(resets_at.timestamp_millis()),
+        window_duration_seconds: Some(window.period_secs),
+    }
+}
+
+/// Stands in for the provider APIs in demo mode.
+pub struct DemoUsageClient;
+
+impl UsageClient for DemoUsageClient {
+    fn fetch_usage<'a>(
+        &'a self,
+        provider: ProviderKind,
+        _org_id: Option<&'a str>,
+        _session_token: Option<&'a str>,
+        _ollama_session_token: Option<&'a str>,
+    ) -> UsageFuture<'a> {
+        let snapshot = usage(provider, crate::clock::now());
+        Box::pin(async move {
+            UsageResponse {
+                result: Ok(snapshot),
+                meta: ResponseMeta::default(),
+            }
+        })
+    }
+
+    fn fetch_claude_usage<'a>(
+        &'a self,
+        _org_id: &'a str,
+        _session_token: &'a str,
+    ) -> UsageFuture<'a> {
+        let snapshot = usage(ProviderKind::Claude, crate::clock::now());
+        Box::pin(async move {
+            UsageResponse {
+                result: Ok(snapshot),
+                meta: ResponseMeta::default(),
+            }
+        })
+    }
+
+    fn fetch_claude_member_usage<'a>(
+        &'a self,
+        _org_id: &'a str,
+        _session_token: &'a str,
+    ) -> ClientFuture<'a, Option<Vec<MemberUsage>>> {
+        Box::pin(async { Ok(Some(Vec::new())) })
+    }
+
+    fn fetch_claude_organizations<'a>(
+        &'a self,
+        _session_token: &'a str,
+    ) -> ClientFuture<'a, Vec<Organization>> {
+        Box::pin(async { Ok(Vec::new()) })
+    }
+}
+
+/// Every provider counts as configured in demo mode.
+pub fn provider_statuses() -> Vec<ProviderStatus> {
+    [
+        ProviderKind::Claude,
+        ProviderKind::Codex,
+        ProviderKind::Ollama,
+    ]
+    .into_iter()
+    .map(|provider| ProviderStatus {
+        provider,
+        configured: true,
+        source: "demo".to_string(),
+        message: Some("Demo mode: showing synthetic usage data.".to_string()),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rises_until_reset_then_drops() {
+        let period_start = Utc.timestamp_opt(5 * HOUR_SECS * 100_000, 0).unwrap();
+        let at = |secs: i64| period_start + chrono::Duration::seconds(secs);
+
+        let start = usage(ProviderKind::Claude, at(0));
+        let halfway = usage(ProviderKind::Claude, at(5 * HOUR_SECS / 2));
+        let next = usage(ProviderKind::Claude, at(5 * HOUR_SECS));
+
+        assert_eq!(start.windows[0].key, "five_hour");
+        assert_eq!(start.windows[0].utilization, 0.0);
+        assert_eq!(halfway.windows[0].utilization, 50.0);
+        assert_eq!(next.windows[0].utilization, 0.0);
+        assert_eq!(
+            halfway.windows[0].resets_at_epoch_ms,
+            Some(at(5 * HOUR_SECS).timestamp_millis())
+        );
+
+        let utilizations = |usage: UsageSnapshot| -> Vec<f64> {
+            usage
+                .windows
+                .iter()
+                .map(|window| window.utilization)
+                .collect()
+        };
+        assert_eq!(
+            utilizations(usage(ProviderKind::Codex, at(1234))),
+            utilizations(usage(ProviderKind::Codex, at(1234)))
+        );
+    }
+}
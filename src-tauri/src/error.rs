@@ -1,3 +1,4 @@
+use crate::types::RateLimitInfo;
 use serde::Serialize;
 use specta::Type;
 use thiserror::Error;
@@ -9,13 +10,83 @@ pub enum AppError {
     #[error("Authentication expired. Refresh your provider login and try again.")]
     InvalidToken,
     #[error("Rate limited. Please wait a moment and try again.")]
-    RateLimited,
+    RateLimited(Option<RateLimitInfo>),
     #[error("{0}")]
     Server(String),
     #[error("Missing configuration: {0}")]
     MissingConfig(String),
     #[error("Storage error: {0}")]
     Storage(String),
+    #[error("Organization {0} is no longer available to this account. Pick another organization.")]
+    OrganizationNotFound(String),
+    #[error("Access denied. Check your organization ID and that your plan includes usage data.")]
+    Forbidden,
+    #[error(
+        "Claude returned a web page (likely a Cloudflare challenge) instead of usage data. Your session token or User-Agent may be blocked; sign in to claude.ai in your browser and copy a fresh session token."
+    )]
+    Blocked,
+    #[error("Claude Monitor is locked. Unlock it with your passphrase first.")]
+    Locked,
+    #[error("Incorrect passphrase.")]
+    WrongPassphrase,
+    #[error("Invalid {setting}: {reason}")]
+    InvalidSetting { setting: String, reason: String },
+}
+
+/// Machine-readable error category, so the UI can offer specific remediation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    Network,
+    InvalidToken,
+    RateLimited,
+    Server,
+    MissingConfig,
+    Storage,
+    OrganizationNotFound,
+    Forbidden,
+    Blocked,
+    Locked,
+    WrongPassphrase,
+    InvalidSetting,
+}
+
+/// Wire format of `AppError`: `{ code, message }`.
+#[derive(Debug, Clone, Serialize, Type)]
+pub struct ErrorPayload {
+    pub code: ErrorCode,
+    pub message: String,
+}
+
+impl AppError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            AppError::Http(_) => ErrorCode::Network,
+            AppError::InvalidToken => ErrorCode::InvalidToken,
+            AppError::RateLimited(_) => ErrorCode::RateLimited,
+            AppError::Server(_) => ErrorCode::Server,
+            AppError::MissingConfig(_) => ErrorCode::MissingConfig,
+            AppError::Storage(_) => ErrorCode::Storage,
+            AppError::OrganizationNotFound(_) => ErrorCode::OrganizationNotFound,
+            AppError::Forbidden => ErrorCode::Forbidden,
+            AppError::Blocked => ErrorCode::Blocked,
+            AppError::Locked => ErrorCode::Locked,
+            AppError::WrongPassphrase => ErrorCode::WrongPassphrase,
+            AppError::InvalidSetting { .. } => ErrorCode::InvalidSetting,
+        }
+    }
+
+    /// The display message with any secrets masked, for the UI and events.
+    pub fn message(&self) -> String {
+        crate::redact::redact(&self.to_string()).into_owned()
+    }
+
+    pub fn to_payload(&self) -> ErrorPayload {
+        ErrorPayload {
+            code: self.code(),
+            message: self.message(),
+        }
+    }
 }
 
 impl Serialize for AppError {
@@ -23,14 +94,44 @@ impl Serialize for AppError {
     where
         S: serde::Serializer,
     {
-        serializer.serialize_str(&self.to_string())
+        self.to_payload().serialize(serializer)
     }
 }
 
 // Implement Type manually since reqwest::Error doesn't implement Type.
-// The error is serialized as a string, so we export it as string type.
+// The error is serialized as an `ErrorPayload`, so we export that shape.
 impl Type for AppError {
-    fn definition(_types: &mut specta::Types) -> specta::datatype::DataType {
-        specta::datatype::DataType::Primitive(specta::datatype::Primitive::str)
+    fn definition(types: &mut specta::Types) -> specta::datatype::DataType {
+        ErrorPayload::definition(types)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_code_and_message() {
+        let value = serde_json::to_value(AppError::RateLimited(None)).unwrap();
+        assert_eq!(value["code"], "rate_limited");
+        assert_eq!(
+            value["message"],
+            "Rate limited. Please wait a moment and try again."
+        );
+
+        let value = serde_json::to_value(AppError::Storage("disk full".to_string())).unwrap();
+        assert_eq!(value["code"], "storage");
+        assert_eq!(value["message"], "Storage error: disk full");
+
+        let error = AppError::InvalidSetting {
+            setting: "interval_minutes".to_string(),
+            reason: "must be between 1 and 1440".to_string(),
+        };
+        let value = serde_json::to_value(error).unwrap();
+        assert_eq!(value["code"], "invalid_setting");
+        assert_eq!(
+            value["message"],
+            "Invalid interval_minutes: must be between 1 and 1440"
+        );
     }
 }
@@ -1,13 +1,44 @@
-use crate::types::{ProviderKind, UsageSnapshot};
+use crate::history_sync::SyncRecord;
+use crate::local_usage::{self, LocalUsageEntry, TokenCounts};
+use crate::types::{FetchSource, MemberUsage, ProviderKind, UsageSnapshot, reset_epoch_ms};
 use rusqlite::{Connection, OptionalExtension, Result as SqliteResult};
 use serde::{Deserialize, Serialize};
 use specta::Type;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::Manager;
 
 static DB: std::sync::OnceLock<Mutex<Connection>> = std::sync::OnceLock::new();
 
+/// While set, nothing new is written to the database (`Preferences.incognito`).
+static INCOGNITO: AtomicBool = AtomicBool::new(false);
+
+/// The primary Claude organization, whose rows charts, stats and projections
+/// read. Other providers have no organizations and use `''`.
+static ACTIVE_ORGANIZATION: Mutex<String> = Mutex::new(String::new());
+
+/// File name inside the app data directory.
+pub const DB_FILE_NAME: &str = "usage_history.db";
+
+/// A sample counts as due this long before its interval is up, so fetch jitter
+/// doesn't push every sample to the following fetch.
+const SAMPLE_INTERVAL_SLACK_SECS: i64 = 30;
+
+/// Unchanged snapshots are still stored this often when they are being skipped.
+const UNCHANGED_SAMPLE_MAX_GAP_MINUTES: u32 = 60;
+
+/// Per-table retention in days (`Preferences.history_retention`).
+static RETENTION: Mutex<BTreeMap<HistoryTable, u32>> = Mutex::new(BTreeMap::new());
+
+/// How often old rows are pruned while the app runs.
+const RETENTION_INTERVAL: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+
+/// How often the open database is re-checked for corruption.
+const INTEGRITY_CHECK_INTERVAL: std::time::Duration =
+    std::time::Duration::from_secs(7 * 24 * 60 * 60);
+
 const LEGACY_SCHEMA: &str = r#"
     CREATE TABLE IF NOT EXISTS usage_history (
         id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -33,16 +64,188 @@ const V2_SCHEMA: &str = r#"
         window_key TEXT NOT NULL,
         label TEXT NOT NULL,
         utilization REAL NOT NULL,
-        resets_at TEXT
+        resets_at TEXT,
+        organization_id TEXT NOT NULL DEFAULT '',
+        app_version TEXT,
+        fetch_source TEXT
     );
 
-    CREATE UNIQUE INDEX IF NOT EXISTS idx_usage_history_v2_unique
-    ON usage_history_v2(provider, timestamp, window_key);
-
     CREATE INDEX IF NOT EXISTS idx_usage_history_v2_lookup
     ON usage_history_v2(provider, timestamp, window_key);
 "#;
 
+/// Additional organizations are sampled at the same instant as the primary
+/// one, so the unique key includes the organization. Replaces the original
+/// key without it.
+const V2_ORGANIZATION_INDEX: &str = r#"
+    DROP INDEX IF EXISTS idx_usage_history_v2_unique;
+
+    CREATE UNIQUE INDEX IF NOT EXISTS idx_usage_history_v2_org_unique
+    ON usage_history_v2(provider, timestamp, window_key, organization_id);
+"#;
+
+const LOCAL_TOKEN_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS local_token_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        message_key TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        project TEXT NOT NULL,
+        model TEXT NOT NULL,
+        input_tokens INTEGER NOT NULL,
+        output_tokens INTEGER NOT NULL,
+        cache_creation_tokens INTEGER NOT NULL,
+        cache_read_tokens INTEGER NOT NULL
+    );
+
+    CREATE UNIQUE INDEX IF NOT EXISTS idx_local_token_history_message
+    ON local_token_history(message_key);
+
+    CREATE INDEX IF NOT EXISTS idx_local_token_history_timestamp
+    ON local_token_history(timestamp);
+"#;
+
+/// Per-member samples from the organization admin usage endpoint.
+const MEMBER_USAGE_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS member_usage_history (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        organization_id TEXT NOT NULL,
+        timestamp TEXT NOT NULL,
+        member_id TEXT NOT NULL,
+        member_name TEXT,
+        member_email TEXT,
+        window_key TEXT NOT NULL,
+        label TEXT NOT NULL,
+        utilization REAL NOT NULL,
+        resets_at TEXT
+    );
+
+    CREATE UNIQUE INDEX IF NOT EXISTS idx_member_usage_history_unique
+    ON member_usage_history(organization_id, timestamp, member_id, window_key);
+"#;
+
+/// How far into each other machine's sync log has been imported.
+const HISTORY_SYNC_SCHEMA: &str = r#"
+    CREATE TABLE IF NOT EXISTS history_sync_logs (
+        file_name TEXT PRIMARY KEY,
+        bytes_read INTEGER NOT NULL
+    );
+"#;
+
+/// Read-only views for external dashboards (e.g. Grafana's SQLite datasource).
+/// Their columns are a stable interface; the tables behind them may change.
+/// Recreated at startup so definition changes ship with the app.
+const REPORTING_VIEWS: &str = r#"
+    DROP VIEW IF EXISTS usage_hourly;
+    CREATE VIEW usage_hourly AS
+    SELECT
+        provider,
+        window_key,
+        MAX(label) AS label,
+        strftime('%Y-%m-%dT%H:00:00Z', timestamp) AS hour,
+        AVG(utilization) AS avg_utilization,
+        MIN(utilization) AS min_utilization,
+        MAX(utilization) AS max_utilization,
+        COUNT(*) AS samples,
+        organization_id
+    FROM usage_history_v2
+    GROUP BY provider, organization_id, window_key, hour;
+
+    DROP VIEW IF EXISTS usage_resets;
+    CREATE VIEW usage_resets AS
+    SELECT provider, window_key, label, timestamp AS reset_observed_at,
+        previous_resets_at, resets_at, utilization_before, utilization AS utilization_after,
+        organization_id
+    FROM (
+        SELECT
+            provider, organization_id, window_key, label, timestamp, resets_at, utilization,
+            LAG(resets_at) OVER w AS previous_resets_at,
+            LAG(utilization) OVER w AS utilization_before
+        FROM usage_history_v2
+        WINDOW w AS (PARTITION BY provider, organization_id, window_key ORDER BY timestamp)
+    )
+    -- A new period moves resets_at forward by far more than fetch-to-fetch jitter
+    WHERE (julianday(resets_at) - julianday(previous_resets_at)) * 24 * 60 > 30;
+"#;
+
+/// How far back the first transcript import reaches, matching the longest range preset.
+const LOCAL_TOKEN_INITIAL_IMPORT_DAYS: i64 = 30;
+
+/// `message_key` prefix of token rows imported from other tools' exports, which
+/// transcript syncing ignores when finding where it left off.
+pub const IMPORTED_MESSAGE_PREFIX: &str = "import:";
+
+/// Samples further apart than this are drawn with a gap between them, unless
+/// the range is downsampled into longer buckets.
+const CHART_GAP_MINUTES: i64 = 90;
+
+/// How far back the tray's burn rate looks.
+const BURN_RATE_LOOKBACK_MINUTES: i64 = 60;
+/// Samples must span at least this long for a burn rate; shorter is mostly noise.
+const BURN_RATE_MIN_SPAN_MINUTES: i64 = 10;
+/// A forecast needs at least this many samples and this R² for medium
+/// confidence...
+const FORECAST_MEDIUM_SAMPLES: usize = 4;
+const FORECAST_MEDIUM_R_SQUARED: f64 = 0.5;
+/// ...and these for high confidence.
+const FORECAST_HIGH_SAMPLES: usize = 8;
+const FORECAST_HIGH_R_SQUARED: f64 = 0.9;
+
+/// A 5-hour period peaking at or above this counts as capped.
+const CAPPED_UTILIZATION: f64 = 99.0;
+
+/// Each provider's 5-hour window (also listed in `count_capped_periods`).
+const FIVE_HOUR_WINDOW_KEYS: [&str; 3] = ["five_hour", "primary", "session"];
+const FIVE_HOUR_MINUTES: i64 = 5 * 60;
+
+/// What the user types to confirm `clear_history`; anything else is refused.
+pub const CLEAR_HISTORY_CONFIRMATION: &str = "DELETE";
+
+/// A history table that grows with time, for retention.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum HistoryTable {
+    /// Utilization samples (`usage_history_v2`).
+    Usage,
+    /// Claude Code token records (`local_token_history`).
+    LocalTokens,
+    /// Per-member samples (`member_usage_history`).
+    MemberUsage,
+}
+
+impl HistoryTable {
+    pub const ALL: [Self; 3] = [Self::Usage, Self::LocalTokens, Self::MemberUsage];
+
+    fn table_name(self) -> &'static str {
+        match self {
+            Self::Usage => "usage_history_v2",
+            Self::LocalTokens => "local_token_history",
+            Self::MemberUsage => "member_usage_history",
+        }
+    }
+
+    /// Days kept when neither the preference nor `config.toml` sets a
+    /// retention; None keeps everything.
+    pub fn default_retention_days(self) -> Option<u32> {
+        match self {
+            Self::Usage | Self::LocalTokens => None,
+            // One row per member and window on every fetch adds up quickly
+            Self::MemberUsage => Some(180),
+        }
+    }
+}
+
+/// Which history `clear_history` deletes.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(tag = "scope", rename_all = "camelCase")]
+pub enum ClearScope {
+    /// Every usage sample and imported token record.
+    All,
+    /// Records older than this many days.
+    OlderThan { days: u32 },
+    /// One window's samples for one provider, e.g. Claude's `seven_day_opus`.
+    Metric { provider: ProviderKind, key: String },
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
 #[serde(rename_all = "camelCase")]
 pub struct UsageHistoryPoint {
@@ -53,6 +256,25 @@ pub struct UsageHistoryPoint {
     pub label: String,
     pub utilization: f64,
     pub resets_at: Option<String>,
+    pub resets_at_epoch_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenHistoryPoint {
+    /// Start of the bucket (`YYYY-MM-DD HH:MM:SS`, UTC).
+    pub timestamp: String,
+    pub tokens: TokenCounts,
+    pub message_count: u32,
+}
+
+/// Utilization and local token history over the same range, for overlay charts.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CombinedHistory {
+    pub utilization: Vec<UsageHistoryPoint>,
+    pub tokens: Vec<TokenHistoryPoint>,
+    pub token_bucket_minutes: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
@@ -71,34 +293,681 @@ pub struct UsageStats {
     pub windows: Vec<WindowStats>,
     pub record_count: i64,
     pub period_hours: f64,
+    /// 5-hour periods in the range whose peak reached `CAPPED_UTILIZATION`.
+    pub capped_five_hour_windows: i64,
+}
+
+/// Size and contents of the history database, for the settings storage panel.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryDbInfo {
+    pub file_size_bytes: i64,
+    pub usage_row_count: i64,
+    pub token_row_count: i64,
+    /// Oldest and newest utilization samples (RFC3339).
+    pub oldest_timestamp: Option<String>,
+    pub newest_timestamp: Option<String>,
+    pub journal_mode: String,
+}
+
+/// A member's latest and peak utilization of one window over a range.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberUsageSummary {
+    pub member_id: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub window_key: String,
+    pub label: String,
+    pub utilization: f64,
+    pub peak_utilization: f64,
+    pub resets_at: Option<String>,
+    /// When the latest sample was recorded (RFC3339).
+    pub timestamp: String,
+}
+
+/// How long 5-hour periods took to reach a utilization threshold.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeToThreshold {
+    pub threshold: f64,
+    /// Mean of `periods`; None when no period reached the threshold.
+    pub average_minutes: Option<f64>,
+    /// Periods in the range, whether or not they reached the threshold.
+    pub period_count: i64,
+    /// Periods that reached it, oldest first.
+    pub periods: Vec<ThresholdCrossing>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ThresholdCrossing {
+    /// Start of the period (RFC3339).
+    pub started_at: String,
+    /// Minutes from the start until the first sample at or above the threshold.
+    pub minutes: f64,
+}
+
+/// Usage consumed in one local hour of one weekday, summed over a range.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HistogramBucket {
+    /// 0 = Monday ... 6 = Sunday.
+    pub weekday: u8,
+    /// 0-23.
+    pub hour: u8,
+    /// Percentage points the window rose by, counting from 0 after resets.
+    pub consumption: f64,
+    pub samples: i64,
+}
+
+/// When a window was used; hours without samples are left out.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageHistogram {
+    pub metric: String,
+    pub buckets: Vec<HistogramBucket>,
+}
+
+/// One window's line, ready to plot.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartSeries {
+    pub key: String,
+    pub label: String,
+    pub points: Vec<ChartPoint>,
 }
 
-pub fn init_database<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> SqliteResult<()> {
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+pub struct ChartPoint {
+    /// Unix epoch milliseconds.
+    pub x: i64,
+    /// Utilization; null breaks the line where no data was recorded.
+    pub y: Option<f64>,
+}
+
+/// When a window would reach 100% at its current burn rate.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LimitProjection {
+    pub key: String,
+    pub label: String,
+    pub utilization: f64,
+    /// Percentage points per hour over the last hour, when known.
+    pub burn_rate: Option<f64>,
+    /// Projected time of reaching 100% (RFC3339); None when the pace is safe.
+    pub projected_at: Option<String>,
+    /// Samples since the latest reset within the last hour.
+    pub sample_count: u32,
+    /// How well a straight line fits those samples (0-1); None with fewer
+    /// than three samples.
+    pub r_squared: Option<f64>,
+    pub confidence: ForecastConfidence,
+}
+
+/// How far to trust a projection, from the samples behind it.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ForecastConfidence {
+    /// Few samples or an erratic pace; show the projection as a rough guess.
+    Low,
+    Medium,
+    High,
+}
+
+/// A provider's most recent snapshot, as printed by `claude-monitor query`.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LatestSnapshot {
+    pub provider: String,
+    pub timestamp: String,
+    pub windows: Vec<crate::types::UsageWindow>,
+}
+
+/// Apply the incognito preference. Called at startup and when preferences change.
+pub fn set_incognito(enabled: bool) {
+    INCOGNITO.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_incognito() -> bool {
+    INCOGNITO.load(Ordering::Relaxed)
+}
+
+/// Scope history reads to the primary Claude organization. Called at startup
+/// and whenever the primary organization changes. Claude rows from before
+/// they were tagged with an organization are adopted by it.
+pub fn set_active_organization(org_id: &str) {
+    *ACTIVE_ORGANIZATION
+        .lock()
+        .unwrap_or_else(|e| e.into_inner()) = org_id.to_string();
+    let adopted = get_db().and_then(|conn| adopt_untagged_rows(&conn, org_id));
+    match adopted {
+        Ok(0) => {}
+        Ok(rows) => {
+            log::info!("Tagged {rows} untagged Claude history rows with organization {org_id}")
+        }
+        Err(e) => log::debug!("Could not tag untagged Claude history rows: {e}"),
+    }
+}
+
+/// The organization id `provider`'s rows are stored under for the primary
+/// organization.
+fn organization_scope(provider: ProviderKind) -> String {
+    match provider {
+        ProviderKind::Claude => ACTIVE_ORGANIZATION
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone(),
+        ProviderKind::Codex | ProviderKind::Ollama => String::new(),
+    }
+}
+
+fn adopt_untagged_rows(conn: &Connection, org_id: &str) -> SqliteResult<usize> {
+    if org_id.is_empty() {
+        return Ok(0);
+    }
+    conn.execute(
+        "UPDATE usage_history_v2 SET organization_id = ?1 WHERE provider = 'claude' AND organization_id = ''",
+        [org_id],
+    )
+}
+
+/// Apply the per-table retention preference. Called at startup and when
+/// preferences change.
+pub fn set_retention_policy(policy: &BTreeMap<HistoryTable, u32>) {
+    *RETENTION.lock().unwrap_or_else(|e| e.into_inner()) = policy.clone();
+}
+
+/// Days of `table` to keep: the preference (0 keeps everything), else the
+/// `config.toml` retention, else the table's default.
+fn retention_days(
+    table: HistoryTable,
+    policy: &BTreeMap<HistoryTable, u32>,
+    configured_days: Option<u32>,
+) -> Option<u32> {
+    match policy.get(&table) {
+        Some(0) => None,
+        Some(days) => Some(*days),
+        None => configured_days.or(table.default_retention_days()),
+    }
+}
+
+/// Delete rows older than each table's retention. Returns how many went.
+pub fn apply_retention() -> SqliteResult<usize> {
+    let policy = RETENTION.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let conn = get_db()?;
+    prune_tables(
+        &conn,
+        &policy,
+        crate::config::get().retention_days,
+        chrono::Utc::now(),
+    )
+}
+
+fn prune_tables(
+    conn: &Connection,
+    policy: &BTreeMap<HistoryTable, u32>,
+    configured_days: Option<u32>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> SqliteResult<usize> {
+    let mut deleted = 0;
+    for table in HistoryTable::ALL {
+        let Some(days) = retention_days(table, policy, configured_days) else {
+            continue;
+        };
+        let cutoff = now - chrono::Duration::days(i64::from(days));
+        deleted += conn.execute(
+            &format!("DELETE FROM {} WHERE timestamp < ?1", table.table_name()),
+            [cutoff.to_rfc3339()],
+        )?;
+    }
+    Ok(deleted)
+}
+
+/// Prune history at startup and every `RETENTION_INTERVAL` after.
+pub async fn retention_loop() {
+    loop {
+        match apply_retention() {
+            Ok(0) => {}
+            Ok(deleted) => log::info!("Pruned {deleted} history rows past their retention"),
+            Err(e) => log::warn!("History retention cleanup failed: {e}"),
+        }
+        tokio::time::sleep(RETENTION_INTERVAL).await;
+    }
+}
+
+/// Open the history database, replacing it with a fresh one if it is corrupt.
+/// Returns where the damaged file was moved when that happened.
+pub fn init_database<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+) -> Result<Option<PathBuf>, crate::error::AppError> {
     let db_path = get_db_path(app).ok_or_else(|| {
-        rusqlite::Error::InvalidPath("Could not determine app data directory".into())
+        crate::error::AppError::Storage("Could not determine app data directory".to_string())
     })?;
 
     if let Some(parent) = db_path.parent() {
         std::fs::create_dir_all(parent).ok();
     }
 
-    let conn = Connection::open(&db_path)?;
-    conn.execute_batch(LEGACY_SCHEMA)?;
-    conn.execute_batch(V2_SCHEMA)?;
-    backfill_legacy_claude_data(&conn)?;
+    let (conn, backup) = open_checked(&db_path)?;
     let _ = DB.set(Mutex::new(conn));
-    Ok(())
+    Ok(backup)
+}
+
+/// Re-run the integrity check on the open database, swapping in a fresh one if
+/// it has become corrupt. Returns where the damaged file was moved.
+pub fn check_integrity() -> Result<Option<PathBuf>, crate::error::AppError> {
+    let mut conn = get_db().map_err(storage_error)?;
+    if !is_corrupt(&conn).map_err(storage_error)? {
+        return Ok(None);
+    }
+
+    let db_path = conn.path().map(PathBuf::from).ok_or_else(|| {
+        crate::error::AppError::Storage("History database has no file path".to_string())
+    })?;
+    // Close the damaged file before moving it
+    drop(std::mem::replace(
+        &mut *conn,
+        Connection::open_in_memory().map_err(storage_error)?,
+    ));
+    let (fresh, backup) = open_checked(&db_path)?;
+    *conn = fresh;
+    Ok(backup)
+}
+
+/// Close the database file for shutdown. Later calls get an empty in-memory
+/// stand-in, so nothing is written after this.
+pub fn close() -> SqliteResult<()> {
+    let mut conn = get_db()?;
+    let file = std::mem::replace(&mut *conn, Connection::open_in_memory()?);
+    file.execute_batch("PRAGMA optimize")?;
+    file.close().map_err(|(_, e)| e)
+}
+
+/// Check the database every `INTEGRITY_CHECK_INTERVAL` while the app runs and
+/// tell the user when it had to be replaced.
+pub async fn integrity_check_loop<R: tauri::Runtime>(app: tauri::AppHandle<R>) {
+    loop {
+        tokio::time::sleep(INTEGRITY_CHECK_INTERVAL).await;
+        match check_integrity() {
+            Ok(Some(backup)) => crate::notifications::notify_history_recovered(&app, &backup),
+            Ok(None) => {}
+            Err(e) => log::warn!("History integrity check failed: {e}"),
+        }
+    }
+}
+
+/// Open `db_path` with the current schema. A file that fails
+/// `PRAGMA quick_check` (or isn't a database at all) is moved aside first.
+fn open_checked(db_path: &Path) -> Result<(Connection, Option<PathBuf>), crate::error::AppError> {
+    let mut conn = Connection::open(db_path).map_err(storage_error)?;
+    let backup = if is_corrupt(&conn).map_err(storage_error)? {
+        drop(conn);
+        let backup = back_up_corrupt_database(db_path, chrono::Utc::now())?;
+        log::error!(
+            "History database failed its integrity check; moved it to {} and started a new one",
+            backup.display()
+        );
+        conn = Connection::open(db_path).map_err(storage_error)?;
+        Some(backup)
+    } else {
+        None
+    };
+
+    create_schema(&conn).map_err(storage_error)?;
+    Ok((conn, backup))
+}
+
+fn create_schema(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(LEGACY_SCHEMA)?;
+    create_v2_schema(conn)?;
+    conn.execute_batch(LOCAL_TOKEN_SCHEMA)?;
+    conn.execute_batch(MEMBER_USAGE_SCHEMA)?;
+    conn.execute_batch(HISTORY_SYNC_SCHEMA)?;
+    conn.execute_batch(REPORTING_VIEWS)?;
+    backfill_legacy_claude_data(conn)
+}
+
+/// Whether `PRAGMA quick_check` finds damage. Corruption errors count as damage;
+/// anything else (e.g. a locked file) is returned, so it never triggers a reset.
+fn is_corrupt(conn: &Connection) -> SqliteResult<bool> {
+    match conn.query_row("PRAGMA quick_check", [], |row| row.get::<_, String>(0)) {
+        Ok(result) => Ok(result != "ok"),
+        Err(e)
+            if matches!(
+                e.sqlite_error_code(),
+                Some(rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase)
+            ) =>
+        {
+            Ok(true)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+/// Move the database and its journal files to `<name>.corrupt-<timestamp>`.
+fn back_up_corrupt_database(
+    db_path: &Path,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<PathBuf, crate::error::AppError> {
+    let suffix = format!("corrupt-{}", now.format("%Y%m%d%H%M%S"));
+    let backup = PathBuf::from(format!("{}.{suffix}", db_path.display()));
+    std::fs::rename(db_path, &backup).map_err(|e| {
+        crate::error::AppError::Storage(format!("Failed to back up {}: {e}", db_path.display()))
+    })?;
+    for journal in ["-wal", "-shm", "-journal"] {
+        let path = PathBuf::from(format!("{}{journal}", db_path.display()));
+        if path.exists() {
+            let _ = std::fs::rename(&path, format!("{}{journal}", backup.display()));
+        }
+    }
+    Ok(backup)
+}
+
+/// Record a snapshot of the primary organization (None for providers without
+/// organizations) and return the stored points, for the `history-appended` event.
+pub fn save_usage_snapshot(
+    snapshot: &UsageSnapshot,
+    org_id: Option<&str>,
+    source: FetchSource,
+) -> SqliteResult<Vec<UsageHistoryPoint>> {
+    if is_incognito() {
+        return Ok(Vec::new());
+    }
+    let conn = get_db()?;
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    let org_id = org_id.unwrap_or_default();
+    insert_organization_snapshot(
+        &conn,
+        org_id,
+        snapshot.provider,
+        &timestamp,
+        &snapshot.windows,
+        Some(source),
+    )?;
+    usage_history(
+        &conn,
+        snapshot.provider,
+        org_id,
+        &timestamp,
+        &timestamp,
+        None,
+    )
+}
+
+/// Whether `snapshot` should be stored as a new primary sample, given the
+/// configured minimum spacing (None stores every fetch) and whether to skip
+/// samples identical to the previous one.
+pub fn sample_due(
+    snapshot: &UsageSnapshot,
+    interval_minutes: Option<u32>,
+    skip_unchanged: bool,
+) -> SqliteResult<bool> {
+    let interval_minutes = interval_minutes.filter(|minutes| *minutes > 0);
+    if interval_minutes.is_none() && !skip_unchanged {
+        return Ok(true);
+    }
+    let conn = get_db()?;
+    let organization = organization_scope(snapshot.provider);
+    let Some(last) =
+        latest_snapshots(&conn, Some(snapshot.provider.as_str()), Some(&organization))?
+            .into_iter()
+            .next()
+    else {
+        return Ok(true);
+    };
+    let Ok(last_at) = chrono::DateTime::parse_from_rfc3339(&last.timestamp) else {
+        return Ok(true);
+    };
+    let last_at = last_at.with_timezone(&chrono::Utc);
+    let now = chrono::Utc::now();
+
+    if interval_minutes.is_some_and(|minutes| !interval_elapsed(last_at, now, minutes)) {
+        return Ok(false);
+    }
+    // Unchanged values are still stored hourly, so gaps mean the app wasn't running
+    Ok(!(skip_unchanged
+        && same_utilization(&last.windows, &snapshot.windows)
+        && !interval_elapsed(last_at, now, UNCHANGED_SAMPLE_MAX_GAP_MINUTES)))
+}
+
+fn interval_elapsed(
+    last: chrono::DateTime<chrono::Utc>,
+    now: chrono::DateTime<chrono::Utc>,
+    interval_minutes: u32,
+) -> bool {
+    now.signed_duration_since(last).num_seconds() + SAMPLE_INTERVAL_SLACK_SECS
+        >= interval_minutes as i64 * 60
+}
+
+/// Same windows at the same utilization; reset times are ignored, since they
+/// drift by seconds between fetches.
+fn same_utilization(
+    previous: &[crate::types::UsageWindow],
+    current: &[crate::types::UsageWindow],
+) -> bool {
+    previous.len() == current.len()
+        && current.iter().all(|window| {
+            previous.iter().any(|previous_window| {
+                previous_window.key == window.key
+                    && previous_window.utilization == window.utilization
+            })
+        })
+}
+
+/// Record a snapshot of an additional organization. Charts, stats and the CLI
+/// read only the primary organization's rows (see `set_active_organization`).
+pub fn save_organization_snapshot(
+    org_id: &str,
+    snapshot: &UsageSnapshot,
+    source: FetchSource,
+) -> SqliteResult<()> {
+    if is_incognito() {
+        return Ok(());
+    }
+    let conn = get_db()?;
+    let timestamp = chrono::Utc::now().to_rfc3339();
+    insert_organization_snapshot(
+        &conn,
+        org_id,
+        snapshot.provider,
+        &timestamp,
+        &snapshot.windows,
+        Some(source),
+    )
 }
 
-pub fn save_usage_snapshot(snapshot: &UsageSnapshot) -> SqliteResult<()> {
+pub fn save_member_usage(org_id: &str, members: &[MemberUsage]) -> SqliteResult<()> {
+    if is_incognito() {
+        return Ok(());
+    }
     let conn = get_db()?;
     let timestamp = chrono::Utc::now().to_rfc3339();
-    insert_snapshot(&conn, snapshot.provider, &timestamp, &snapshot.windows)
+    insert_member_usage(&conn, org_id, &timestamp, members)
+}
+
+/// Each member's windows over the range, highest peak first, so whoever is
+/// hitting caps is at the top.
+pub fn get_member_usage(org_id: &str, range: &str) -> SqliteResult<Vec<MemberUsageSummary>> {
+    let conn = get_db()?;
+    let hours = get_range_hours(range) as i64;
+    let from = (chrono::Utc::now() - chrono::Duration::hours(hours)).to_rfc3339();
+    member_usage_summary(&conn, org_id, &from)
 }
 
+/// Percentage points per hour a window gained over the last hour, or None
+/// without enough recent samples.
+pub fn get_burn_rate(provider: ProviderKind, window_key: &str) -> SqliteResult<Option<f64>> {
+    let conn = get_db()?;
+    let organization = organization_scope(provider);
+    Ok(burn_rate(&burn_rate_samples(
+        &conn,
+        provider,
+        &organization,
+        window_key,
+    )?))
+}
+
+/// Each window of the latest snapshot with its projected time to 100%.
+pub fn get_projected_limits(provider: ProviderKind) -> SqliteResult<Vec<LimitProjection>> {
+    let conn = get_db()?;
+    let now = crate::clock::now();
+    let organization = organization_scope(provider);
+    let Some(snapshot) = latest_snapshots(&conn, Some(provider.as_str()), Some(&organization))?
+        .into_iter()
+        .next()
+    else {
+        return Ok(Vec::new());
+    };
+
+    snapshot
+        .windows
+        .into_iter()
+        .map(|window| {
+            let samples = burn_rate_samples(&conn, provider, &organization, &window.key)?;
+            let samples = since_last_reset(&samples);
+            let burn_rate = burn_rate(samples);
+            let r_squared = r_squared(samples);
+            let projected_at = burn_rate
+                .and_then(|rate| {
+                    projected_limit_time(window.utilization, rate, window.resets_at.as_deref(), now)
+                })
+                .map(|time| time.to_rfc3339());
+            Ok(LimitProjection {
+                key: window.key,
+                label: window.label,
+                utilization: window.utilization,
+                burn_rate,
+                projected_at,
+                sample_count: samples.len() as u32,
+                r_squared,
+                confidence: forecast_confidence(samples.len(), r_squared),
+            })
+        })
+        .collect()
+}
+
+/// When `utilization` reaches 100% at `rate` points per hour, or None when it
+/// isn't climbing or the window resets first.
+pub(crate) fn projected_limit_time(
+    utilization: f64,
+    rate: f64,
+    resets_at: Option<&str>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    if rate <= 0.0 {
+        return None;
+    }
+    let seconds = ((100.0 - utilization).max(0.0) / rate * 3600.0).round() as i64;
+    let projected = now + chrono::Duration::seconds(seconds);
+    let resets_first = resets_at
+        .and_then(|resets_at| chrono::DateTime::parse_from_rfc3339(resets_at).ok())
+        .is_some_and(|resets_at| resets_at <= projected);
+    (!resets_first).then_some(projected)
+}
+
+/// The window's samples over the burn rate lookback, oldest first.
+fn burn_rate_samples(
+    conn: &Connection,
+    provider: ProviderKind,
+    organization: &str,
+    window_key: &str,
+) -> SqliteResult<Vec<(chrono::DateTime<chrono::Utc>, f64)>> {
+    let from =
+        (chrono::Utc::now() - chrono::Duration::minutes(BURN_RATE_LOOKBACK_MINUTES)).to_rfc3339();
+    let mut stmt = conn.prepare(
+        r#"SELECT timestamp, utilization FROM usage_history_v2
+        WHERE provider = ?1 AND organization_id = ?4 AND window_key = ?2 AND timestamp >= ?3
+        ORDER BY timestamp ASC"#,
+    )?;
+    let samples = stmt
+        .query_map(
+            rusqlite::params![provider.as_str(), window_key, from, organization],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, f64>(1)?)),
+        )?
+        .collect::<SqliteResult<Vec<_>>>()?
+        .into_iter()
+        .filter_map(|(timestamp, utilization)| {
+            let timestamp = chrono::DateTime::parse_from_rfc3339(&timestamp).ok()?;
+            Some((timestamp.with_timezone(&chrono::Utc), utilization))
+        })
+        .collect();
+    Ok(samples)
+}
+
+/// Samples since the latest drop (a reset), so a reset within the hour
+/// doesn't read as a negative burn.
+fn since_last_reset(
+    samples: &[(chrono::DateTime<chrono::Utc>, f64)],
+) -> &[(chrono::DateTime<chrono::Utc>, f64)] {
+    let start = samples
+        .windows(2)
+        .rposition(|pair| pair[1].1 < pair[0].1)
+        .map_or(0, |index| index + 1);
+    &samples[start..]
+}
+
+/// Rate between the first and last sample since the latest reset.
+fn burn_rate(samples: &[(chrono::DateTime<chrono::Utc>, f64)]) -> Option<f64> {
+    let samples = since_last_reset(samples);
+    let (first, last) = (samples.first()?, samples.last()?);
+    let minutes = last.0.signed_duration_since(first.0).num_minutes();
+    (minutes >= BURN_RATE_MIN_SPAN_MINUTES).then(|| (last.1 - first.1) / minutes as f64 * 60.0)
+}
+
+/// Coefficient of determination of a least-squares line through the samples.
+/// Flat usage fits perfectly; None with fewer than three samples or when they
+/// share a timestamp.
+fn r_squared(samples: &[(chrono::DateTime<chrono::Utc>, f64)]) -> Option<f64> {
+    if samples.len() < 3 {
+        return None;
+    }
+    let origin = samples[0].0;
+    let points = samples
+        .iter()
+        .map(|(at, y)| ((*at - origin).num_seconds() as f64, *y))
+        .collect::<Vec<_>>();
+    let n = points.len() as f64;
+    let mean_x = points.iter().map(|(x, _)| x).sum::<f64>() / n;
+    let mean_y = points.iter().map(|(_, y)| y).sum::<f64>() / n;
+    let sxx = points
+        .iter()
+        .map(|(x, _)| (x - mean_x).powi(2))
+        .sum::<f64>();
+    let syy = points
+        .iter()
+        .map(|(_, y)| (y - mean_y).powi(2))
+        .sum::<f64>();
+    let sxy = points
+        .iter()
+        .map(|(x, y)| (x - mean_x) * (y - mean_y))
+        .sum::<f64>();
+    if sxx == 0.0 {
+        return None;
+    }
+    if syy == 0.0 {
+        return Some(1.0);
+    }
+    Some((sxy * sxy / (sxx * syy)).clamp(0.0, 1.0))
+}
+
+fn forecast_confidence(sample_count: usize, r_squared: Option<f64>) -> ForecastConfidence {
+    match r_squared {
+        Some(r2) if sample_count >= FORECAST_HIGH_SAMPLES && r2 >= FORECAST_HIGH_R_SQUARED => {
+            ForecastConfidence::High
+        }
+        Some(r2) if sample_count >= FORECAST_MEDIUM_SAMPLES && r2 >= FORECAST_MEDIUM_R_SQUARED => {
+            ForecastConfidence::Medium
+        }
+        _ => ForecastConfidence::Low,
+    }
+}
+
+/// Samples over `range`, limited to the window keys in `metrics` unless it's empty.
 pub fn get_usage_history_by_range(
     provider: ProviderKind,
     range: &str,
+    metrics: &[String],
 ) -> SqliteResult<Vec<UsageHistoryPoint>> {
     let now = chrono::Utc::now();
     let hours = get_range_hours(range) as i64;
@@ -106,11 +975,106 @@ pub fn get_usage_history_by_range(
     let from_str = from.to_rfc3339();
     let now_str = now.to_rfc3339();
 
+    let metrics = metric_filter(metrics);
     if let Some(bucket_minutes) = get_downsample_bucket_minutes(range) {
-        get_usage_history_downsampled(provider, &from_str, &now_str, bucket_minutes)
+        get_usage_history_downsampled(
+            provider,
+            &from_str,
+            &now_str,
+            bucket_minutes,
+            metrics.as_deref(),
+        )
     } else {
-        get_usage_history(provider, &from_str, &now_str)
+        get_usage_history(provider, &from_str, &now_str, metrics.as_deref())
+    }
+}
+
+/// Every primary sample in `range`, never downsampled, for reports that need
+/// exact peaks.
+pub fn get_full_usage_history(
+    provider: ProviderKind,
+    range: &str,
+) -> SqliteResult<Vec<UsageHistoryPoint>> {
+    let now = chrono::Utc::now();
+    let from = now - chrono::Duration::hours(get_range_hours(range) as i64);
+    get_usage_history_between(provider, from, now)
+}
+
+/// Every primary sample from `from` to `to`, e.g. one ended period.
+pub fn get_usage_history_between(
+    provider: ProviderKind,
+    from: chrono::DateTime<chrono::Utc>,
+    to: chrono::DateTime<chrono::Utc>,
+) -> SqliteResult<Vec<UsageHistoryPoint>> {
+    get_usage_history(provider, &from.to_rfc3339(), &to.to_rfc3339(), None)
+}
+
+/// The samples of `get_usage_history_by_range` as one series per window, with
+/// gaps marked where the app wasn't recording.
+pub fn get_chart_series(
+    provider: ProviderKind,
+    range: &str,
+    metrics: &[String],
+) -> SqliteResult<Vec<ChartSeries>> {
+    let points = get_usage_history_by_range(provider, range, metrics)?;
+    let bucket_minutes = get_downsample_bucket_minutes(range).unwrap_or(0) as i64;
+    let gap = chrono::Duration::minutes(CHART_GAP_MINUTES.max(2 * bucket_minutes));
+    Ok(chart_series(&points, gap))
+}
+
+/// History timestamps are RFC3339, or SQLite's `datetime()` format in UTC
+/// (downsampled buckets).
+pub(crate) fn parse_timestamp(timestamp: &str) -> Option<chrono::DateTime<chrono::Utc>> {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|at| at.with_timezone(&chrono::Utc))
+        .ok()
+        .or_else(|| {
+            chrono::NaiveDateTime::parse_from_str(timestamp, "%Y-%m-%d %H:%M:%S")
+                .ok()
+                .map(|naive| naive.and_utc())
+        })
+}
+
+/// Series in order of first appearance, each in time order, with a null
+/// point between samples further apart than `gap`.
+fn chart_series(points: &[UsageHistoryPoint], gap: chrono::Duration) -> Vec<ChartSeries> {
+    let mut series: Vec<(ChartSeries, Option<i64>)> = Vec::new();
+    let mut samples = points
+        .iter()
+        .filter_map(|point| Some((parse_timestamp(&point.timestamp)?, point)))
+        .collect::<Vec<_>>();
+    samples.sort_by_key(|(at, _)| *at);
+
+    for (at, point) in samples {
+        let index = match series.iter().position(|(s, _)| s.key == point.window_key) {
+            Some(index) => index,
+            None => {
+                series.push((
+                    ChartSeries {
+                        key: point.window_key.clone(),
+                        label: point.label.clone(),
+                        points: Vec::new(),
+                    },
+                    None,
+                ));
+                series.len() - 1
+            }
+        };
+        let (line, last_x) = &mut series[index];
+        let x = at.timestamp_millis();
+        if let Some(gap_start) = last_x.filter(|last_x| x - last_x > gap.num_milliseconds()) {
+            line.points.push(ChartPoint {
+                x: gap_start + (x - gap_start) / 2,
+                y: None,
+            });
+        }
+        line.points.push(ChartPoint {
+            x,
+            y: Some(point.utilization),
+        });
+        *last_x = Some(x);
     }
+    series.into_iter().map(|(line, _)| line).collect()
 }
 
 pub fn get_usage_stats(provider: ProviderKind, range: &str) -> SqliteResult<UsageStats> {
@@ -121,6 +1085,7 @@ pub fn get_usage_stats(provider: ProviderKind, range: &str) -> SqliteResult<Usag
     let from_str = from.to_rfc3339();
     let now_str = now.to_rfc3339();
     let provider_str = provider.as_str();
+    let organization = organization_scope(provider);
 
     let mut stmt = conn.prepare(
         r#"
@@ -136,7 +1101,7 @@ pub fn get_usage_stats(provider: ProviderKind, range: &str) -> SqliteResult<Usag
                 ROW_NUMBER() OVER (PARTITION BY window_key ORDER BY timestamp ASC, id ASC) AS asc_rank,
                 ROW_NUMBER() OVER (PARTITION BY window_key ORDER BY timestamp DESC, id DESC) AS desc_rank
             FROM usage_history_v2
-            WHERE provider = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+            WHERE provider = ?1 AND organization_id = ?4 AND timestamp >= ?2 AND timestamp <= ?3
         )
         SELECT
             window_key,
@@ -150,58 +1115,694 @@ pub fn get_usage_stats(provider: ProviderKind, range: &str) -> SqliteResult<Usag
         "#,
     )?;
 
-    let windows = stmt
-        .query_map(
-            rusqlite::params![provider_str, &from_str, &now_str],
-            |row| {
-                let current: Option<f64> = row.get(2)?;
-                let first_value: Option<f64> = row.get(3)?;
-                let last_value: Option<f64> = row.get(4)?;
-                let change = match (first_value, last_value) {
-                    (Some(first), Some(last)) => Some(last - first),
-                    _ => None,
-                };
-                let velocity = change.and_then(|delta| {
-                    if delta >= 0.0 && period_hours > 0.0 {
-                        Some(delta / period_hours)
-                    } else {
-                        None
+    let windows = stmt
+        .query_map(
+            rusqlite::params![provider_str, &from_str, &now_str],
+            |row| {
+                let current: Option<f64> = row.get(2)?;
+                let first_value: Option<f64> = row.get(3)?;
+                let last_value: Option<f64> = row.get(4)?;
+                let change = match (first_value, last_value) {
+                    (Some(first), Some(last)) => Some(last - first),
+                    _ => None,
+                };
+                let velocity = change.and_then(|delta| {
+                    if delta >= 0.0 && period_hours > 0.0 {
+                        Some(delta / period_hours)
+                    } else {
+                        None
+                    }
+                });
+
+                Ok(WindowStats {
+                    key: row.get(0)?,
+                    label: row.get(1)?,
+                    current,
+                    change,
+                    velocity,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let record_count: i64 = conn.query_row(
+        r#"SELECT COUNT(*) FROM usage_history_v2
+        WHERE provider = ?1 AND organization_id = ?4 AND timestamp >= ?2 AND timestamp <= ?3"#,
+        rusqlite::params![provider_str, &from_str, &now_str, &organization],
+        |row| row.get(0),
+    )?;
+
+    let capped_five_hour_windows =
+        count_capped_periods(&conn, provider, &organization, &from_str, &now_str)?;
+
+    Ok(UsageStats {
+        windows,
+        record_count,
+        period_hours,
+        capped_five_hour_windows,
+    })
+}
+
+/// Periods of the provider's 5-hour window between `from` and `to` that
+/// peaked at `CAPPED_UTILIZATION` or above. Periods are split like in the
+/// `usage_resets` view; one cut off by the range counts by its samples inside.
+fn count_capped_periods(
+    conn: &Connection,
+    provider: ProviderKind,
+    organization: &str,
+    from: &str,
+    to: &str,
+) -> SqliteResult<i64> {
+    conn.query_row(
+        r#"
+        WITH samples AS (
+            SELECT
+                timestamp,
+                utilization,
+                CASE WHEN (julianday(resets_at) - julianday(LAG(resets_at) OVER w)) * 24 * 60 > 30
+                    THEN 1 ELSE 0 END AS new_period
+            FROM usage_history_v2
+            WHERE provider = ?1 AND organization_id = ?5
+                AND window_key IN ('five_hour', 'primary', 'session')
+                AND timestamp >= ?2 AND timestamp <= ?3
+            WINDOW w AS (ORDER BY timestamp, id)
+        ),
+        periods AS (
+            SELECT utilization, SUM(new_period) OVER (ORDER BY timestamp ROWS UNBOUNDED PRECEDING) AS period
+            FROM samples
+        )
+        SELECT COUNT(*) FROM (
+            SELECT period FROM periods GROUP BY period HAVING MAX(utilization) >= ?4
+        )
+        "#,
+        rusqlite::params![
+            provider.as_str(),
+            from,
+            to,
+            CAPPED_UTILIZATION,
+            organization
+        ],
+        |row| row.get(0),
+    )
+}
+
+/// Consumption of the `metric` window over `range`, by local weekday and hour.
+pub fn get_usage_histogram(
+    provider: ProviderKind,
+    range: &str,
+    metric: &str,
+) -> SqliteResult<UsageHistogram> {
+    let conn = get_db()?;
+    let now = chrono::Utc::now();
+    let from = now - chrono::Duration::hours(get_range_hours(range) as i64);
+    usage_histogram(
+        &conn,
+        provider,
+        &organization_scope(provider),
+        metric,
+        &from.to_rfc3339(),
+        &now.to_rfc3339(),
+        "localtime",
+    )
+}
+
+/// `time_modifier` is the SQLite date modifier moving timestamps into the
+/// time zone to group by.
+fn usage_histogram(
+    conn: &Connection,
+    provider: ProviderKind,
+    organization: &str,
+    metric: &str,
+    from: &str,
+    to: &str,
+    time_modifier: &str,
+) -> SqliteResult<UsageHistogram> {
+    let mut stmt = conn.prepare(
+        r#"
+        WITH steps AS (
+            SELECT
+                timestamp,
+                utilization,
+                LAG(utilization) OVER w AS previous,
+                -- A new period moves resets_at forward by far more than fetch-to-fetch jitter
+                (julianday(resets_at) - julianday(LAG(resets_at) OVER w)) * 24 * 60 > 30 AS is_reset
+            FROM usage_history_v2
+            WHERE provider = ?1 AND organization_id = ?6 AND window_key = ?2
+                AND timestamp >= ?3 AND timestamp <= ?4
+            WINDOW w AS (ORDER BY timestamp, id)
+        )
+        SELECT
+            (CAST(strftime('%w', timestamp, ?5) AS INTEGER) + 6) % 7 AS weekday,
+            CAST(strftime('%H', timestamp, ?5) AS INTEGER) AS hour,
+            SUM(CASE WHEN is_reset THEN utilization ELSE MAX(utilization - previous, 0) END),
+            COUNT(*)
+        FROM steps
+        WHERE previous IS NOT NULL
+        GROUP BY weekday, hour
+        ORDER BY weekday, hour
+        "#,
+    )?;
+    let buckets = stmt
+        .query_map(
+            rusqlite::params![
+                provider.as_str(),
+                metric,
+                from,
+                to,
+                time_modifier,
+                organization
+            ],
+            |row| {
+                Ok(HistogramBucket {
+                    weekday: row.get(0)?,
+                    hour: row.get(1)?,
+                    consumption: row.get(2)?,
+                    samples: row.get(3)?,
+                })
+            },
+        )?
+        .collect::<SqliteResult<Vec<_>>>()?;
+    Ok(UsageHistogram {
+        metric: metric.to_string(),
+        buckets,
+    })
+}
+
+/// Average time from the start of the provider's 5-hour periods in `range`
+/// until they reached `threshold`.
+pub fn get_time_to_threshold(
+    provider: ProviderKind,
+    range: &str,
+    threshold: f64,
+) -> SqliteResult<TimeToThreshold> {
+    let now = chrono::Utc::now();
+    let from = now - chrono::Duration::hours(get_range_hours(range) as i64);
+    let samples = get_usage_history_between(provider, from, now)?
+        .into_iter()
+        .filter(|point| FIVE_HOUR_WINDOW_KEYS.contains(&point.window_key.as_str()))
+        .filter_map(|point| {
+            let timestamp = chrono::DateTime::parse_from_rfc3339(&point.timestamp).ok()?;
+            let reset = point
+                .resets_at_epoch_ms
+                .and_then(chrono::DateTime::from_timestamp_millis)?;
+            Some((
+                timestamp.with_timezone(&chrono::Utc),
+                point.utilization,
+                reset,
+            ))
+        })
+        .collect::<Vec<_>>();
+    Ok(time_to_threshold(&samples, threshold))
+}
+
+/// Splits `(timestamp, utilization, resets_at)` samples, in timestamp order,
+/// into periods like the `usage_resets` view. A period counts only once a
+/// sample below the threshold was seen in it, so one that was already past it
+/// when polling started doesn't read as reached at the first sample.
+fn time_to_threshold(
+    samples: &[(
+        chrono::DateTime<chrono::Utc>,
+        f64,
+        chrono::DateTime<chrono::Utc>,
+    )],
+    threshold: f64,
+) -> TimeToThreshold {
+    let mut period_count = 0;
+    let mut periods = Vec::new();
+    let mut period_reset: Option<chrono::DateTime<chrono::Utc>> = None;
+    let mut seen_below = false;
+    let mut crossed = false;
+    for (timestamp, utilization, reset) in samples {
+        let new_period =
+            period_reset.is_none_or(|previous| *reset - previous > chrono::Duration::minutes(30));
+        if new_period {
+            period_count += 1;
+            seen_below = false;
+            crossed = false;
+        }
+        period_reset = Some(*reset);
+
+        if *utilization < threshold {
+            seen_below = true;
+        } else if seen_below && !crossed {
+            crossed = true;
+            let started_at = *reset - chrono::Duration::minutes(FIVE_HOUR_MINUTES);
+            periods.push(ThresholdCrossing {
+                started_at: started_at.to_rfc3339(),
+                minutes: (*timestamp - started_at).num_seconds().max(0) as f64 / 60.0,
+            });
+        }
+    }
+
+    let average_minutes = (!periods.is_empty())
+        .then(|| periods.iter().map(|period| period.minutes).sum::<f64>() / periods.len() as f64);
+    TimeToThreshold {
+        threshold,
+        average_minutes,
+        period_count,
+        periods,
+    }
+}
+
+/// Bytes of a sync log already imported (0 for a new log).
+pub fn sync_log_position(file_name: &str) -> SqliteResult<usize> {
+    let conn = get_db()?;
+    let bytes_read: Option<i64> = conn
+        .query_row(
+            "SELECT bytes_read FROM history_sync_logs WHERE file_name = ?1",
+            [file_name],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(bytes_read.unwrap_or(0) as usize)
+}
+
+/// Store points from another machine's sync log and move the log's position
+/// to `bytes_read`. Returns how many points were new.
+pub fn import_synced_records(
+    file_name: &str,
+    records: &[SyncRecord],
+    bytes_read: usize,
+) -> SqliteResult<usize> {
+    let conn = get_db()?;
+    let tx = conn.unchecked_transaction()?;
+    let inserted = insert_synced_records(&tx, records, &organization_scope(ProviderKind::Claude))?;
+    tx.execute(
+        "INSERT OR REPLACE INTO history_sync_logs (file_name, bytes_read) VALUES (?1, ?2)",
+        rusqlite::params![file_name, bytes_read as i64],
+    )?;
+    tx.commit()?;
+    Ok(inserted)
+}
+
+/// Import new Claude Code transcript messages into `local_token_history`.
+/// Re-reads an hour of overlap before the newest stored message; duplicates are ignored.
+pub fn sync_local_usage() -> Result<usize, crate::error::AppError> {
+    if is_incognito() {
+        return Ok(0);
+    }
+    let newest: Option<String> = {
+        let conn = get_db().map_err(storage_error)?;
+        conn.query_row(
+            "SELECT MAX(timestamp) FROM local_token_history WHERE message_key NOT LIKE ?1 || '%'",
+            [IMPORTED_MESSAGE_PREFIX],
+            |row| row.get(0),
+        )
+        .map_err(storage_error)?
+    };
+
+    let since = newest
+        .and_then(|timestamp| chrono::DateTime::parse_from_rfc3339(&timestamp).ok())
+        .map(|timestamp| timestamp.with_timezone(&chrono::Utc) - chrono::Duration::hours(1))
+        .unwrap_or_else(|| {
+            chrono::Utc::now() - chrono::Duration::days(LOCAL_TOKEN_INITIAL_IMPORT_DAYS)
+        });
+
+    let entries = local_usage::load_entries_since(since)?;
+    let conn = get_db().map_err(storage_error)?;
+    insert_local_usage_entries(&conn, &entries).map_err(storage_error)
+}
+
+/// Store token rows imported from another tool. Other tools read the same
+/// transcripts, so only whole days before the earliest transcript message
+/// stored here (or before the first transcript import's reach, when there is
+/// none yet) are kept. Returns how many rows were new.
+pub fn import_local_usage(entries: &[LocalUsageEntry]) -> Result<usize, crate::error::AppError> {
+    if is_incognito() {
+        return Ok(0);
+    }
+    let conn = get_db().map_err(storage_error)?;
+    let earliest: Option<String> = conn
+        .query_row(
+            "SELECT MIN(timestamp) FROM local_token_history WHERE message_key NOT LIKE ?1 || '%'",
+            [IMPORTED_MESSAGE_PREFIX],
+            |row| row.get(0),
+        )
+        .map_err(storage_error)?;
+    let cutoff = earliest
+        .and_then(|timestamp| chrono::DateTime::parse_from_rfc3339(&timestamp).ok())
+        .map(|timestamp| timestamp.with_timezone(&chrono::Utc))
+        .unwrap_or_else(|| {
+            chrono::Utc::now() - chrono::Duration::days(LOCAL_TOKEN_INITIAL_IMPORT_DAYS)
+        });
+
+    let entries: Vec<LocalUsageEntry> = entries
+        .iter()
+        .filter(|entry| entry.timestamp + chrono::Duration::days(1) <= cutoff)
+        .cloned()
+        .collect();
+    insert_local_usage_entries(&conn, &entries).map_err(storage_error)
+}
+
+pub fn get_combined_history(
+    provider: ProviderKind,
+    range: &str,
+    metrics: &[String],
+) -> SqliteResult<CombinedHistory> {
+    let utilization = get_usage_history_by_range(provider, range, metrics)?;
+
+    let now = chrono::Utc::now();
+    let hours = get_range_hours(range) as i64;
+    let from_str = (now - chrono::Duration::hours(hours)).to_rfc3339();
+    let now_str = now.to_rfc3339();
+    let token_bucket_minutes = get_token_bucket_minutes(range);
+
+    let conn = get_db()?;
+    let query = format!(
+        r#"SELECT
+            datetime((strftime('%s', timestamp) / ({token_bucket_minutes} * 60)) * ({token_bucket_minutes} * 60), 'unixepoch') AS bucket,
+            SUM(input_tokens),
+            SUM(output_tokens),
+            SUM(cache_creation_tokens),
+            SUM(cache_read_tokens),
+            COUNT(*)
+        FROM local_token_history
+        WHERE timestamp >= ?1 AND timestamp <= ?2
+        GROUP BY strftime('%s', timestamp) / ({token_bucket_minutes} * 60)
+        ORDER BY bucket ASC"#
+    );
+
+    let mut stmt = conn.prepare(&query)?;
+    let tokens = stmt
+        .query_map(rusqlite::params![&from_str, &now_str], |row| {
+            Ok(TokenHistoryPoint {
+                timestamp: row.get(0)?,
+                tokens: TokenCounts {
+                    input_tokens: row.get::<_, i64>(1)? as u64,
+                    output_tokens: row.get::<_, i64>(2)? as u64,
+                    cache_creation_tokens: row.get::<_, i64>(3)? as u64,
+                    cache_read_tokens: row.get::<_, i64>(4)? as u64,
+                },
+                message_count: row.get::<_, i64>(5)? as u32,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(CombinedHistory {
+        utilization,
+        tokens,
+        token_bucket_minutes,
+    })
+}
+
+pub fn get_token_bucket_minutes(range: &str) -> u32 {
+    match range {
+        "1h" => 5,
+        "6h" => 15,
+        "7d" => 240,
+        "30d" => 1440,
+        _ => 60,
+    }
+}
+
+pub fn cleanup_old_data(retention_days: u32) -> SqliteResult<usize> {
+    let conn = get_db()?;
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(retention_days as i64);
+    let cutoff_str = cutoff.to_rfc3339();
+
+    let deleted = conn.execute(
+        "DELETE FROM usage_history_v2 WHERE timestamp < ?1",
+        rusqlite::params![cutoff_str],
+    )?;
+    conn.execute(
+        "DELETE FROM local_token_history WHERE timestamp < ?1",
+        rusqlite::params![cutoff_str],
+    )?;
+    conn.execute(
+        "DELETE FROM member_usage_history WHERE timestamp < ?1",
+        rusqlite::params![cutoff_str],
+    )?;
+
+    Ok(deleted)
+}
+
+/// Reset times from the provider's most recent snapshot.
+pub fn latest_resets_at(provider: ProviderKind) -> SqliteResult<Vec<String>> {
+    let conn = get_db()?;
+    let mut stmt = conn.prepare(
+        r#"SELECT resets_at FROM usage_history_v2
+        WHERE provider = ?1 AND organization_id = ?2 AND resets_at IS NOT NULL
+            AND timestamp = (
+                SELECT MAX(timestamp) FROM usage_history_v2
+                WHERE provider = ?1 AND organization_id = ?2
+            )"#,
+    )?;
+    stmt.query_map(
+        rusqlite::params![provider.as_str(), organization_scope(provider)],
+        |row| row.get(0),
+    )?
+    .collect()
+}
+
+/// The latest stored snapshot of each provider.
+pub fn get_latest_snapshots() -> SqliteResult<Vec<LatestSnapshot>> {
+    let conn = get_db()?;
+    latest_snapshots(&conn, None, Some(&organization_scope(ProviderKind::Claude)))
+}
+
+/// Read the latest snapshot per provider straight from the database file,
+/// read-only, for the CLI. Works whether or not the app is running. Claude
+/// rows are limited to `organization` when it is known.
+pub fn query_latest_snapshots(
+    db_path: &Path,
+    provider: Option<&str>,
+    organization: Option<&str>,
+) -> SqliteResult<Vec<LatestSnapshot>> {
+    let conn = Connection::open_with_flags(db_path, rusqlite::OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+    latest_snapshots(&conn, provider, organization)
+}
+
+/// `organization` is the Claude organization to read; other providers always
+/// read their untagged rows.
+fn latest_snapshots(
+    conn: &Connection,
+    provider: Option<&str>,
+    organization: Option<&str>,
+) -> SqliteResult<Vec<LatestSnapshot>> {
+    let mut stmt = conn.prepare(
+        r#"WITH scoped AS (
+            SELECT * FROM usage_history_v2
+            WHERE (?1 IS NULL OR provider = ?1)
+                AND organization_id = CASE
+                    WHEN provider = 'claude' THEN COALESCE(?2, organization_id) ELSE ''
+                END
+        ),
+        latest AS (
+            SELECT provider, MAX(timestamp) AS timestamp FROM scoped GROUP BY provider
+        )
+        SELECT h.provider, h.timestamp, h.window_key, h.label, h.utilization, h.resets_at
+        FROM latest
+        JOIN scoped h ON h.provider = latest.provider AND h.timestamp = latest.timestamp
+        ORDER BY h.provider, h.id"#,
+    )?;
+    let rows = stmt.query_map(rusqlite::params![provider, organization], |row| {
+        let resets_at: Option<String> = row.get(5)?;
+        Ok((
+            row.get::<_, String>(0)?,
+            row.get::<_, String>(1)?,
+            crate::types::UsageWindow {
+                key: row.get(2)?,
+                label: row.get(3)?,
+                utilization: row.get(4)?,
+                resets_at_epoch_ms: reset_epoch_ms(resets_at.as_deref()),
+                resets_at,
+                window_duration_seconds: None,
+            },
+        ))
+    })?;
+
+    let mut snapshots: Vec<LatestSnapshot> = Vec::new();
+    for row in rows {
+        let (provider, timestamp, window) = row?;
+        match snapshots.last_mut() {
+            Some(last) if last.provider == provider => last.windows.push(window),
+            _ => snapshots.push(LatestSnapshot {
+                provider,
+                timestamp,
+                windows: vec![window],
+            }),
+        }
+    }
+    Ok(snapshots)
+}
+
+/// A reporting view served by the local reporting endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportingView {
+    Hourly,
+    Resets,
+}
+
+impl ReportingView {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "usage_hourly" => Some(Self::Hourly),
+            "usage_resets" => Some(Self::Resets),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Hourly => "usage_hourly",
+            Self::Resets => "usage_resets",
+        }
+    }
+
+    fn time_column(self) -> &'static str {
+        match self {
+            Self::Hourly => "hour",
+            Self::Resets => "reset_observed_at",
+        }
+    }
+}
+
+/// Rows of a reporting view as JSON objects keyed by column, oldest first,
+/// optionally limited to one provider and a time range (RFC3339, inclusive).
+pub fn query_reporting_view(
+    view: ReportingView,
+    provider: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> SqliteResult<Vec<serde_json::Map<String, serde_json::Value>>> {
+    let conn = get_db()?;
+    reporting_view_rows(&conn, view, provider, from, to)
+}
+
+fn reporting_view_rows(
+    conn: &Connection,
+    view: ReportingView,
+    provider: Option<&str>,
+    from: Option<&str>,
+    to: Option<&str>,
+) -> SqliteResult<Vec<serde_json::Map<String, serde_json::Value>>> {
+    let time = view.time_column();
+    let mut stmt = conn.prepare(&format!(
+        r#"SELECT * FROM {view}
+        WHERE (?1 IS NULL OR provider = ?1)
+            AND (?2 IS NULL OR julianday({time}) >= julianday(?2))
+            AND (?3 IS NULL OR julianday({time}) <= julianday(?3))
+        ORDER BY {time}"#,
+        view = view.name(),
+    ))?;
+    let columns: Vec<String> = stmt.column_names().into_iter().map(String::from).collect();
+    stmt.query_map(rusqlite::params![provider, from, to], |row| {
+        columns
+            .iter()
+            .enumerate()
+            .map(|(index, column)| {
+                let value: serde_json::Value = match row.get_ref(index)? {
+                    rusqlite::types::ValueRef::Integer(value) => value.into(),
+                    rusqlite::types::ValueRef::Real(value) => value.into(),
+                    rusqlite::types::ValueRef::Text(value) => {
+                        String::from_utf8_lossy(value).into_owned().into()
                     }
-                });
+                    rusqlite::types::ValueRef::Null | rusqlite::types::ValueRef::Blob(_) => {
+                        serde_json::Value::Null
+                    }
+                };
+                Ok((column.clone(), value))
+            })
+            .collect()
+    })?
+    .collect()
+}
 
-                Ok(WindowStats {
-                    key: row.get(0)?,
-                    label: row.get(1)?,
-                    current,
-                    change,
-                    velocity,
-                })
-            },
-        )?
-        .collect::<Result<Vec<_>, _>>()?;
+pub fn get_db_info() -> SqliteResult<HistoryDbInfo> {
+    let conn = get_db()?;
+    db_info(&conn)
+}
 
-    let record_count: i64 = conn.query_row(
-        r#"SELECT COUNT(*) FROM usage_history_v2 WHERE provider = ?1 AND timestamp >= ?2 AND timestamp <= ?3"#,
-        rusqlite::params![provider_str, &from_str, &now_str],
-        |row| row.get(0),
+fn db_info(conn: &Connection) -> SqliteResult<HistoryDbInfo> {
+    let page_count: i64 = conn.query_row("PRAGMA page_count", [], |row| row.get(0))?;
+    let page_size: i64 = conn.query_row("PRAGMA page_size", [], |row| row.get(0))?;
+    let journal_mode: String = conn.query_row("PRAGMA journal_mode", [], |row| row.get(0))?;
+
+    let (usage_row_count, oldest_timestamp, newest_timestamp) = conn.query_row(
+        "SELECT COUNT(*), MIN(timestamp), MAX(timestamp) FROM usage_history_v2",
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
     )?;
+    let token_row_count =
+        conn.query_row("SELECT COUNT(*) FROM local_token_history", [], |row| {
+            row.get(0)
+        })?;
 
-    Ok(UsageStats {
-        windows,
-        record_count,
-        period_hours,
+    Ok(HistoryDbInfo {
+        file_size_bytes: page_count * page_size,
+        usage_row_count,
+        token_row_count,
+        oldest_timestamp,
+        newest_timestamp,
+        journal_mode,
     })
 }
 
-pub fn cleanup_old_data(retention_days: u32) -> SqliteResult<usize> {
+/// Delete history for a scope and return the number of usage samples removed.
+/// Legacy rows are cleared too, so the startup backfill doesn't restore them.
+pub fn clear_history(scope: &ClearScope) -> SqliteResult<usize> {
     let conn = get_db()?;
-    let cutoff = chrono::Utc::now() - chrono::Duration::days(retention_days as i64);
-    let cutoff_str = cutoff.to_rfc3339();
+    clear_scope(&conn, scope)
+}
 
-    conn.execute(
-        "DELETE FROM usage_history_v2 WHERE timestamp < ?1",
-        rusqlite::params![cutoff_str],
-    )
+fn clear_scope(conn: &Connection, scope: &ClearScope) -> SqliteResult<usize> {
+    let tx = conn.unchecked_transaction()?;
+    let deleted = match scope {
+        ClearScope::All => {
+            tx.execute("DELETE FROM usage_history", [])?;
+            tx.execute("DELETE FROM local_token_history", [])?;
+            tx.execute("DELETE FROM member_usage_history", [])?;
+            tx.execute("DELETE FROM usage_history_v2", [])?
+        }
+        ClearScope::OlderThan { days } => {
+            let cutoff = (chrono::Utc::now() - chrono::Duration::days(*days as i64)).to_rfc3339();
+            tx.execute(
+                "DELETE FROM usage_history WHERE timestamp < ?1",
+                rusqlite::params![cutoff],
+            )?;
+            tx.execute(
+                "DELETE FROM local_token_history WHERE timestamp < ?1",
+                rusqlite::params![cutoff],
+            )?;
+            tx.execute(
+                "DELETE FROM member_usage_history WHERE timestamp < ?1",
+                rusqlite::params![cutoff],
+            )?;
+            tx.execute(
+                "DELETE FROM usage_history_v2 WHERE timestamp < ?1",
+                rusqlite::params![cutoff],
+            )?
+        }
+        ClearScope::Metric { provider, key } => {
+            if *provider == ProviderKind::Claude {
+                if let Some((utilization, resets_at)) = legacy_columns(key) {
+                    tx.execute(
+                        &format!(
+                            "UPDATE usage_history SET {utilization} = NULL, {resets_at} = NULL"
+                        ),
+                        [],
+                    )?;
+                }
+            }
+            tx.execute(
+                "DELETE FROM usage_history_v2 WHERE provider = ?1 AND window_key = ?2",
+                rusqlite::params![provider.as_str(), key],
+            )?
+        }
+    };
+    tx.commit()?;
+    Ok(deleted)
+}
+
+/// Legacy `usage_history` columns holding a Claude window, as mapped by the backfill.
+fn legacy_columns(key: &str) -> Option<(&'static str, &'static str)> {
+    match key {
+        "five_hour" => Some(("five_hour_utilization", "five_hour_resets_at")),
+        "seven_day" => Some(("seven_day_utilization", "seven_day_resets_at")),
+        "seven_day_sonnet" => Some(("sonnet_utilization", "sonnet_resets_at")),
+        "seven_day_opus" => Some(("opus_utilization", "opus_resets_at")),
+        _ => None,
+    }
 }
 
 pub fn get_downsample_bucket_minutes(range: &str) -> Option<u32> {
@@ -212,21 +1813,46 @@ pub fn get_downsample_bucket_minutes(range: &str) -> Option<u32> {
     }
 }
 
+/// JSON array of window keys for `?4` in the history queries, or None for all.
+fn metric_filter(metrics: &[String]) -> Option<String> {
+    (!metrics.is_empty()).then(|| serde_json::Value::from(metrics.to_vec()).to_string())
+}
+
 fn get_usage_history(
     provider: ProviderKind,
     from: &str,
     to: &str,
+    metrics: Option<&str>,
 ) -> SqliteResult<Vec<UsageHistoryPoint>> {
     let conn = get_db()?;
+    usage_history(
+        &conn,
+        provider,
+        &organization_scope(provider),
+        from,
+        to,
+        metrics,
+    )
+}
+
+fn usage_history(
+    conn: &Connection,
+    provider: ProviderKind,
+    organization: &str,
+    from: &str,
+    to: &str,
+    metrics: Option<&str>,
+) -> SqliteResult<Vec<UsageHistoryPoint>> {
     let mut stmt = conn.prepare(
         r#"SELECT id, provider, timestamp, window_key, label, utilization, resets_at
         FROM usage_history_v2
-        WHERE provider = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+        WHERE provider = ?1 AND organization_id = ?5 AND timestamp >= ?2 AND timestamp <= ?3
+            AND (?4 IS NULL OR window_key IN (SELECT value FROM json_each(?4)))
         ORDER BY timestamp ASC, window_key ASC"#,
     )?;
 
     stmt.query_map(
-        rusqlite::params![provider.as_str(), from, to],
+        rusqlite::params![provider.as_str(), from, to, metrics, organization],
         map_history_point,
     )?
     .collect::<Result<Vec<_>, _>>()
@@ -237,6 +1863,7 @@ fn get_usage_history_downsampled(
     from: &str,
     to: &str,
     bucket_minutes: u32,
+    metrics: Option<&str>,
 ) -> SqliteResult<Vec<UsageHistoryPoint>> {
     let conn = get_db()?;
     let query = format!(
@@ -249,14 +1876,21 @@ fn get_usage_history_downsampled(
             AVG(utilization) AS utilization,
             MAX(resets_at) AS resets_at
         FROM usage_history_v2
-        WHERE provider = ?1 AND timestamp >= ?2 AND timestamp <= ?3
+        WHERE provider = ?1 AND organization_id = ?5 AND timestamp >= ?2 AND timestamp <= ?3
+            AND (?4 IS NULL OR window_key IN (SELECT value FROM json_each(?4)))
         GROUP BY provider, window_key, label, (strftime('%s', timestamp) / ({bucket_minutes} * 60))
         ORDER BY timestamp ASC, window_key ASC"#
     );
 
     let mut stmt = conn.prepare(&query)?;
     stmt.query_map(
-        rusqlite::params![provider.as_str(), from, to],
+        rusqlite::params![
+            provider.as_str(),
+            from,
+            to,
+            metrics,
+            organization_scope(provider)
+        ],
         map_history_point,
     )?
     .collect::<Result<Vec<_>, _>>()
@@ -264,6 +1898,7 @@ fn get_usage_history_downsampled(
 
 fn map_history_point(row: &rusqlite::Row<'_>) -> SqliteResult<UsageHistoryPoint> {
     let provider_raw: String = row.get(1)?;
+    let resets_at: Option<String> = row.get(6)?;
     Ok(UsageHistoryPoint {
         id: row.get(0)?,
         provider: parse_provider(&provider_raw),
@@ -271,7 +1906,8 @@ fn map_history_point(row: &rusqlite::Row<'_>) -> SqliteResult<UsageHistoryPoint>
         window_key: row.get(3)?,
         label: row.get(4)?,
         utilization: row.get(5)?,
-        resets_at: row.get(6)?,
+        resets_at_epoch_ms: reset_epoch_ms(resets_at.as_deref()),
+        resets_at,
     })
 }
 
@@ -289,10 +1925,26 @@ fn insert_snapshot(
     timestamp: &str,
     windows: &[crate::types::UsageWindow],
 ) -> SqliteResult<()> {
+    insert_organization_snapshot(conn, "", provider, timestamp, windows, None)
+}
+
+/// Rows from a fetch record the app version and what triggered it; rows
+/// without a `source` (migrated legacy data) leave both unknown.
+fn insert_organization_snapshot(
+    conn: &Connection,
+    org_id: &str,
+    provider: ProviderKind,
+    timestamp: &str,
+    windows: &[crate::types::UsageWindow],
+    source: Option<FetchSource>,
+) -> SqliteResult<()> {
+    let app_version = source.map(|_| env!("CARGO_PKG_VERSION"));
+    let fetch_source = source.map(FetchSource::as_str);
     let mut stmt = conn.prepare(
         r#"INSERT OR IGNORE INTO usage_history_v2
-        (provider, timestamp, window_key, label, utilization, resets_at)
-        VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+        (provider, timestamp, window_key, label, utilization, resets_at, organization_id,
+         app_version, fetch_source)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
     )?;
 
     for window in windows {
@@ -303,12 +1955,175 @@ fn insert_snapshot(
             &window.label,
             window.utilization,
             &window.resets_at,
+            org_id,
+            app_version,
+            fetch_source,
         ])?;
     }
 
     Ok(())
 }
 
+fn insert_member_usage(
+    conn: &Connection,
+    org_id: &str,
+    timestamp: &str,
+    members: &[MemberUsage],
+) -> SqliteResult<()> {
+    let mut stmt = conn.prepare(
+        r#"INSERT OR IGNORE INTO member_usage_history
+        (organization_id, timestamp, member_id, member_name, member_email,
+            window_key, label, utilization, resets_at)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+    )?;
+
+    for member in members {
+        for window in &member.windows {
+            stmt.execute(rusqlite::params![
+                org_id,
+                timestamp,
+                &member.member_id,
+                &member.name,
+                &member.email,
+                &window.key,
+                &window.label,
+                window.utilization,
+                &window.resets_at,
+            ])?;
+        }
+    }
+
+    Ok(())
+}
+
+fn member_usage_summary(
+    conn: &Connection,
+    org_id: &str,
+    from: &str,
+) -> SqliteResult<Vec<MemberUsageSummary>> {
+    let mut stmt = conn.prepare(
+        r#"WITH ranged AS (
+            SELECT * FROM member_usage_history WHERE organization_id = ?1 AND timestamp >= ?2
+        ),
+        latest AS (
+            SELECT member_id, window_key, MAX(timestamp) AS timestamp,
+                MAX(utilization) AS peak_utilization
+            FROM ranged
+            GROUP BY member_id, window_key
+        )
+        SELECT r.member_id, r.member_name, r.member_email, r.window_key, r.label,
+            r.utilization, latest.peak_utilization, r.resets_at, r.timestamp
+        FROM latest
+        JOIN ranged r ON r.member_id = latest.member_id
+            AND r.window_key = latest.window_key AND r.timestamp = latest.timestamp
+        ORDER BY latest.peak_utilization DESC, r.member_id, r.window_key"#,
+    )?;
+
+    stmt.query_map(rusqlite::params![org_id, from], |row| {
+        Ok(MemberUsageSummary {
+            member_id: row.get(0)?,
+            name: row.get(1)?,
+            email: row.get(2)?,
+            window_key: row.get(3)?,
+            label: row.get(4)?,
+            utilization: row.get(5)?,
+            peak_utilization: row.get(6)?,
+            resets_at: row.get(7)?,
+            timestamp: row.get(8)?,
+        })
+    })?
+    .collect()
+}
+
+/// Imported rows keep the organization they were stored under and no
+/// provenance; Claude lines without one (older versions) are filed under
+/// `primary_organization`. The same sample arriving twice is ignored by the
+/// unique key.
+fn insert_synced_records(
+    conn: &Connection,
+    records: &[SyncRecord],
+    primary_organization: &str,
+) -> SqliteResult<usize> {
+    let mut stmt = conn.prepare(
+        r#"INSERT OR IGNORE INTO usage_history_v2
+        (provider, timestamp, window_key, label, utilization, resets_at, organization_id)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)"#,
+    )?;
+
+    let mut inserted = 0;
+    for record in records {
+        let organization_id = match record.provider {
+            ProviderKind::Claude if record.organization_id.is_empty() => primary_organization,
+            ProviderKind::Claude => record.organization_id.as_str(),
+            ProviderKind::Codex | ProviderKind::Ollama => "",
+        };
+        inserted += stmt.execute(rusqlite::params![
+            record.provider.as_str(),
+            &record.timestamp,
+            &record.window_key,
+            &record.label,
+            record.utilization,
+            &record.resets_at,
+            organization_id,
+        ])?;
+    }
+
+    Ok(inserted)
+}
+
+fn insert_local_usage_entries(
+    conn: &Connection,
+    entries: &[LocalUsageEntry],
+) -> SqliteResult<usize> {
+    let mut stmt = conn.prepare(
+        r#"INSERT OR IGNORE INTO local_token_history
+        (message_key, timestamp, project, model, input_tokens, output_tokens,
+            cache_creation_tokens, cache_read_tokens)
+        VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)"#,
+    )?;
+
+    let mut inserted = 0;
+    for entry in entries {
+        inserted += stmt.execute(rusqlite::params![
+            &entry.message_key,
+            entry.timestamp.to_rfc3339(),
+            &entry.project,
+            &entry.model,
+            entry.tokens.input_tokens as i64,
+            entry.tokens.output_tokens as i64,
+            entry.tokens.cache_creation_tokens as i64,
+            entry.tokens.cache_read_tokens as i64,
+        ])?;
+    }
+
+    Ok(inserted)
+}
+
+/// Create the usage table, adding the organization and provenance columns to
+/// databases from before they existed.
+fn create_v2_schema(conn: &Connection) -> SqliteResult<()> {
+    conn.execute_batch(V2_SCHEMA)?;
+    for (column, definition) in [
+        ("organization_id", "TEXT NOT NULL DEFAULT ''"),
+        ("app_version", "TEXT"),
+        ("fetch_source", "TEXT"),
+    ] {
+        let has_column = conn
+            .prepare("SELECT 1 FROM pragma_table_info('usage_history_v2') WHERE name = ?1")?
+            .exists([column])?;
+        if !has_column {
+            conn.execute_batch(&format!(
+                "ALTER TABLE usage_history_v2 ADD COLUMN {column} {definition}"
+            ))?;
+        }
+    }
+    conn.execute_batch(V2_ORGANIZATION_INDEX)
+}
+
+fn storage_error(e: rusqlite::Error) -> crate::error::AppError {
+    crate::error::AppError::Storage(e.to_string())
+}
+
 fn backfill_legacy_claude_data(conn: &Connection) -> SqliteResult<()> {
     let has_legacy_rows: Option<i64> = conn
         .query_row("SELECT COUNT(*) FROM usage_history", [], |row| row.get(0))
@@ -400,12 +2215,13 @@ fn legacy_window(
         key: key.to_string(),
         label: label.to_string(),
         utilization: utilization?,
+        resets_at_epoch_ms: reset_epoch_ms(resets_at.as_deref()),
         resets_at,
         window_duration_seconds: None,
     })
 }
 
-fn get_range_hours(range: &str) -> f64 {
+pub fn get_range_hours(range: &str) -> f64 {
     match range {
         "1h" => 1.0,
         "6h" => 6.0,
@@ -417,10 +2233,13 @@ fn get_range_hours(range: &str) -> f64 {
 }
 
 fn get_db_path<R: tauri::Runtime>(app: &tauri::AppHandle<R>) -> Option<PathBuf> {
-    app.path()
-        .app_data_dir()
-        .ok()
-        .map(|dir| dir.join("usage_history.db"))
+    app.path().app_data_dir().ok().map(|dir| {
+        dir.join(if crate::demo::is_enabled() {
+            crate::demo::DEMO_DB_FILE_NAME
+        } else {
+            DB_FILE_NAME
+        })
+    })
 }
 
 fn get_db() -> SqliteResult<std::sync::MutexGuard<'static, Connection>> {
@@ -432,6 +2251,28 @@ fn get_db() -> SqliteResult<std::sync::MutexGuard<'static, Connection>> {
 mod tests {
     use super::*;
 
+    fn window(key: &str, utilization: f64, resets_at: Option<&str>) -> crate::types::UsageWindow {
+        crate::types::UsageWindow {
+            key: key.to_string(),
+            label: key.to_string(),
+            utilization,
+            resets_at: resets_at.map(str::to_string),
+            resets_at_epoch_ms: None,
+            window_duration_seconds: None,
+        }
+    }
+
+    /// Stores each window as a snapshot of its own at the given timestamp.
+    fn insert_samples(
+        conn: &Connection,
+        provider: ProviderKind,
+        samples: &[(&str, crate::types::UsageWindow)],
+    ) {
+        for (timestamp, window) in samples {
+            insert_snapshot(conn, provider, timestamp, std::slice::from_ref(window)).unwrap();
+        }
+    }
+
     #[test]
     fn returns_expected_range_hours() {
         assert_eq!(get_range_hours("1h"), 1.0);
@@ -446,9 +2287,849 @@ mod tests {
         assert_eq!(get_downsample_bucket_minutes("30d"), Some(240));
     }
 
+    #[test]
+    fn returns_expected_token_buckets() {
+        assert_eq!(get_token_bucket_minutes("1h"), 5);
+        assert_eq!(get_token_bucket_minutes("24h"), 60);
+        assert_eq!(get_token_bucket_minutes("30d"), 1440);
+    }
+
+    #[test]
+    fn local_usage_inserts_are_idempotent() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(LOCAL_TOKEN_SCHEMA).unwrap();
+
+        let entry = LocalUsageEntry {
+            message_key: "msg_1:req_1".to_string(),
+            timestamp: chrono::Utc::now(),
+            project: "/repo".to_string(),
+            model: "claude-sonnet-4".to_string(),
+            tokens: TokenCounts {
+                input_tokens: 10,
+                ..TokenCounts::default()
+            },
+        };
+
+        assert_eq!(
+            insert_local_usage_entries(&conn, &[entry.clone()]).unwrap(),
+            1
+        );
+        assert_eq!(insert_local_usage_entries(&conn, &[entry]).unwrap(), 0);
+    }
+
+    #[test]
+    fn reads_latest_snapshot_per_provider() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_v2_schema(&conn).unwrap();
+
+        let window = |key: &str, utilization| crate::types::UsageWindow {
+            key: key.to_string(),
+            label: key.to_string(),
+            utilization,
+            resets_at: None,
+            resets_at_epoch_ms: None,
+            window_duration_seconds: None,
+        };
+        let snapshots = [
+            (ProviderKind::Claude, "2026-04-13T09:00:00+00:00", 10.0),
+            (ProviderKind::Claude, "2026-04-13T10:00:00+00:00", 20.0),
+            (ProviderKind::Codex, "2026-04-13T08:00:00+00:00", 30.0),
+        ];
+        for (provider, timestamp, utilization) in snapshots {
+            insert_snapshot(
+                &conn,
+                provider,
+                timestamp,
+                &[
+                    window("session", utilization),
+                    window("weekly", utilization),
+                ],
+            )
+            .unwrap();
+        }
+
+        let latest = latest_snapshots(&conn, None, None).unwrap();
+        assert_eq!(latest.len(), 2);
+        assert_eq!(latest[0].provider, "claude");
+        assert_eq!(latest[0].timestamp, "2026-04-13T10:00:00+00:00");
+        assert_eq!(latest[0].windows.len(), 2);
+        assert_eq!(latest[0].windows[0].utilization, 20.0);
+        assert_eq!(latest[1].provider, "codex");
+
+        let codex = latest_snapshots(&conn, Some("codex"), None).unwrap();
+        assert_eq!(codex.len(), 1);
+        assert_eq!(codex[0].windows[1].key, "weekly");
+    }
+
+    #[test]
+    fn reporting_views_roll_up_hours_and_find_resets() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_v2_schema(&conn).unwrap();
+        conn.execute_batch(REPORTING_VIEWS).unwrap();
+
+        let samples = [
+            (
+                "2026-04-13T10:05:00+00:00",
+                40.0,
+                "2026-04-13T12:00:00+00:00",
+            ),
+            (
+                "2026-04-13T10:35:00+00:00",
+                60.0,
+                "2026-04-13T12:00:10+00:00",
+            ),
+            (
+                "2026-04-13T11:55:00+00:00",
+                90.0,
+                "2026-04-13T12:00:00+00:00",
+            ),
+            (
+                "2026-04-13T12:05:00+00:00",
+                2.0,
+                "2026-04-13T17:00:00+00:00",
+            ),
+        ];
+        for (timestamp, utilization, resets_at) in samples {
+            insert_snapshot(
+                &conn,
+                ProviderKind::Claude,
+                timestamp,
+                &[crate::types::UsageWindow {
+                    key: "five_hour".to_string(),
+                    label: "5 Hour".to_string(),
+                    utilization,
+                    resets_at: Some(resets_at.to_string()),
+                    resets_at_epoch_ms: None,
+                    window_duration_seconds: None,
+                }],
+            )
+            .unwrap();
+        }
+
+        let (hour, avg, samples): (String, f64, i64) = conn
+            .query_row(
+                "SELECT hour, avg_utilization, samples FROM usage_hourly ORDER BY hour LIMIT 1",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!(hour, "2026-04-13T10:00:00Z");
+        assert_eq!(avg, 50.0);
+        assert_eq!(samples, 2);
+
+        let resets: Vec<(String, f64)> = conn
+            .prepare("SELECT reset_observed_at, utilization_before FROM usage_resets")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(
+            resets,
+            vec![("2026-04-13T12:05:00+00:00".to_string(), 90.0)]
+        );
+
+        let hours = reporting_view_rows(
+            &conn,
+            ReportingView::Hourly,
+            Some("claude"),
+            Some("2026-04-13T11:00:00+00:00"),
+            None,
+        )
+        .unwrap();
+        assert_eq!(hours.len(), 2);
+        assert_eq!(hours[0]["hour"], "2026-04-13T11:00:00Z");
+        assert_eq!(hours[0]["samples"], 1);
+        assert!(
+            reporting_view_rows(&conn, ReportingView::Resets, Some("codex"), None, None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn measures_time_until_periods_reach_the_threshold() {
+        let at = |timestamp: &str| {
+            chrono::DateTime::parse_from_rfc3339(timestamp)
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+        let first_reset = at("2026-04-13T12:00:00+00:00");
+        let second_reset = at("2026-04-13T17:00:00+00:00");
+        let samples = [
+            // Already past 80% when polling started: not measured
+            (at("2026-04-13T09:00:00+00:00"), 85.0, first_reset),
+            (at("2026-04-13T12:30:00+00:00"), 10.0, second_reset),
+            (at("2026-04-13T14:00:00+00:00"), 79.0, second_reset),
+            (at("2026-04-13T14:30:00+00:00"), 82.0, second_reset),
+            (at("2026-04-13T15:00:00+00:00"), 95.0, second_reset),
+            (
+                at("2026-04-13T17:30:00+00:00"),
+                5.0,
+                at("2026-04-13T22:00:00+00:00"),
+            ),
+        ];
+
+        let stats = time_to_threshold(&samples, 80.0);
+        assert_eq!(stats.period_count, 3);
+        assert_eq!(
+            stats.periods,
+            vec![ThresholdCrossing {
+                started_at: "2026-04-13T12:00:00+00:00".to_string(),
+                minutes: 150.0,
+            }]
+        );
+        assert_eq!(stats.average_minutes, Some(150.0));
+        assert_eq!(time_to_threshold(&samples, 99.0).average_minutes, None);
+    }
+
+    #[test]
+    fn groups_consumption_by_weekday_and_hour() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_v2_schema(&conn).unwrap();
+
+        // Monday 2026-04-13
+        insert_samples(
+            &conn,
+            ProviderKind::Claude,
+            &[
+                (
+                    "2026-04-13T10:05:00+00:00",
+                    window("five_hour", 40.0, Some("2026-04-13T12:00:00+00:00")),
+                ),
+                (
+                    "2026-04-13T10:35:00+00:00",
+                    window("five_hour", 60.0, Some("2026-04-13T12:00:10+00:00")),
+                ),
+                // Noise isn't negative consumption
+                (
+                    "2026-04-13T10:50:00+00:00",
+                    window("five_hour", 59.5, Some("2026-04-13T12:00:00+00:00")),
+                ),
+                (
+                    "2026-04-13T11:55:00+00:00",
+                    window("five_hour", 90.0, Some("2026-04-13T12:00:00+00:00")),
+                ),
+                // After a reset everything since it counts
+                (
+                    "2026-04-13T12:05:00+00:00",
+                    window("five_hour", 2.0, Some("2026-04-13T17:00:00+00:00")),
+                ),
+                (
+                    "2026-04-19T23:30:00+00:00",
+                    window("five_hour", 7.0, Some("2026-04-20T02:00:00+00:00")),
+                ),
+            ],
+        );
+
+        let histogram = usage_histogram(
+            &conn,
+            ProviderKind::Claude,
+            "",
+            "five_hour",
+            "2026-04-13T00:00:00+00:00",
+            "2026-04-20T00:00:00+00:00",
+            "+0 hours",
+        )
+        .unwrap();
+        let buckets = histogram
+            .buckets
+            .iter()
+            .map(|bucket| {
+                (
+                    bucket.weekday,
+                    bucket.hour,
+                    bucket.consumption,
+                    bucket.samples,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            buckets,
+            vec![
+                (0, 10, 20.0, 2),
+                (0, 11, 30.5, 1),
+                (0, 12, 2.0, 1),
+                (6, 23, 7.0, 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_chart_series_with_gaps() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_v2_schema(&conn).unwrap();
+
+        // Legacy and RFC 3339 timestamps mix in the same chart
+        insert_samples(
+            &conn,
+            ProviderKind::Claude,
+            &[
+                ("2026-04-13 10:00:00", window("five_hour", 10.0, None)),
+                ("2026-04-13T10:00:00+00:00", window("seven_day", 30.0, None)),
+                ("2026-04-13 11:00:00", window("five_hour", 20.0, None)),
+                ("2026-04-13 15:00:00", window("five_hour", 5.0, None)),
+            ],
+        );
+        let points = usage_history(
+            &conn,
+            ProviderKind::Claude,
+            "",
+            "2026-04-13",
+            "2026-04-14",
+            None,
+        )
+        .unwrap();
+        assert_eq!(points.len(), 4);
+
+        let series = chart_series(&points, chrono::Duration::minutes(90));
+        assert_eq!(series.len(), 2);
+        assert_eq!(series[0].key, "five_hour");
+        let hour = |h: i64| 1_776_074_400_000 + h * 3_600_000;
+        assert_eq!(
+            series[0].points,
+            vec![
+                ChartPoint {
+                    x: hour(0),
+                    y: Some(10.0)
+                },
+                ChartPoint {
+                    x: hour(1),
+                    y: Some(20.0)
+                },
+                ChartPoint {
+                    x: hour(3),
+                    y: None
+                },
+                ChartPoint {
+                    x: hour(5),
+                    y: Some(5.0)
+                },
+            ]
+        );
+        assert_eq!(series[1].points.len(), 1);
+    }
+
+    #[test]
+    fn prunes_each_table_by_its_retention() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_schema(&conn).unwrap();
+        let now = chrono::DateTime::parse_from_rfc3339("2026-04-13T10:00:00+00:00")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+        for days_ago in [10, 100, 400] {
+            let timestamp = (now - chrono::Duration::days(days_ago)).to_rfc3339();
+            conn.execute(
+                "INSERT INTO usage_history_v2 (provider, timestamp, window_key, label, utilization)
+                VALUES ('claude', ?1, 'five_hour', '5 Hour', 10.0)",
+                [&timestamp],
+            )
+            .unwrap();
+            conn.execute(
+                "INSERT INTO member_usage_history
+                (organization_id, timestamp, member_id, window_key, label, utilization)
+                VALUES ('org', ?1, 'member', 'five_hour', '5 Hour', 10.0)",
+                [&timestamp],
+            )
+            .unwrap();
+        }
+        let count = |table: &str| -> i64 {
+            conn.query_row(&format!("SELECT COUNT(*) FROM {table}"), [], |row| {
+                row.get(0)
+            })
+            .unwrap()
+        };
+
+        // Defaults: usage is kept, member usage for 180 days
+        assert_eq!(prune_tables(&conn, &BTreeMap::new(), None, now).unwrap(), 1);
+        assert_eq!(count("usage_history_v2"), 3);
+        assert_eq!(count("member_usage_history"), 2);
+
+        // The preference wins over config.toml; 0 keeps everything
+        let policy = BTreeMap::from([(HistoryTable::Usage, 30), (HistoryTable::MemberUsage, 0)]);
+        assert_eq!(prune_tables(&conn, &policy, Some(5), now).unwrap(), 2);
+        assert_eq!(count("usage_history_v2"), 1);
+        assert_eq!(count("member_usage_history"), 2);
+        assert_eq!(
+            retention_days(HistoryTable::LocalTokens, &policy, Some(5)),
+            Some(5)
+        );
+    }
+
+    #[test]
+    fn counts_five_hour_periods_that_capped() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_v2_schema(&conn).unwrap();
+
+        insert_samples(
+            &conn,
+            ProviderKind::Claude,
+            &[
+                (
+                    "2026-04-13T10:00:00+00:00",
+                    window("five_hour", 70.0, Some("2026-04-13T12:00:00+00:00")),
+                ),
+                (
+                    "2026-04-13T11:00:00+00:00",
+                    window("five_hour", 99.5, Some("2026-04-13T12:00:10+00:00")),
+                ),
+                (
+                    "2026-04-13T11:30:00+00:00",
+                    window("five_hour", 100.0, Some("2026-04-13T12:00:00+00:00")),
+                ),
+                (
+                    "2026-04-13T12:30:00+00:00",
+                    window("five_hour", 40.0, Some("2026-04-13T17:00:00+00:00")),
+                ),
+                (
+                    "2026-04-13T17:30:00+00:00",
+                    window("five_hour", 99.0, Some("2026-04-13T22:00:00+00:00")),
+                ),
+                // Other windows don't count
+                (
+                    "2026-04-13T17:30:00+00:00",
+                    window("seven_day", 100.0, Some("2026-04-19T00:00:00+00:00")),
+                ),
+            ],
+        );
+
+        let count = |from: &str| {
+            count_capped_periods(
+                &conn,
+                ProviderKind::Claude,
+                "",
+                from,
+                "2026-04-14T00:00:00+00:00",
+            )
+            .unwrap()
+        };
+        assert_eq!(count("2026-04-13T00:00:00+00:00"), 2);
+        assert_eq!(count("2026-04-13T12:00:00+00:00"), 1);
+        assert_eq!(
+            count_capped_periods(
+                &conn,
+                ProviderKind::Codex,
+                "",
+                "2026-04-13T00:00:00+00:00",
+                "2026-04-14T00:00:00+00:00"
+            )
+            .unwrap(),
+            0
+        );
+    }
+
+    #[test]
+    fn reports_db_info() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_v2_schema(&conn).unwrap();
+        conn.execute_batch(LOCAL_TOKEN_SCHEMA).unwrap();
+
+        let empty = db_info(&conn).unwrap();
+        assert_eq!(empty.usage_row_count, 0);
+        assert_eq!(empty.oldest_timestamp, None);
+
+        insert_samples(
+            &conn,
+            ProviderKind::Claude,
+            &[
+                ("2026-04-13T10:00:00+00:00", window("session", 10.0, None)),
+                ("2026-04-13T10:00:00+00:00", window("weekly", 10.0, None)),
+                ("2026-04-12T10:00:00+00:00", window("session", 10.0, None)),
+                ("2026-04-12T10:00:00+00:00", window("weekly", 10.0, None)),
+            ],
+        );
+
+        let info = db_info(&conn).unwrap();
+        assert_eq!(info.usage_row_count, 4);
+        assert_eq!(info.token_row_count, 0);
+        assert_eq!(
+            info.oldest_timestamp.as_deref(),
+            Some("2026-04-12T10:00:00+00:00")
+        );
+        assert_eq!(
+            info.newest_timestamp.as_deref(),
+            Some("2026-04-13T10:00:00+00:00")
+        );
+        assert_eq!(info.journal_mode, "memory");
+        assert!(info.file_size_bytes > 0);
+    }
+
+    #[test]
+    fn clears_a_single_metric_including_legacy_columns() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(LEGACY_SCHEMA).unwrap();
+        create_v2_schema(&conn).unwrap();
+        conn.execute_batch(LOCAL_TOKEN_SCHEMA).unwrap();
+        conn.execute(
+            "INSERT INTO usage_history (timestamp, five_hour_utilization, opus_utilization)
+            VALUES ('2026-04-13T10:00:00+00:00', 20.0, 5.0)",
+            [],
+        )
+        .unwrap();
+        backfill_legacy_claude_data(&conn).unwrap();
+
+        let scope = ClearScope::Metric {
+            provider: ProviderKind::Claude,
+            key: "seven_day_opus".to_string(),
+        };
+        assert_eq!(clear_scope(&conn, &scope).unwrap(), 1);
+
+        // Running the backfill again must not bring the metric back
+        backfill_legacy_claude_data(&conn).unwrap();
+        let keys: Vec<String> = conn
+            .prepare("SELECT window_key FROM usage_history_v2")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(keys, vec!["five_hour".to_string()]);
+
+        assert_eq!(clear_scope(&conn, &ClearScope::All).unwrap(), 1);
+        assert_eq!(db_info(&conn).unwrap().usage_row_count, 0);
+    }
+
     #[test]
     fn parses_provider_names() {
         assert_eq!(parse_provider("claude"), ProviderKind::Claude);
         assert_eq!(parse_provider("codex"), ProviderKind::Codex);
     }
+
+    #[test]
+    fn summarizes_member_usage_by_peak() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(MEMBER_USAGE_SCHEMA).unwrap();
+
+        let member = |id: &str, utilization| MemberUsage {
+            member_id: id.to_string(),
+            name: Some(id.to_uppercase()),
+            email: None,
+            windows: vec![window("five_hour", utilization, None)],
+        };
+        let samples = [
+            ("org", "2026-04-13T08:00:00+00:00", member("ada", 20.0)),
+            ("org", "2026-04-13T09:00:00+00:00", member("ada", 100.0)),
+            ("org", "2026-04-13T10:00:00+00:00", member("ada", 5.0)),
+            ("org", "2026-04-13T10:00:00+00:00", member("bob", 60.0)),
+            ("other", "2026-04-13T10:00:00+00:00", member("eve", 99.0)),
+        ];
+        for (org_id, timestamp, member) in samples {
+            insert_member_usage(&conn, org_id, timestamp, &[member]).unwrap();
+        }
+
+        let summary = member_usage_summary(&conn, "org", "2026-04-13T08:30:00+00:00").unwrap();
+        assert_eq!(summary.len(), 2);
+        assert_eq!(summary[0].member_id, "ada");
+        assert_eq!(summary[0].utilization, 5.0);
+        assert_eq!(summary[0].peak_utilization, 100.0);
+        assert_eq!(summary[1].member_id, "bob");
+        assert_eq!(summary[1].timestamp, "2026-04-13T10:00:00+00:00");
+    }
+
+    #[test]
+    fn keeps_additional_organizations_out_of_primary_history() {
+        let conn = Connection::open_in_memory().unwrap();
+        // A table from before the organization column existed
+        conn.execute_batch(
+            r#"CREATE TABLE usage_history_v2 (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                provider TEXT NOT NULL,
+                timestamp TEXT NOT NULL,
+                window_key TEXT NOT NULL,
+                label TEXT NOT NULL,
+                utilization REAL NOT NULL,
+                resets_at TEXT
+            );
+            CREATE UNIQUE INDEX idx_usage_history_v2_unique
+            ON usage_history_v2(provider, timestamp, window_key);"#,
+        )
+        .unwrap();
+        create_v2_schema(&conn).unwrap();
+        create_v2_schema(&conn).unwrap();
+
+        let timestamp = "2026-04-13T10:00:00+00:00";
+        insert_samples(
+            &conn,
+            ProviderKind::Claude,
+            &[(timestamp, window("five_hour", 20.0, None))],
+        );
+        insert_organization_snapshot(
+            &conn,
+            "other-org",
+            ProviderKind::Claude,
+            timestamp,
+            &[window("five_hour", 80.0, None)],
+            Some(FetchSource::Hourly),
+        )
+        .unwrap();
+
+        let provenance: Vec<(Option<String>, Option<String>)> = conn
+            .prepare("SELECT app_version, fetch_source FROM usage_history_v2 ORDER BY id")
+            .unwrap()
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .collect::<SqliteResult<_>>()
+            .unwrap();
+        assert_eq!(
+            provenance,
+            vec![
+                (None, None),
+                (
+                    Some(env!("CARGO_PKG_VERSION").to_string()),
+                    Some("hourly".to_string())
+                ),
+            ]
+        );
+
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM usage_history_v2", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(rows, 2);
+        // Rows from before organizations were tagged belong to the primary one
+        assert_eq!(adopt_untagged_rows(&conn, "primary-org").unwrap(), 1);
+        let latest = latest_snapshots(&conn, Some("claude"), Some("primary-org")).unwrap();
+        assert_eq!(latest.len(), 1);
+        assert_eq!(latest[0].windows.len(), 1);
+        assert_eq!(latest[0].windows[0].utilization, 20.0);
+        let other = latest_snapshots(&conn, Some("claude"), Some("other-org")).unwrap();
+        assert_eq!(other[0].windows[0].utilization, 80.0);
+        let points = usage_history(
+            &conn,
+            ProviderKind::Claude,
+            "primary-org",
+            timestamp,
+            timestamp,
+            None,
+        )
+        .unwrap();
+        assert_eq!(points.len(), 1);
+        assert_eq!(points[0].utilization, 20.0);
+    }
+
+    #[test]
+    fn computes_burn_rate_since_last_reset() {
+        let at = |minute: i64| {
+            chrono::DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+                + chrono::Duration::minutes(minute)
+        };
+
+        assert_eq!(burn_rate(&[(at(0), 10.0), (at(30), 14.0)]), Some(8.0));
+        // A reset at minute 20 restarts the measurement
+        assert_eq!(
+            burn_rate(&[(at(0), 80.0), (at(20), 1.0), (at(50), 3.0)]),
+            Some(4.0)
+        );
+        assert_eq!(burn_rate(&[(at(0), 10.0), (at(5), 12.0)]), None);
+        assert_eq!(burn_rate(&[]), None);
+    }
+
+    #[test]
+    fn projects_limit_time_unless_reset_comes_first() {
+        let now = chrono::DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        assert_eq!(
+            projected_limit_time(60.0, 20.0, Some("2026-04-13T13:00:00Z"), now),
+            Some(now + chrono::Duration::hours(2))
+        );
+        assert_eq!(
+            projected_limit_time(60.0, 20.0, Some("2026-04-13T11:00:00Z"), now),
+            None
+        );
+        assert_eq!(
+            projected_limit_time(60.0, 20.0, None, now),
+            Some(now + chrono::Duration::hours(2))
+        );
+        assert_eq!(projected_limit_time(60.0, 0.0, None, now), None);
+        assert_eq!(projected_limit_time(60.0, -3.0, None, now), None);
+    }
+
+    #[test]
+    fn rates_forecast_confidence_by_fit_and_samples() {
+        let at = |minute: i64| {
+            chrono::DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+                + chrono::Duration::minutes(minute)
+        };
+        let steady = (0..8)
+            .map(|i| (at(i * 5), 10.0 + i as f64))
+            .collect::<Vec<_>>();
+        assert!(r_squared(&steady).unwrap() > 0.999);
+        assert_eq!(
+            forecast_confidence(8, r_squared(&steady)),
+            ForecastConfidence::High
+        );
+        assert_eq!(
+            forecast_confidence(4, r_squared(&steady)),
+            ForecastConfidence::Medium
+        );
+
+        let erratic = [
+            (at(0), 10.0),
+            (at(10), 30.0),
+            (at(20), 11.0),
+            (at(30), 29.0),
+        ];
+        assert!(r_squared(&erratic).unwrap() < FORECAST_MEDIUM_R_SQUARED);
+        assert_eq!(
+            forecast_confidence(4, r_squared(&erratic)),
+            ForecastConfidence::Low
+        );
+
+        assert_eq!(r_squared(&steady[..2]), None);
+        assert_eq!(forecast_confidence(2, None), ForecastConfidence::Low);
+        assert_eq!(
+            since_last_reset(&[(at(0), 80.0), (at(20), 1.0), (at(50), 3.0)]).len(),
+            2
+        );
+    }
+
+    #[test]
+    fn filters_history_by_metric() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_v2_schema(&conn).unwrap();
+
+        let timestamp = "2026-04-13T10:00:00+00:00";
+        insert_samples(
+            &conn,
+            ProviderKind::Claude,
+            &[
+                (timestamp, window("five_hour", 10.0, None)),
+                (timestamp, window("seven_day", 10.0, None)),
+                (timestamp, window("seven_day_opus", 10.0, None)),
+            ],
+        );
+
+        let keys = |metrics: &[&str]| {
+            let metrics: Vec<String> = metrics.iter().map(|m| m.to_string()).collect();
+            usage_history(
+                &conn,
+                ProviderKind::Claude,
+                "",
+                "2026-04-13T00:00:00+00:00",
+                "2026-04-14T00:00:00+00:00",
+                metric_filter(&metrics).as_deref(),
+            )
+            .unwrap()
+            .into_iter()
+            .map(|point| point.window_key)
+            .collect::<Vec<_>>()
+        };
+
+        assert_eq!(keys(&["five_hour"]), vec!["five_hour"]);
+        assert_eq!(
+            keys(&["seven_day_opus", "five_hour"]),
+            vec!["five_hour", "seven_day_opus"]
+        );
+        assert_eq!(keys(&[]).len(), 3);
+    }
+
+    #[test]
+    fn replaces_corrupt_database_with_a_backup() {
+        let dir = std::env::temp_dir().join(format!("claude-monitor-db-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let db_path = dir.join(DB_FILE_NAME);
+        std::fs::write(&db_path, vec![0x42; 8192]).unwrap();
+
+        let (conn, backup) = open_checked(&db_path).unwrap();
+        let backup = backup.unwrap();
+        assert_eq!(std::fs::read(&backup).unwrap(), vec![0x42; 8192]);
+        assert!(!is_corrupt(&conn).unwrap());
+        drop(conn);
+
+        // A healthy database is left alone
+        let (_conn, backup) = open_checked(&db_path).unwrap();
+        assert!(backup.is_none());
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn spaces_samples_by_interval() {
+        let at = |time: &str| {
+            chrono::DateTime::parse_from_rfc3339(time)
+                .unwrap()
+                .with_timezone(&chrono::Utc)
+        };
+        let last = at("2026-04-13T10:00:05Z");
+
+        assert!(!interval_elapsed(last, at("2026-04-13T10:05:00Z"), 10));
+        // Fetch jitter of a few seconds still counts as due
+        assert!(interval_elapsed(last, at("2026-04-13T10:10:01Z"), 10));
+        assert!(interval_elapsed(last, at("2026-04-13T10:20:00Z"), 10));
+    }
+
+    #[test]
+    fn compares_utilization_only() {
+        let previous = [
+            window("five_hour", 10.0, Some("2026-04-13T12:00:00Z")),
+            window("seven_day", 40.0, Some("2026-04-18T12:00:00Z")),
+        ];
+
+        assert!(same_utilization(
+            &previous,
+            &[
+                window("seven_day", 40.0, Some("2026-04-18T12:00:01Z")),
+                window("five_hour", 10.0, Some("2026-04-13T12:00:01Z")),
+            ]
+        ));
+        assert!(!same_utilization(
+            &previous,
+            &[
+                window("five_hour", 11.0, Some("2026-04-13T12:00:00Z")),
+                window("seven_day", 40.0, Some("2026-04-18T12:00:00Z")),
+            ]
+        ));
+        assert!(!same_utilization(
+            &previous,
+            &[window("five_hour", 10.0, Some("2026-04-13T12:00:00Z"))]
+        ));
+    }
+
+    #[test]
+    fn ignores_synced_records_already_stored() {
+        let conn = Connection::open_in_memory().unwrap();
+        create_v2_schema(&conn).unwrap();
+
+        let record = |timestamp: &str| SyncRecord {
+            provider: ProviderKind::Claude,
+            timestamp: timestamp.to_string(),
+            window_key: "five_hour".to_string(),
+            label: "5 Hour".to_string(),
+            utilization: 30.0,
+            resets_at: None,
+            organization_id: String::new(),
+        };
+        let records = [
+            record("2026-04-13T10:00:00+00:00"),
+            record("2026-04-13T10:05:00+00:00"),
+        ];
+
+        assert_eq!(insert_synced_records(&conn, &records, "org").unwrap(), 2);
+        assert_eq!(insert_synced_records(&conn, &records, "org").unwrap(), 0);
+        let other_org = SyncRecord {
+            organization_id: "other-org".to_string(),
+            ..record("2026-04-13T10:00:00+00:00")
+        };
+        assert_eq!(
+            insert_synced_records(&conn, &[other_org], "org").unwrap(),
+            1
+        );
+        let history = usage_history(
+            &conn,
+            ProviderKind::Claude,
+            "org",
+            "2026-04-13T00:00:00+00:00",
+            "2026-04-14T00:00:00+00:00",
+            None,
+        )
+        .unwrap();
+        assert_eq!(history.len(), 2);
+    }
 }
@@ -0,0 +1,228 @@
+//! History Sync Folder
+//!
+//! With a sync folder set (Dropbox, iCloud Drive, Syncthing, ...), each machine
+//! appends the primary history points it stores to its own append-only NDJSON
+//! log there, `claude-monitor-<machine id>.ndjson`, and imports whatever other
+//! machines appended since it last looked. Every machine writes only its own
+//! file, so the sync tool never has to merge one, and the import remembers how
+//! far into each log it got.
+
+use crate::error::AppError;
+use crate::history::{UsageHistoryPoint, import_synced_records, sync_log_position};
+use crate::types::ProviderKind;
+use rand::RngExt;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::Path;
+
+const LOG_PREFIX: &str = "claude-monitor-";
+const LOG_EXTENSION: &str = ".ndjson";
+
+/// One stored history point, as a line of a sync log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncRecord {
+    pub provider: ProviderKind,
+    pub timestamp: String,
+    pub window_key: String,
+    pub label: String,
+    pub utilization: f64,
+    pub resets_at: Option<String>,
+    /// The Claude organization the point belongs to; empty for other
+    /// providers and in lines from versions that didn't write it.
+    #[serde(default)]
+    pub organization_id: String,
+}
+
+impl SyncRecord {
+    fn new(point: &UsageHistoryPoint, organization_id: &str) -> Self {
+        Self {
+            provider: point.provider,
+            timestamp: point.timestamp.clone(),
+            window_key: point.window_key.clone(),
+            label: point.label.clone(),
+            utilization: point.utilization,
+            resets_at: point.resets_at.clone(),
+            organization_id: organization_id.to_string(),
+        }
+    }
+}
+
+/// A random id naming this machine's log, generated once and kept in settings.
+pub fn new_machine_id() -> String {
+    format!("{:016x}", rand::rng().random::<u64>())
+}
+
+fn log_file_name(machine_id: &str) -> String {
+    format!("{LOG_PREFIX}{machine_id}{LOG_EXTENSION}")
+}
+
+/// Append newly stored points of `organization_id` to this machine's log, then
+/// import the other machines' logs. Returns how many points were imported.
+pub fn sync(
+    folder: &Path,
+    machine_id: &str,
+    organization_id: &str,
+    points: &[UsageHistoryPoint],
+) -> Result<usize, AppError> {
+    append_points(folder, machine_id, organization_id, points)?;
+    import_logs(folder, machine_id)
+}
+
+fn append_points(
+    folder: &Path,
+    machine_id: &str,
+    organization_id: &str,
+    points: &[UsageHistoryPoint],
+) -> Result<(), AppError> {
+    if points.is_empty() {
+        return Ok(());
+    }
+
+    let mut lines = String::new();
+    for point in points {
+        let line = serde_json::to_string(&SyncRecord::new(point, organization_id))
+            .map_err(|e| AppError::Storage(format!("Failed to encode sync record: {e}")))?;
+        lines.push_str(&line);
+        lines.push('\n');
+    }
+
+    // One write per snapshot, so a reader never sees half a snapshot's lines
+    // unless the sync tool copies the file mid-write
+    let path = folder.join(log_file_name(machine_id));
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(lines.as_bytes()))
+        .map_err(|e| AppError::Storage(format!("Failed to write {}: {e}", path.display())))
+}
+
+/// Import the lines other machines appended since the last import. Returns
+/// how many new points were stored.
+pub fn import_logs(folder: &Path, machine_id: &str) -> Result<usize, AppError> {
+    if crate::history::is_incognito() {
+        return Ok(0);
+    }
+
+    let own_log = log_file_name(machine_id);
+    let entries = std::fs::read_dir(folder)
+        .map_err(|e| AppError::Storage(format!("Failed to read {}: {e}", folder.display())))?;
+
+    let mut imported = 0;
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        if file_name == own_log
+            || !file_name.starts_with(LOG_PREFIX)
+            || !file_name.ends_with(LOG_EXTENSION)
+        {
+            continue;
+        }
+
+        let position = sync_log_position(&file_name).map_err(storage_error)?;
+        let (start, contents) = match read_from(&entry.path(), position) {
+            Ok(read) => read,
+            Err(e) => {
+                log::warn!("Failed to read sync log {file_name}: {e}");
+                continue;
+            }
+        };
+        let complete = complete_lines(&contents);
+        if complete.is_empty() {
+            continue;
+        }
+
+        let records = parse_records(&String::from_utf8_lossy(complete));
+        imported += import_synced_records(&file_name, &records, start + complete.len())
+            .map_err(storage_error)?;
+    }
+    Ok(imported)
+}
+
+/// The bytes of a log from `position` on, and where they start. A log that
+/// shrank was replaced and is read from the start; duplicates are ignored.
+fn read_from(path: &Path, position: usize) -> std::io::Result<(usize, Vec<u8>)> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    let start = if position as u64 <= len { position } else { 0 };
+    file.seek(SeekFrom::Start(start as u64))?;
+    let mut contents = Vec::new();
+    file.read_to_end(&mut contents)?;
+    Ok((start, contents))
+}
+
+/// The bytes up to and including the last newline; a trailing partial line is
+/// still being written or synced and is read next time.
+fn complete_lines(bytes: &[u8]) -> &[u8] {
+    match bytes.iter().rposition(|byte| *byte == b'\n') {
+        Some(end) => &bytes[..=end],
+        None => &[],
+    }
+}
+
+/// Parse log lines, skipping ones that don't decode (e.g. from a newer version).
+fn parse_records(lines: &str) -> Vec<SyncRecord> {
+    lines
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| {
+            serde_json::from_str(line)
+                .inspect_err(|e| log::debug!("Skipping unreadable sync log line: {e}"))
+                .ok()
+        })
+        .collect()
+}
+
+fn storage_error(e: rusqlite::Error) -> AppError {
+    AppError::Storage(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_only_complete_lines() {
+        assert_eq!(complete_lines(b"{\"a\":1}\n{\"b\""), b"{\"a\":1}\n");
+        assert_eq!(complete_lines(b"{\"b\""), b"");
+        assert_eq!(complete_lines(b""), b"");
+    }
+
+    #[test]
+    fn reads_only_what_was_appended_since_the_last_import() {
+        let path =
+            std::env::temp_dir().join(format!("{LOG_PREFIX}{}{LOG_EXTENSION}", new_machine_id()));
+        std::fs::write(&path, b"first\nsecond\n").unwrap();
+
+        assert_eq!(read_from(&path, 6).unwrap(), (6, b"second\n".to_vec()));
+        // Replaced by a shorter log
+        assert_eq!(
+            read_from(&path, 100).unwrap(),
+            (0, b"first\nsecond\n".to_vec())
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn parses_records_and_skips_bad_lines() {
+        let lines = concat!(
+            r#"{"provider":"claude","timestamp":"2026-04-13T10:00:00+00:00","windowKey":"five_hour","label":"5 Hour","utilization":42.0,"resetsAt":null}"#,
+            "\n",
+            "not json\n",
+            "\n",
+            r#"{"provider":"codex","timestamp":"2026-04-13T10:05:00+00:00","windowKey":"primary","label":"Primary","utilization":7.5,"resetsAt":"2026-04-13T12:00:00+00:00"}"#,
+            "\n",
+        );
+
+        let records = parse_records(lines);
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].provider, ProviderKind::Claude);
+        assert_eq!(records[0].utilization, 42.0);
+        assert_eq!(records[0].organization_id, "");
+        assert_eq!(records[1].window_key, "primary");
+        assert_eq!(
+            records[1].resets_at.as_deref(),
+            Some("2026-04-13T12:00:00+00:00")
+        );
+    }
+}
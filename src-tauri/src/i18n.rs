@@ -0,0 +1,610 @@
+//! Localization of backend-rendered strings (notifications and tray).
+//!
+//! Strings live in embedded tables keyed by `Text`. The active locale is chosen
+//! from `Preferences.language`, falling back to the OS locale and then English.
+
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// Language preference as stored in settings.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    /// Follow the OS locale.
+    #[default]
+    System,
+    En,
+    De,
+    Es,
+    Fr,
+    Ja,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(u8)]
+enum Locale {
+    En,
+    De,
+    Es,
+    Fr,
+    Ja,
+}
+
+impl Locale {
+    const ALL: [Locale; 5] = [Locale::En, Locale::De, Locale::Es, Locale::Fr, Locale::Ja];
+
+    fn from_tag(tag: &str) -> Option<Self> {
+        let primary = tag.split(['-', '_', '.']).next()?.to_ascii_lowercase();
+        match primary.as_str() {
+            "en" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "es" => Some(Locale::Es),
+            "fr" => Some(Locale::Fr),
+            "ja" => Some(Locale::Ja),
+            _ => None,
+        }
+    }
+}
+
+/// Translatable strings. `{0}`, `{1}` are positional placeholders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    UsageAlertTitle,
+    Reached,
+    CrossedThreshold,
+    ResetsWithin,
+    ResetsIn,
+    StillAbove,
+    BackBelow,
+    ListSeparator,
+    PercentUsed,
+    ResetsAt,
+    TodayCost,
+    BurnRate,
+    UpdatedAgo,
+    AccessDeniedTitle,
+    AccessDeniedBody,
+    TrayUnavailableTitle,
+    TrayUnavailableBody,
+    HistoryRecoveredTitle,
+    HistoryRecoveredBody,
+    KeychainIssueTitle,
+    KeychainUnavailableBody,
+    KeychainLockedBody,
+    KeychainAccessDeniedBody,
+    KeychainUnreadableBody,
+    KeychainFailedBody,
+    TokenExpiringTitle,
+    TokenExpiringBody,
+    ModelAdvisoryTitle,
+    ModelAdvisoryBody,
+    WeeklySummaryTitle,
+    WeeklySummaryPeaks,
+    WeeklySummaryCapped,
+    BudgetTitle,
+    BudgetAheadBody,
+    BudgetExceededBody,
+    StaleDataTitle,
+    StaleDataActiveBody,
+    StaleDataResetBody,
+    MemberUsageUnsupportedTitle,
+    MemberUsageUnsupportedBody,
+    CompactMode,
+    Open,
+    CheckForUpdates,
+    SwitchOrganization,
+    Quit,
+}
+
+static LOCALE: AtomicU8 = AtomicU8::new(Locale::En as u8);
+
+/// Apply a language preference. Called at startup and when preferences change.
+pub fn set_language(language: Language) {
+    LOCALE.store(
+        resolve(language, sys_locale::get_locale().as_deref()) as u8,
+        Ordering::Relaxed,
+    );
+}
+
+fn resolve(language: Language, system_locale: Option<&str>) -> Locale {
+    match language {
+        Language::System => system_locale
+            .and_then(Locale::from_tag)
+            .unwrap_or(Locale::En),
+        Language::En => Locale::En,
+        Language::De => Locale::De,
+        Language::Es => Locale::Es,
+        Language::Fr => Locale::Fr,
+        Language::Ja => Locale::Ja,
+    }
+}
+
+fn current_locale() -> Locale {
+    let value = LOCALE.load(Ordering::Relaxed);
+    Locale::ALL
+        .into_iter()
+        .find(|locale| *locale as u8 == value)
+        .unwrap_or(Locale::En)
+}
+
+/// Translate a string without placeholders.
+pub fn t(text: Text) -> &'static str {
+    lookup(current_locale(), text)
+}
+
+/// Translate a string and substitute `{0}`, `{1}`, ... with `args`.
+pub fn tf(text: Text, args: &[&str]) -> String {
+    format_template(lookup(current_locale(), text), args)
+}
+
+/// Abbreviated weekday name, for reset times more than a day away.
+pub fn weekday_short(weekday: chrono::Weekday) -> &'static str {
+    weekday_names(current_locale())[weekday.num_days_from_monday() as usize]
+}
+
+/// Abbreviated weekday names, Monday first.
+fn weekday_names(locale: Locale) -> [&'static str; 7] {
+    match locale {
+        Locale::En => ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+        Locale::De => ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+        Locale::Es => ["lun", "mar", "mié", "jue", "vie", "sáb", "dom"],
+        Locale::Fr => ["lun.", "mar.", "mer.", "jeu.", "ven.", "sam.", "dim."],
+        Locale::Ja => ["月", "火", "水", "木", "金", "土", "日"],
+    }
+}
+
+fn format_template(template: &str, args: &[&str]) -> String {
+    args.iter()
+        .enumerate()
+        .fold(template.to_string(), |result, (index, arg)| {
+            result.replace(&format!("{{{index}}}"), arg)
+        })
+}
+
+fn lookup(locale: Locale, text: Text) -> &'static str {
+    use Locale::*;
+    use Text::*;
+
+    match (text, locale) {
+        (UsageAlertTitle, En) => "{0} Usage Alert",
+        (UsageAlertTitle, De) => "{0}: Nutzungswarnung",
+        (UsageAlertTitle, Es) => "Alerta de uso: {0}",
+        (UsageAlertTitle, Fr) => "Alerte d'utilisation : {0}",
+        (UsageAlertTitle, Ja) => "{0} 使用量アラート",
+
+        (Reached, En) => "reached {0}%",
+        (Reached, De) => "{0}% erreicht",
+        (Reached, Es) => "alcanzó el {0}%",
+        (Reached, Fr) => "a atteint {0} %",
+        (Reached, Ja) => "{0}%に到達",
+
+        (CrossedThreshold, En) => "crossed {0}% threshold",
+        (CrossedThreshold, De) => "Schwelle von {0}% überschritten",
+        (CrossedThreshold, Es) => "superó el umbral del {0}%",
+        (CrossedThreshold, Fr) => "a dépassé le seuil de {0} %",
+        (CrossedThreshold, Ja) => "しきい値{0}%を超過",
+
+        (ResetsWithin, En) => "resets in < {0}",
+        (ResetsWithin, De) => "Reset in < {0}",
+        (ResetsWithin, Es) => "se reinicia en < {0}",
+        (ResetsWithin, Fr) => "réinitialisation dans < {0}",
+        (ResetsWithin, Ja) => "リセットまで{0}未満",
+
+        (ResetsIn, En) => "resets in {0}",
+        (ResetsIn, De) => "Reset in {0}",
+        (ResetsIn, Es) => "se reinicia en {0}",
+        (ResetsIn, Fr) => "réinitialisation dans {0}",
+        (ResetsIn, Ja) => "リセットまで{0}",
+
+        (StillAbove, En) => "still above {0}%",
+        (StillAbove, De) => "weiterhin über {0}%",
+        (StillAbove, Es) => "sigue por encima del {0}%",
+        (StillAbove, Fr) => "toujours au-dessus de {0} %",
+        (StillAbove, Ja) => "{0}%を超えたまま",
+
+        (BackBelow, En) => "back below {0}%",
+        (BackBelow, De) => "wieder unter {0}%",
+        (BackBelow, Es) => "de nuevo por debajo del {0}%",
+        (BackBelow, Fr) => "de nouveau sous {0} %",
+        (BackBelow, Ja) => "{0}%未満に回復",
+
+        (ListSeparator, En) => " and ",
+        (ListSeparator, De) => " und ",
+        (ListSeparator, Es) => " y ",
+        (ListSeparator, Fr) => " et ",
+        (ListSeparator, Ja) => "、",
+
+        (PercentUsed, En) => "({0}% used)",
+        (PercentUsed, De) => "({0}% genutzt)",
+        (PercentUsed, Es) => "({0}% usado)",
+        (PercentUsed, Fr) => "({0} % utilisé)",
+        (PercentUsed, Ja) => "(使用率{0}%)",
+
+        (ResetsAt, En) => "resets at {0}, in {1}",
+        (ResetsAt, De) => "Reset um {0}, in {1}",
+        (ResetsAt, Es) => "se reinicia a las {0}, en {1}",
+        (ResetsAt, Fr) => "réinitialisation à {0}, dans {1}",
+        (ResetsAt, Ja) => "{0}にリセット（あと{1}）",
+
+        (TodayCost, En) => "Today: ${0} (est.)",
+        (TodayCost, De) => "Heute: ${0} (geschätzt)",
+        (TodayCost, Es) => "Hoy: ${0} (est.)",
+        (TodayCost, Fr) => "Aujourd'hui : {0} $ (est.)",
+        (TodayCost, Ja) => "本日: ${0}（推定）",
+
+        (BurnRate, En) => "{0}: {1}%/h over the last hour",
+        (BurnRate, De) => "{0}: {1}%/h in der letzten Stunde",
+        (BurnRate, Es) => "{0}: {1}%/h en la última hora",
+        (BurnRate, Fr) => "{0} : {1} %/h sur la dernière heure",
+        (BurnRate, Ja) => "{0}: 直近1時間で{1}%/h",
+
+        (UpdatedAgo, En) => "(updated {0} ago)",
+        (UpdatedAgo, De) => "(aktualisiert vor {0})",
+        (UpdatedAgo, Es) => "(actualizado hace {0})",
+        (UpdatedAgo, Fr) => "(mis à jour il y a {0})",
+        (UpdatedAgo, Ja) => "（{0}前に更新）",
+
+        (AccessDeniedTitle, En) => "{0}: Access Denied",
+        (AccessDeniedTitle, De) => "{0}: Zugriff verweigert",
+        (AccessDeniedTitle, Es) => "{0}: acceso denegado",
+        (AccessDeniedTitle, Fr) => "{0} : accès refusé",
+        (AccessDeniedTitle, Ja) => "{0}: アクセス拒否",
+
+        (AccessDeniedBody, En) => {
+            "Check your organization ID and that your plan includes usage data."
+        }
+        (AccessDeniedBody, De) => {
+            "Prüfe deine Organisations-ID und ob dein Tarif Nutzungsdaten enthält."
+        }
+        (AccessDeniedBody, Es) => {
+            "Comprueba el ID de tu organización y que tu plan incluya datos de uso."
+        }
+        (AccessDeniedBody, Fr) => {
+            "Vérifiez l'ID de votre organisation et que votre forfait inclut les données d'utilisation."
+        }
+        (AccessDeniedBody, Ja) => {
+            "組織IDと、プランに使用量データが含まれているか確認してください。"
+        }
+
+        (TrayUnavailableTitle, En) => "Tray Icon Unavailable",
+        (TrayUnavailableTitle, De) => "Tray-Symbol nicht verfügbar",
+        (TrayUnavailableTitle, Es) => "Icono de la bandeja no disponible",
+        (TrayUnavailableTitle, Fr) => "Icône de la zone de notification indisponible",
+        (TrayUnavailableTitle, Ja) => "トレイアイコンを表示できません",
+
+        (TrayUnavailableBody, En) => {
+            "This desktop can't show tray icons, so the window stays open. Launch the app again to bring it back."
+        }
+        (TrayUnavailableBody, De) => {
+            "Diese Arbeitsumgebung zeigt keine Tray-Symbole an, daher bleibt das Fenster offen. Starte die App erneut, um es zurückzuholen."
+        }
+        (TrayUnavailableBody, Es) => {
+            "Este escritorio no muestra iconos en la bandeja, así que la ventana permanece abierta. Vuelve a abrir la app para recuperarla."
+        }
+        (TrayUnavailableBody, Fr) => {
+            "Ce bureau n'affiche pas les icônes de la zone de notification, la fenêtre reste donc ouverte. Relancez l'application pour la retrouver."
+        }
+        (TrayUnavailableBody, Ja) => {
+            "このデスクトップではトレイアイコンを表示できないため、ウィンドウを開いたままにします。アプリを再度起動すると表示されます。"
+        }
+
+        (HistoryRecoveredTitle, En) => "Usage History Reset",
+        (HistoryRecoveredTitle, De) => "Nutzungsverlauf zurückgesetzt",
+        (HistoryRecoveredTitle, Es) => "Historial de uso restablecido",
+        (HistoryRecoveredTitle, Fr) => "Historique d'utilisation réinitialisé",
+        (HistoryRecoveredTitle, Ja) => "使用履歴をリセットしました",
+
+        (HistoryRecoveredBody, En) => {
+            "The history database was damaged, so a new one was started. The old file was kept as {0}."
+        }
+        (HistoryRecoveredBody, De) => {
+            "Die Verlaufsdatenbank war beschädigt, daher wurde eine neue angelegt. Die alte Datei wurde als {0} aufbewahrt."
+        }
+        (HistoryRecoveredBody, Es) => {
+            "La base de datos del historial estaba dañada, así que se creó una nueva. El archivo anterior se guardó como {0}."
+        }
+        (HistoryRecoveredBody, Fr) => {
+            "La base de l'historique était endommagée, une nouvelle a donc été créée. L'ancien fichier a été conservé sous {0}."
+        }
+        (HistoryRecoveredBody, Ja) => {
+            "履歴データベースが破損していたため、新しく作成しました。古いファイルは{0}として保存されています。"
+        }
+
+        (KeychainIssueTitle, En) => "Saved Credentials Not Loaded",
+        (KeychainIssueTitle, De) => "Gespeicherte Zugangsdaten nicht geladen",
+        (KeychainIssueTitle, Es) => "No se cargaron las credenciales guardadas",
+        (KeychainIssueTitle, Fr) => "Identifiants enregistrés non chargés",
+        (KeychainIssueTitle, Ja) => "保存された認証情報を読み込めません",
+
+        (KeychainUnavailableBody, En) => {
+            "No Secret Service is running. Install and start gnome-keyring or KWallet, then restart the app. Until then, credentials you enter are kept only until you quit."
+        }
+        (KeychainUnavailableBody, De) => {
+            "Es läuft kein Secret Service. Installiere und starte gnome-keyring oder KWallet und starte die App neu. Bis dahin bleiben eingegebene Zugangsdaten nur bis zum Beenden erhalten."
+        }
+        (KeychainUnavailableBody, Es) => {
+            "No hay ningún Secret Service en ejecución. Instala e inicia gnome-keyring o KWallet y reinicia la app. Mientras tanto, las credenciales que introduzcas solo se conservan hasta que salgas."
+        }
+        (KeychainUnavailableBody, Fr) => {
+            "Aucun Secret Service n'est lancé. Installez et démarrez gnome-keyring ou KWallet, puis relancez l'application. D'ici là, les identifiants saisis ne sont conservés que jusqu'à la fermeture."
+        }
+        (KeychainUnavailableBody, Ja) => {
+            "Secret Serviceが動作していません。gnome-keyringまたはKWalletをインストールして起動し、アプリを再起動してください。それまでは、入力した認証情報は終了するまでしか保持されません。"
+        }
+
+        (KeychainLockedBody, En) => {
+            "The keyring is locked. Unlock your login keyring (for example in Passwords and Keys), then restart the app. Until then, credentials you enter are kept only until you quit."
+        }
+        (KeychainLockedBody, De) => {
+            "Der Schlüsselbund ist gesperrt. Entsperre deinen Anmelde-Schlüsselbund (zum Beispiel in „Passwörter und Verschlüsselung“) und starte die App neu. Bis dahin bleiben eingegebene Zugangsdaten nur bis zum Beenden erhalten."
+        }
+        (KeychainLockedBody, Es) => {
+            "El llavero está bloqueado. Desbloquea tu llavero de inicio de sesión (por ejemplo en Contraseñas y claves) y reinicia la app. Mientras tanto, las credenciales que introduzcas solo se conservan hasta que salgas."
+        }
+        (KeychainLockedBody, Fr) => {
+            "Le trousseau est verrouillé. Déverrouillez votre trousseau de connexion (par exemple dans Mots de passe et clés), puis relancez l'application. D'ici là, les identifiants saisis ne sont conservés que jusqu'à la fermeture."
+        }
+        (KeychainLockedBody, Ja) => {
+            "キーリングがロックされています。ログインキーリングのロックを解除して（「パスワードと鍵」など）、アプリを再起動してください。それまでは、入力した認証情報は終了するまでしか保持されません。"
+        }
+
+        (KeychainAccessDeniedBody, En) => {
+            "Access to the keychain was denied. Allow Claude Monitor in your keychain settings, then restart the app. Until then, credentials you enter are kept only until you quit."
+        }
+        (KeychainAccessDeniedBody, De) => {
+            "Der Zugriff auf den Schlüsselbund wurde verweigert. Erlaube Claude Monitor in den Schlüsselbund-Einstellungen und starte die App neu. Bis dahin bleiben eingegebene Zugangsdaten nur bis zum Beenden erhalten."
+        }
+        (KeychainAccessDeniedBody, Es) => {
+            "Se denegó el acceso al llavero. Permite Claude Monitor en la configuración del llavero y reinicia la app. Mientras tanto, las credenciales que introduzcas solo se conservan hasta que salgas."
+        }
+        (KeychainAccessDeniedBody, Fr) => {
+            "L'accès au trousseau a été refusé. Autorisez Claude Monitor dans les réglages du trousseau, puis relancez l'application. D'ici là, les identifiants saisis ne sont conservés que jusqu'à la fermeture."
+        }
+        (KeychainAccessDeniedBody, Ja) => {
+            "キーチェーンへのアクセスが拒否されました。キーチェーンの設定でClaude Monitorを許可し、アプリを再起動してください。それまでは、入力した認証情報は終了するまでしか保持されません。"
+        }
+
+        (KeychainUnreadableBody, En) => {
+            "The saved credentials are damaged. Enter them again to replace them."
+        }
+        (KeychainUnreadableBody, De) => {
+            "Die gespeicherten Zugangsdaten sind beschädigt. Gib sie erneut ein, um sie zu ersetzen."
+        }
+        (KeychainUnreadableBody, Es) => {
+            "Las credenciales guardadas están dañadas. Vuelve a introducirlas para reemplazarlas."
+        }
+        (KeychainUnreadableBody, Fr) => {
+            "Les identifiants enregistrés sont endommagés. Saisissez-les à nouveau pour les remplacer."
+        }
+        (KeychainUnreadableBody, Ja) => {
+            "保存された認証情報が破損しています。もう一度入力して置き換えてください。"
+        }
+
+        (KeychainFailedBody, En) => {
+            "The keychain reported an error: {0}. Credentials you enter are kept only until you quit."
+        }
+        (KeychainFailedBody, De) => {
+            "Der Schlüsselbund meldete einen Fehler: {0}. Eingegebene Zugangsdaten bleiben nur bis zum Beenden erhalten."
+        }
+        (KeychainFailedBody, Es) => {
+            "El llavero informó de un error: {0}. Las credenciales que introduzcas solo se conservan hasta que salgas."
+        }
+        (KeychainFailedBody, Fr) => {
+            "Le trousseau a signalé une erreur : {0}. Les identifiants saisis ne sont conservés que jusqu'à la fermeture."
+        }
+        (KeychainFailedBody, Ja) => {
+            "キーチェーンでエラーが発生しました: {0}。入力した認証情報は終了するまでしか保持されません。"
+        }
+
+        (TokenExpiringTitle, En) => "Claude Session Expiring",
+        (TokenExpiringTitle, De) => "Claude-Sitzung läuft ab",
+        (TokenExpiringTitle, Es) => "La sesión de Claude está por caducar",
+        (TokenExpiringTitle, Fr) => "La session Claude expire bientôt",
+        (TokenExpiringTitle, Ja) => "Claudeのセッションがまもなく期限切れ",
+
+        (TokenExpiringBody, En) => {
+            "Your session token expires in {0}. Paste a new one in Settings to keep usage updating."
+        }
+        (TokenExpiringBody, De) => {
+            "Dein Sitzungstoken läuft in {0} ab. Füge in den Einstellungen ein neues ein, damit die Nutzung weiter aktualisiert wird."
+        }
+        (TokenExpiringBody, Es) => {
+            "Tu token de sesión caduca en {0}. Pega uno nuevo en Ajustes para que el uso siga actualizándose."
+        }
+        (TokenExpiringBody, Fr) => {
+            "Votre jeton de session expire dans {0}. Collez-en un nouveau dans les Réglages pour que l'utilisation continue de se mettre à jour."
+        }
+        (TokenExpiringBody, Ja) => {
+            "セッショントークンはあと{0}で期限切れになります。使用量の更新を続けるには、設定で新しいトークンを貼り付けてください。"
+        }
+
+        (ModelAdvisoryTitle, En) => "Opus Weekly Usage at {0}%",
+        (ModelAdvisoryTitle, De) => "Opus-Wochennutzung bei {0}%",
+        (ModelAdvisoryTitle, Es) => "Uso semanal de Opus al {0}%",
+        (ModelAdvisoryTitle, Fr) => "Utilisation hebdomadaire d'Opus à {0} %",
+        (ModelAdvisoryTitle, Ja) => "Opusの週間使用量が{0}%",
+
+        (ModelAdvisoryBody, En) => "Sonnet is only at {0}%. Switch models to save Opus for later.",
+        (ModelAdvisoryBody, De) => {
+            "Sonnet liegt erst bei {0}%. Wechsle das Modell, um Opus für später aufzusparen."
+        }
+        (ModelAdvisoryBody, Es) => {
+            "Sonnet solo está al {0}%. Cambia de modelo para reservar Opus para más tarde."
+        }
+        (ModelAdvisoryBody, Fr) => {
+            "Sonnet n'est qu'à {0} %. Changez de modèle pour garder Opus pour plus tard."
+        }
+        (ModelAdvisoryBody, Ja) => "Sonnetはまだ{0}%です。モデルを切り替えてOpusを温存しましょう。",
+
+        (WeeklySummaryTitle, En) => "Last Week on {0}",
+        (WeeklySummaryTitle, De) => "Letzte Woche bei {0}",
+        (WeeklySummaryTitle, Es) => "La semana pasada en {0}",
+        (WeeklySummaryTitle, Fr) => "La semaine dernière sur {0}",
+        (WeeklySummaryTitle, Ja) => "{0}の先週のまとめ",
+
+        (WeeklySummaryPeaks, En) => "Peaks: {0}.",
+        (WeeklySummaryPeaks, De) => "Höchstwerte: {0}.",
+        (WeeklySummaryPeaks, Es) => "Picos: {0}.",
+        (WeeklySummaryPeaks, Fr) => "Pics : {0}.",
+        (WeeklySummaryPeaks, Ja) => "ピーク: {0}。",
+
+        (WeeklySummaryCapped, En) => "The {0} window hit 100% {1} times.",
+        (WeeklySummaryCapped, De) => "Das Fenster {0} erreichte {1}-mal 100%.",
+        (WeeklySummaryCapped, Es) => "La ventana {0} llegó al 100% {1} veces.",
+        (WeeklySummaryCapped, Fr) => "La fenêtre {0} a atteint 100 % {1} fois.",
+        (WeeklySummaryCapped, Ja) => "{0}枠は100%に{1}回達しました。",
+
+        (BudgetTitle, En) => "{0} Budget",
+        (BudgetTitle, De) => "Budget für {0}",
+        (BudgetTitle, Es) => "Presupuesto de {0}",
+        (BudgetTitle, Fr) => "Budget {0}",
+        (BudgetTitle, Ja) => "{0}の予算",
+
+        (BudgetAheadBody, En) => {
+            "At {0}%, {1} points ahead of the plan to stay under {2}%. Slow down to stay on budget."
+        }
+        (BudgetAheadBody, De) => {
+            "Bei {0}% und damit {1} Punkte über dem Plan, unter {2}% zu bleiben. Etwas langsamer, um im Budget zu bleiben."
+        }
+        (BudgetAheadBody, Es) => {
+            "Al {0}%, {1} puntos por delante del plan para quedarte por debajo del {2}%. Reduce el ritmo para cumplir el presupuesto."
+        }
+        (BudgetAheadBody, Fr) => {
+            "À {0} %, {1} points au-dessus du plan pour rester sous {2} %. Ralentissez pour tenir le budget."
+        }
+        (BudgetAheadBody, Ja) => {
+            "現在{0}%で、{2}%未満に抑える計画より{1}ポイント先行しています。予算内に収めるにはペースを落としてください。"
+        }
+
+        (BudgetExceededBody, En) => "At {0}%, past the {1}% budget.",
+        (BudgetExceededBody, De) => "Bei {0}% und damit über dem Budget von {1}%.",
+        (BudgetExceededBody, Es) => "Al {0}%, por encima del presupuesto del {1}%.",
+        (BudgetExceededBody, Fr) => "À {0} %, au-delà du budget de {1} %.",
+        (BudgetExceededBody, Ja) => "現在{0}%で、予算の{1}%を超えました。",
+
+        (StaleDataTitle, En) => "Usage Data May Be Stale",
+        (StaleDataTitle, De) => "Nutzungsdaten möglicherweise veraltet",
+        (StaleDataTitle, Es) => "Los datos de uso pueden estar desactualizados",
+        (StaleDataTitle, Fr) => "Données d'utilisation peut-être obsolètes",
+        (StaleDataTitle, Ja) => "使用状況データが古い可能性があります",
+
+        (StaleDataActiveBody, En) => {
+            "{0} usage hasn't changed in over an hour while Claude Code was in use. The session token may return cached data or belong to another account."
+        }
+        (StaleDataActiveBody, De) => {
+            "Die {0}-Nutzung hat sich seit über einer Stunde nicht geändert, obwohl Claude Code verwendet wurde. Das Sitzungstoken liefert möglicherweise zwischengespeicherte Daten oder gehört zu einem anderen Konto."
+        }
+        (StaleDataActiveBody, Es) => {
+            "El uso de {0} no ha cambiado en más de una hora mientras se usaba Claude Code. Es posible que el token de sesión devuelva datos en caché o pertenezca a otra cuenta."
+        }
+        (StaleDataActiveBody, Fr) => {
+            "L'utilisation {0} n'a pas changé depuis plus d'une heure alors que Claude Code était utilisé. Le jeton de session renvoie peut-être des données en cache ou appartient à un autre compte."
+        }
+        (StaleDataActiveBody, Ja) => {
+            "Claude Codeの使用中にもかかわらず、{0}の使用量が1時間以上変化していません。セッショントークンがキャッシュされたデータを返しているか、別のアカウントのものである可能性があります。"
+        }
+
+        (StaleDataResetBody, En) => {
+            "{0} still reports the {1} window's old usage although it should have reset. The session token may return cached data or belong to another account."
+        }
+        (StaleDataResetBody, De) => {
+            "{0} meldet für das Fenster {1} weiterhin die alte Nutzung, obwohl es zurückgesetzt sein sollte. Das Sitzungstoken liefert möglicherweise zwischengespeicherte Daten oder gehört zu einem anderen Konto."
+        }
+        (StaleDataResetBody, Es) => {
+            "{0} sigue mostrando el uso anterior de la ventana {1} aunque ya debería haberse restablecido. Es posible que el token de sesión devuelva datos en caché o pertenezca a otra cuenta."
+        }
+        (StaleDataResetBody, Fr) => {
+            "{0} indique toujours l'ancienne utilisation de la fenêtre {1} alors qu'elle aurait dû être réinitialisée. Le jeton de session renvoie peut-être des données en cache ou appartient à un autre compte."
+        }
+        (StaleDataResetBody, Ja) => {
+            "{0}の{1}枠はリセットされているはずですが、以前の使用量が表示されたままです。セッショントークンがキャッシュされたデータを返しているか、別のアカウントのものである可能性があります。"
+        }
+
+        (MemberUsageUnsupportedTitle, En) => "Member Usage Unavailable",
+        (MemberUsageUnsupportedTitle, De) => "Mitgliedernutzung nicht verfügbar",
+        (MemberUsageUnsupportedTitle, Es) => "Uso por miembro no disponible",
+        (MemberUsageUnsupportedTitle, Fr) => "Utilisation par membre indisponible",
+        (MemberUsageUnsupportedTitle, Ja) => "メンバー別の使用状況は利用できません",
+
+        (MemberUsageUnsupportedBody, En) => {
+            "This organization doesn't offer per-member usage, so it is no longer requested. Enable member usage again to retry."
+        }
+        (MemberUsageUnsupportedBody, De) => {
+            "Diese Organisation bietet keine Nutzung pro Mitglied an, daher wird sie nicht mehr abgefragt. Aktiviere die Mitgliedernutzung erneut, um es noch einmal zu versuchen."
+        }
+        (MemberUsageUnsupportedBody, Es) => {
+            "Esta organización no ofrece el uso por miembro, así que ya no se solicita. Vuelve a activar el uso por miembro para reintentarlo."
+        }
+        (MemberUsageUnsupportedBody, Fr) => {
+            "Cette organisation ne propose pas l'utilisation par membre ; elle n'est donc plus demandée. Réactivez l'utilisation par membre pour réessayer."
+        }
+        (MemberUsageUnsupportedBody, Ja) => {
+            "この組織はメンバー別の使用状況を提供していないため、取得を停止しました。再試行するにはメンバー別の使用状況をもう一度有効にしてください。"
+        }
+
+        (CompactMode, En) => "Compact Mode",
+        (CompactMode, De) => "Kompaktmodus",
+        (CompactMode, Es) => "Modo compacto",
+        (CompactMode, Fr) => "Mode compact",
+        (CompactMode, Ja) => "コンパクト表示",
+
+        (Open, En) => "Open",
+        (Open, De) => "Öffnen",
+        (Open, Es) => "Abrir",
+        (Open, Fr) => "Ouvrir",
+        (Open, Ja) => "開く",
+
+        (CheckForUpdates, En) => "Check for Updates",
+        (CheckForUpdates, De) => "Nach Updates suchen",
+        (CheckForUpdates, Es) => "Buscar actualizaciones",
+        (CheckForUpdates, Fr) => "Rechercher des mises à jour",
+        (CheckForUpdates, Ja) => "アップデートを確認",
+
+        (SwitchOrganization, En) => "Organization",
+        (SwitchOrganization, De) => "Organisation",
+        (SwitchOrganization, Es) => "Organización",
+        (SwitchOrganization, Fr) => "Organisation",
+        (SwitchOrganization, Ja) => "組織",
+
+        (Quit, En) => "Quit",
+        (Quit, De) => "Beenden",
+        (Quit, Es) => "Salir",
+        (Quit, Fr) => "Quitter",
+        (Quit, Ja) => "終了",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_system_locale_with_fallback() {
+        assert_eq!(resolve(Language::System, Some("de-DE")), Locale::De);
+        assert_eq!(resolve(Language::System, Some("fr_CA.UTF-8")), Locale::Fr);
+        assert_eq!(resolve(Language::System, Some("pt-BR")), Locale::En);
+        assert_eq!(resolve(Language::System, None), Locale::En);
+        assert_eq!(resolve(Language::Ja, Some("de-DE")), Locale::Ja);
+    }
+
+    #[test]
+    fn substitutes_placeholders() {
+        assert_eq!(
+            format_template(lookup(Locale::En, Text::ResetsAt), &["14:30", "2h 10m"]),
+            "resets at 14:30, in 2h 10m"
+        );
+        assert_eq!(
+            format_template(lookup(Locale::De, Text::Reached), &["80"]),
+            "80% erreicht"
+        );
+    }
+
+    #[test]
+    fn names_weekdays_per_locale() {
+        let wednesday = chrono::Weekday::Wed.num_days_from_monday() as usize;
+        assert_eq!(weekday_names(Locale::En)[wednesday], "Wed");
+        assert_eq!(weekday_names(Locale::De)[wednesday], "Mi");
+        assert_eq!(weekday_names(Locale::Ja)[wednesday], "水");
+    }
+}
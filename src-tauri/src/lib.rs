@@ -1,54 +1,227 @@
 mod api;
+mod app_lock;
 mod auto_refresh;
+mod budgets;
+mod capture;
+mod cli;
+mod clock;
 mod commands;
+mod config;
+mod crash_reporter;
 mod credentials;
+mod demo;
 mod error;
 mod history;
+mod history_sync;
+mod i18n;
+mod local_usage;
 mod notifications;
+mod power;
+mod pricing;
+mod redact;
+mod report;
+mod reporting_server;
+mod secret;
+mod shutdown;
+mod time_format;
+#[cfg(feature = "transcript-watcher")]
+mod transcript_watcher;
 mod tray;
 mod types;
+mod usage_import;
 mod validation;
+mod window_state;
+#[cfg(windows)]
+mod windows_toast;
 
+#[cfg(target_os = "macos")]
+mod time_sensitive;
 #[cfg(target_os = "macos")]
 mod wake_detection;
 
-use auto_refresh::auto_refresh_loop;
+use auto_refresh::start_auto_refresh;
 use commands::{
-    cleanup_history, clear_credentials, clear_ollama_credentials, get_default_settings,
-    get_provider_statuses, get_usage, get_usage_history_by_range, get_usage_stats, refresh_now,
-    save_credentials, save_ollama_credentials, set_active_provider, set_auto_refresh,
-    set_hourly_refresh, set_notification_settings,
+    cleanup_history, clear_credentials, clear_history, clear_ollama_credentials, generate_report,
+    get_active_sessions, get_app_lock_status, get_budget_progress, get_chart_series,
+    get_combined_history, get_cost_estimate, get_default_settings, get_history_db_info,
+    get_keychain_status, get_last_fetch_result, get_member_usage, get_model_usage,
+    get_organization_usage, get_preferences, get_project_usage, get_projected_limit_time,
+    get_provider_statuses, get_staleness, get_time_to_threshold, get_usage, get_usage_histogram,
+    get_usage_history_by_range, get_usage_stats, import_usage_data, list_claude_organizations,
+    lock_app, refresh_now, replay_response_capture, save_credentials, save_ollama_credentials,
+    set_active_provider, set_active_session_interval, set_additional_organizations,
+    set_app_passphrase, set_auto_lock_timeout, set_auto_refresh, set_autostart, set_compact_mode,
+    set_history_sample_interval, set_history_sync_folder, set_hourly_refresh,
+    set_hourly_refresh_timing, set_idle_suspend, set_member_usage_enabled, set_model_advisory,
+    set_near_reset_polling, set_notification_settings, set_preferences, set_skip_unchanged_samples,
+    set_startup_jitter, set_token_expiry_warning, set_usage_budgets, set_weekly_summary_enabled,
+    set_window_pinned, simulate_usage, snooze_until_reset, start_response_capture,
+    stop_response_capture, switch_organization, sync_history_now, unlock_app,
 };
 use tray::create_tray;
-use types::{AppState, AutoRefreshConfig, NotificationSettings, NotificationState};
+use types::{
+    ActiveSessionsChanged, AppLockChanged, AppState, AutoRefreshConfig, CheckForUpdates,
+    CompactModeChanged, HistoryAppended, HistoryChanged, KeychainIssue, LocalUsageChanged,
+    ModelAdvisory, NotificationSettings, NotificationState, Preferences, RateLimitUpdated,
+    RefreshLoopRestarted, Settings, StaleDataSuspected, UsageError, UsageUpdated,
+};
 
 use specta_typescript::Typescript;
-use std::backtrace::Backtrace;
 use std::sync::Arc;
 use tauri_plugin_log::{Target, TargetKind};
 use tauri_plugin_store::StoreExt;
-use tauri_specta::{Builder, collect_commands};
+use tauri_specta::{Builder, Event, collect_commands, collect_events};
 use tokio::sync::{Mutex, watch};
 
+/// Passed by the OS login item so launches at login can be told apart.
+const AUTOSTART_ARG: &str = "--autostart";
+/// Opens the window on launch, e.g. when the tray icon is missing.
+const SHOW_WINDOW_ARG: &str = "--show-window";
+/// Asks a running instance to fetch now instead of showing its window.
+const REFRESH_ARG: &str = "--refresh";
+
+/// A setting from the settings store, or None when the store couldn't be
+/// opened, the key is unset or the value doesn't fit `T` (a number out of
+/// range for `u32` reads as unset rather than being truncated).
+fn load_setting<T: serde::de::DeserializeOwned>(
+    store: Option<&tauri_plugin_store::Store<tauri::Wry>>,
+    key: &str,
+) -> Option<T> {
+    let value = store?.get(key)?;
+    serde_json::from_value(value)
+        .inspect_err(|e| log::warn!("Ignoring stored {key}: {e}"))
+        .ok()
+}
+
+/// A stored setting checked with the same validator as its command, so a
+/// hand-edited value the command would refuse falls back to the default.
+fn load_setting_if_valid<T: serde::de::DeserializeOwned>(
+    store: Option<&tauri_plugin_store::Store<tauri::Wry>>,
+    key: &str,
+    validate: impl FnOnce(&T) -> Result<(), error::AppError>,
+) -> Option<T> {
+    let value = load_setting(store, key)?;
+    validate(&value)
+        .inspect_err(|e| log::warn!("Ignoring stored {key}: {e}"))
+        .ok()?;
+    Some(value)
+}
+
+/// `load_setting_if_valid` for number settings, whose validators take the
+/// setting name for the error.
+fn load_validated_setting(
+    store: Option<&tauri_plugin_store::Store<tauri::Wry>>,
+    key: &str,
+    validate: impl Fn(&str, u32) -> Result<(), error::AppError>,
+) -> Option<u32> {
+    load_setting_if_valid(store, key, |value| validate(key, *value))
+}
+
+/// Put back the default of a stored field its command would refuse.
+fn reset_if_invalid<T: Default>(
+    value: &mut T,
+    key: &str,
+    validate: impl FnOnce(&T) -> Result<(), error::AppError>,
+) {
+    if let Err(e) = validate(value) {
+        log::warn!("Ignoring stored {key}: {e}");
+        *value = T::default();
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let builder = Builder::<tauri::Wry>::new().commands(collect_commands![
-        get_usage,
-        get_default_settings,
-        save_credentials,
-        clear_credentials,
-        save_ollama_credentials,
-        clear_ollama_credentials,
-        get_provider_statuses,
-        set_active_provider,
-        set_auto_refresh,
-        set_hourly_refresh,
-        refresh_now,
-        set_notification_settings,
-        get_usage_history_by_range,
-        get_usage_stats,
-        cleanup_history
-    ]);
+    // `claude-monitor query` and friends exit before any window or tray exists
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(code) = cli::run(&args) {
+        std::process::exit(code);
+    }
+
+    let builder = Builder::<tauri::Wry>::new()
+        .commands(collect_commands![
+            set_startup_jitter,
+            get_staleness,
+            generate_report,
+            get_usage,
+            get_default_settings,
+            save_credentials,
+            clear_credentials,
+            save_ollama_credentials,
+            clear_ollama_credentials,
+            get_keychain_status,
+            get_app_lock_status,
+            set_app_passphrase,
+            set_auto_lock_timeout,
+            lock_app,
+            unlock_app,
+            get_provider_statuses,
+            set_active_provider,
+            set_auto_refresh,
+            set_hourly_refresh,
+            set_hourly_refresh_timing,
+            refresh_now,
+            set_notification_settings,
+            get_usage_history_by_range,
+            get_usage_stats,
+            get_time_to_threshold,
+            get_usage_histogram,
+            get_chart_series,
+            get_combined_history,
+            get_projected_limit_time,
+            get_member_usage,
+            cleanup_history,
+            get_history_db_info,
+            clear_history,
+            snooze_until_reset,
+            simulate_usage,
+            start_response_capture,
+            stop_response_capture,
+            replay_response_capture,
+            get_last_fetch_result,
+            get_preferences,
+            set_preferences,
+            get_project_usage,
+            get_model_usage,
+            get_cost_estimate,
+            get_active_sessions,
+            set_active_session_interval,
+            set_member_usage_enabled,
+            set_model_advisory,
+            set_weekly_summary_enabled,
+            set_usage_budgets,
+            get_budget_progress,
+            set_near_reset_polling,
+            set_token_expiry_warning,
+            set_idle_suspend,
+            set_history_sample_interval,
+            set_skip_unchanged_samples,
+            set_history_sync_folder,
+            sync_history_now,
+            import_usage_data,
+            set_autostart,
+            list_claude_organizations,
+            set_additional_organizations,
+            switch_organization,
+            get_organization_usage,
+            set_window_pinned,
+            set_compact_mode
+        ])
+        .events(collect_events![
+            UsageUpdated,
+            UsageError,
+            ActiveSessionsChanged,
+            LocalUsageChanged,
+            RateLimitUpdated,
+            CheckForUpdates,
+            ModelAdvisory,
+            CompactModeChanged,
+            HistoryAppended,
+            HistoryChanged,
+            AppLockChanged,
+            KeychainIssue,
+            StaleDataSuspected,
+            RefreshLoopRestarted
+        ]);
 
     #[cfg(debug_assertions)]
     builder
@@ -57,10 +230,21 @@ pub fn run() {
 
     // Initialize platform-agnostic plugins
     let app_builder = tauri::Builder::default()
-        .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
+        // Must be registered first so a second launch exits before starting
+        // another tray icon and refresh loop
+        .plugin(tauri_plugin_single_instance::init(|app, args, _cwd| {
             use tauri::Manager;
+            if args.iter().any(|arg| arg == REFRESH_ARG) {
+                log::info!("Refresh requested from the command line");
+                if let Some(state) = app.try_state::<Arc<AppState>>() {
+                    let _ = state.restart_tx.send(());
+                }
+                return;
+            }
+            log::info!("Another instance was launched, focusing the existing window");
             // When a second instance is launched, show the main window
             if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
                 let _ = window.show();
                 let _ = window.set_focus();
             }
@@ -72,6 +256,16 @@ pub fn run() {
                     Target::new(TargetKind::LogDir { file_name: None }),
                     Target::new(TargetKind::Webview),
                 ])
+                // The plugin's default layout, with secrets masked in every target
+                .format(|out, message, record| {
+                    out.finish(format_args!(
+                        "{}[{}][{}] {}",
+                        chrono::Utc::now().format("[%Y-%m-%d][%H:%M:%S]"),
+                        record.level(),
+                        record.target(),
+                        redact::redact(&message.to_string())
+                    ))
+                })
                 .build(),
         )
         .plugin(tauri_plugin_opener::init())
@@ -79,7 +273,7 @@ pub fn run() {
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_autostart::init(
             tauri_plugin_autostart::MacosLauncher::LaunchAgent,
-            None,
+            Some(vec![AUTOSTART_ARG]),
         ));
 
     // Add platform-specific plugins
@@ -96,71 +290,210 @@ pub fn run() {
 
     app_builder
         .invoke_handler(builder.invoke_handler())
-        .setup(|app| {
+        .setup(move |app| {
             use tauri::Manager;
 
-            // Set up panic hook to log panics before crashing
-            let default_panic = std::panic::take_hook();
-            std::panic::set_hook(Box::new(move |info| {
-                let backtrace = Backtrace::force_capture();
-                log::error!("Panic: {info}\n{backtrace}");
-                default_panic(info);
-            }));
+            // Set up panic hook first so panics anywhere in setup are logged; crash
+            // files are written once the preference below turns them on
+            crash_reporter::install(app.handle());
+
+            // Register typed events before anything can emit them
+            builder.mount_events(app);
+
+            // Load deployment config (config.toml + CLAUDE_MONITOR_* env) before any state
+            let app_config = config::init(app.handle());
+            crash_reporter::init_upload();
+
+            let settings_store = app.store("settings.json").ok();
+            let store = settings_store.as_deref();
+
+            // With an app lock passphrase set, start locked and leave the
+            // credentials in the keychain until `unlock_app`
+            let app_lock_hash: Option<String> = load_setting(store, app_lock::PASSPHRASE_HASH_KEY);
+            let auto_lock_minutes = load_setting(store, app_lock::AUTO_LOCK_MINUTES_KEY);
+            let locked = app_lock_hash.is_some();
 
             // Try to load credentials from OS keychain
-            let initial_credentials = credentials::load_credentials();
-            let ollama_token = credentials::load_ollama_credentials();
-
-            let settings_store = app.store("settings.json");
-
-            // Load hourly refresh setting from store
-            let hourly_refresh_enabled = match &settings_store {
-                Ok(store) => store
-                    .get("hourly_refresh_enabled")
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(false),
-                Err(_) => false,
+            let ((organization_id, session_token, session_expires_at), ollama_token) = if locked {
+                ((None, None, None), None)
+            } else {
+                (
+                    credentials::load_credentials()
+                        .map(|(id, token, expires_at)| (Some(id), Some(token), expires_at))
+                        .unwrap_or_default(),
+                    credentials::load_ollama_credentials(),
+                )
             };
 
-            let active_provider = match &settings_store {
-                Ok(store) => store
-                    .get("active_provider")
-                    .and_then(|v| serde_json::from_value(v.clone()).ok())
-                    .unwrap_or(types::ProviderKind::Claude),
-                Err(_) => types::ProviderKind::Claude,
-            };
+            let hourly_refresh_enabled =
+                load_setting(store, "hourly_refresh_enabled").unwrap_or(false);
+            let hourly_refresh_gap_secs = load_validated_setting(
+                store,
+                "hourly_refresh_gap_secs",
+                validation::validate_hourly_refresh_gap,
+            )
+            .or(app_config.hourly_refresh_gap_secs)
+            .unwrap_or(auto_refresh::HOURLY_REFRESH_INITIAL_GAP_SECS);
+            let hourly_refresh_jitter_secs = load_validated_setting(
+                store,
+                "hourly_refresh_jitter_secs",
+                validation::validate_hourly_refresh_jitter,
+            )
+            .or(app_config.hourly_refresh_jitter_secs)
+            .unwrap_or(auto_refresh::HOURLY_REFRESH_JITTER_MAX_SECS);
+            let startup_jitter_secs = load_validated_setting(
+                store,
+                "startup_jitter_secs",
+                validation::validate_startup_jitter,
+            )
+            .or(app_config.startup_jitter_secs)
+            .unwrap_or(auto_refresh::STARTUP_JITTER_MAX_SECS);
+            let active_session_interval_minutes = load_validated_setting(
+                store,
+                "active_session_interval_minutes",
+                validation::validate_interval_minutes,
+            );
+            let near_reset_minutes = load_validated_setting(
+                store,
+                "near_reset_minutes",
+                validation::validate_interval_minutes,
+            );
+            let idle_suspend_hours = load_validated_setting(
+                store,
+                "idle_suspend_hours",
+                validation::validate_idle_hours,
+            );
+            let token_expiry_warning_days = load_validated_setting(
+                store,
+                "token_expiry_warning_days",
+                validation::validate_token_expiry_warning_days,
+            )
+            .unwrap_or(notifications::DEFAULT_TOKEN_EXPIRY_WARNING_DAYS);
+            let member_usage_enabled = load_setting(store, "member_usage_enabled").unwrap_or(false);
+            let member_usage_unsupported_org = load_setting(store, "member_usage_unsupported_org");
+            let weekly_summary_enabled =
+                load_setting(store, "weekly_summary_enabled").unwrap_or(false);
+            let budgets = load_setting(store, budgets::BUDGETS_KEY).unwrap_or_default();
+            let history_sample_interval_minutes = load_validated_setting(
+                store,
+                "history_sample_interval_minutes",
+                validation::validate_interval_minutes,
+            );
+            let skip_unchanged_samples =
+                load_setting(store, "skip_unchanged_samples").unwrap_or(false);
+            let history_sync_folder = load_setting(store, "history_sync_folder");
+
+            // Generated on first launch; names this machine's sync log
+            let machine_id = load_setting(store, "machine_id").unwrap_or_else(|| {
+                let machine_id = history_sync::new_machine_id();
+                if let Some(store) = store {
+                    store.set("machine_id", machine_id.clone());
+                }
+                machine_id
+            });
+
+            let active_provider =
+                load_setting(store, "active_provider").unwrap_or(types::ProviderKind::Claude);
+            let organization_name = load_setting(store, "organization_name");
+            let additional_organizations =
+                load_setting(store, "additional_organizations").unwrap_or_default();
+            let model_advisory =
+                load_setting_if_valid(store, "model_advisory", validation::validate_model_advisory)
+                    .unwrap_or_default();
 
             // Create initial config with loaded credentials
             let initial_config = AutoRefreshConfig {
                 active_provider,
-                organization_id: initial_credentials.as_ref().map(|(id, _)| id.clone()),
-                session_token: initial_credentials.as_ref().map(|(_, token)| token.clone()),
+                organization_id,
+                organization_name,
+                additional_organizations,
+                session_token,
+                session_expires_at,
+                token_expiry_warning_days,
                 ollama_session_token: ollama_token,
                 enabled: true,
-                interval_minutes: 5,
+                interval_minutes: Settings::from_config(app_config).refresh_interval_minutes,
                 hourly_refresh_enabled,
+                hourly_refresh_gap_secs,
+                hourly_refresh_jitter_secs,
+                startup_jitter_secs,
+                active_session_interval_minutes,
+                near_reset_minutes,
+                idle_suspend_hours,
+                history_sample_interval_minutes,
+                skip_unchanged_samples,
+                history_sync_folder,
+                machine_id,
+                member_usage_enabled,
+                member_usage_unsupported_org,
+                model_advisory,
+                weekly_summary_enabled,
+                budgets,
+                app_lock_hash,
+                auto_lock_minutes,
+                locked,
             };
 
-            // Load notification settings from store
-            let notification_settings = match &settings_store {
-                Ok(store) => store
-                    .get("notification_settings")
-                    .and_then(|v| serde_json::from_value(v.clone()).ok())
-                    .unwrap_or_default(),
-                Err(_) => NotificationSettings::default(),
-            };
+            let notification_settings: NotificationSettings = load_setting_if_valid(
+                store,
+                "notification_settings",
+                validation::validate_notification_settings,
+            )
+            .unwrap_or_default();
+            let notification_state: NotificationState =
+                load_setting(store, "notification_state").unwrap_or_default();
+            // Backend-owned preferences; a hand-edited severity or retention
+            // that `set_preferences` would refuse falls back to its default
+            let mut preferences: Preferences =
+                load_setting(store, "preferences").unwrap_or_default();
+            reset_if_invalid(
+                &mut preferences.severity,
+                "preferences.severity",
+                validation::validate_severity_config,
+            );
+            reset_if_invalid(
+                &mut preferences.history_retention,
+                "preferences.history_retention",
+                validation::validate_retention_policy,
+            );
 
-            // Load notification state from store
-            let notification_state = match &settings_store {
-                Ok(store) => store
-                    .get("notification_state")
-                    .and_then(|v| serde_json::from_value(v.clone()).ok())
-                    .unwrap_or_default(),
-                Err(_) => NotificationState::default(),
-            };
+            let launched_at_login = std::env::args().any(|arg| arg == AUTOSTART_ARG);
+            let show_window_on_launch = (launched_at_login && !preferences.start_hidden_at_login)
+                || std::env::args().any(|arg| arg == SHOW_WINDOW_ARG);
+            let compact_mode = preferences.compact_mode;
 
-            // Initialize history database (ignore errors - analytics is non-critical)
-            let _ = history::init_database(app.handle());
+            // Localize notifications and tray labels before the tray is created
+            i18n::set_language(preferences.language);
+            time_format::set_time_format(preferences.time_format);
+            api::set_user_agent_mode(preferences.user_agent);
+            window_state::set_window_placement(preferences.window_placement);
+            window_state::set_pinned(preferences.window_pinned);
+            notifications::set_time_sensitive_alerts(preferences.time_sensitive_alerts);
+            history::set_incognito(preferences.incognito);
+            history::set_retention_policy(&preferences.history_retention);
+            demo::set_enabled(
+                preferences.demo_mode || std::env::args().any(|arg| arg == demo::DEMO_ARG),
+            );
+            crash_reporter::set_enabled(preferences.crash_reports_enabled);
+
+            // Explain missing credentials instead of leaving a bare setup screen
+            if let Some(issue) = credentials::keychain_issue() {
+                notifications::notify_keychain_issue(app.handle(), &issue);
+                let _ = issue.emit(app.handle());
+            }
+
+            // Initialize history database (errors are only logged - analytics is
+            // non-critical). A corrupt file is replaced and the user told.
+            match history::init_database(app.handle()) {
+                Ok(Some(backup)) => notifications::notify_history_recovered(app.handle(), &backup),
+                Ok(None) => {}
+                Err(e) => log::warn!("Failed to open history database: {e}"),
+            }
+            if let Some(org_id) = &initial_config.organization_id {
+                commands::set_history_organization(app.handle(), org_id);
+            }
+            tauri::async_runtime::spawn(history::integrity_check_loop(app.handle().clone()));
+            tauri::async_runtime::spawn(history::retention_loop());
 
             // Create app state with watch channel for restart signals
             let (restart_tx, _) = watch::channel(());
@@ -169,6 +502,22 @@ pub fn run() {
                 restart_tx,
                 notification_settings: Mutex::new(notification_settings),
                 notification_state: Mutex::new(notification_state),
+                preferences: Mutex::new(preferences),
+                last_fetch: Mutex::new(None),
+                organization_usage: Mutex::new(Vec::new()),
+                organization_fetch: Mutex::new(()),
+                spike_filter: Mutex::new(auto_refresh::SpikeFilter::default()),
+                last_success_at: Mutex::new(None),
+                last_applied_usage: Mutex::new(None),
+                paused_reason: Mutex::new(None),
+                backoff_until: Mutex::new(None),
+                usage_client: if demo::is_enabled() {
+                    Arc::new(demo::DemoUsageClient)
+                } else {
+                    Arc::new(api::HttpUsageClient)
+                },
+                #[cfg(feature = "transcript-watcher")]
+                transcript_watcher: Mutex::new(None),
                 #[cfg(target_os = "macos")]
                 wake_observer: Mutex::new(None),
             });
@@ -180,15 +529,30 @@ pub fn run() {
                 *state.wake_observer.blocking_lock() = Some(wake_observer);
             }
 
+            // Watch Claude Code transcripts so local usage views refresh promptly
+            #[cfg(feature = "transcript-watcher")]
+            {
+                *state.transcript_watcher.blocking_lock() =
+                    transcript_watcher::start_transcript_watcher(app.handle().clone());
+            }
+
+            // Serve the reporting views to local dashboards when configured
+            reporting_server::start(config::get().integrations.reporting_port);
+
             // Manage state
             app.manage(state.clone());
 
             // Spawn auto-refresh loop
-            let app_handle = app.handle().clone();
-            tauri::async_runtime::spawn(auto_refresh_loop(app_handle, state.clone()));
+            start_auto_refresh(app.handle().clone(), state.clone());
+            tauri::async_runtime::spawn(app_lock::auto_lock_loop(
+                app.handle().clone(),
+                state.clone(),
+            ));
 
             // Create tray (required by NSPopover plugin which looks up tray by ID "main")
             create_tray(app.handle())?;
+            tray::update_organization_menu(app.handle(), &state.config.blocking_lock());
+            window_state::set_compact(app.handle(), compact_mode);
 
             // Set activation policy to Accessory on macOS for proper tray app behavior
             #[cfg(target_os = "macos")]
@@ -206,19 +570,62 @@ pub fn run() {
                 }
             }
 
+            // Without a tray the window is the only way in, so keep it on the taskbar
+            let tray_available = tray::is_tray_available();
+            if !tray_available {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.set_skip_taskbar(false);
+                }
+                notifications::notify_tray_unavailable(app.handle());
+            }
+
+            // The window starts hidden in the tray unless the user asked to see it
+            if show_window_on_launch || !tray_available {
+                if let Some(window) = app.get_webview_window("main") {
+                    let _ = window.show();
+                    let _ = window.set_focus();
+                }
+            }
+
             Ok(())
         })
         .on_window_event(|window, event| {
+            #[cfg(not(target_os = "macos"))]
+            use tauri::Manager;
+
+            // The auto-lock timer counts time away from the window
+            if let tauri::WindowEvent::Focused(focused) = event {
+                app_lock::note_window_focus(*focused);
+            }
+
             // On non-macOS platforms, handle window events manually
             #[cfg(not(target_os = "macos"))]
             match event {
-                // Hide window when it loses focus
-                tauri::WindowEvent::Focused(false) => {
+                // Hide window when it loses focus, unless the user pinned it or
+                // there is no tray to bring it back from
+                tauri::WindowEvent::Focused(false)
+                    if !window_state::is_pinned() && tray::is_tray_available() =>
+                {
+                    if let Some(webview_window) = window.get_webview_window(window.label()) {
+                        window_state::save_geometry(&webview_window);
+                    }
                     let _ = window.hide();
                 }
-                // Hide window instead of closing
+                // Keep the tray icon visible against the new taskbar colour
+                tauri::WindowEvent::ThemeChanged(theme) => {
+                    tray::set_tray_theme(window.app_handle(), *theme);
+                }
+                // Hide window instead of closing, or minimize it to the taskbar
+                // when there is no tray
                 tauri::WindowEvent::CloseRequested { api, .. } => {
-                    let _ = window.hide();
+                    if let Some(webview_window) = window.get_webview_window(window.label()) {
+                        window_state::save_geometry(&webview_window);
+                    }
+                    if tray::is_tray_available() {
+                        let _ = window.hide();
+                    } else {
+                        let _ = window.minimize();
+                    }
                     api.prevent_close();
                 }
                 _ => {}
@@ -231,6 +638,11 @@ pub fn run() {
                 api.prevent_close();
             }
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::ExitRequested { .. } = event {
+                shutdown::flush(app);
+            }
+        });
 }
@@ -0,0 +1,586 @@
+//! Claude Code Local Usage
+//!
+//! Parses the JSONL session transcripts that Claude Code writes under
+//! `~/.claude/projects` (or `$CLAUDE_CONFIG_DIR/projects`) and aggregates
+//! the token usage reported on each assistant message.
+
+use crate::error::AppError;
+use crate::pricing::{self, ModelPricing};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// A transcript written to within this window counts as an active session.
+pub const ACTIVE_SESSION_WINDOW_SECS: u64 = 300;
+
+/// Transcript directory listings kept between active-session checks, which
+/// run on every refresh loop iteration.
+static TRANSCRIPT_LISTINGS: Mutex<BTreeMap<PathBuf, DirListing>> = Mutex::new(BTreeMap::new());
+
+/// One directory's transcripts and subdirectories, valid while its mtime is
+/// unchanged (adding or removing an entry updates it; appending to a file doesn't).
+struct DirListing {
+    modified: SystemTime,
+    files: Vec<PathBuf>,
+    subdirs: Vec<PathBuf>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptLine {
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    timestamp: Option<String>,
+    cwd: Option<String>,
+    #[serde(rename = "requestId")]
+    request_id: Option<String>,
+    message: Option<TranscriptMessage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptMessage {
+    id: Option<String>,
+    model: Option<String>,
+    usage: Option<TranscriptUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranscriptUsage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+    #[serde(default)]
+    cache_creation_input_tokens: u64,
+    #[serde(default)]
+    cache_read_input_tokens: u64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct TokenCounts {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+}
+
+impl TokenCounts {
+    pub fn total(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_tokens + self.cache_read_tokens
+    }
+
+    pub fn add(&mut self, other: &TokenCounts) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+    }
+}
+
+/// Token usage of a single assistant message parsed from a transcript.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalUsageEntry {
+    /// Stable identity of the response (`message.id:requestId`) used for deduplication.
+    pub message_key: String,
+    pub timestamp: DateTime<Utc>,
+    pub project: String,
+    pub model: String,
+    pub tokens: TokenCounts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectUsage {
+    pub project: String,
+    pub tokens: TokenCounts,
+    pub message_count: u32,
+    pub estimated_cost_usd: f64,
+    pub last_activity: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelUsage {
+    pub model: String,
+    /// Model family (`opus`, `sonnet`, `haiku`, or `other`) for matching API windows.
+    pub family: String,
+    pub tokens: TokenCounts,
+    pub message_count: u32,
+    pub estimated_cost_usd: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSession {
+    pub session_id: String,
+    pub project: String,
+    pub last_activity: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveSessions {
+    pub count: u32,
+    pub sessions: Vec<ActiveSession>,
+}
+
+/// Directory containing one sub-directory of transcripts per project.
+pub fn get_projects_dir() -> PathBuf {
+    if let Some(config_dir) = &crate::config::get().integrations.claude_config_dir {
+        return config_dir.join("projects");
+    }
+
+    if let Ok(config_dir) = std::env::var("CLAUDE_CONFIG_DIR") {
+        return PathBuf::from(config_dir).join("projects");
+    }
+
+    let home = dirs::home_dir().unwrap_or_default();
+    home.join(".claude").join("projects")
+}
+
+/// The projects directory, or None when transcript reading is disabled by
+/// configuration or Claude Code has never been run on this machine.
+fn transcripts_dir() -> Option<PathBuf> {
+    if !crate::config::get().integrations.local_usage {
+        return None;
+    }
+
+    let projects_dir = get_projects_dir();
+    projects_dir.is_dir().then_some(projects_dir)
+}
+
+/// Load all assistant messages recorded at or after `since`.
+/// Returns an empty list when Claude Code has never been run on this machine.
+pub fn load_entries_since(since: DateTime<Utc>) -> Result<Vec<LocalUsageEntry>, AppError> {
+    let Some(projects_dir) = transcripts_dir() else {
+        return Ok(Vec::new());
+    };
+
+    let mut files = Vec::new();
+    collect_transcript_files(&projects_dir, &mut files)?;
+
+    let since_system_time = SystemTime::from(since);
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+
+    for file in files {
+        // Transcripts are append-only, so a file untouched since the range
+        // start cannot contain messages inside the range.
+        let modified = std::fs::metadata(&file).and_then(|meta| meta.modified());
+        if matches!(modified, Ok(modified) if modified < since_system_time) {
+            continue;
+        }
+
+        let fallback_project = fallback_project_name(&projects_dir, &file);
+        let raw = match std::fs::read_to_string(&file) {
+            Ok(raw) => raw,
+            Err(e) => {
+                log::warn!("Failed to read transcript {}: {e}", file.display());
+                continue;
+            }
+        };
+
+        for line in raw.lines() {
+            let Some(entry) = parse_transcript_line(line, &fallback_project) else {
+                continue;
+            };
+            if entry.timestamp < since {
+                continue;
+            }
+            // Streaming responses are written once per content block with the
+            // same message/request id, so count each response only once.
+            if !seen.insert(entry.message_key.clone()) {
+                continue;
+            }
+            entries.push(entry);
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Claude Code sessions whose transcript was modified within the last
+/// [`ACTIVE_SESSION_WINDOW_SECS`], most recent first.
+pub fn get_active_sessions() -> Result<ActiveSessions, AppError> {
+    let Some(projects_dir) = transcripts_dir() else {
+        return Ok(ActiveSessions::default());
+    };
+
+    let mut files = Vec::new();
+    collect_transcript_files_cached(
+        &projects_dir,
+        &mut TRANSCRIPT_LISTINGS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner()),
+        &mut files,
+    )?;
+
+    let now = SystemTime::now();
+    let window = Duration::from_secs(ACTIVE_SESSION_WINDOW_SECS);
+    let mut sessions = files
+        .iter()
+        .filter_map(|file| {
+            let modified = std::fs::metadata(file).and_then(|m| m.modified()).ok()?;
+            let age = now.duration_since(modified).unwrap_or_default();
+            (age <= window).then_some((file, modified))
+        })
+        .map(|(file, modified)| ActiveSession {
+            session_id: file
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            project: fallback_project_name(&projects_dir, file),
+            last_activity: DateTime::<Utc>::from(modified).to_rfc3339(),
+        })
+        .collect::<Vec<_>>();
+    sessions.sort_by(|a, b| b.last_activity.cmp(&a.last_activity));
+
+    Ok(ActiveSessions {
+        count: sessions.len() as u32,
+        sessions,
+    })
+}
+
+/// Token usage per project directory within a time range preset.
+pub fn get_project_usage(
+    range: &str,
+    pricing_overrides: &BTreeMap<String, ModelPricing>,
+) -> Result<Vec<ProjectUsage>, AppError> {
+    let entries = load_entries_since(range_start(range))?;
+    Ok(aggregate_by_project(&entries, pricing_overrides))
+}
+
+/// Token usage per model id within a time range preset.
+pub fn get_model_usage(
+    range: &str,
+    pricing_overrides: &BTreeMap<String, ModelPricing>,
+) -> Result<Vec<ModelUsage>, AppError> {
+    let entries = load_entries_since(range_start(range))?;
+    Ok(aggregate_by_model(&entries, pricing_overrides))
+}
+
+pub fn range_start(range: &str) -> DateTime<Utc> {
+    let hours = crate::history::get_range_hours(range) as i64;
+    Utc::now() - chrono::Duration::hours(hours)
+}
+
+pub fn aggregate_by_project(
+    entries: &[LocalUsageEntry],
+    pricing_overrides: &BTreeMap<String, ModelPricing>,
+) -> Vec<ProjectUsage> {
+    let mut by_project: BTreeMap<&str, ProjectUsage> = BTreeMap::new();
+
+    for entry in entries {
+        let usage = by_project
+            .entry(entry.project.as_str())
+            .or_insert_with(|| ProjectUsage {
+                project: entry.project.clone(),
+                tokens: TokenCounts::default(),
+                message_count: 0,
+                estimated_cost_usd: 0.0,
+                last_activity: None,
+            });
+        usage.tokens.add(&entry.tokens);
+        usage.message_count += 1;
+        usage.estimated_cost_usd +=
+            pricing::estimate_entry_cost(entry, pricing_overrides).unwrap_or(0.0);
+
+        let timestamp = entry.timestamp.to_rfc3339();
+        if usage
+            .last_activity
+            .as_ref()
+            .is_none_or(|last| *last < timestamp)
+        {
+            usage.last_activity = Some(timestamp);
+        }
+    }
+
+    let mut projects = by_project.into_values().collect::<Vec<_>>();
+    projects.sort_by(|a, b| b.tokens.total().cmp(&a.tokens.total()));
+    projects
+}
+
+pub fn aggregate_by_model(
+    entries: &[LocalUsageEntry],
+    pricing_overrides: &BTreeMap<String, ModelPricing>,
+) -> Vec<ModelUsage> {
+    let mut by_model: BTreeMap<&str, ModelUsage> = BTreeMap::new();
+
+    for entry in entries {
+        let usage = by_model
+            .entry(entry.model.as_str())
+            .or_insert_with(|| ModelUsage {
+                model: entry.model.clone(),
+                family: model_family(&entry.model).to_string(),
+                tokens: TokenCounts::default(),
+                message_count: 0,
+                estimated_cost_usd: 0.0,
+            });
+        usage.tokens.add(&entry.tokens);
+        usage.message_count += 1;
+        usage.estimated_cost_usd +=
+            pricing::estimate_entry_cost(entry, pricing_overrides).unwrap_or(0.0);
+    }
+
+    let mut models = by_model.into_values().collect::<Vec<_>>();
+    models.sort_by(|a, b| b.tokens.total().cmp(&a.tokens.total()));
+    models
+}
+
+pub fn model_family(model: &str) -> &'static str {
+    let model = model.to_lowercase();
+    if model.contains("opus") {
+        "opus"
+    } else if model.contains("sonnet") {
+        "sonnet"
+    } else if model.contains("haiku") {
+        "haiku"
+    } else {
+        "other"
+    }
+}
+
+fn collect_transcript_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), AppError> {
+    let read_dir = std::fs::read_dir(dir).map_err(|e| {
+        AppError::Storage(format!(
+            "Failed to read Claude Code directory {}: {e}",
+            dir.display()
+        ))
+    })?;
+
+    for entry in read_dir.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_transcript_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "jsonl") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Like `collect_transcript_files`, but re-reads only directories whose mtime
+/// changed since `listings` saw them.
+fn collect_transcript_files_cached(
+    dir: &Path,
+    listings: &mut BTreeMap<PathBuf, DirListing>,
+    files: &mut Vec<PathBuf>,
+) -> Result<(), AppError> {
+    let read_error = |e: std::io::Error| {
+        AppError::Storage(format!(
+            "Failed to read Claude Code directory {}: {e}",
+            dir.display()
+        ))
+    };
+    let modified = std::fs::metadata(dir)
+        .and_then(|m| m.modified())
+        .map_err(read_error)?;
+    if listings
+        .get(dir)
+        .is_none_or(|listing| listing.modified != modified)
+    {
+        let mut listing = DirListing {
+            modified,
+            files: Vec::new(),
+            subdirs: Vec::new(),
+        };
+        for entry in std::fs::read_dir(dir).map_err(read_error)?.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                listing.subdirs.push(path);
+            } else if path.extension().is_some_and(|ext| ext == "jsonl") {
+                listing.files.push(path);
+            }
+        }
+        listings.insert(dir.to_path_buf(), listing);
+    }
+
+    let Some(listing) = listings.get(dir) else {
+        return Ok(());
+    };
+    files.extend(listing.files.iter().cloned());
+    for subdir in listing.subdirs.clone() {
+        collect_transcript_files_cached(&subdir, listings, files)?;
+    }
+    Ok(())
+}
+
+/// Project name derived from the transcript location, used when a line has no `cwd`.
+/// Claude Code names each project directory after its path with `/` replaced by `-`.
+fn fallback_project_name(projects_dir: &Path, file: &Path) -> String {
+    file.strip_prefix(projects_dir)
+        .ok()
+        .and_then(|relative| relative.components().next())
+        .map(|component| component.as_os_str().to_string_lossy().into_owned())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+fn parse_transcript_line(line: &str, fallback_project: &str) -> Option<LocalUsageEntry> {
+    let line = line.trim();
+    if line.is_empty() {
+        return None;
+    }
+
+    let parsed: TranscriptLine = serde_json::from_str(line).ok()?;
+    if parsed.kind.as_deref() != Some("assistant") {
+        return None;
+    }
+
+    let message = parsed.message?;
+    let usage = message.usage?;
+    let raw_timestamp = parsed.timestamp?;
+    let timestamp = DateTime::parse_from_rfc3339(&raw_timestamp)
+        .ok()?
+        .with_timezone(&Utc);
+    let project = parsed
+        .cwd
+        .filter(|cwd| !cwd.is_empty())
+        .unwrap_or_else(|| fallback_project.to_string());
+    let model = message.model.unwrap_or_else(|| "unknown".to_string());
+
+    let message_key = match (message.id, parsed.request_id) {
+        (Some(id), Some(request_id)) => format!("{id}:{request_id}"),
+        (Some(id), None) => id,
+        _ => format!("{raw_timestamp}:{project}:{model}"),
+    };
+
+    Some(LocalUsageEntry {
+        message_key,
+        timestamp,
+        project,
+        model,
+        tokens: TokenCounts {
+            input_tokens: usage.input_tokens,
+            output_tokens: usage.output_tokens,
+            cache_creation_tokens: usage.cache_creation_input_tokens,
+            cache_read_tokens: usage.cache_read_input_tokens,
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ASSISTANT_LINE: &str = r#"{"type":"assistant","timestamp":"2026-04-12T10:00:00.000Z","cwd":"/Users/me/repo","requestId":"req_1","message":{"id":"msg_1","model":"claude-opus-4-1","usage":{"input_tokens":10,"output_tokens":20,"cache_creation_input_tokens":30,"cache_read_input_tokens":40}}}"#;
+
+    fn entry(project: &str, timestamp: &str, output_tokens: u64) -> LocalUsageEntry {
+        LocalUsageEntry {
+            message_key: format!("{project}:{timestamp}"),
+            timestamp: DateTime::parse_from_rfc3339(timestamp)
+                .unwrap()
+                .with_timezone(&Utc),
+            project: project.to_string(),
+            model: "claude-sonnet-4".to_string(),
+            tokens: TokenCounts {
+                output_tokens,
+                ..TokenCounts::default()
+            },
+        }
+    }
+
+    #[test]
+    fn parses_assistant_line() {
+        let entry = parse_transcript_line(ASSISTANT_LINE, "-fallback").unwrap();
+        assert_eq!(entry.message_key, "msg_1:req_1");
+        assert_eq!(entry.project, "/Users/me/repo");
+        assert_eq!(entry.model, "claude-opus-4-1");
+        assert_eq!(entry.tokens.total(), 100);
+    }
+
+    #[test]
+    fn ignores_user_and_malformed_lines() {
+        let user =
+            r#"{"type":"user","timestamp":"2026-04-12T10:00:00Z","message":{"role":"user"}}"#;
+        assert!(parse_transcript_line(user, "p").is_none());
+        assert!(parse_transcript_line("not json", "p").is_none());
+        assert!(parse_transcript_line("", "p").is_none());
+    }
+
+    #[test]
+    fn falls_back_to_directory_name_without_cwd() {
+        let line = ASSISTANT_LINE.replace(r#""cwd":"/Users/me/repo","#, "");
+        let entry = parse_transcript_line(&line, "-Users-me-repo").unwrap();
+        assert_eq!(entry.project, "-Users-me-repo");
+    }
+
+    #[test]
+    fn reuses_listings_until_a_directory_changes() {
+        let dir =
+            std::env::temp_dir().join(format!("claude-monitor-listing-{}", std::process::id()));
+        let project = dir.join("-Users-me-repo");
+        std::fs::create_dir_all(&project).unwrap();
+        std::fs::write(project.join("session.jsonl"), ASSISTANT_LINE).unwrap();
+
+        let mut listings = BTreeMap::new();
+        let mut files = Vec::new();
+        collect_transcript_files_cached(&dir, &mut listings, &mut files).unwrap();
+        assert_eq!(files, vec![project.join("session.jsonl")]);
+
+        // An unchanged mtime serves the cached listing without reading the directory
+        let cached = project.join("cached.jsonl");
+        listings
+            .get_mut(&project)
+            .unwrap()
+            .files
+            .push(cached.clone());
+        files.clear();
+        collect_transcript_files_cached(&dir, &mut listings, &mut files).unwrap();
+        assert!(files.contains(&cached));
+
+        listings.get_mut(&project).unwrap().modified = SystemTime::UNIX_EPOCH;
+        files.clear();
+        collect_transcript_files_cached(&dir, &mut listings, &mut files).unwrap();
+        assert_eq!(files, vec![project.join("session.jsonl")]);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn classifies_model_families() {
+        assert_eq!(model_family("claude-opus-4-1-20250805"), "opus");
+        assert_eq!(model_family("claude-3-5-sonnet-20241022"), "sonnet");
+        assert_eq!(model_family("claude-haiku-4-5"), "haiku");
+        assert_eq!(model_family("<synthetic>"), "other");
+    }
+
+    #[test]
+    fn aggregates_tokens_per_model() {
+        let mut opus = entry("repo", "2026-04-12T10:00:00Z", 10);
+        opus.model = "claude-opus-4-1".to_string();
+        let entries = vec![entry("repo", "2026-04-12T10:00:00Z", 5), opus.clone(), opus];
+
+        let models = aggregate_by_model(&entries, &BTreeMap::new());
+        assert_eq!(models.len(), 2);
+        assert_eq!(models[0].family, "opus");
+        assert_eq!(models[0].message_count, 2);
+        assert_eq!(models[1].tokens.output_tokens, 5);
+    }
+
+    #[test]
+    fn aggregates_and_sorts_projects_by_tokens() {
+        let entries = vec![
+            entry("small", "2026-04-12T10:00:00Z", 5),
+            entry("big", "2026-04-12T09:00:00Z", 50),
+            entry("big", "2026-04-12T11:00:00Z", 50),
+        ];
+
+        let projects = aggregate_by_project(&entries, &BTreeMap::new());
+        assert_eq!(projects.len(), 2);
+        assert_eq!(projects[0].project, "big");
+        assert_eq!(projects[0].message_count, 2);
+        assert_eq!(projects[0].tokens.output_tokens, 100);
+        assert!(
+            projects[0]
+                .last_activity
+                .as_deref()
+                .unwrap()
+                .starts_with("2026-04-12T11:00:00")
+        );
+    }
+}
@@ -1,6 +1,38 @@
-use crate::types::{NotificationRule, NotificationSettings, NotificationState, UsageSnapshot};
+use crate::budgets::{BudgetStatus, UsageBudget};
+use crate::i18n::{Text, t, tf};
+use crate::report::WindowSummary;
+use crate::time_format::{format_duration, format_reset};
+use crate::types::{
+    KeychainIssue, KeychainProblem, ModelAdvisory, ModelAdvisorySettings, NotificationRule,
+    NotificationSettings, NotificationState, Severity, SeverityConfig, StaleDataReason,
+    StaleDataSuspected, UsageSnapshot,
+};
 use chrono::{DateTime, Utc};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri_plugin_notification::NotificationExt;
+use tauri_specta::Event;
+
+/// Reset times of one period drift by up to this much between fetches; a
+/// bigger move forward means a new period.
+const PERIOD_JITTER_MINUTES: i64 = 30;
+
+/// Days before a known session expiry to warn, unless configured otherwise.
+pub const DEFAULT_TOKEN_EXPIRY_WARNING_DAYS: u32 = 3;
+
+static TIME_SENSITIVE: AtomicBool = AtomicBool::new(false);
+
+/// Apply the time-sensitive alert preference. Called at startup and when
+/// preferences change.
+pub fn set_time_sensitive_alerts(enabled: bool) {
+    let was_enabled = TIME_SENSITIVE.swap(enabled, Ordering::Relaxed);
+    #[cfg(target_os = "macos")]
+    if enabled && !was_enabled {
+        crate::time_sensitive::request_authorization();
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = was_enabled;
+}
 
 fn compound_key(provider: crate::types::ProviderKind, window_key: &str) -> String {
     format!("{}:{window_key}", provider.as_str())
@@ -38,7 +70,7 @@ fn check_threshold_notification(
     current_utilization: f64,
     last_notified: f64,
     thresholds: &[u32],
-    fired_thresholds: &[String],
+    fired_thresholds: &HashSet<String>,
     key: &str,
 ) -> Option<u32> {
     thresholds.iter().copied().find(|threshold| {
@@ -52,14 +84,10 @@ fn check_threshold_notification(
 fn check_time_remaining_notification(
     resets_at: Option<&String>,
     time_thresholds_minutes: &[u32],
-    fired_time_remaining: &[String],
+    fired_time_remaining: &HashSet<String>,
     key: &str,
 ) -> Option<u32> {
-    let resets_at = resets_at?;
-    let reset_time = DateTime::parse_from_rfc3339(resets_at)
-        .ok()
-        .map(|dt| dt.with_timezone(&Utc))?;
-    let minutes_remaining = reset_time.signed_duration_since(Utc::now()).num_minutes();
+    let minutes_remaining = minutes_until(resets_at?, crate::clock::now())?;
 
     if minutes_remaining <= 0 {
         return None;
@@ -74,31 +102,253 @@ fn check_time_remaining_notification(
     })
 }
 
-fn format_time_remaining(minutes: u32) -> String {
-    if minutes >= 60 {
-        let hours = minutes / 60;
-        let mins = minutes % 60;
-        if mins > 0 {
-            format!("{hours}h {mins}m")
-        } else {
-            format!("{hours}h")
+/// Fired thresholds for `key` that utilization has fallen back under.
+fn recovered_thresholds(
+    fired_thresholds: &HashSet<String>,
+    key: &str,
+    utilization: f64,
+) -> Vec<u32> {
+    let prefix = format!("{key}:");
+    let mut recovered: Vec<u32> = fired_thresholds
+        .iter()
+        .filter_map(|item| item.strip_prefix(&prefix)?.parse::<u32>().ok())
+        .filter(|threshold| utilization < *threshold as f64)
+        .collect();
+    recovered.sort_unstable();
+    recovered
+}
+
+/// True once `interval_minutes` have passed since the window last alerted.
+fn reminder_due(last_reminded: Option<&str>, interval_minutes: u32, now: DateTime<Utc>) -> bool {
+    interval_minutes > 0
+        && last_reminded
+            .and_then(|value| DateTime::parse_from_rfc3339(value).ok())
+            .is_some_and(|last| {
+                now.signed_duration_since(last.with_timezone(&Utc))
+                    .num_minutes()
+                    >= interval_minutes as i64
+            })
+}
+
+/// Minutes from `now` until an RFC3339 reset time (negative once it passed).
+pub(crate) fn minutes_until(resets_at: &str, now: DateTime<Utc>) -> Option<i64> {
+    let reset_time = DateTime::parse_from_rfc3339(resets_at)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))?;
+    Some(reset_time.signed_duration_since(now).num_minutes())
+}
+
+/// Minutes left when `expires_at` is within the warning period and still ahead.
+fn token_expiry_due(expires_at: &str, warning_days: u32, now: DateTime<Utc>) -> Option<i64> {
+    if warning_days == 0 {
+        return None;
+    }
+    let remaining = DateTime::parse_from_rfc3339(expires_at)
+        .ok()?
+        .signed_duration_since(now)
+        .num_minutes();
+    (remaining > 0 && remaining <= i64::from(warning_days) * 24 * 60).then_some(remaining)
+}
+
+fn is_snoozed(state: &NotificationState, now: DateTime<Utc>) -> bool {
+    state
+        .snoozed_until
+        .as_deref()
+        .and_then(|until| DateTime::parse_from_rfc3339(until).ok())
+        .is_some_and(|until| until > now)
+}
+
+/// The soonest upcoming reset, i.e. the end of the current short window.
+pub fn next_reset<'a>(
+    resets_at: impl IntoIterator<Item = &'a str>,
+    now: DateTime<Utc>,
+) -> Option<String> {
+    resets_at
+        .into_iter()
+        .filter_map(|value| DateTime::parse_from_rfc3339(value).ok())
+        .map(|reset| reset.with_timezone(&Utc))
+        .filter(|reset| *reset > now)
+        .min()
+        .map(|reset| reset.to_rfc3339())
+}
+
+/// Triggers that fired for one window during a single pass.
+struct WindowAlert<'a> {
+    label: &'a str,
+    triggers: Vec<String>,
+    utilization: f64,
+    resets_at: Option<&'a str>,
+    severity: Severity,
+    /// May break through Focus on macOS (`SeverityConfig::time_sensitive`).
+    time_sensitive: bool,
+}
+
+impl WindowAlert<'_> {
+    fn summary(&self) -> String {
+        let mut summary = format!(
+            "{} {}",
+            self.triggers.join(t(Text::ListSeparator)),
+            tf(Text::PercentUsed, &[&format!("{:.0}", self.utilization)])
+        );
+        if let Some(reset) = self.resets_at.and_then(format_reset) {
+            summary.push_str(&format!(", {reset}"));
         }
-    } else {
-        format!("{minutes}m")
+        summary
     }
 }
 
+/// Title and body for everything that fired in one pass. A single window keeps
+/// the one-line format; several windows get a line each under one title.
+fn format_alerts(
+    provider: crate::types::ProviderKind,
+    alerts: &[WindowAlert<'_>],
+) -> Option<(String, String)> {
+    let provider_name = provider.as_str().to_uppercase();
+    match alerts {
+        [] => None,
+        [alert] => Some((
+            tf(Text::UsageAlertTitle, &[alert.label]),
+            format!("{provider_name} {}", alert.summary()),
+        )),
+        alerts => Some((
+            tf(Text::UsageAlertTitle, &[&provider_name]),
+            alerts
+                .iter()
+                .map(|alert| format!("{}: {}", alert.label, alert.summary()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )),
+    }
+}
+
+/// Show a usage alert. Alerts with a critical window (see `SeverityConfig`)
+/// use critical urgency on Linux; on macOS, those past the time-sensitive
+/// cutoff go through Focus when the user opted in; on Windows, the toast
+/// shows the highest window as a progress bar. Everything else goes through
+/// `show_notification`.
+fn show_alert<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    title: &str,
+    body: &str,
+    alerts: &[WindowAlert<'_>],
+) {
+    if alerts.is_empty() {
+        return;
+    }
+    let critical = alerts
+        .iter()
+        .any(|alert| alert.severity == Severity::Critical);
+    let time_sensitive = alerts.iter().any(|alert| alert.time_sensitive);
+
+    #[cfg(target_os = "macos")]
+    if time_sensitive
+        && TIME_SENSITIVE.load(Ordering::Relaxed)
+        && crate::time_sensitive::show(title, body)
+    {
+        return;
+    }
+    #[cfg(not(target_os = "macos"))]
+    let _ = time_sensitive;
+
+    #[cfg(windows)]
+    {
+        let peak = alerts
+            .iter()
+            .max_by(|a, b| a.utilization.total_cmp(&b.utilization));
+        if peak.is_some_and(|peak| {
+            crate::windows_toast::show(app, title, body, peak.label, peak.utilization)
+        }) {
+            return;
+        }
+    }
+
+    show_notification(app, title, body, critical);
+}
+
+/// Post a plain notification through the notification plugin. On Linux, when
+/// the plugin fails, the notification server is called over D-Bus instead, on a
+/// blocking thread so callers holding `notification_state` aren't held up by it.
+fn show_notification<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    title: &str,
+    body: &str,
+    critical: bool,
+) {
+    let Err(e) = app.notification().builder().title(title).body(body).show() else {
+        log::debug!("Notification shown through the notification plugin");
+        return;
+    };
+
+    #[cfg(target_os = "linux")]
+    {
+        log::info!("Notification plugin failed ({e}); falling back to D-Bus");
+        let (title, body) = (title.to_string(), body.to_string());
+        tauri::async_runtime::spawn_blocking(move || match notify_dbus(&title, &body, critical) {
+            Ok(id) => log::info!("Notification {id} delivered over D-Bus"),
+            Err(e) => log::warn!("Failed to show notification over D-Bus: {e}"),
+        });
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        let _ = critical;
+        log::warn!("Failed to show notification: {e}");
+    }
+}
+
+/// Call `org.freedesktop.Notifications.Notify` directly, returning the id the
+/// server assigned.
+#[cfg(target_os = "linux")]
+fn notify_dbus(title: &str, body: &str, critical: bool) -> Result<u32, dbus::Error> {
+    use dbus::arg::{PropMap, RefArg, Variant};
+    use dbus::blocking::Connection;
+
+    let connection = Connection::new_session()?;
+    let proxy = connection.with_proxy(
+        "org.freedesktop.Notifications",
+        "/org/freedesktop/Notifications",
+        std::time::Duration::from_secs(2),
+    );
+
+    // Urgency: 1 = normal, 2 = critical
+    let urgency: u8 = if critical { 2 } else { 1 };
+    let mut hints = PropMap::new();
+    hints.insert(
+        "urgency".to_string(),
+        Variant(Box::new(urgency) as Box<dyn RefArg>),
+    );
+
+    let (id,): (u32,) = proxy.method_call(
+        "org.freedesktop.Notifications",
+        "Notify",
+        (
+            "Claude Monitor",
+            0u32,
+            "",
+            title,
+            body,
+            Vec::<String>::new(),
+            hints,
+            -1i32,
+        ),
+    )?;
+    Ok(id)
+}
+
 pub fn process_notifications<R: tauri::Runtime>(
     app: &tauri::AppHandle<R>,
     usage: &UsageSnapshot,
     settings: &NotificationSettings,
+    severity: &SeverityConfig,
     state: &NotificationState,
 ) -> NotificationState {
     if !settings.enabled {
         return state.clone();
     }
 
+    let now = crate::clock::now();
+    let snoozed = is_snoozed(state, now);
     let mut new_state = state.clone();
+    let mut alerts = Vec::new();
 
     for window in &usage.windows {
         let key = compound_key(usage.provider, &window.key);
@@ -112,7 +362,7 @@ pub fn process_notifications<R: tauri::Runtime>(
                 last_notified,
                 rule.interval_percent,
             ) {
-                notifications.push(format!("reached {level}%"));
+                notifications.push(tf(Text::Reached, &[&level.to_string()]));
             }
         }
 
@@ -124,10 +374,10 @@ pub fn process_notifications<R: tauri::Runtime>(
                 &new_state.fired_thresholds,
                 &key,
             ) {
-                notifications.push(format!("crossed {threshold}% threshold"));
+                notifications.push(tf(Text::CrossedThreshold, &[&threshold.to_string()]));
                 new_state
                     .fired_thresholds
-                    .push(format!("{key}:{threshold}"));
+                    .insert(format!("{key}:{threshold}"));
             }
         }
 
@@ -138,45 +388,477 @@ pub fn process_notifications<R: tauri::Runtime>(
                 &new_state.fired_time_remaining,
                 &key,
             ) {
-                notifications.push(format!(
-                    "resets in < {}",
-                    format_time_remaining(threshold_minutes)
+                notifications.push(tf(
+                    Text::ResetsWithin,
+                    &[&format_duration(threshold_minutes as i64)],
                 ));
                 new_state
                     .fired_time_remaining
-                    .push(format!("{key}:time:{threshold_minutes}"));
+                    .insert(format!("{key}:time:{threshold_minutes}"));
             }
         }
 
-        if !notifications.is_empty() {
-            let title = format!("{} Usage Alert", window.label);
-            let body = format!(
-                "{} {} ({:.0}% used)",
-                usage.provider.as_str().to_uppercase(),
-                notifications.join(" and "),
-                window.utilization
-            );
+        // Keep alerting every N minutes while above the reminder threshold,
+        // counting from the last alert for this window
+        if rule.reminder_enabled && window.utilization >= rule.reminder_threshold as f64 {
+            let last_reminded = new_state.last_reminded.get(&key).map(String::as_str);
+            if notifications.is_empty()
+                && reminder_due(last_reminded, rule.reminder_interval_minutes, now)
+            {
+                notifications.push(tf(
+                    Text::StillAbove,
+                    &[&rule.reminder_threshold.to_string()],
+                ));
+            }
+            if last_reminded.is_none() || !notifications.is_empty() {
+                new_state
+                    .last_reminded
+                    .insert(key.clone(), now.to_rfc3339());
+            }
+        } else {
+            new_state.last_reminded.remove(&key);
+        }
 
-            let _ = app
-                .notification()
-                .builder()
-                .title(&title)
-                .body(&body)
-                .show();
+        if !notifications.is_empty() {
+            alerts.push(WindowAlert {
+                label: &window.label,
+                triggers: notifications,
+                utilization: window.utilization,
+                resets_at: window.resets_at.as_deref(),
+                severity: severity.severity(&window.key, window.utilization),
+                time_sensitive: severity.is_time_sensitive(&window.key, window.utilization),
+            });
         }
 
         new_state.last_notified.insert(key, window.utilization);
     }
 
+    // One popup per refresh, however many windows crossed something
+    if let Some((title, body)) = format_alerts(usage.provider, &alerts).filter(|_| !snoozed) {
+        show_alert(app, &title, &body, &alerts);
+    }
+
     new_state
 }
 
+/// Tell the user when a window drops back under a threshold that fired, for
+/// rules with recovery enabled. Runs before `reset_notification_state_if_needed`
+/// so a reset still counts; the recovered thresholds are cleared and can fire
+/// again.
+pub fn notify_recoveries<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    usage: &UsageSnapshot,
+    settings: &NotificationSettings,
+    severity: &SeverityConfig,
+    state: &NotificationState,
+) -> NotificationState {
+    if !settings.enabled {
+        return state.clone();
+    }
+
+    let mut new_state = state.clone();
+    let mut alerts = Vec::new();
+
+    for window in &usage.windows {
+        if !get_rule(settings, usage.provider, &window.key).recovery_enabled {
+            continue;
+        }
+        let key = compound_key(usage.provider, &window.key);
+        let recovered = recovered_thresholds(&new_state.fired_thresholds, &key, window.utilization);
+        // Name the lowest threshold it's back under
+        let Some(lowest) = recovered.first() else {
+            continue;
+        };
+
+        for threshold in &recovered {
+            new_state
+                .fired_thresholds
+                .remove(&format!("{key}:{threshold}"));
+        }
+        alerts.push(WindowAlert {
+            label: &window.label,
+            triggers: vec![tf(Text::BackBelow, &[&lowest.to_string()])],
+            utilization: window.utilization,
+            resets_at: window.resets_at.as_deref(),
+            severity: severity.severity(&window.key, window.utilization),
+            time_sensitive: severity.is_time_sensitive(&window.key, window.utilization),
+        });
+    }
+
+    if let Some((title, body)) =
+        format_alerts(usage.provider, &alerts).filter(|_| !is_snoozed(state, crate::clock::now()))
+    {
+        show_alert(app, &title, &body, &alerts);
+    }
+
+    new_state
+}
+
+/// Alert once when the provider starts answering 403, instead of on every retry.
+pub fn notify_access_denied<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    provider: crate::types::ProviderKind,
+    settings: &NotificationSettings,
+    state: &NotificationState,
+) -> NotificationState {
+    if !settings.enabled || state.access_denied_notified || is_snoozed(state, crate::clock::now()) {
+        return state.clone();
+    }
+
+    show_notification(
+        app,
+        &tf(
+            Text::AccessDeniedTitle,
+            &[&provider.as_str().to_uppercase()],
+        ),
+        t(Text::AccessDeniedBody),
+        false,
+    );
+
+    NotificationState {
+        access_denied_notified: true,
+        ..state.clone()
+    }
+}
+
+/// Warn once per expiry when the Claude session token is about to expire, so it
+/// can be replaced before fetches start failing.
+pub fn notify_token_expiring<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    expires_at: &str,
+    warning_days: u32,
+    settings: &NotificationSettings,
+    state: &NotificationState,
+) -> NotificationState {
+    let now = crate::clock::now();
+    if !settings.enabled
+        || state.token_expiry_notified.as_deref() == Some(expires_at)
+        || is_snoozed(state, now)
+    {
+        return state.clone();
+    }
+    let Some(remaining) = token_expiry_due(expires_at, warning_days, now) else {
+        return state.clone();
+    };
+
+    show_notification(
+        app,
+        t(Text::TokenExpiringTitle),
+        &tf(Text::TokenExpiringBody, &[&format_duration(remaining)]),
+        false,
+    );
+
+    NotificationState {
+        token_expiry_notified: Some(expires_at.to_string()),
+        ..state.clone()
+    }
+}
+
+/// Emit and notify once when Opus's weekly window runs high while Sonnet's has
+/// headroom; re-arms after the condition stops holding (e.g. after a reset).
+pub fn notify_model_advisory<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    usage: &UsageSnapshot,
+    advisory: &ModelAdvisorySettings,
+    settings: &NotificationSettings,
+    state: &NotificationState,
+) -> NotificationState {
+    let Some(event) = model_advisory(usage, advisory) else {
+        return NotificationState {
+            model_advisory_notified: false,
+            ..state.clone()
+        };
+    };
+    if state.model_advisory_notified {
+        return state.clone();
+    }
+
+    if let Err(e) = event.clone().emit(app) {
+        log::warn!("Failed to emit model advisory: {e}");
+    }
+    if settings.enabled && !is_snoozed(state, crate::clock::now()) {
+        show_notification(
+            app,
+            &tf(
+                Text::ModelAdvisoryTitle,
+                &[&format!("{:.0}", event.opus_utilization)],
+            ),
+            &tf(
+                Text::ModelAdvisoryBody,
+                &[&format!("{:.0}", event.sonnet_utilization)],
+            ),
+            false,
+        );
+    }
+
+    NotificationState {
+        model_advisory_notified: true,
+        ..state.clone()
+    }
+}
+
+fn model_advisory(
+    usage: &UsageSnapshot,
+    advisory: &ModelAdvisorySettings,
+) -> Option<ModelAdvisory> {
+    if !advisory.enabled || usage.provider != crate::types::ProviderKind::Claude {
+        return None;
+    }
+    let utilization = |key: &str| {
+        usage
+            .windows
+            .iter()
+            .find(|window| window.key == key)
+            .map(|window| window.utilization)
+    };
+    let opus_utilization = utilization("seven_day_opus")?;
+    let sonnet_utilization = utilization("seven_day_sonnet")?;
+
+    (opus_utilization >= advisory.opus_threshold as f64
+        && sonnet_utilization <= advisory.sonnet_max as f64)
+        .then_some(ModelAdvisory {
+            opus_utilization,
+            sonnet_utilization,
+        })
+}
+
+/// Keys of a provider's 7-day window and of the short window whose capped
+/// periods the weekly summary counts.
+fn weekly_summary_windows(provider: crate::types::ProviderKind) -> (&'static str, &'static str) {
+    match provider {
+        crate::types::ProviderKind::Claude => ("seven_day", "five_hour"),
+        crate::types::ProviderKind::Codex => ("secondary", "primary"),
+        crate::types::ProviderKind::Ollama => ("weekly", "session"),
+    }
+}
+
+/// The reset that just passed, when the 7-day window's reset moved forward by
+/// more than fetch-to-fetch jitter since `previous`.
+fn weekly_rollover(previous: Option<&str>, resets_at: &str) -> Option<DateTime<Utc>> {
+    let previous = DateTime::parse_from_rfc3339(previous?).ok()?;
+    let resets_at = DateTime::parse_from_rfc3339(resets_at).ok()?;
+    (resets_at - previous > chrono::Duration::minutes(PERIOD_JITTER_MINUTES))
+        .then(|| previous.with_timezone(&Utc))
+}
+
+/// Body of the weekly summary, e.g. `Peaks: 7 Day 91%, Opus (7 Day) 64%. The
+/// 5 Hour window hit 100% 3 times.` None without history for the week.
+fn weekly_summary_body(windows: &[WindowSummary], short_key: &str) -> Option<String> {
+    if windows.is_empty() {
+        return None;
+    }
+    let peaks: Vec<String> = windows
+        .iter()
+        .filter(|window| window.key != short_key)
+        .map(|window| format!("{} {:.0}%", window.label, window.peak))
+        .collect();
+    let mut body = tf(Text::WeeklySummaryPeaks, &[&peaks.join(", ")]);
+    if let Some(short) = windows
+        .iter()
+        .find(|window| window.key == short_key && window.periods_at_limit > 0)
+    {
+        body.push(' ');
+        body.push_str(&tf(
+            Text::WeeklySummaryCapped,
+            &[&short.label, &short.periods_at_limit.to_string()],
+        ));
+    }
+    Some(body)
+}
+
+/// When a provider's 7-day window resets, summarize the week that ended from
+/// history: each window's peak and how often the short window hit 100%. Only
+/// the reset is tracked while the summary is off, so turning it on never
+/// reports a week late.
+pub fn notify_weekly_summary<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    usage: &UsageSnapshot,
+    enabled: bool,
+    settings: &NotificationSettings,
+    state: &NotificationState,
+) -> NotificationState {
+    let (weekly_key, short_key) = weekly_summary_windows(usage.provider);
+    let Some(resets_at) = usage
+        .windows
+        .iter()
+        .find(|window| window.key == weekly_key)
+        .and_then(|window| window.resets_at.clone())
+    else {
+        return state.clone();
+    };
+
+    let mut new_state = state.clone();
+    let previous = new_state
+        .weekly_resets
+        .insert(usage.provider.as_str().to_string(), resets_at.clone());
+    let Some(week_end) = weekly_rollover(previous.as_deref(), &resets_at) else {
+        return new_state;
+    };
+    if !enabled || !settings.enabled || is_snoozed(state, crate::clock::now()) {
+        return new_state;
+    }
+
+    let week_start = week_end - chrono::Duration::days(7);
+    match crate::report::summarize_period(usage.provider, week_start, week_end) {
+        Ok(windows) => {
+            if let Some(body) = weekly_summary_body(&windows, short_key) {
+                show_notification(
+                    app,
+                    &tf(
+                        Text::WeeklySummaryTitle,
+                        &[&usage.provider.as_str().to_uppercase()],
+                    ),
+                    &body,
+                    false,
+                );
+            }
+        }
+        Err(e) => log::warn!("Failed to summarize last week's usage: {e}"),
+    }
+    new_state
+}
+
+fn same_period(resets_at: &str, other: &str) -> bool {
+    match (
+        DateTime::parse_from_rfc3339(resets_at),
+        DateTime::parse_from_rfc3339(other),
+    ) {
+        (Ok(a), Ok(b)) => (a - b).num_minutes().abs() <= PERIOD_JITTER_MINUTES,
+        _ => resets_at == other,
+    }
+}
+
+/// Alert once per period when a budget runs ahead of its planned burn or
+/// passes its target.
+pub fn notify_budgets<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    usage: &UsageSnapshot,
+    budgets: &[UsageBudget],
+    settings: &NotificationSettings,
+    state: &NotificationState,
+) -> NotificationState {
+    let now = crate::clock::now();
+    let mut new_state = state.clone();
+    for budget in budgets
+        .iter()
+        .filter(|budget| budget.provider == usage.provider)
+    {
+        let Some(progress) = usage
+            .windows
+            .iter()
+            .find(|window| window.key == budget.window_key)
+            .and_then(|window| crate::budgets::progress(budget, window, now))
+        else {
+            continue;
+        };
+        let utilization = format!("{:.0}", progress.utilization);
+        let target = budget.target_percent.to_string();
+        let body = match progress.status {
+            BudgetStatus::OnTrack => continue,
+            BudgetStatus::Ahead => tf(
+                Text::BudgetAheadBody,
+                &[&utilization, &format!("{:.0}", progress.ahead_by), &target],
+            ),
+            BudgetStatus::Exceeded => tf(Text::BudgetExceededBody, &[&utilization, &target]),
+        };
+
+        let key = compound_key(usage.provider, &budget.window_key);
+        if new_state
+            .budget_alerts
+            .get(&key)
+            .is_some_and(|alerted| same_period(alerted, &progress.resets_at))
+        {
+            continue;
+        }
+        new_state
+            .budget_alerts
+            .insert(key, progress.resets_at.clone());
+        if settings.enabled && !is_snoozed(state, now) {
+            show_notification(
+                app,
+                &tf(Text::BudgetTitle, &[&progress.label]),
+                &body,
+                false,
+            );
+        }
+    }
+    new_state
+}
+
+/// Explain why the window stays open when the desktop has no tray. Sent even
+/// with usage alerts off, since the app is otherwise hard to find again.
+pub fn notify_tray_unavailable<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    show_notification(
+        app,
+        t(Text::TrayUnavailableTitle),
+        t(Text::TrayUnavailableBody),
+        false,
+    );
+}
+
+/// Tell the user the history database was corrupt and has been started over.
+/// Sent even with usage alerts off, since charts suddenly lose their history.
+pub fn notify_history_recovered<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    backup: &std::path::Path,
+) {
+    let file_name = backup
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| backup.display().to_string());
+    show_notification(
+        app,
+        t(Text::HistoryRecoveredTitle),
+        &tf(Text::HistoryRecoveredBody, &[&file_name]),
+        false,
+    );
+}
+
+/// Tell the user why saved credentials are missing and what to do about it.
+/// Sent even with usage alerts off, since the app otherwise just looks unconfigured.
+pub fn notify_keychain_issue<R: tauri::Runtime>(app: &tauri::AppHandle<R>, issue: &KeychainIssue) {
+    let body = match issue.problem {
+        KeychainProblem::Unavailable => t(Text::KeychainUnavailableBody).to_string(),
+        KeychainProblem::Locked => t(Text::KeychainLockedBody).to_string(),
+        KeychainProblem::AccessDenied => t(Text::KeychainAccessDeniedBody).to_string(),
+        KeychainProblem::Unreadable => t(Text::KeychainUnreadableBody).to_string(),
+        KeychainProblem::Failed => tf(Text::KeychainFailedBody, &[&issue.detail]),
+    };
+    show_notification(app, t(Text::KeychainIssueTitle), &body, false);
+}
+
+/// Explain that member usage stopped because the organization doesn't offer it.
+pub fn notify_member_usage_unsupported<R: tauri::Runtime>(app: &tauri::AppHandle<R>) {
+    show_notification(
+        app,
+        t(Text::MemberUsageUnsupportedTitle),
+        t(Text::MemberUsageUnsupportedBody),
+        false,
+    );
+}
+
+/// Warn that usage looks stale. Shown regardless of notification settings: the
+/// numbers everything else relies on may be wrong.
+pub fn notify_stale_data<R: tauri::Runtime>(app: &tauri::AppHandle<R>, stale: &StaleDataSuspected) {
+    let provider = stale.provider.as_str().to_uppercase();
+    let body = match (stale.reason, stale.window.as_deref()) {
+        (StaleDataReason::ResetPassed, Some(window)) => {
+            tf(Text::StaleDataResetBody, &[&provider, window])
+        }
+        _ => tf(Text::StaleDataActiveBody, &[&provider]),
+    };
+    show_notification(app, t(Text::StaleDataTitle), &body, false);
+}
+
 pub fn reset_notification_state_if_needed(
     usage: &UsageSnapshot,
     state: &NotificationState,
 ) -> NotificationState {
     let mut new_state = state.clone();
 
+    // A successful fetch means access works again
+    new_state.access_denied_notified = false;
+
     for window in &usage.windows {
         let key = compound_key(usage.provider, &window.key);
         let last_notified = *new_state.last_notified.get(&key).unwrap_or(&0.0);
@@ -190,8 +872,47 @@ pub fn reset_notification_state_if_needed(
                 .fired_time_remaining
                 .retain(|item| !item.starts_with(&format!("{key}:time:")));
         }
+
+        // A new period has started once the reset is further away than a fired
+        // time-remaining threshold, even if utilization never dropped much.
+        if let Some(minutes_remaining) = window
+            .resets_at
+            .as_deref()
+            .and_then(|resets_at| minutes_until(resets_at, crate::clock::now()))
+        {
+            let prefix = format!("{key}:time:");
+            new_state.fired_time_remaining.retain(|item| {
+                item.strip_prefix(&prefix)
+                    .and_then(|threshold| threshold.parse::<i64>().ok())
+                    .is_none_or(|threshold| minutes_remaining <= threshold)
+            });
+        }
+    }
+
+    // Drop keys for windows this provider no longer reports
+    if usage.windows.is_empty() {
+        return new_state;
     }
 
+    let provider_prefix = format!("{}:", usage.provider.as_str());
+    let current_keys: HashSet<String> = usage
+        .windows
+        .iter()
+        .map(|window| compound_key(usage.provider, &window.key))
+        .collect();
+    let is_stale = |item: &str| {
+        item.starts_with(&provider_prefix)
+            && !current_keys
+                .iter()
+                .any(|key| item == key || item.starts_with(&format!("{key}:")))
+    };
+    new_state.last_notified.retain(|key, _| !is_stale(key));
+    new_state.last_reminded.retain(|key, _| !is_stale(key));
+    new_state.fired_thresholds.retain(|item| !is_stale(item));
+    new_state
+        .fired_time_remaining
+        .retain(|item| !is_stale(item));
+
     new_state
 }
 
@@ -209,26 +930,237 @@ mod tests {
                 label: "5 Hour".to_string(),
                 utilization,
                 resets_at: None,
+                resets_at_epoch_ms: None,
                 window_duration_seconds: Some(18_000),
             }],
             account_email: None,
             plan_type: None,
+            rate_limit: None,
         }
     }
 
+    #[test]
+    fn warns_within_token_expiry_period() {
+        let now = DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert_eq!(
+            token_expiry_due("2026-04-15T10:00:00Z", 3, now),
+            Some(2 * 24 * 60)
+        );
+        assert_eq!(token_expiry_due("2026-04-20T10:00:00Z", 3, now), None);
+        assert_eq!(token_expiry_due("2026-04-15T10:00:00Z", 0, now), None);
+        assert_eq!(token_expiry_due("2026-04-13T09:00:00Z", 3, now), None);
+        assert_eq!(token_expiry_due("not a date", 3, now), None);
+    }
+
+    #[test]
+    fn snoozes_until_the_soonest_upcoming_reset() {
+        let now = DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let resets = [
+            "2026-04-18T00:00:00Z",
+            "2026-04-13T12:30:00+00:00",
+            "2026-04-13T09:00:00Z",
+            "not a date",
+        ];
+        let until = next_reset(resets, now);
+        assert_eq!(until.as_deref(), Some("2026-04-13T12:30:00+00:00"));
+        assert_eq!(next_reset(["2026-04-13T09:00:00Z"], now), None);
+
+        let state = NotificationState {
+            snoozed_until: until,
+            ..NotificationState::default()
+        };
+        assert!(is_snoozed(&state, now));
+        assert!(!is_snoozed(&state, now + chrono::Duration::hours(3)));
+        assert!(!is_snoozed(&NotificationState::default(), now));
+    }
+
     #[test]
     fn resets_state_when_window_drops_significantly() {
         let mut state = NotificationState::default();
         state
             .last_notified
             .insert("codex:primary".to_string(), 90.0);
-        state.fired_thresholds.push("codex:primary:80".to_string());
+        state
+            .fired_thresholds
+            .insert("codex:primary:80".to_string());
 
         let new_state = reset_notification_state_if_needed(&snapshot(10.0), &state);
         assert_eq!(new_state.last_notified.get("codex:primary"), Some(&0.0));
         assert!(new_state.fired_thresholds.is_empty());
     }
 
+    #[test]
+    fn prunes_keys_for_missing_windows_and_elapsed_periods() {
+        let mut state = NotificationState::default();
+        state
+            .last_notified
+            .insert("codex:secondary".to_string(), 50.0);
+        state
+            .last_notified
+            .insert("claude:five_hour".to_string(), 50.0);
+        state
+            .fired_thresholds
+            .insert("codex:secondary:80".to_string());
+        state
+            .fired_time_remaining
+            .insert("codex:primary:time:30".to_string());
+
+        let mut usage = snapshot(40.0);
+        usage.windows[0].resets_at = Some((Utc::now() + chrono::Duration::hours(4)).to_rfc3339());
+
+        let new_state = reset_notification_state_if_needed(&usage, &state);
+        assert!(!new_state.last_notified.contains_key("codex:secondary"));
+        assert!(new_state.last_notified.contains_key("claude:five_hour"));
+        assert!(new_state.fired_thresholds.is_empty());
+        assert!(new_state.fired_time_remaining.is_empty());
+    }
+
+    #[test]
+    fn serializes_fired_keys_in_sorted_order() {
+        let mut state = NotificationState::default();
+        state
+            .fired_thresholds
+            .insert("claude:seven_day:90".to_string());
+        state
+            .fired_thresholds
+            .insert("claude:five_hour:80".to_string());
+
+        let value = serde_json::to_value(&state).unwrap();
+        assert_eq!(
+            value["fired_thresholds"],
+            serde_json::json!(["claude:five_hour:80", "claude:seven_day:90"])
+        );
+    }
+
+    #[test]
+    fn groups_alerts_from_one_pass_into_one_notification() {
+        let alert = |label, triggers: &[&str], utilization| WindowAlert {
+            label,
+            triggers: triggers.iter().map(|s| s.to_string()).collect(),
+            utilization,
+            resets_at: None,
+            severity: Severity::Normal,
+            time_sensitive: false,
+        };
+
+        assert_eq!(format_alerts(ProviderKind::Claude, &[]), None);
+        assert_eq!(
+            format_alerts(
+                ProviderKind::Claude,
+                &[alert("5 Hour", &["reached 80%"], 81.0)]
+            ),
+            Some((
+                "5 Hour Usage Alert".to_string(),
+                "CLAUDE reached 80% (81% used)".to_string()
+            ))
+        );
+        assert_eq!(
+            format_alerts(
+                ProviderKind::Claude,
+                &[
+                    alert("5 Hour", &["reached 80%", "crossed 80% threshold"], 80.0),
+                    alert("Sonnet", &["crossed 90% threshold"], 92.4),
+                ]
+            ),
+            Some((
+                "CLAUDE Usage Alert".to_string(),
+                "5 Hour: reached 80% and crossed 80% threshold (80% used)\nSonnet: crossed 90% threshold (92% used)".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn summarizes_the_week_after_a_rollover() {
+        assert_eq!(
+            weekly_rollover(Some("2026-04-13T10:00:00Z"), "2026-04-20T10:00:00Z"),
+            Some(
+                DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+                    .unwrap()
+                    .with_timezone(&Utc)
+            )
+        );
+        assert_eq!(
+            weekly_rollover(Some("2026-04-13T10:00:00Z"), "2026-04-13T10:00:59Z"),
+            None
+        );
+        assert_eq!(weekly_rollover(None, "2026-04-20T10:00:00Z"), None);
+
+        let window = |key: &str, label: &str, peak: f64, periods_at_limit: usize| WindowSummary {
+            key: key.to_string(),
+            label: label.to_string(),
+            peak,
+            peaked_at: Utc::now(),
+            average: peak / 2.0,
+            periods_at_limit,
+        };
+        let windows = [
+            window("five_hour", "5 Hour", 100.0, 3),
+            window("seven_day", "7 Day", 91.2, 0),
+            window("seven_day_opus", "Opus (7 Day)", 64.0, 0),
+        ];
+        assert_eq!(
+            weekly_summary_body(&windows, "five_hour").as_deref(),
+            Some("Peaks: 7 Day 91%, Opus (7 Day) 64%. The 5 Hour window hit 100% 3 times.")
+        );
+        assert_eq!(
+            weekly_summary_body(&windows[1..], "five_hour").as_deref(),
+            Some("Peaks: 7 Day 91%, Opus (7 Day) 64%.")
+        );
+        assert_eq!(weekly_summary_body(&[], "five_hour"), None);
+    }
+
+    #[test]
+    fn treats_reset_jitter_as_the_same_period() {
+        assert!(same_period(
+            "2026-04-20T00:00:00+00:00",
+            "2026-04-20T00:00:10+00:00"
+        ));
+        assert!(!same_period(
+            "2026-04-20T00:00:00+00:00",
+            "2026-04-27T00:00:00+00:00"
+        ));
+    }
+
+    #[test]
+    fn reminds_once_the_interval_has_passed() {
+        let now = DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        assert!(reminder_due(Some("2026-04-13T09:30:00Z"), 30, now));
+        assert!(!reminder_due(Some("2026-04-13T09:45:00Z"), 30, now));
+        assert!(!reminder_due(Some("2026-04-13T09:00:00Z"), 0, now));
+        assert!(!reminder_due(None, 30, now));
+    }
+
+    #[test]
+    fn finds_thresholds_utilization_fell_back_under() {
+        let fired: HashSet<String> = [
+            "claude:five_hour:90",
+            "claude:five_hour:80",
+            "claude:five_hour:50",
+            "claude:seven_day:80",
+        ]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+        assert_eq!(
+            recovered_thresholds(&fired, "claude:five_hour", 60.0),
+            vec![80, 90]
+        );
+        assert!(recovered_thresholds(&fired, "claude:five_hour", 95.0).is_empty());
+        assert_eq!(
+            recovered_thresholds(&fired, "claude:seven_day", 0.0),
+            vec![80]
+        );
+    }
+
     #[test]
     fn uses_default_rule_when_no_specific_rule_exists() {
         let settings = NotificationSettings {
@@ -239,4 +1171,45 @@ mod tests {
         let rule = get_rule(&settings, ProviderKind::Claude, "five_hour");
         assert_eq!(rule.thresholds, vec![80, 90]);
     }
+
+    #[test]
+    fn advises_switching_when_opus_runs_ahead_of_sonnet() {
+        let usage = |opus: f64, sonnet: f64| UsageSnapshot {
+            provider: ProviderKind::Claude,
+            windows: [("seven_day_opus", opus), ("seven_day_sonnet", sonnet)]
+                .into_iter()
+                .map(|(key, utilization)| UsageWindow {
+                    key: key.to_string(),
+                    label: key.to_string(),
+                    utilization,
+                    resets_at: None,
+                    resets_at_epoch_ms: None,
+                    window_duration_seconds: None,
+                })
+                .collect(),
+            account_email: None,
+            plan_type: None,
+            rate_limit: None,
+        };
+        let settings = ModelAdvisorySettings::default();
+
+        let advisory = model_advisory(&usage(85.0, 20.0), &settings).unwrap();
+        assert_eq!(
+            (advisory.opus_utilization, advisory.sonnet_utilization),
+            (85.0, 20.0)
+        );
+        assert!(model_advisory(&usage(70.0, 20.0), &settings).is_none());
+        assert!(model_advisory(&usage(85.0, 60.0), &settings).is_none());
+        assert!(model_advisory(&snapshot(90.0), &settings).is_none());
+        assert!(
+            model_advisory(
+                &usage(85.0, 20.0),
+                &ModelAdvisorySettings {
+                    enabled: false,
+                    ..settings
+                }
+            )
+            .is_none()
+        );
+    }
 }
@@ -0,0 +1,51 @@
+//! Background Throttling (macOS App Nap)
+//!
+//! While the popover is hidden macOS may nap the app, coalescing its timers by
+//! minutes and stretching the gaps between recorded history points. An
+//! `NSProcessInfo` activity is held while a fetch runs and during the last tick
+//! before a scheduled one, so napping still saves power the rest of the time.
+//! Other platforms don't throttle background timers and get a no-op guard.
+
+#[cfg(target_os = "macos")]
+use objc2::rc::Retained;
+#[cfg(target_os = "macos")]
+use objc2::runtime::{NSObjectProtocol, ProtocolObject};
+#[cfg(target_os = "macos")]
+use objc2_foundation::{NSActivityOptions, NSProcessInfo, NSString};
+
+/// Keeps timers precise until dropped.
+#[must_use = "the activity ends when the guard is dropped"]
+pub struct Activity {
+    #[cfg(target_os = "macos")]
+    token: Retained<ProtocolObject<dyn NSObjectProtocol>>,
+}
+
+// SAFETY: the token is an opaque handle only passed back to `NSProcessInfo`,
+// which is thread-safe, so the guard may be dropped on any tokio worker.
+#[cfg(target_os = "macos")]
+unsafe impl Send for Activity {}
+
+/// Opt out of App Nap until the returned guard is dropped. Idle system sleep
+/// stays allowed.
+#[cfg(target_os = "macos")]
+pub fn begin_activity(reason: &str) -> Activity {
+    let token = unsafe {
+        NSProcessInfo::processInfo().beginActivityWithOptions_reason(
+            NSActivityOptions::UserInitiatedAllowingIdleSystemSleep,
+            &NSString::from_str(reason),
+        )
+    };
+    Activity { token }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn begin_activity(_reason: &str) -> Activity {
+    Activity {}
+}
+
+#[cfg(target_os = "macos")]
+impl Drop for Activity {
+    fn drop(&mut self) {
+        unsafe { NSProcessInfo::processInfo().endActivity(&self.token) };
+    }
+}
@@ -0,0 +1,230 @@
+//! Model pricing and cost estimation for local Claude Code usage.
+
+use crate::error::AppError;
+use crate::local_usage::{self, LocalUsageEntry, TokenCounts};
+use chrono::{Datelike, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeMap;
+
+/// Prices in USD per million tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+pub struct ModelPricing {
+    pub input: f64,
+    pub output: f64,
+    pub cache_write: f64,
+    pub cache_read: f64,
+}
+
+impl ModelPricing {
+    const fn new(input: f64, output: f64, cache_write: f64, cache_read: f64) -> Self {
+        Self {
+            input,
+            output,
+            cache_write,
+            cache_read,
+        }
+    }
+
+    pub fn cost(&self, tokens: &TokenCounts) -> f64 {
+        (tokens.input_tokens as f64 * self.input
+            + tokens.output_tokens as f64 * self.output
+            + tokens.cache_creation_tokens as f64 * self.cache_write
+            + tokens.cache_read_tokens as f64 * self.cache_read)
+            / 1_000_000.0
+    }
+}
+
+/// Built-in rates matched against the model id in order, so more specific
+/// patterns must come before the family-wide fallbacks.
+const DEFAULT_PRICING: &[(&str, ModelPricing)] = &[
+    ("opus-4-5", ModelPricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("opus-4-6", ModelPricing::new(5.0, 25.0, 6.25, 0.5)),
+    ("opus", ModelPricing::new(15.0, 75.0, 18.75, 1.5)),
+    ("sonnet", ModelPricing::new(3.0, 15.0, 3.75, 0.3)),
+    ("haiku-4", ModelPricing::new(1.0, 5.0, 1.25, 0.1)),
+    ("3-5-haiku", ModelPricing::new(0.8, 4.0, 1.0, 0.08)),
+    ("haiku", ModelPricing::new(0.25, 1.25, 0.3, 0.03)),
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CostBucket {
+    /// Local calendar date the bucket starts on (`YYYY-MM-DD`).
+    pub period_start: String,
+    pub cost_usd: f64,
+    pub tokens: TokenCounts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CostEstimate {
+    pub total_cost_usd: f64,
+    pub daily: Vec<CostBucket>,
+    pub weekly: Vec<CostBucket>,
+    /// Models with no known or overridden price; their tokens are excluded from costs.
+    pub unpriced_models: Vec<String>,
+}
+
+/// Resolve the price for a model id. User overrides are matched as substrings
+/// of the model id (longest match wins) before falling back to the defaults.
+pub fn pricing_for_model(
+    model: &str,
+    overrides: &BTreeMap<String, ModelPricing>,
+) -> Option<ModelPricing> {
+    let model = model.to_lowercase();
+
+    let user_match = overrides
+        .iter()
+        .filter(|(pattern, _)| model.contains(&pattern.to_lowercase()))
+        .max_by_key(|(pattern, _)| pattern.len())
+        .map(|(_, pricing)| *pricing);
+
+    user_match.or_else(|| {
+        DEFAULT_PRICING
+            .iter()
+            .find(|(pattern, _)| model.contains(pattern))
+            .map(|(_, pricing)| *pricing)
+    })
+}
+
+pub fn estimate_entry_cost(
+    entry: &LocalUsageEntry,
+    overrides: &BTreeMap<String, ModelPricing>,
+) -> Option<f64> {
+    pricing_for_model(&entry.model, overrides).map(|pricing| pricing.cost(&entry.tokens))
+}
+
+pub fn get_cost_estimate(
+    range: &str,
+    overrides: &BTreeMap<String, ModelPricing>,
+) -> Result<CostEstimate, AppError> {
+    let entries = local_usage::load_entries_since(local_usage::range_start(range))?;
+    Ok(build_cost_estimate(&entries, overrides))
+}
+
+/// Estimated cost of everything sent since local midnight, for the tray tooltip.
+pub fn get_cost_today(overrides: &BTreeMap<String, ModelPricing>) -> Result<f64, AppError> {
+    let midnight = Local::now()
+        .date_naive()
+        .and_hms_opt(0, 0, 0)
+        .and_then(|naive| naive.and_local_timezone(Local).earliest())
+        .map(|local| local.to_utc())
+        .unwrap_or_else(chrono::Utc::now);
+
+    let entries = local_usage::load_entries_since(midnight)?;
+    Ok(entries
+        .iter()
+        .filter_map(|entry| estimate_entry_cost(entry, overrides))
+        .sum())
+}
+
+pub fn build_cost_estimate(
+    entries: &[LocalUsageEntry],
+    overrides: &BTreeMap<String, ModelPricing>,
+) -> CostEstimate {
+    let mut daily: BTreeMap<NaiveDate, CostBucket> = BTreeMap::new();
+    let mut weekly: BTreeMap<NaiveDate, CostBucket> = BTreeMap::new();
+    let mut unpriced_models = Vec::new();
+    let mut total_cost_usd = 0.0;
+
+    for entry in entries {
+        let Some(cost) = estimate_entry_cost(entry, overrides) else {
+            if !unpriced_models.contains(&entry.model) {
+                unpriced_models.push(entry.model.clone());
+            }
+            continue;
+        };
+
+        let day = entry.timestamp.with_timezone(&Local).date_naive();
+        let week_start = day - chrono::Duration::days(day.weekday().num_days_from_monday() as i64);
+
+        for (buckets, start) in [(&mut daily, day), (&mut weekly, week_start)] {
+            let bucket = buckets.entry(start).or_insert_with(|| CostBucket {
+                period_start: start.to_string(),
+                cost_usd: 0.0,
+                tokens: TokenCounts::default(),
+            });
+            bucket.cost_usd += cost;
+            bucket.tokens.add(&entry.tokens);
+        }
+
+        total_cost_usd += cost;
+    }
+
+    unpriced_models.sort();
+
+    CostEstimate {
+        total_cost_usd,
+        daily: daily.into_values().collect(),
+        weekly: weekly.into_values().collect(),
+        unpriced_models,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{DateTime, Utc};
+
+    fn entry(model: &str, timestamp: &str, input_tokens: u64) -> LocalUsageEntry {
+        LocalUsageEntry {
+            message_key: format!("{model}:{timestamp}"),
+            timestamp: DateTime::parse_from_rfc3339(timestamp)
+                .unwrap()
+                .with_timezone(&Utc),
+            project: "repo".to_string(),
+            model: model.to_string(),
+            tokens: TokenCounts {
+                input_tokens,
+                ..TokenCounts::default()
+            },
+        }
+    }
+
+    #[test]
+    fn resolves_default_pricing_by_family() {
+        let overrides = BTreeMap::new();
+        let opus = pricing_for_model("claude-opus-4-1-20250805", &overrides).unwrap();
+        assert_eq!(opus.input, 15.0);
+        let new_opus = pricing_for_model("claude-opus-4-5-20251101", &overrides).unwrap();
+        assert_eq!(new_opus.input, 5.0);
+        let sonnet = pricing_for_model("claude-sonnet-4-5", &overrides).unwrap();
+        assert_eq!(sonnet.output, 15.0);
+        assert!(pricing_for_model("gpt-5", &overrides).is_none());
+    }
+
+    #[test]
+    fn user_override_takes_precedence() {
+        let mut overrides = BTreeMap::new();
+        overrides.insert("sonnet".to_string(), ModelPricing::new(1.0, 2.0, 0.0, 0.0));
+        let pricing = pricing_for_model("claude-sonnet-4", &overrides).unwrap();
+        assert_eq!(pricing.input, 1.0);
+    }
+
+    #[test]
+    fn computes_cost_per_million_tokens() {
+        let pricing = ModelPricing::new(3.0, 15.0, 3.75, 0.3);
+        let tokens = TokenCounts {
+            input_tokens: 1_000_000,
+            output_tokens: 100_000,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+        };
+        assert!((pricing.cost(&tokens) - 4.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn groups_costs_and_reports_unpriced_models() {
+        let entries = vec![
+            entry("claude-sonnet-4", "2026-04-13T12:00:00Z", 1_000_000),
+            entry("claude-sonnet-4", "2026-04-14T12:00:00Z", 1_000_000),
+            entry("mystery-model", "2026-04-14T12:00:00Z", 1_000_000),
+        ];
+
+        let estimate = build_cost_estimate(&entries, &BTreeMap::new());
+        assert!((estimate.total_cost_usd - 6.0).abs() < 1e-9);
+        assert_eq!(estimate.daily.len(), 2);
+        assert_eq!(estimate.unpriced_models, vec!["mystery-model".to_string()]);
+    }
+}
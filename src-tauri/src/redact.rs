@@ -0,0 +1,55 @@
+//! Secret Redaction
+//!
+//! The one place credentials are masked in text that leaves the process: every
+//! log line (all targets of the log plugin), crash reports and Sentry events,
+//! error messages sent to the UI (including the last-fetch diagnostics), and
+//! response snippets quoted in `Server` errors.
+
+use regex::Regex;
+use std::borrow::Cow;
+use std::sync::LazyLock;
+
+/// Session keys and cookies, API keys, bearer tokens, JWTs, `key=value` /
+/// `"key": "value"` secrets and email addresses.
+static SECRET_PATTERN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"(?i)(?P<key>__secure-session|session_?key|access_token|refresh_token|id_token|token|api_key|password|secret)(?P<sep>"?\s*[:=]\s*"?)[^"&;,\s}]+|bearer\s+[a-z0-9._~+/=-]+|sk-ant-[a-z0-9_-]+|eyJ[a-z0-9_-]+\.[a-z0-9_.-]+|[a-z0-9._%+-]+@[a-z0-9.-]+\.[a-z]{2,}"#,
+    )
+    .expect("secret pattern is valid")
+});
+
+/// `text` with secrets replaced by `[redacted]`, keeping the key of
+/// `key=value` pairs so the message still says what was there.
+pub fn redact(text: &str) -> Cow<'_, str> {
+    SECRET_PATTERN.replace_all(text, |caps: &regex::Captures| {
+        match (caps.name("key"), caps.name("sep")) {
+            (Some(key), Some(sep)) => format!("{}{}[redacted]", key.as_str(), sep.as_str()),
+            _ => "[redacted]".to_string(),
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn masks_credentials_in_messages() {
+        assert_eq!(
+            redact("Cookie: sessionKey=sk-ant-sid01-abc; __Secure-session=xyz"),
+            "Cookie: sessionKey=[redacted]; __Secure-session=[redacted]"
+        );
+        assert_eq!(
+            redact("Authorization: Bearer abc.def-123"),
+            "Authorization: [redacted]"
+        );
+        assert_eq!(
+            redact("key sk-ant-api03-abc leaked"),
+            "key [redacted] leaked"
+        );
+        assert_eq!(
+            redact("Usage refresh failed for provider=claude: Rate limited"),
+            "Usage refresh failed for provider=claude: Rate limited"
+        );
+    }
+}
@@ -0,0 +1,497 @@
+//! Usage Reports
+//!
+//! `generate_report` writes a self-contained Markdown or HTML summary of a
+//! history range for expensing or retros: each window's peak and average, how
+//! many of its periods reached 100%, the local cost estimate when transcripts
+//! are available, and a chart per provider as inline SVG (raw HTML, which
+//! Markdown renderers pass through).
+
+use crate::error::AppError;
+use crate::history::{self, UsageHistoryPoint};
+use crate::pricing::{self, CostEstimate, ModelPricing};
+use crate::types::ProviderKind;
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// A reset moves `resets_at` forward by far more than fetch-to-fetch jitter
+/// (the same rule as the `usage_resets` view).
+const NEW_PERIOD_THRESHOLD_MS: i64 = 30 * 60 * 1000;
+
+const CHART_WIDTH: f64 = 640.0;
+const CHART_HEIGHT: f64 = 200.0;
+const CHART_PADDING: f64 = 32.0;
+/// Columns a chart line is reduced to, keeping each column's highest value.
+const CHART_COLUMNS: usize = 240;
+const CHART_COLORS: [&str; 6] = [
+    "#d97757", "#3b82f6", "#10b981", "#a855f7", "#f59e0b", "#64748b",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Type, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+/// One usage window over the report range.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct WindowSummary {
+    pub key: String,
+    pub label: String,
+    pub peak: f64,
+    pub peaked_at: DateTime<Utc>,
+    pub average: f64,
+    /// Reset periods in which the window reached 100%.
+    pub periods_at_limit: usize,
+}
+
+struct ProviderSection {
+    provider: ProviderKind,
+    windows: Vec<WindowSummary>,
+    chart: String,
+}
+
+struct Report {
+    range: String,
+    generated_at: DateTime<Utc>,
+    providers: Vec<ProviderSection>,
+    cost: Option<CostEstimate>,
+}
+
+/// Write a report of `range` (a history range preset such as `7d`) to `path`.
+pub fn generate_report(
+    range: &str,
+    format: ReportFormat,
+    pricing_overrides: &BTreeMap<String, ModelPricing>,
+    path: &Path,
+) -> Result<(), AppError> {
+    let to = Utc::now();
+    let from = to - chrono::Duration::hours(history::get_range_hours(range) as i64);
+
+    let mut providers = Vec::new();
+    for provider in [
+        ProviderKind::Claude,
+        ProviderKind::Codex,
+        ProviderKind::Ollama,
+    ] {
+        let points = history::get_full_usage_history(provider, range)
+            .map_err(|e| AppError::Storage(format!("Failed to read usage history: {e}")))?;
+        if points.is_empty() {
+            continue;
+        }
+        providers.push(ProviderSection {
+            provider,
+            windows: summarize_windows(&points),
+            chart: render_chart(&points, from, to),
+        });
+    }
+
+    let cost = match pricing::get_cost_estimate(range, pricing_overrides) {
+        Ok(cost) if !cost.daily.is_empty() => Some(cost),
+        Ok(_) => None,
+        Err(e) => {
+            log::debug!("Leaving the cost estimate out of the report: {e}");
+            None
+        }
+    };
+
+    let report = Report {
+        range: range.to_string(),
+        generated_at: to,
+        providers,
+        cost,
+    };
+    let contents = match format {
+        ReportFormat::Markdown => render_markdown(&report),
+        ReportFormat::Html => render_html(&report),
+    };
+    std::fs::write(path, contents)
+        .map_err(|e| AppError::Storage(format!("Failed to write {}: {e}", path.display())))?;
+    log::info!("Wrote usage report to {}", path.display());
+    Ok(())
+}
+
+/// Each window of `provider` from `from` to `to`, as in a report.
+pub(crate) fn summarize_period(
+    provider: ProviderKind,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Vec<WindowSummary>, AppError> {
+    let points = history::get_usage_history_between(provider, from, to)
+        .map_err(|e| AppError::Storage(format!("Failed to read usage history: {e}")))?;
+    Ok(summarize_windows(&points))
+}
+
+/// Samples grouped by window key, each group in timestamp order.
+fn samples_by_window(
+    points: &[UsageHistoryPoint],
+) -> BTreeMap<&str, Vec<(DateTime<Utc>, &UsageHistoryPoint)>> {
+    let mut by_window: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+    for point in points {
+        if let Some(at) = history::parse_timestamp(&point.timestamp) {
+            by_window
+                .entry(point.window_key.as_str())
+                .or_default()
+                .push((at, point));
+        }
+    }
+    for samples in by_window.values_mut() {
+        samples.sort_by_key(|(at, _)| *at);
+    }
+    by_window
+}
+
+fn summarize_windows(points: &[UsageHistoryPoint]) -> Vec<WindowSummary> {
+    samples_by_window(points)
+        .into_values()
+        .filter_map(|samples| summarize_window(&samples))
+        .collect()
+}
+
+fn summarize_window(samples: &[(DateTime<Utc>, &UsageHistoryPoint)]) -> Option<WindowSummary> {
+    let (peaked_at, peak) = samples
+        .iter()
+        .max_by(|(_, a), (_, b)| a.utilization.total_cmp(&b.utilization))?;
+    let average = samples.iter().map(|(_, s)| s.utilization).sum::<f64>() / samples.len() as f64;
+
+    let mut periods_at_limit = 0;
+    let mut period_reset: Option<i64> = None;
+    let mut period_at_limit = false;
+    for (_, sample) in samples {
+        let new_period = match (period_reset, sample.resets_at_epoch_ms) {
+            (Some(previous), Some(reset)) => reset - previous > NEW_PERIOD_THRESHOLD_MS,
+            _ => false,
+        };
+        if new_period {
+            periods_at_limit += usize::from(period_at_limit);
+            period_at_limit = false;
+        }
+        period_reset = sample.resets_at_epoch_ms.or(period_reset);
+        period_at_limit |= sample.utilization >= 100.0;
+    }
+    periods_at_limit += usize::from(period_at_limit);
+
+    Some(WindowSummary {
+        key: peak.window_key.clone(),
+        label: peak.label.clone(),
+        peak: peak.utilization,
+        peaked_at: *peaked_at,
+        average,
+        periods_at_limit,
+    })
+}
+
+/// Line chart of every window from `from` to `to`, 0–100% on the y axis.
+fn render_chart(points: &[UsageHistoryPoint], from: DateTime<Utc>, to: DateTime<Utc>) -> String {
+    let span_ms = (to - from).num_milliseconds().max(1) as f64;
+    let plot_width = CHART_WIDTH - 2.0 * CHART_PADDING;
+    let plot_height = CHART_HEIGHT - 2.0 * CHART_PADDING;
+    let y = |utilization: f64| {
+        CHART_PADDING + (1.0 - utilization.clamp(0.0, 100.0) / 100.0) * plot_height
+    };
+
+    let mut svg = vec![format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{CHART_WIDTH}" height="{CHART_HEIGHT}" viewBox="0 0 {CHART_WIDTH} {CHART_HEIGHT}" font-family="sans-serif" font-size="10">"#
+    )];
+    for utilization in [0.0, 50.0, 100.0] {
+        svg.push(format!(
+            r##"<line x1="{CHART_PADDING}" y1="{y:.1}" x2="{x2}" y2="{y:.1}" stroke="#ddd"/><text x="4" y="{ty:.1}" fill="#666">{utilization}%</text>"##,
+            x2 = CHART_WIDTH - CHART_PADDING,
+            y = y(utilization),
+            ty = y(utilization) + 3.0,
+        ));
+    }
+    svg.push(format!(
+        r##"<text x="{CHART_PADDING}" y="{ty}" fill="#666">{start}</text><text x="{x}" y="{ty}" fill="#666" text-anchor="end">{end}</text>"##,
+        ty = CHART_HEIGHT - CHART_PADDING + 12.0,
+        x = CHART_WIDTH - CHART_PADDING,
+        start = format_local(from),
+        end = format_local(to),
+    ));
+
+    for (index, samples) in samples_by_window(points).into_values().enumerate() {
+        let color = CHART_COLORS[index % CHART_COLORS.len()];
+        let mut columns: BTreeMap<usize, f64> = BTreeMap::new();
+        for (at, sample) in &samples {
+            let fraction = ((*at - from).num_milliseconds() as f64 / span_ms).clamp(0.0, 1.0);
+            let column = (fraction * (CHART_COLUMNS - 1) as f64).round() as usize;
+            let highest = columns.entry(column).or_insert(sample.utilization);
+            *highest = highest.max(sample.utilization);
+        }
+        let line: Vec<String> = columns
+            .iter()
+            .map(|(column, utilization)| {
+                let x = CHART_PADDING + *column as f64 / (CHART_COLUMNS - 1) as f64 * plot_width;
+                format!("{x:.1},{:.1}", y(*utilization))
+            })
+            .collect();
+        svg.push(format!(
+            r#"<polyline fill="none" stroke="{color}" stroke-width="1.5" points="{}"/>"#,
+            line.join(" ")
+        ));
+
+        let label = samples
+            .first()
+            .map(|(_, sample)| sample.label.as_str())
+            .unwrap_or_default();
+        svg.push(format!(
+            r#"<text x="{x}" y="{ty}" fill="{color}">{label}</text>"#,
+            x = CHART_PADDING + index as f64 * 110.0,
+            ty = CHART_HEIGHT - 6.0,
+            label = escape_html(label),
+        ));
+    }
+    svg.push("</svg>".to_string());
+    svg.join("\n")
+}
+
+fn format_local(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M")
+        .to_string()
+}
+
+fn provider_name(provider: ProviderKind) -> &'static str {
+    match provider {
+        ProviderKind::Claude => "Claude",
+        ProviderKind::Codex => "Codex",
+        ProviderKind::Ollama => "Ollama",
+    }
+}
+
+/// Mirrors the presets of `history::get_range_hours`, including its fallback.
+fn range_label(range: &str) -> &'static str {
+    match range {
+        "1h" => "Last hour",
+        "6h" => "Last 6 hours",
+        "7d" => "Last 7 days",
+        "30d" => "Last 30 days",
+        _ => "Last 24 hours",
+    }
+}
+
+const WINDOW_HEADERS: [&str; 5] = ["Window", "Peak", "Peak at", "Average", "Periods at 100%"];
+const COST_HEADERS: [&str; 3] = ["Day", "Estimated cost", "Tokens"];
+
+fn window_rows(windows: &[WindowSummary]) -> Vec<Vec<String>> {
+    windows
+        .iter()
+        .map(|window| {
+            vec![
+                window.label.clone(),
+                format!("{:.1}%", window.peak),
+                format_local(window.peaked_at),
+                format!("{:.1}%", window.average),
+                window.periods_at_limit.to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn cost_rows(cost: &CostEstimate) -> Vec<Vec<String>> {
+    cost.daily
+        .iter()
+        .map(|day| {
+            vec![
+                day.period_start.clone(),
+                format!("${:.2}", day.cost_usd),
+                day.tokens.total().to_string(),
+            ]
+        })
+        .collect()
+}
+
+fn cost_summary(cost: &CostEstimate) -> String {
+    format!(
+        "${:.2} in total, estimated from local Claude Code transcripts at API prices.",
+        cost.total_cost_usd
+    )
+}
+
+fn unpriced_note(cost: &CostEstimate) -> Option<String> {
+    (!cost.unpriced_models.is_empty()).then(|| {
+        format!(
+            "Not included (no known price): {}.",
+            cost.unpriced_models.join(", ")
+        )
+    })
+}
+
+fn render_markdown(report: &Report) -> String {
+    let table = |headers: &[&str], rows: Vec<Vec<String>>| {
+        let mut lines = vec![
+            format!("| {} |", headers.join(" | ")),
+            format!("|{}", "---|".repeat(headers.len())),
+        ];
+        lines.extend(rows.into_iter().map(|row| {
+            let cells: Vec<String> = row.iter().map(|cell| cell.replace('|', "\\|")).collect();
+            format!("| {} |", cells.join(" | "))
+        }));
+        lines.join("\n")
+    };
+
+    let mut out = vec![
+        "# Usage report".to_string(),
+        format!(
+            "{}, generated {}.",
+            range_label(&report.range),
+            format_local(report.generated_at)
+        ),
+    ];
+    if report.providers.is_empty() {
+        out.push("No usage history in this range.".to_string());
+    }
+    for section in &report.providers {
+        out.push(format!("## {}", provider_name(section.provider)));
+        out.push(table(&WINDOW_HEADERS, window_rows(&section.windows)));
+        out.push(section.chart.clone());
+    }
+    if let Some(cost) = &report.cost {
+        out.push("## Estimated cost".to_string());
+        out.push(cost_summary(cost));
+        out.push(table(&COST_HEADERS, cost_rows(cost)));
+        out.extend(unpriced_note(cost));
+    }
+    out.join("\n\n") + "\n"
+}
+
+fn render_html(report: &Report) -> String {
+    let table = |headers: &[&str], rows: Vec<Vec<String>>| {
+        let head: String = headers
+            .iter()
+            .map(|header| format!("<th>{header}</th>"))
+            .collect();
+        let body: String = rows
+            .iter()
+            .map(|row| {
+                let cells: String = row
+                    .iter()
+                    .map(|cell| format!("<td>{}</td>", escape_html(cell)))
+                    .collect();
+                format!("<tr>{cells}</tr>\n")
+            })
+            .collect();
+        format!("<table>\n<tr>{head}</tr>\n{body}</table>")
+    };
+
+    let mut body = vec![
+        "<h1>Usage report</h1>".to_string(),
+        format!(
+            "<p>{}, generated {}.</p>",
+            range_label(&report.range),
+            format_local(report.generated_at)
+        ),
+    ];
+    if report.providers.is_empty() {
+        body.push("<p>No usage history in this range.</p>".to_string());
+    }
+    for section in &report.providers {
+        body.push(format!("<h2>{}</h2>", provider_name(section.provider)));
+        body.push(table(&WINDOW_HEADERS, window_rows(&section.windows)));
+        body.push(section.chart.clone());
+    }
+    if let Some(cost) = &report.cost {
+        body.push("<h2>Estimated cost</h2>".to_string());
+        body.push(format!("<p>{}</p>", cost_summary(cost)));
+        body.push(table(&COST_HEADERS, cost_rows(cost)));
+        body.extend(unpriced_note(cost).map(|note| format!("<p>{}</p>", escape_html(&note))));
+    }
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Usage report</title>
+<style>
+body {{ font-family: sans-serif; max-width: 720px; margin: 2em auto; color: #222; }}
+table {{ border-collapse: collapse; margin: 1em 0; }}
+th, td {{ border: 1px solid #ddd; padding: 4px 8px; text-align: left; }}
+</style>
+</head>
+<body>
+{}
+</body>
+</html>
+"#,
+        body.join("\n")
+    )
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(minute: i64, utilization: f64, resets_at: &str) -> UsageHistoryPoint {
+        let timestamp = DateTime::parse_from_rfc3339("2026-04-13T10:00:00+00:00").unwrap()
+            + chrono::Duration::minutes(minute);
+        UsageHistoryPoint {
+            id: minute,
+            provider: ProviderKind::Claude,
+            timestamp: timestamp.to_rfc3339(),
+            window_key: "five_hour".to_string(),
+            label: "5 Hour".to_string(),
+            utilization,
+            resets_at: Some(resets_at.to_string()),
+            resets_at_epoch_ms: crate::types::reset_epoch_ms(Some(resets_at)),
+        }
+    }
+
+    #[test]
+    fn counts_periods_that_reached_the_limit() {
+        let points = [
+            point(0, 60.0, "2026-04-13T12:00:00+00:00"),
+            point(60, 100.0, "2026-04-13T12:00:10+00:00"),
+            point(110, 100.0, "2026-04-13T12:00:00+00:00"),
+            point(130, 5.0, "2026-04-13T17:00:00+00:00"),
+            point(400, 100.0, "2026-04-13T22:00:00+00:00"),
+            point(500, 15.0, "2026-04-14T03:00:00+00:00"),
+        ];
+
+        let windows = summarize_windows(&points);
+        assert_eq!(windows.len(), 1);
+        assert_eq!(windows[0].label, "5 Hour");
+        assert_eq!(windows[0].peak, 100.0);
+        assert_eq!(windows[0].average, 380.0 / 6.0);
+        assert_eq!(windows[0].periods_at_limit, 2);
+    }
+
+    #[test]
+    fn renders_escaped_tables_with_inline_charts() {
+        let mut points = vec![point(0, 40.0, "2026-04-13T12:00:00+00:00")];
+        points[0].label = "Opus <max> | 7 Day".to_string();
+        let from = DateTime::parse_from_rfc3339("2026-04-13T09:00:00+00:00")
+            .unwrap()
+            .with_timezone(&Utc);
+        let report = Report {
+            range: "7d".to_string(),
+            generated_at: from,
+            providers: vec![ProviderSection {
+                provider: ProviderKind::Claude,
+                windows: summarize_windows(&points),
+                chart: render_chart(&points, from, from + chrono::Duration::hours(2)),
+            }],
+            cost: None,
+        };
+
+        let markdown = render_markdown(&report);
+        assert!(markdown.starts_with("# Usage report\n\nLast 7 days, generated "));
+        assert!(markdown.contains("| Opus <max> \\| 7 Day | 40.0% |"));
+        assert!(markdown.contains("<polyline"));
+        assert!(!markdown.contains("Estimated cost"));
+
+        let html = render_html(&report);
+        assert!(html.contains("<td>Opus &lt;max&gt; | 7 Day</td>"));
+        assert!(html.contains("<svg"));
+    }
+}
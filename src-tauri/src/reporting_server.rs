@@ -0,0 +1,193 @@
+//! Reporting Endpoint
+//!
+//! Serves the reporting views as JSON on localhost, for dashboards (e.g.
+//! Grafana's JSON and Infinity datasources) that can't open the SQLite file.
+//! Off unless `[integrations] reporting_port` is set, read-only, and answers
+//! only `GET /usage_hourly` and `GET /usage_resets`.
+
+use crate::commands::run_blocking;
+use crate::error::AppError;
+use crate::history::{self, ReportingView};
+use chrono::{DateTime, SecondsFormat, Utc};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+/// Requests are a single line and a few headers; anything larger is refused.
+const MAX_REQUEST_BYTES: usize = 8 * 1024;
+
+/// A client that doesn't finish sending its request in time is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Listen on `127.0.0.1:port` when a port is configured.
+pub fn start(port: Option<u16>) {
+    let Some(port) = port else {
+        return;
+    };
+    tauri::async_runtime::spawn(async move {
+        let listener = match TcpListener::bind(("127.0.0.1", port)).await {
+            Ok(listener) => listener,
+            Err(e) => {
+                log::error!("Failed to start the reporting endpoint on port {port}: {e}");
+                return;
+            }
+        };
+        log::info!("Serving reporting views on http://127.0.0.1:{port}");
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tauri::async_runtime::spawn(handle_connection(stream));
+                }
+                Err(e) => log::warn!("Reporting endpoint failed to accept a connection: {e}"),
+            }
+        }
+    });
+}
+
+async fn handle_connection(mut stream: TcpStream) {
+    let (status, body) = match tokio::time::timeout(REQUEST_TIMEOUT, read_head(&mut stream)).await {
+        Ok(Some(head)) => respond(&head).await,
+        _ => (400, error_body("Malformed request")),
+    };
+    let response = format!(
+        "HTTP/1.1 {status} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        reason_phrase(status),
+        body.len()
+    );
+    if let Err(e) = stream.write_all(response.as_bytes()).await {
+        log::debug!("Failed to answer a reporting request: {e}");
+    }
+    let _ = stream.shutdown().await;
+}
+
+/// The request line and headers, or None when they are too large or the
+/// connection closed first.
+async fn read_head(stream: &mut TcpStream) -> Option<String> {
+    let mut head = Vec::new();
+    let mut buffer = [0u8; 1024];
+    while !head.windows(4).any(|window| window == b"\r\n\r\n") {
+        let read = stream.read(&mut buffer).await.ok()?;
+        if read == 0 || head.len() + read > MAX_REQUEST_BYTES {
+            return None;
+        }
+        head.extend_from_slice(&buffer[..read]);
+    }
+    String::from_utf8(head).ok()
+}
+
+async fn respond(head: &str) -> (u16, String) {
+    let mut request_line = head.lines().next().unwrap_or_default().split_whitespace();
+    let (Some(method), Some(target)) = (request_line.next(), request_line.next()) else {
+        return (400, error_body("Malformed request"));
+    };
+    if method != "GET" {
+        return (405, error_body("Only GET is supported"));
+    }
+    let Ok(url) = tauri::Url::parse(&format!("http://localhost{target}")) else {
+        return (400, error_body("Malformed request target"));
+    };
+    let Some(view) = ReportingView::from_name(url.path().trim_start_matches('/')) else {
+        return (
+            404,
+            error_body("Unknown view; use /usage_hourly or /usage_resets"),
+        );
+    };
+
+    let mut provider = None;
+    let mut from = None;
+    let mut to = None;
+    for (key, value) in url.query_pairs() {
+        match key.as_ref() {
+            "provider" => provider = Some(value.into_owned()),
+            "from" | "to" => {
+                let Some(time) = parse_time(&value) else {
+                    let message = format!("Invalid {key}: expected epoch milliseconds or RFC3339");
+                    return (400, error_body(&message));
+                };
+                if key == "from" {
+                    from = Some(time);
+                } else {
+                    to = Some(time);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let rows = run_blocking(move || {
+        history::query_reporting_view(view, provider.as_deref(), from.as_deref(), to.as_deref())
+            .map_err(|e| AppError::Storage(e.to_string()))
+    })
+    .await;
+    let body = rows.and_then(|rows| {
+        serde_json::to_string(&rows)
+            .map_err(|e| AppError::Server(format!("Failed to encode rows: {e}")))
+    });
+    match body {
+        Ok(body) => (200, body),
+        Err(e) => {
+            log::warn!("Failed to query reporting view: {}", e.message());
+            (500, error_body(&e.message()))
+        }
+    }
+}
+
+/// A time bound as Grafana sends it (`${__from}` is epoch milliseconds) or
+/// RFC3339, normalised to UTC RFC3339 for the view query.
+fn parse_time(value: &str) -> Option<String> {
+    let time = match value.parse::<i64>() {
+        Ok(epoch_ms) => DateTime::from_timestamp_millis(epoch_ms)?,
+        Err(_) => DateTime::parse_from_rfc3339(value)
+            .ok()?
+            .with_timezone(&Utc),
+    };
+    Some(time.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+fn error_body(message: &str) -> String {
+    serde_json::json!({ "error": message }).to_string()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        _ => "Internal Server Error",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_grafana_and_rfc3339_times() {
+        assert_eq!(
+            parse_time("1776074700000").as_deref(),
+            Some("2026-04-13T10:05:00Z")
+        );
+        assert_eq!(
+            parse_time("2026-04-13T12:05:00+02:00").as_deref(),
+            Some("2026-04-13T10:05:00Z")
+        );
+        assert_eq!(parse_time("yesterday"), None);
+    }
+
+    #[tokio::test]
+    async fn refuses_unknown_requests() {
+        assert_eq!(respond("POST /usage_hourly HTTP/1.1\r\n\r\n").await.0, 405);
+        assert_eq!(
+            respond("GET /usage_history_v2 HTTP/1.1\r\n\r\n").await.0,
+            404
+        );
+        assert_eq!(
+            respond("GET /usage_hourly?from=soon HTTP/1.1\r\n\r\n")
+                .await
+                .0,
+            400
+        );
+        assert_eq!(respond("\r\n\r\n").await.0, 400);
+    }
+}
@@ -0,0 +1,47 @@
+//! Secret Strings
+//!
+//! Session tokens are held as `SecretString`, which wipes its buffer when the
+//! last handle drops and prints as `[redacted]`, so a token neither lingers in
+//! freed memory nor ends up in a log through a `Debug` derive.
+
+use std::fmt;
+use std::sync::Arc;
+use zeroize::Zeroizing;
+
+/// Clones share one buffer, so handing a token to a fetch task doesn't leave
+/// another copy behind.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SecretString(Arc<Zeroizing<String>>);
+
+impl SecretString {
+    /// The token itself, for a request header or the keychain. Keep the borrow
+    /// short; anything built from it is an unprotected copy.
+    pub fn expose(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> Self {
+        Self(Arc::new(Zeroizing::new(secret)))
+    }
+}
+
+impl fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn never_prints_the_secret() {
+        let secret = SecretString::from("sk-ant-sid01-secret".to_string());
+        assert_eq!(format!("{secret:?}"), "[redacted]");
+        assert_eq!(format!("{:?}", Some(secret.clone())), "Some([redacted])");
+        assert_eq!(secret.expose(), "sk-ant-sid01-secret");
+    }
+}
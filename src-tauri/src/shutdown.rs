@@ -0,0 +1,48 @@
+//! Graceful Shutdown
+//!
+//! On `RunEvent::ExitRequested` the refresh loop is stopped, state that is
+//! otherwise only written when it changes is flushed — the notification state
+//! and the settings store's debounced save — and the history database is
+//! closed so SQLite finishes its journal instead of leaving it to recovery.
+
+use crate::auto_refresh::stop_auto_refresh;
+use crate::commands::{persist_notification_state, settings_store};
+use crate::history;
+use crate::types::AppState;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use tauri::Manager;
+
+/// Exit can be requested more than once; only the first flushes.
+static FLUSHED: AtomicBool = AtomicBool::new(false);
+
+pub fn flush(app: &tauri::AppHandle) {
+    if FLUSHED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    stop_auto_refresh();
+
+    let state = app
+        .try_state::<Arc<AppState>>()
+        .filter(|_| !history::is_incognito());
+    if let Some(state) = state {
+        let notification_state =
+            tauri::async_runtime::block_on(async { state.notification_state.lock().await.clone() });
+        if let Err(e) = persist_notification_state(app, &notification_state) {
+            log::warn!("Failed to persist notification state on exit: {e}");
+        }
+    }
+    match settings_store(app) {
+        Ok(store) => {
+            if let Err(e) = store.save() {
+                log::warn!("Failed to save settings on exit: {e}");
+            }
+        }
+        Err(e) => log::warn!("Failed to open settings on exit: {e}"),
+    }
+
+    if let Err(e) = history::close() {
+        log::warn!("Failed to close history database: {e}");
+    }
+    log::info!("Shutdown complete");
+}
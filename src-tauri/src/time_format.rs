@@ -0,0 +1,144 @@
+//! Human-readable reset times and durations for the tray and notifications.
+//!
+//! Reset timestamps arrive as RFC3339 (usually UTC); these helpers render them
+//! in the user's local timezone alongside a relative duration.
+
+use crate::i18n::{Text, tf, weekday_short};
+use chrono::{DateTime, Datelike, Local, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Clock style preference for times rendered by the backend.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum TimeFormat {
+    /// 12-hour in regions that conventionally use it (e.g. `en-US`), otherwise 24-hour.
+    #[default]
+    System,
+    TwelveHour,
+    TwentyFourHour,
+}
+
+static TWELVE_HOUR: AtomicBool = AtomicBool::new(false);
+
+/// Apply a time format preference. Called at startup and when preferences change.
+pub fn set_time_format(format: TimeFormat) {
+    TWELVE_HOUR.store(
+        uses_twelve_hour(format, sys_locale::get_locale().as_deref()),
+        Ordering::Relaxed,
+    );
+}
+
+fn uses_twelve_hour(format: TimeFormat, system_locale: Option<&str>) -> bool {
+    match format {
+        TimeFormat::TwelveHour => true,
+        TimeFormat::TwentyFourHour => false,
+        TimeFormat::System => system_locale.is_some_and(|locale| {
+            let region = locale
+                .split(['-', '_', '.'])
+                .nth(1)
+                .unwrap_or_default()
+                .to_ascii_uppercase();
+            locale.to_ascii_lowercase().starts_with("en")
+                && matches!(region.as_str(), "US" | "CA" | "AU" | "NZ" | "PH" | "IN")
+        }),
+    }
+}
+
+/// Compact duration such as `45m`, `2h 10m` or `3d 4h`.
+pub fn format_duration(minutes: i64) -> String {
+    let minutes = minutes.max(0);
+    let days = minutes / (24 * 60);
+    let hours = (minutes % (24 * 60)) / 60;
+    let mins = minutes % 60;
+
+    match (days, hours, mins) {
+        (0, 0, mins) => format!("{mins}m"),
+        (0, hours, 0) => format!("{hours}h"),
+        (0, hours, mins) => format!("{hours}h {mins}m"),
+        (days, 0, _) => format!("{days}d"),
+        (days, hours, _) => format!("{days}d {hours}h"),
+    }
+}
+
+/// `resets at 14:30, in 2h 10m` in the local timezone, or None when the
+/// timestamp is missing, invalid or already in the past.
+pub fn format_reset(resets_at: &str) -> Option<String> {
+    format_reset_in(resets_at, crate::clock::now(), &Local)
+}
+
+fn format_reset_in<Tz: TimeZone>(resets_at: &str, now: DateTime<Utc>, tz: &Tz) -> Option<String>
+where
+    Tz::Offset: std::fmt::Display,
+{
+    let reset = DateTime::parse_from_rfc3339(resets_at)
+        .ok()?
+        .with_timezone(&Utc);
+    let minutes = reset.signed_duration_since(now).num_minutes();
+    if minutes < 0 {
+        return None;
+    }
+
+    let local_reset = reset.with_timezone(tz);
+    let same_day = local_reset.date_naive() == now.with_timezone(tz).date_naive();
+    let clock = if TWELVE_HOUR.load(Ordering::Relaxed) {
+        "%-I:%M %p"
+    } else {
+        "%H:%M"
+    };
+    let at = if same_day {
+        local_reset.format(clock).to_string()
+    } else {
+        // chrono's `%a` is always English
+        format!(
+            "{} {}",
+            weekday_short(local_reset.weekday()),
+            local_reset.format(clock)
+        )
+    };
+
+    Some(tf(Text::ResetsAt, &[&at, &format_duration(minutes)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    #[test]
+    fn formats_durations() {
+        assert_eq!(format_duration(45), "45m");
+        assert_eq!(format_duration(120), "2h");
+        assert_eq!(format_duration(130), "2h 10m");
+        assert_eq!(format_duration(3 * 24 * 60 + 4 * 60 + 5), "3d 4h");
+        assert_eq!(format_duration(-5), "0m");
+    }
+
+    #[test]
+    fn formats_reset_in_local_timezone() {
+        let now = DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let tz = FixedOffset::east_opt(2 * 3600).unwrap();
+
+        assert_eq!(
+            format_reset_in("2026-04-13T12:30:00Z", now, &tz).as_deref(),
+            Some("resets at 14:30, in 2h 30m")
+        );
+        assert_eq!(
+            format_reset_in("2026-04-15T12:30:00Z", now, &tz).as_deref(),
+            Some("resets at Wed 14:30, in 2d 2h")
+        );
+        assert_eq!(format_reset_in("2026-04-13T09:00:00Z", now, &tz), None);
+    }
+
+    #[test]
+    fn picks_clock_style_from_preference_or_region() {
+        assert!(uses_twelve_hour(TimeFormat::TwelveHour, Some("de-DE")));
+        assert!(!uses_twelve_hour(TimeFormat::TwentyFourHour, Some("en-US")));
+        assert!(uses_twelve_hour(TimeFormat::System, Some("en_US.UTF-8")));
+        assert!(!uses_twelve_hour(TimeFormat::System, Some("en-GB")));
+        assert!(!uses_twelve_hour(TimeFormat::System, None));
+    }
+}
@@ -0,0 +1,71 @@
+//! macOS Time-Sensitive Alerts
+//!
+//! The notification plugin can't set an interruption level, so critical alerts
+//! the user opted into are posted through `UNUserNotificationCenter` with
+//! `UNNotificationInterruptionLevel::TimeSensitive`, letting them through Focus
+//! modes. Delivery at that level needs the
+//! `com.apple.developer.usernotifications.time-sensitive` entitlement in the
+//! signed bundle; without it macOS shows them as regular alerts.
+
+use block2::RcBlock;
+use objc2::runtime::Bool;
+use objc2_foundation::{NSBundle, NSError, NSString};
+use objc2_user_notifications::{
+    UNAuthorizationOptions, UNMutableNotificationContent, UNNotificationInterruptionLevel,
+    UNNotificationRequest, UNNotificationSound, UNUserNotificationCenter,
+};
+
+/// `UNUserNotificationCenter` throws outside an app bundle (e.g. `tauri dev`).
+fn is_bundled() -> bool {
+    NSBundle::mainBundle().bundleIdentifier().is_some()
+}
+
+/// Ask for notification permission. Called when the user opts in, since the
+/// plugin's permission doesn't cover this notification center.
+pub fn request_authorization() {
+    if !is_bundled() {
+        return;
+    }
+
+    let handler = RcBlock::new(|granted: Bool, _error: *mut NSError| {
+        if !granted.as_bool() {
+            log::warn!("Time-sensitive alerts were not authorized");
+        }
+    });
+    unsafe {
+        UNUserNotificationCenter::currentNotificationCenter()
+            .requestAuthorizationWithOptions_completionHandler(
+                UNAuthorizationOptions::Alert | UNAuthorizationOptions::Sound,
+                &handler,
+            );
+    }
+}
+
+/// Post a time-sensitive notification. Returns false when this process can't
+/// use the notification center, so the caller can fall back to the plugin.
+pub fn show(title: &str, body: &str) -> bool {
+    if !is_bundled() {
+        return false;
+    }
+
+    unsafe {
+        let content = UNMutableNotificationContent::new();
+        content.setTitle(&NSString::from_str(title));
+        content.setBody(&NSString::from_str(body));
+        content.setSound(Some(&UNNotificationSound::defaultSound()));
+        content.setInterruptionLevel(UNNotificationInterruptionLevel::TimeSensitive);
+
+        let identifier = NSString::from_str(&format!(
+            "usage-alert-{}",
+            chrono::Utc::now().timestamp_millis()
+        ));
+        let request = UNNotificationRequest::requestWithIdentifier_content_trigger(
+            &identifier,
+            &content,
+            None,
+        );
+        UNUserNotificationCenter::currentNotificationCenter()
+            .addNotificationRequest_withCompletionHandler(&request, None);
+    }
+    true
+}
@@ -0,0 +1,124 @@
+//! Claude Code Transcript Watcher
+//!
+//! Watches the Claude Code projects directory and emits `local-usage-changed`
+//! shortly after a transcript is written, so local token stats refresh within
+//! seconds instead of waiting for the next poll.
+
+use crate::commands::run_blocking;
+use crate::history::sync_local_usage;
+use crate::local_usage::get_projects_dir;
+use crate::types::LocalUsageChanged;
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use std::time::Duration;
+use tauri_specta::Event as _;
+use tokio::sync::mpsc;
+
+/// Quiet period required after the last write before emitting, so a streamed
+/// response produces a single event rather than one per line.
+const DEBOUNCE: Duration = Duration::from_secs(2);
+
+/// Start watching transcripts.
+/// Returns a handle that must be kept alive to continue receiving changes, or
+/// None when the directory does not exist or the watcher cannot be created.
+pub fn start_transcript_watcher(app: tauri::AppHandle) -> Option<RecommendedWatcher> {
+    let integrations = &crate::config::get().integrations;
+    if !integrations.local_usage || !integrations.watch_transcripts {
+        log::info!("Transcript watching disabled by configuration");
+        return None;
+    }
+
+    let projects_dir = get_projects_dir();
+    if !projects_dir.is_dir() {
+        log::info!(
+            "Claude Code projects directory {} not found, transcript watching disabled",
+            projects_dir.display()
+        );
+        return None;
+    }
+
+    let (tx, mut rx) = mpsc::unbounded_channel::<()>();
+    let mut watcher =
+        notify::recommended_watcher(move |result: notify::Result<Event>| match result {
+            Ok(event) if is_transcript_change(&event) => {
+                let _ = tx.send(());
+            }
+            Ok(_) => {}
+            Err(e) => log::warn!("Transcript watcher error: {e}"),
+        })
+        .inspect_err(|e| log::warn!("Failed to create transcript watcher: {e}"))
+        .ok()?;
+
+    watcher
+        .watch(&projects_dir, RecursiveMode::Recursive)
+        .inspect_err(|e| {
+            log::warn!(
+                "Failed to watch Claude Code projects directory {}: {e}",
+                projects_dir.display()
+            )
+        })
+        .ok()?;
+
+    tauri::async_runtime::spawn(async move {
+        while rx.recv().await.is_some() {
+            // Keep absorbing changes until the transcripts have been quiet for a moment
+            loop {
+                match tokio::time::timeout(DEBOUNCE, rx.recv()).await {
+                    Ok(Some(())) => continue,
+                    Ok(None) => return,
+                    Err(_) => break,
+                }
+            }
+
+            if let Err(e) = run_blocking(sync_local_usage).await {
+                log::debug!("Failed to sync local token history: {e}");
+            }
+            let _ = LocalUsageChanged.emit(&app);
+        }
+    });
+
+    Some(watcher)
+}
+
+fn is_transcript_change(event: &Event) -> bool {
+    (event.kind.is_create() || event.kind.is_modify())
+        && event
+            .paths
+            .iter()
+            .any(|path| path.extension().is_some_and(|ext| ext == "jsonl"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use notify::EventKind;
+    use notify::event::{CreateKind, ModifyKind, RemoveKind};
+    use std::path::PathBuf;
+
+    fn event(kind: EventKind, path: &str) -> Event {
+        Event::new(kind).add_path(PathBuf::from(path))
+    }
+
+    #[test]
+    fn detects_transcript_writes() {
+        assert!(is_transcript_change(&event(
+            EventKind::Modify(ModifyKind::Any),
+            "/p/-repo/session.jsonl"
+        )));
+        assert!(is_transcript_change(&event(
+            EventKind::Create(CreateKind::File),
+            "/p/-repo/session.jsonl"
+        )));
+    }
+
+    #[test]
+    fn ignores_other_files_and_removals() {
+        assert!(!is_transcript_change(&event(
+            EventKind::Modify(ModifyKind::Any),
+            "/p/-repo/notes.txt"
+        )));
+        assert!(!is_transcript_change(&event(
+            EventKind::Remove(RemoveKind::File),
+            "/p/-repo/session.jsonl"
+        )));
+    }
+}
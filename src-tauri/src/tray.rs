@@ -1,50 +1,573 @@
-use crate::types::UsageSnapshot;
+use crate::commands::{apply_compact_mode, apply_primary_organization};
+use crate::i18n::{Text, t, tf};
+use crate::notifications::minutes_until;
+use crate::time_format::format_duration;
+use crate::types::{
+    AppState, AutoRefreshConfig, CheckForUpdates, Organization, OrganizationUsage, Preferences,
+    Severity, SeverityConfig, UsageSnapshot, UsageWindow,
+};
 #[cfg(not(target_os = "macos"))]
+use crate::window_state;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use tauri::Manager;
 use tauri::{
-    Emitter, Runtime,
-    menu::{Menu, MenuEvent, MenuItemBuilder, PredefinedMenuItem},
+    Runtime, Theme,
+    image::Image,
+    menu::{
+        CheckMenuItem, CheckMenuItemBuilder, Menu, MenuEvent, MenuItem, MenuItemBuilder,
+        PredefinedMenuItem, Submenu, SubmenuBuilder,
+    },
     tray::{MouseButton, MouseButtonState, TrayIconBuilder, TrayIconEvent},
 };
 #[cfg(not(target_os = "macos"))]
 use tauri_plugin_positioner::{Position, WindowExt, on_tray_event};
+use tauri_specta::Event;
+
+/// Alternating dimmed/normal frames, about three seconds in total.
+const FLASH_FRAMES: u32 = 8;
+const FLASH_FRAME_MS: u64 = 400;
+
+/// Menu id prefix of the organization switcher items, followed by the id.
+const ORGANIZATION_ITEM_PREFIX: &str = "organization:";
+
+/// Glyph colours for the tray icon on Windows/Linux, picked against the OS theme.
+const LIGHT_GLYPH: [u8; 3] = [0xF5, 0xF5, 0xF5];
+const DARK_GLYPH: [u8; 3] = [0x1F, 0x1F, 0x1F];
+
+/// `provider:window` keys currently graded critical.
+static CRITICAL_WINDOWS: Mutex<BTreeSet<String>> = Mutex::new(BTreeSet::new());
+/// Whether the OS uses a light theme, so the tray needs a dark glyph.
+static LIGHT_THEME: AtomicBool = AtomicBool::new(false);
+/// False on Linux sessions with no StatusNotifier host, where the tray icon never appears.
+static TRAY_AVAILABLE: AtomicBool = AtomicBool::new(true);
+
+/// Whether the tray icon can be shown, so the window may hide into it.
+pub fn is_tray_available() -> bool {
+    TRAY_AVAILABLE.load(Ordering::Relaxed)
+}
+
+/// AppIndicator needs a StatusNotifier watcher on the session bus; without one
+/// (e.g. GNOME without the AppIndicator extension) the icon is silently dropped.
+#[cfg(target_os = "linux")]
+fn status_notifier_available() -> bool {
+    use dbus::blocking::Connection;
+
+    let Ok(connection) = Connection::new_session() else {
+        return false;
+    };
+    let proxy = connection.with_proxy(
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        std::time::Duration::from_millis(500),
+    );
+    proxy
+        .method_call(
+            "org.freedesktop.DBus",
+            "NameHasOwner",
+            ("org.kde.StatusNotifierWatcher",),
+        )
+        .map(|(has_owner,): (bool,)| has_owner)
+        .unwrap_or(false)
+}
+
+/// How additional organizations show up in the tray tooltip.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum TrayAggregation {
+    /// A line per organization below the primary one.
+    #[default]
+    PerOrganization,
+    /// One line with each metric's highest utilization across all organizations.
+    WorstCase,
+}
+
+/// `5 Hour: 40% | 7 Day: 12%`
+fn window_summary(windows: &[UsageWindow]) -> String {
+    windows
+        .iter()
+        .map(|window| format!("{}: {:.0}%", window.label, window.utilization))
+        .collect::<Vec<_>>()
+        .join(" | ")
+}
+
+/// One line per additional organization, e.g. `Acme — 5 Hour: 40% | 7 Day: 12%`.
+fn organization_lines(organizations: &[OrganizationUsage]) -> Vec<String> {
+    organizations
+        .iter()
+        .filter(|entry| !entry.usage.windows.is_empty())
+        .map(|entry| {
+            format!(
+                "{} — {}",
+                entry.organization.name,
+                window_summary(&entry.usage.windows)
+            )
+        })
+        .collect()
+}
+
+/// Each metric at its highest utilization across the primary and additional
+/// organizations, in the primary's order; metrics only others have come last.
+fn worst_case_windows(
+    primary: &[UsageWindow],
+    organizations: &[OrganizationUsage],
+) -> Vec<UsageWindow> {
+    let mut windows = primary.to_vec();
+    for window in organizations.iter().flat_map(|entry| &entry.usage.windows) {
+        match windows.iter_mut().find(|w| w.key == window.key) {
+            Some(worst) if window.utilization > worst.utilization => *worst = window.clone(),
+            Some(_) => {}
+            None => windows.push(window.clone()),
+        }
+    }
+    windows
+}
+
+/// Placeholder text for a metric the current snapshot doesn't have.
+const MISSING_METRIC: &str = "–";
+
+/// Render a tooltip template. `{key}` is a window's utilization and
+/// `{key}_resets_in` the time until it resets; `key` is a window key such as
+/// `five_hour`, or a suffix of one (`opus` for `seven_day_opus`). Missing
+/// metrics render as `–`; anything else in braces is kept as written.
+fn render_tooltip_template(template: &str, windows: &[UsageWindow], now: DateTime<Utc>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let Some(end) = after.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+        let name = &after[..end];
+        match template_value(name, windows, now) {
+            Some(value) => rendered.push_str(&value),
+            None => rendered.push_str(&rest[start..start + end + 2]),
+        }
+        rest = &after[end + 1..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// The value of a placeholder, or None if `name` isn't a placeholder at all.
+fn template_value(name: &str, windows: &[UsageWindow], now: DateTime<Utc>) -> Option<String> {
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+
+    let (key, resets_in) = match name.strip_suffix("_resets_in") {
+        Some(key) => (key, true),
+        None => (name, false),
+    };
+    let suffix = format!("_{key}");
+    let Some(window) = windows
+        .iter()
+        .find(|window| window.key == key)
+        .or_else(|| windows.iter().find(|window| window.key.ends_with(&suffix)))
+    else {
+        return Some(MISSING_METRIC.to_string());
+    };
 
-pub fn update_tray_tooltip<R: Runtime>(app: &tauri::AppHandle<R>, usage: Option<&UsageSnapshot>) {
+    let value = if resets_in {
+        window
+            .resets_at
+            .as_deref()
+            .and_then(|resets_at| minutes_until(resets_at, now))
+            .filter(|minutes| *minutes >= 0)
+            .map(format_duration)
+    } else {
+        Some(format!("{:.0}", window.utilization))
+    };
+    Some(value.unwrap_or_else(|| MISSING_METRIC.to_string()))
+}
+
+pub fn update_tray_tooltip<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    usage: Option<&UsageSnapshot>,
+    organizations: &[OrganizationUsage],
+    preferences: &Preferences,
+    burn_rate: Option<f64>,
+    cost_today: Option<f64>,
+    updated_minutes_ago: Option<i64>,
+) {
     if let Some(tray) = app.tray_by_id("main") {
-        let tooltip = match usage {
+        let aggregation = preferences.tray_aggregation;
+        let mut tooltip = match usage {
             Some(snapshot) => {
-                let parts = snapshot
-                    .windows
+                // Additional organizations are only fetched alongside Claude
+                let organizations = if snapshot.provider == crate::types::ProviderKind::Claude {
+                    organizations
+                } else {
+                    &[]
+                };
+                let windows = match aggregation {
+                    TrayAggregation::WorstCase if !organizations.is_empty() => {
+                        worst_case_windows(&snapshot.windows, organizations)
+                    }
+                    _ => snapshot.windows.clone(),
+                };
+
+                let now = crate::clock::now();
+                let lines: Vec<String> = windows
                     .iter()
-                    .map(|window| format!("{}: {:.0}%", window.label, window.utilization))
-                    .collect::<Vec<_>>();
+                    .map(|window| window_countdown(window, now))
+                    .collect();
+                update_usage_menu(app, &lines);
 
-                let provider_name = match snapshot.provider {
-                    crate::types::ProviderKind::Claude => "Claude Monitor",
-                    crate::types::ProviderKind::Codex => "Codex Monitor",
-                    crate::types::ProviderKind::Ollama => "Ollama Monitor",
+                let mut tooltip = match preferences
+                    .tray_tooltip_format
+                    .as_deref()
+                    .filter(|template| !template.trim().is_empty())
+                {
+                    Some(template) => render_tooltip_template(template, &windows, now),
+                    None => default_tooltip(snapshot, &lines),
                 };
 
-                if parts.is_empty() {
-                    provider_name.to_string()
-                } else {
-                    format!("{provider_name}\n{}", parts.join(" | "))
+                if aggregation == TrayAggregation::PerOrganization {
+                    for line in organization_lines(organizations) {
+                        tooltip.push('\n');
+                        tooltip.push_str(&line);
+                    }
+                }
+
+                if let (Some(rate), Some(window)) = (burn_rate, snapshot.windows.first()) {
+                    tooltip.push('\n');
+                    tooltip.push_str(&burn_rate_line(&window.label, rate));
                 }
+
+                if let Some(minutes) = updated_minutes_ago {
+                    tooltip.push('\n');
+                    tooltip.push_str(&tf(Text::UpdatedAgo, &[&format_duration(minutes)]));
+                }
+
+                tooltip
             }
             None => "Claude Monitor".to_string(),
         };
+        if let Some(cost) = cost_today {
+            tooltip.push('\n');
+            tooltip.push_str(&tf(Text::TodayCost, &[&format!("{cost:.2}")]));
+        }
         let _ = tray.set_tooltip(Some(&tooltip));
     }
 }
 
+/// `5 Hour` → `5h`, `7 Day` → `7d`; other labels are lowercased.
+pub(crate) fn short_label(label: &str) -> String {
+    match label.split_once(' ') {
+        Some((count, "Hour")) if count.parse::<u32>().is_ok() => format!("{count}h"),
+        Some((count, "Day")) if count.parse::<u32>().is_ok() => format!("{count}d"),
+        _ => label.to_lowercase(),
+    }
+}
+
+/// `5h: 72% · resets in 1h 40m`, without the countdown when the reset is
+/// unknown or already passed.
+fn window_countdown(window: &UsageWindow, now: DateTime<Utc>) -> String {
+    let usage = format!("{}: {:.0}%", short_label(&window.label), window.utilization);
+    match window
+        .resets_at
+        .as_deref()
+        .and_then(|resets_at| minutes_until(resets_at, now))
+        .filter(|minutes| *minutes >= 0)
+    {
+        Some(minutes) => format!(
+            "{usage} · {}",
+            tf(Text::ResetsIn, &[&format_duration(minutes)])
+        ),
+        None => usage,
+    }
+}
+
+/// `5h: +8%/h over the last hour`; the sign shows whether usage is climbing.
+fn burn_rate_line(label: &str, rate: f64) -> String {
+    tf(
+        Text::BurnRate,
+        &[&short_label(label), &format!("{rate:+.0}")],
+    )
+}
+
+/// `Claude Monitor`, then a line per window with its time to reset. Short
+/// labels keep four windows within the Windows tooltip limit.
+fn default_tooltip(snapshot: &UsageSnapshot, lines: &[String]) -> String {
+    let provider_name = match snapshot.provider {
+        crate::types::ProviderKind::Claude => "Claude Monitor",
+        crate::types::ProviderKind::Codex => "Codex Monitor",
+        crate::types::ProviderKind::Ollama => "Ollama Monitor",
+    };
+
+    std::iter::once(provider_name.to_string())
+        .chain(lines.iter().cloned())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Show each window's usage and countdown as disabled items at the top of the
+/// tray menu, replacing the previous ones.
+fn update_usage_menu<R: Runtime>(app: &tauri::AppHandle<R>, lines: &[String]) {
+    let Some(usage_menu) = app.try_state::<UsageMenu<R>>() else {
+        return;
+    };
+    let Ok(mut items) = usage_menu.items.lock() else {
+        return;
+    };
+
+    for item in items.drain(..) {
+        let _ = usage_menu.menu.remove(&item);
+    }
+    for (index, line) in lines.iter().enumerate() {
+        let item = MenuItemBuilder::with_id(format!("usage_{index}"), line)
+            .enabled(false)
+            .build(app);
+        match item {
+            // Below the app name and version
+            Ok(item) => match usage_menu.menu.insert(&item, index + 1) {
+                Ok(()) => items.push(item),
+                Err(e) => log::warn!("Failed to add usage to the tray menu: {e}"),
+            },
+            Err(e) => log::warn!("Failed to create usage menu item: {e}"),
+        }
+    }
+}
+
+/// Flash the tray icon when a window newly reaches critical usage under
+/// `severity`. The set of critical windows is tracked even when flashing is
+/// off, so turning it on doesn't fire for windows that were already critical.
+pub fn flash_on_critical_usage<R: Runtime>(
+    app: &tauri::AppHandle<R>,
+    usage: &UsageSnapshot,
+    severity: &SeverityConfig,
+    enabled: bool,
+) {
+    let newly_critical = {
+        let Ok(mut critical) = CRITICAL_WINDOWS.lock() else {
+            return;
+        };
+        update_critical_windows(&mut critical, usage, severity)
+    };
+
+    if enabled && newly_critical {
+        flash_tray_icon(app);
+    }
+}
+
+/// Replace this provider's critical keys; returns true if any key is new.
+fn update_critical_windows(
+    critical: &mut BTreeSet<String>,
+    usage: &UsageSnapshot,
+    severity: &SeverityConfig,
+) -> bool {
+    let prefix = format!("{}:", usage.provider.as_str());
+    let current: BTreeSet<String> = usage
+        .windows
+        .iter()
+        .filter(|window| severity.severity(&window.key, window.utilization) == Severity::Critical)
+        .map(|window| format!("{prefix}{}", window.key))
+        .collect();
+
+    let newly_critical = current.iter().any(|key| !critical.contains(key));
+    critical.retain(|key| !key.starts_with(&prefix));
+    critical.extend(current);
+    newly_critical
+}
+
+fn flash_tray_icon<R: Runtime>(app: &tauri::AppHandle<R>) {
+    let (Some(tray), Some(icon)) = (app.tray_by_id("main"), tray_icon(app)) else {
+        return;
+    };
+    let dimmed = dimmed_icon(&icon);
+
+    tauri::async_runtime::spawn(async move {
+        for frame in 0..FLASH_FRAMES {
+            let image = if frame % 2 == 0 { &dimmed } else { &icon };
+            let _ = tray.set_icon(Some(image.clone()));
+            tokio::time::sleep(std::time::Duration::from_millis(FLASH_FRAME_MS)).await;
+        }
+        let _ = tray.set_icon(Some(icon));
+        #[cfg(target_os = "macos")]
+        let _ = tray.set_icon_as_template(true);
+    });
+}
+
+/// The same icon at a quarter opacity, so the flash also works for macOS template icons.
+fn dimmed_icon(icon: &Image<'_>) -> Image<'static> {
+    let rgba = icon
+        .rgba()
+        .chunks_exact(4)
+        .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3] / 4])
+        .collect();
+    Image::new_owned(rgba, icon.width(), icon.height())
+}
+
+/// Switch the tray icon to match a new OS theme. Not needed on macOS, where
+/// the menu bar tints the template icon itself.
+#[cfg(not(target_os = "macos"))]
+pub fn set_tray_theme<R: Runtime>(app: &tauri::AppHandle<R>, theme: Theme) {
+    LIGHT_THEME.store(theme == Theme::Light, Ordering::Relaxed);
+    if let (Some(tray), Some(icon)) = (app.tray_by_id("main"), tray_icon(app)) {
+        let _ = tray.set_icon(Some(icon));
+    }
+}
+
+/// The icon for the current theme: the app icon as a template on macOS, and a
+/// light or dark single-colour glyph of its shape elsewhere, so it doesn't
+/// vanish against a light taskbar.
+fn tray_icon<R: Runtime>(app: &tauri::AppHandle<R>) -> Option<Image<'static>> {
+    let icon = app.default_window_icon()?;
+    if cfg!(target_os = "macos") {
+        return Some(icon.clone().to_owned());
+    }
+    let glyph = if LIGHT_THEME.load(Ordering::Relaxed) {
+        DARK_GLYPH
+    } else {
+        LIGHT_GLYPH
+    };
+    Some(glyph_icon(icon, glyph))
+}
+
+/// The icon's shape filled with a single colour, keeping its alpha.
+fn glyph_icon(icon: &Image<'_>, [red, green, blue]: [u8; 3]) -> Image<'static> {
+    let rgba = icon
+        .rgba()
+        .chunks_exact(4)
+        .flat_map(|pixel| [red, green, blue, pixel[3]])
+        .collect();
+    Image::new_owned(rgba, icon.width(), icon.height())
+}
+
+/// The tray's compact mode checkbox, managed so `set_compact_mode` can keep it in sync.
+pub struct CompactModeMenuItem<R: Runtime>(pub CheckMenuItem<R>);
+
+/// The tray menu and the usage lines currently shown in it.
+struct UsageMenu<R: Runtime> {
+    menu: Menu<R>,
+    items: Mutex<Vec<MenuItem<R>>>,
+}
+
+/// The organization switcher, while there is more than one organization.
+struct OrganizationMenu<R: Runtime>(Mutex<Option<Submenu<R>>>);
+
+/// Items of the organization switcher: id, label and whether it's the primary
+/// organization, primary first.
+fn organization_entries(
+    primary_id: &str,
+    primary_name: Option<&str>,
+    additional: &[Organization],
+) -> Vec<(String, String, bool)> {
+    std::iter::once((
+        primary_id.to_string(),
+        primary_name.unwrap_or(primary_id).to_string(),
+        true,
+    ))
+    .chain(
+        additional
+            .iter()
+            .filter(|organization| organization.id != primary_id)
+            .map(|organization| (organization.id.clone(), organization.name.clone(), false)),
+    )
+    .collect()
+}
+
+/// Rebuild the tray's organization switcher from `config`, checking the
+/// primary organization. Left out without additional organizations.
+pub fn update_organization_menu<R: Runtime>(app: &tauri::AppHandle<R>, config: &AutoRefreshConfig) {
+    let (Some(usage_menu), Some(organization_menu)) = (
+        app.try_state::<UsageMenu<R>>(),
+        app.try_state::<OrganizationMenu<R>>(),
+    ) else {
+        return;
+    };
+    let Ok(mut current) = organization_menu.0.lock() else {
+        return;
+    };
+
+    if let Some(submenu) = current.take() {
+        let _ = usage_menu.menu.remove(&submenu);
+    }
+    let Some(primary_id) = config.organization_id.as_deref() else {
+        return;
+    };
+    if config.additional_organizations.is_empty() {
+        return;
+    }
+
+    let entries = organization_entries(
+        primary_id,
+        config.organization_name.as_deref(),
+        &config.additional_organizations,
+    );
+    let submenu = entries
+        .iter()
+        .try_fold(
+            SubmenuBuilder::new(app, t(Text::SwitchOrganization)),
+            |builder, (id, label, primary)| {
+                CheckMenuItemBuilder::with_id(format!("{ORGANIZATION_ITEM_PREFIX}{id}"), label)
+                    .checked(*primary)
+                    .build(app)
+                    .map(|item| builder.item(&item))
+            },
+        )
+        .and_then(|builder| builder.build());
+    // Above the separator and Quit
+    let index = usage_menu
+        .menu
+        .items()
+        .map_or(0, |items| items.len().saturating_sub(2));
+    match submenu {
+        Ok(submenu) => match usage_menu.menu.insert(&submenu, index) {
+            Ok(()) => *current = Some(submenu),
+            Err(e) => log::warn!("Failed to add organizations to the tray menu: {e}"),
+        },
+        Err(e) => log::warn!("Failed to create organization menu: {e}"),
+    }
+}
+
 fn handle_menu_event<R: Runtime>(app: &tauri::AppHandle<R>, event: MenuEvent) {
-    if event.id().as_ref() == "check_updates" {
-        // Emit event to frontend to trigger update check
-        let _ = app.emit("check-for-updates", ());
+    match event.id().as_ref() {
+        "check_updates" => {
+            // Emit event to frontend to trigger update check
+            let _ = CheckForUpdates.emit(app);
+        }
+        "compact_mode" => {
+            let app = app.clone();
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<Arc<AppState>>();
+                let compact = !state.preferences.lock().await.compact_mode;
+                if let Err(e) = apply_compact_mode(&app, &state, compact).await {
+                    log::warn!("Failed to toggle compact mode: {e}");
+                }
+            });
+        }
+        id => {
+            let Some(org_id) = id.strip_prefix(ORGANIZATION_ITEM_PREFIX) else {
+                return;
+            };
+            let (app, org_id) = (app.clone(), org_id.to_string());
+            tauri::async_runtime::spawn(async move {
+                let state = app.state::<Arc<AppState>>();
+                if let Err(e) = apply_primary_organization(&app, &state, &org_id).await {
+                    log::warn!("Failed to switch organization: {e}");
+                    // Undo the checkmark the click toggled
+                    update_organization_menu(&app, &state.config.lock().await);
+                }
+            });
+        }
     }
 }
 
 pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
+    #[cfg(target_os = "linux")]
+    if !status_notifier_available() {
+        log::warn!("No StatusNotifier host found; the tray icon will not be visible");
+        TRAY_AVAILABLE.store(false, Ordering::Relaxed);
+    }
+
     // Get app name and version
     let package_info = app.package_info();
     let app_label = format!("{} v{}", package_info.name, package_info.version);
@@ -53,17 +576,38 @@ pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
     let app_info = MenuItemBuilder::with_id("app_info", &app_label)
         .enabled(false)
         .build(app)?;
+    let compact_mode =
+        CheckMenuItemBuilder::with_id("compact_mode", t(Text::CompactMode)).build(app)?;
     let check_updates =
-        MenuItemBuilder::with_id("check_updates", "Check for Updates").build(app)?;
+        MenuItemBuilder::with_id("check_updates", t(Text::CheckForUpdates)).build(app)?;
     let separator = PredefinedMenuItem::separator(app)?;
-    let quit_i = PredefinedMenuItem::quit(app, Some("Quit"))?;
+    let quit_i = PredefinedMenuItem::quit(app, Some(t(Text::Quit)))?;
 
-    let menu = Menu::with_items(app, &[&app_info, &check_updates, &separator, &quit_i])?;
+    let menu = Menu::with_items(
+        app,
+        &[
+            &app_info,
+            &compact_mode,
+            &check_updates,
+            &separator,
+            &quit_i,
+        ],
+    )?;
+    app.manage(CompactModeMenuItem(compact_mode));
+    app.manage(UsageMenu {
+        menu: menu.clone(),
+        items: Mutex::new(Vec::new()),
+    });
+    app.manage(OrganizationMenu::<R>(Mutex::new(None)));
 
-    let icon = app
-        .default_window_icon()
-        .ok_or_else(|| tauri::Error::AssetNotFound("default window icon".into()))?
-        .clone();
+    // Hidden windows still report the OS theme; assume a dark taskbar if unknown
+    let theme = app
+        .get_webview_window("main")
+        .and_then(|window| window.theme().ok())
+        .unwrap_or(Theme::Dark);
+    LIGHT_THEME.store(theme == Theme::Light, Ordering::Relaxed);
+    let icon =
+        tray_icon(app).ok_or_else(|| tauri::Error::AssetNotFound("default window icon".into()))?;
 
     let _tray = TrayIconBuilder::with_id("main")
         .icon(icon)
@@ -101,6 +645,8 @@ pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
                 if let TrayIconEvent::Click {
                     button: MouseButton::Left,
                     button_state: MouseButtonState::Up,
+                    position,
+                    rect,
                     ..
                 } = event
                 {
@@ -108,7 +654,11 @@ pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
                         if window.is_visible().unwrap_or(false) {
                             let _ = window.hide();
                         } else {
-                            let _ = window.move_window(Position::TrayCenter);
+                            if !window_state::restore_geometry(&window)
+                                && !window_state::move_to_tray(&window, position, &rect)
+                            {
+                                let _ = window.move_window(Position::TrayCenter);
+                            }
                             let _ = window.set_always_on_top(true);
                             let _ = window.show();
                             let _ = window.set_focus();
@@ -121,3 +671,190 @@ pub fn create_tray<R: Runtime>(app: &tauri::AppHandle<R>) -> tauri::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{ProviderKind, UsageWindow};
+
+    fn usage(utilizations: &[(&str, f64)]) -> UsageSnapshot {
+        UsageSnapshot {
+            provider: ProviderKind::Claude,
+            windows: utilizations
+                .iter()
+                .map(|(key, utilization)| UsageWindow {
+                    key: key.to_string(),
+                    label: key.to_string(),
+                    utilization: *utilization,
+                    resets_at: None,
+                    resets_at_epoch_ms: None,
+                    window_duration_seconds: None,
+                })
+                .collect(),
+            account_email: None,
+            plan_type: None,
+            rate_limit: None,
+        }
+    }
+
+    #[test]
+    fn lists_primary_organization_first() {
+        let organization = |id: &str, name: &str| Organization {
+            id: id.to_string(),
+            name: name.to_string(),
+            plan: None,
+        };
+        let additional = [
+            organization("org-b", "Team B"),
+            organization("org-a", "Stale copy of primary"),
+        ];
+
+        assert_eq!(
+            organization_entries("org-a", Some("Team A"), &additional),
+            vec![
+                ("org-a".to_string(), "Team A".to_string(), true),
+                ("org-b".to_string(), "Team B".to_string(), false),
+            ]
+        );
+        // Set up from saved credentials, the primary's name isn't known
+        assert_eq!(organization_entries("org-a", None, &[])[0].1, "org-a");
+    }
+
+    #[test]
+    fn summarizes_additional_organizations() {
+        let organization = |name: &str, windows: &[(&str, f64)]| OrganizationUsage {
+            organization: crate::types::Organization {
+                id: name.to_lowercase(),
+                name: name.to_string(),
+                plan: None,
+            },
+            usage: usage(windows),
+        };
+
+        assert_eq!(
+            organization_lines(&[
+                organization("Acme", &[("5 Hour", 40.4), ("7 Day", 12.0)]),
+                organization("Empty", &[]),
+            ]),
+            vec!["Acme — 5 Hour: 40% | 7 Day: 12%".to_string()]
+        );
+    }
+
+    #[test]
+    fn aggregates_worst_case_per_metric() {
+        let organization = |windows: &[(&str, f64)]| OrganizationUsage {
+            organization: crate::types::Organization {
+                id: "acme".to_string(),
+                name: "Acme".to_string(),
+                plan: None,
+            },
+            usage: usage(windows),
+        };
+
+        let worst = worst_case_windows(
+            &usage(&[("5 Hour", 40.0), ("7 Day", 30.0)]).windows,
+            &[
+                organization(&[("5 Hour", 85.0), ("7 Day", 10.0)]),
+                organization(&[("Opus", 5.0)]),
+            ],
+        );
+        assert_eq!(
+            window_summary(&worst),
+            "5 Hour: 85% | 7 Day: 30% | Opus: 5%"
+        );
+    }
+
+    #[test]
+    fn renders_tooltip_template() {
+        let now = DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut snapshot = usage(&[("five_hour", 72.4), ("seven_day_opus", 12.0)]);
+        snapshot.windows[0].resets_at = Some("2026-04-13T11:40:00Z".to_string());
+
+        assert_eq!(
+            render_tooltip_template(
+                "{five_hour}% ({five_hour_resets_in}) | O:{opus}%",
+                &snapshot.windows,
+                now
+            ),
+            "72% (1h 40m) | O:12%"
+        );
+        assert_eq!(
+            render_tooltip_template(
+                "{seven_day}% {opus_resets_in} {not a key} {",
+                &snapshot.windows,
+                now
+            ),
+            "–% – {not a key} {"
+        );
+    }
+
+    #[test]
+    fn formats_window_countdown() {
+        let now = DateTime::parse_from_rfc3339("2026-04-13T10:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut snapshot = usage(&[("5 Hour", 72.4), ("7 Day", 12.0)]);
+        snapshot.windows[0].resets_at = Some("2026-04-13T11:40:00Z".to_string());
+        snapshot.windows[1].resets_at = Some("2026-04-13T09:00:00Z".to_string());
+
+        assert_eq!(
+            window_countdown(&snapshot.windows[0], now),
+            "5h: 72% · resets in 1h 40m"
+        );
+        assert_eq!(window_countdown(&snapshot.windows[1], now), "7d: 12%");
+    }
+
+    #[test]
+    fn formats_signed_burn_rate() {
+        assert_eq!(
+            burn_rate_line("5 Hour", 8.2),
+            "5h: +8%/h over the last hour"
+        );
+        assert_eq!(
+            burn_rate_line("5 Hour", -1.6),
+            "5h: -2%/h over the last hour"
+        );
+    }
+
+    #[test]
+    fn glyph_icon_keeps_shape() {
+        let icon = Image::new(&[10, 20, 30, 255, 200, 100, 50, 0], 2, 1);
+        let glyph = glyph_icon(&icon, DARK_GLYPH);
+        assert_eq!(glyph.rgba(), &[0x1F, 0x1F, 0x1F, 255, 0x1F, 0x1F, 0x1F, 0]);
+        assert_eq!((glyph.width(), glyph.height()), (2, 1));
+    }
+
+    #[test]
+    fn flags_only_newly_critical_windows() {
+        let mut critical = BTreeSet::new();
+        assert!(!update_critical_windows(
+            &mut critical,
+            &usage(&[("five_hour", 80.0)]),
+            &SeverityConfig::default()
+        ));
+        assert!(update_critical_windows(
+            &mut critical,
+            &usage(&[("five_hour", 92.0)]),
+            &SeverityConfig::default()
+        ));
+        assert!(!update_critical_windows(
+            &mut critical,
+            &usage(&[("five_hour", 95.0)]),
+            &SeverityConfig::default()
+        ));
+
+        // Dropping below and crossing again flashes again
+        assert!(!update_critical_windows(
+            &mut critical,
+            &usage(&[("five_hour", 10.0)]),
+            &SeverityConfig::default()
+        ));
+        assert!(update_critical_windows(
+            &mut critical,
+            &usage(&[("five_hour", 90.0)]),
+            &SeverityConfig::default()
+        ));
+    }
+}
@@ -1,6 +1,18 @@
+use crate::api::{UsageClient, UserAgentMode};
+use crate::budgets::UsageBudget;
+use crate::error::{ErrorCode, ErrorPayload};
+use crate::i18n::Language;
+use crate::local_usage::ActiveSessions;
+use crate::pricing::ModelPricing;
+use crate::secret::SecretString;
+use crate::time_format::TimeFormat;
+use crate::tray::TrayAggregation;
+use crate::window_state::WindowPlacement;
 use serde::{Deserialize, Deserializer, Serialize};
 use specta::Type;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
+use std::sync::Arc;
+use tauri_specta::Event;
 use tokio::sync::{Mutex, watch};
 
 #[cfg(target_os = "macos")]
@@ -28,6 +40,31 @@ impl ProviderKind {
     }
 }
 
+/// What triggered a fetch, recorded with each stored snapshot so sampling
+/// patterns can be told apart later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FetchSource {
+    /// The regular polling interval elapsed.
+    Scheduled,
+    /// The user asked for it, or changed settings that restart polling.
+    Manual,
+    /// The machine resumed, or the wall clock jumped.
+    Wake,
+    /// The fetch just after the top of the hour.
+    Hourly,
+}
+
+impl FetchSource {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Manual => "manual",
+            Self::Wake => "wake",
+            Self::Hourly => "hourly",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct UsageWindow {
@@ -35,9 +72,32 @@ pub struct UsageWindow {
     pub label: String,
     pub utilization: f64,
     pub resets_at: Option<String>,
+    /// `resets_at` as milliseconds since the Unix epoch, parsed once here.
+    #[serde(default)]
+    pub resets_at_epoch_ms: Option<i64>,
     pub window_duration_seconds: Option<i64>,
 }
 
+/// Milliseconds since the Unix epoch of an RFC3339 reset time, or None when
+/// it's missing or unparseable.
+pub fn reset_epoch_ms(resets_at: Option<&str>) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(resets_at?)
+        .ok()
+        .map(|resets_at| resets_at.timestamp_millis())
+}
+
+/// Request quota reported by the provider's rate-limit response headers.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitInfo {
+    pub limit: Option<u64>,
+    pub remaining: Option<u64>,
+    /// When the quota resets (RFC3339).
+    pub reset_at: Option<String>,
+    /// Server-requested delay from `Retry-After`.
+    pub retry_after_seconds: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 #[serde(rename_all = "camelCase")]
 pub struct UsageSnapshot {
@@ -45,6 +105,8 @@ pub struct UsageSnapshot {
     pub windows: Vec<UsageWindow>,
     pub account_email: Option<String>,
     pub plan_type: Option<String>,
+    #[serde(default)]
+    pub rate_limit: Option<RateLimitInfo>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
@@ -55,6 +117,33 @@ pub struct ProviderStatus {
     pub message: Option<String>,
 }
 
+/// A Claude organization the session token can access.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Organization {
+    pub id: String,
+    pub name: String,
+    pub plan: Option<String>,
+}
+
+/// Latest usage of an organization monitored alongside the primary one.
+#[derive(Debug, Clone, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct OrganizationUsage {
+    pub organization: Organization,
+    pub usage: UsageSnapshot,
+}
+
+/// One member's usage windows, from the organization admin usage endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MemberUsage {
+    pub member_id: String,
+    pub name: Option<String>,
+    pub email: Option<String>,
+    pub windows: Vec<UsageWindow>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Type)]
 pub struct Settings {
     pub active_provider: ProviderKind,
@@ -70,6 +159,147 @@ impl Default for Settings {
     }
 }
 
+impl Settings {
+    /// Defaults with `config.toml`'s initial refresh interval applied.
+    pub fn from_config(config: &crate::config::AppConfig) -> Self {
+        let defaults = Self::default();
+        Self {
+            refresh_interval_minutes: config
+                .refresh_interval_minutes
+                .unwrap_or(defaults.refresh_interval_minutes),
+            ..defaults
+        }
+    }
+}
+
+/// Backend-owned preferences persisted under the `preferences` store key.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct Preferences {
+    pub show_cost_in_tray: bool,
+    /// Add the first window's burn rate over the last hour to the tray tooltip.
+    pub show_burn_rate_in_tray: bool,
+    /// Per-million-token prices keyed by a model id substring (e.g. `"sonnet"`).
+    pub pricing_overrides: BTreeMap<String, ModelPricing>,
+    /// Opt-in: write crash files (and upload them when Sentry is configured).
+    pub crash_reports_enabled: bool,
+    /// Start in the tray when launched at login; otherwise the window is shown.
+    pub start_hidden_at_login: bool,
+    /// Language for notifications and tray labels.
+    pub language: Language,
+    /// 12/24-hour clock for times in the tray and notifications.
+    pub time_format: TimeFormat,
+    /// User-Agent for provider requests.
+    pub user_agent: UserAgentMode,
+    /// Windows/Linux: open at the tray icon or where the window was last left.
+    pub window_placement: WindowPlacement,
+    /// Windows/Linux: don't hide the window when it loses focus.
+    pub window_pinned: bool,
+    /// Don't flash the tray icon when usage turns critical.
+    pub reduce_motion: bool,
+    /// Shrink the window to a minimal strip of usage bars.
+    pub compact_mode: bool,
+    /// macOS: let critical alerts (see `severity`) break through Focus modes.
+    pub time_sensitive_alerts: bool,
+    /// Hold back a fetch whose utilization jumps implausibly until the next
+    /// fetch, so one-off API glitches stay out of history and alerts.
+    pub reject_usage_spikes: bool,
+    /// Keep history and notification state in memory only; nothing new is
+    /// written to disk while on.
+    pub incognito: bool,
+    /// Per-organization lines or one worst-case line in the tray tooltip.
+    pub tray_aggregation: TrayAggregation,
+    /// Custom tray tooltip, e.g. `{five_hour}% ({five_hour_resets_in})`; None
+    /// keeps the default layout.
+    pub tray_tooltip_format: Option<String>,
+    /// Show synthetic usage instead of fetching it (see `demo.rs`). Read at
+    /// launch, like the `--demo` flag.
+    pub demo_mode: bool,
+    /// Days to keep per history table (0 keeps everything); tables left out
+    /// use `config.toml`'s `retention_days` or their default.
+    pub history_retention: BTreeMap<crate::history::HistoryTable, u32>,
+    /// Warning and critical utilization cutoffs.
+    pub severity: SeverityConfig,
+}
+
+/// How serious a window's utilization is, graded by `SeverityConfig`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Normal,
+    Warning,
+    Critical,
+}
+
+/// Utilization cutoffs of one window; each applies at or above its value.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, Type)]
+pub struct SeverityThresholds {
+    pub warning: f64,
+    pub critical: f64,
+}
+
+pub const DEFAULT_SEVERITY_THRESHOLDS: SeverityThresholds = SeverityThresholds {
+    warning: 70.0,
+    critical: 90.0,
+};
+
+/// Time-sensitive alerts keep the cutoff they had before severity was
+/// configurable, so most critical alerts still respect Focus.
+pub const DEFAULT_TIME_SENSITIVE_UTILIZATION: f64 = 95.0;
+
+/// The one place warning and critical cutoffs are configured. The tray flash,
+/// notification urgency, the shell prompt's colors and the frontend's bars all
+/// grade usage with it.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Type)]
+#[serde(default)]
+pub struct SeverityConfig {
+    /// Cutoffs for windows without their own.
+    pub default: SeverityThresholds,
+    /// Cutoffs by window key (e.g. `seven_day`), overriding `default`.
+    pub windows: BTreeMap<String, SeverityThresholds>,
+    /// macOS: utilization at which a critical alert breaks through Focus,
+    /// when `Preferences.time_sensitive_alerts` is on.
+    pub time_sensitive: f64,
+}
+
+impl SeverityConfig {
+    const DEFAULT: Self = Self {
+        default: DEFAULT_SEVERITY_THRESHOLDS,
+        windows: BTreeMap::new(),
+        time_sensitive: DEFAULT_TIME_SENSITIVE_UTILIZATION,
+    };
+
+    pub fn thresholds(&self, window_key: &str) -> SeverityThresholds {
+        self.windows
+            .get(window_key)
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    pub fn severity(&self, window_key: &str, utilization: f64) -> Severity {
+        let thresholds = self.thresholds(window_key);
+        if utilization >= thresholds.critical {
+            Severity::Critical
+        } else if utilization >= thresholds.warning {
+            Severity::Warning
+        } else {
+            Severity::Normal
+        }
+    }
+
+    /// Whether an alert for this window may break through Focus.
+    pub fn is_time_sensitive(&self, window_key: &str, utilization: f64) -> bool {
+        self.severity(window_key, utilization) == Severity::Critical
+            && utilization >= self.time_sensitive
+    }
+}
+
+impl Default for SeverityConfig {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
 // ============================================================================
 // Notification Types
 // ============================================================================
@@ -82,6 +312,24 @@ pub struct NotificationRule {
     pub thresholds: Vec<u32>,
     pub time_remaining_enabled: bool,
     pub time_remaining_minutes: Vec<u32>,
+    /// Repeat the alert while utilization stays at or above `reminder_threshold`.
+    #[serde(default)]
+    pub reminder_enabled: bool,
+    #[serde(default = "default_reminder_threshold")]
+    pub reminder_threshold: u32,
+    #[serde(default = "default_reminder_interval_minutes")]
+    pub reminder_interval_minutes: u32,
+    /// Notify when utilization falls back under a fired threshold.
+    #[serde(default)]
+    pub recovery_enabled: bool,
+}
+
+fn default_reminder_threshold() -> u32 {
+    90
+}
+
+fn default_reminder_interval_minutes() -> u32 {
+    30
 }
 
 impl Default for NotificationRule {
@@ -93,6 +341,10 @@ impl Default for NotificationRule {
             thresholds: vec![80, 90],
             time_remaining_enabled: false,
             time_remaining_minutes: vec![30, 60],
+            reminder_enabled: false,
+            reminder_threshold: default_reminder_threshold(),
+            reminder_interval_minutes: default_reminder_interval_minutes(),
+            recovery_enabled: false,
         }
     }
 }
@@ -166,23 +418,124 @@ impl<'de> Deserialize<'de> for NotificationSettings {
 #[derive(Debug, Clone, Serialize, Deserialize, Default, Type)]
 pub struct NotificationState {
     pub last_notified: BTreeMap<String, f64>,
-    pub fired_thresholds: Vec<String>,
-    pub fired_time_remaining: Vec<String>,
+    #[serde(serialize_with = "serialize_sorted_set")]
+    pub fired_thresholds: HashSet<String>,
+    #[serde(serialize_with = "serialize_sorted_set")]
+    pub fired_time_remaining: HashSet<String>,
+    /// Set once the access-denied alert fired; cleared by the next successful fetch.
+    #[serde(default)]
+    pub access_denied_notified: bool,
+    /// Alerts stay silent until this time (RFC3339); thresholds crossed meanwhile
+    /// are still recorded so they don't fire late.
+    #[serde(default)]
+    pub snoozed_until: Option<String>,
+    /// When each window last alerted while above its reminder threshold (RFC3339).
+    #[serde(default)]
+    pub last_reminded: BTreeMap<String, String>,
+    /// The session expiry (RFC3339) the user was already warned about.
+    #[serde(default)]
+    pub token_expiry_notified: Option<String>,
+    /// Set once the model advisory fired; cleared when it no longer applies.
+    #[serde(default)]
+    pub model_advisory_notified: bool,
+    /// Last seen reset (RFC3339) of each provider's 7-day window; a later one
+    /// means the week rolled over.
+    #[serde(default)]
+    pub weekly_resets: BTreeMap<String, String>,
+    /// Reset (RFC3339) of the period each budget last alerted in, by
+    /// `provider:window_key`.
+    #[serde(default)]
+    pub budget_alerts: BTreeMap<String, String>,
+}
+
+/// Persist sets in sorted order so the store file stays stable between saves.
+fn serialize_sorted_set<S>(set: &HashSet<String>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    let mut items: Vec<&String> = set.iter().collect();
+    items.sort();
+    items.serialize(serializer)
+}
+
+/// Suggest Sonnet while Opus's weekly window runs high and Sonnet's has room.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(default, rename_all = "camelCase")]
+pub struct ModelAdvisorySettings {
+    pub enabled: bool,
+    /// Opus 7-day utilization at or above which to advise.
+    pub opus_threshold: u32,
+    /// Sonnet 7-day utilization at or below which it counts as having headroom.
+    pub sonnet_max: u32,
+}
+
+impl Default for ModelAdvisorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            opus_threshold: 80,
+            sonnet_max: 50,
+        }
+    }
 }
 
 // ============================================================================
 // Auto-Refresh Types
 // ============================================================================
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Not serializable: it holds the session tokens.
+#[derive(Debug, Clone)]
 pub struct AutoRefreshConfig {
     pub active_provider: ProviderKind,
     pub organization_id: Option<String>,
-    pub session_token: Option<String>,
-    pub ollama_session_token: Option<String>,
+    /// Name of the primary organization, when it was picked from the tray.
+    pub organization_name: Option<String>,
+    /// Other organizations of the same session, fetched alongside the primary one.
+    pub additional_organizations: Vec<Organization>,
+    pub session_token: Option<SecretString>,
+    /// When the Claude session token expires (RFC3339), if known.
+    pub session_expires_at: Option<String>,
+    /// Warn this many days before `session_expires_at` (0 disables the warning).
+    pub token_expiry_warning_days: u32,
+    pub ollama_session_token: Option<SecretString>,
     pub enabled: bool,
     pub interval_minutes: u32,
     pub hourly_refresh_enabled: bool,
+    /// Seconds after the top of the hour before the hourly fetch.
+    pub hourly_refresh_gap_secs: u32,
+    /// Upper bound of the random delay added to the hourly fetch.
+    pub hourly_refresh_jitter_secs: u32,
+    /// Upper bound of the random delay before the first fetch after launch or a wake.
+    pub startup_jitter_secs: u32,
+    /// Faster interval used while Claude Code sessions are active (None disables it).
+    pub active_session_interval_minutes: Option<u32>,
+    /// Poll every minute during this many minutes before the next reset (None disables it).
+    pub near_reset_minutes: Option<u32>,
+    /// Pause polling after this many hours without Claude activity (None disables it).
+    pub idle_suspend_hours: Option<u32>,
+    /// Store at most one history sample per this many minutes (None stores every fetch).
+    pub history_sample_interval_minutes: Option<u32>,
+    /// Don't store a snapshot identical to the previous one (still stored hourly).
+    pub skip_unchanged_samples: bool,
+    /// Folder shared with other machines for history sync (None disables it).
+    pub history_sync_folder: Option<String>,
+    /// Names this machine's log in the history sync folder.
+    pub machine_id: String,
+    /// Also record per-member usage for the organization (admins only).
+    pub member_usage_enabled: bool,
+    /// Organization whose member usage request answered 404; it isn't asked
+    /// again until member usage is re-enabled.
+    pub member_usage_unsupported_org: Option<String>,
+    pub model_advisory: ModelAdvisorySettings,
+    /// Summarize the ended week when a 7-day window resets.
+    pub weekly_summary_enabled: bool,
+    pub budgets: Vec<UsageBudget>,
+    /// Argon2 hash of the app lock passphrase (None disables the lock).
+    pub app_lock_hash: Option<String>,
+    /// Lock after this many minutes away from the window (None disables it).
+    pub auto_lock_minutes: Option<u32>,
+    /// Locked: the session tokens aren't loaded and nothing is fetched.
+    pub locked: bool,
 }
 
 impl Default for AutoRefreshConfig {
@@ -190,27 +543,262 @@ impl Default for AutoRefreshConfig {
         Self {
             active_provider: ProviderKind::Claude,
             organization_id: None,
+            organization_name: None,
+            additional_organizations: Vec::new(),
             session_token: None,
+            session_expires_at: None,
+            token_expiry_warning_days: crate::notifications::DEFAULT_TOKEN_EXPIRY_WARNING_DAYS,
             ollama_session_token: None,
             enabled: true,
             interval_minutes: 5,
             hourly_refresh_enabled: false,
+            hourly_refresh_gap_secs: crate::auto_refresh::HOURLY_REFRESH_INITIAL_GAP_SECS,
+            hourly_refresh_jitter_secs: crate::auto_refresh::HOURLY_REFRESH_JITTER_MAX_SECS,
+            startup_jitter_secs: crate::auto_refresh::STARTUP_JITTER_MAX_SECS,
+            active_session_interval_minutes: None,
+            near_reset_minutes: None,
+            idle_suspend_hours: None,
+            history_sample_interval_minutes: None,
+            skip_unchanged_samples: false,
+            history_sync_folder: None,
+            machine_id: String::new(),
+            member_usage_enabled: false,
+            member_usage_unsupported_org: None,
+            model_advisory: ModelAdvisorySettings::default(),
+            weekly_summary_enabled: false,
+            budgets: Vec::new(),
+            app_lock_hash: None,
+            auto_lock_minutes: None,
+            locked: false,
         }
     }
 }
 
 #[derive(Debug, Clone, Serialize, Type)]
 #[serde(rename_all = "camelCase")]
-pub struct UsageUpdateEvent {
+pub struct AppLockStatus {
+    /// A passphrase is set.
+    pub enabled: bool,
+    pub locked: bool,
+    pub auto_lock_minutes: Option<u32>,
+}
+
+// Event payloads. The event name is the kebab-cased type name (e.g. `usage-updated`).
+
+#[derive(Debug, Clone, Serialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageUpdated {
     pub usage: UsageSnapshot,
     pub next_refresh_at: Option<i64>,
+    /// Utilization change per window key since the previous successful fetch of
+    /// the same provider; windows without a previous value are left out.
+    pub deltas: BTreeMap<String, f64>,
+    /// When usage was last updated successfully (RFC3339). Older than `usage`
+    /// only while a spike is held back.
+    pub last_success_at: Option<String>,
+    /// False while scheduled fetching is off (see `PausedReason::stops_monitoring`).
+    pub monitoring_enabled: bool,
+    pub paused_reason: Option<PausedReason>,
 }
 
-#[derive(Debug, Clone, Serialize, Type)]
+#[derive(Debug, Clone, Serialize, Type, Event)]
 #[serde(rename_all = "camelCase")]
-pub struct UsageErrorEvent {
+pub struct UsageError {
     pub provider: ProviderKind,
     pub error: String,
+    pub code: ErrorCode,
+    pub monitoring_enabled: bool,
+    pub paused_reason: Option<PausedReason>,
+}
+
+/// Why scheduled fetches don't run on the regular interval, so consumers can
+/// say why no countdown is shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum PausedReason {
+    /// Auto-refresh is turned off.
+    UserPause,
+    /// The app is locked.
+    Locked,
+    NoCredentials,
+    /// The provider rejected the credentials; polling goes on at the interval.
+    AuthExpired,
+    /// Rate limited; the next fetch waits out the backoff.
+    Backoff,
+    /// Idle suspension: fetching once an hour until there is activity again.
+    Idle,
+}
+
+impl PausedReason {
+    /// Whether scheduled fetching stops altogether, rather than slowing down.
+    pub fn stops_monitoring(self) -> bool {
+        matches!(self, Self::UserPause | Self::Locked | Self::NoCredentials)
+    }
+}
+
+/// `monitoring_enabled` of events, from the current paused reason.
+pub fn monitoring_enabled(paused_reason: Option<PausedReason>) -> bool {
+    !paused_reason.is_some_and(PausedReason::stops_monitoring)
+}
+
+/// Emitted after each fetch that reported rate-limit headers, and whenever the
+/// backoff changes, so the UI can explain why refreshes are delayed.
+#[derive(Debug, Clone, Serialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct RateLimitUpdated {
+    pub provider: ProviderKind,
+    pub rate_limit: Option<RateLimitInfo>,
+    /// Seconds until the next attempt while backing off, 0 when not backing off.
+    pub backoff_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Type, Event)]
+pub struct ActiveSessionsChanged(pub ActiveSessions);
+
+#[derive(Debug, Clone, Serialize, Type, Event)]
+pub struct LocalUsageChanged;
+
+#[derive(Debug, Clone, Serialize, Type, Event)]
+pub struct CheckForUpdates;
+
+/// Opus's weekly window is running high while Sonnet's has headroom.
+#[derive(Debug, Clone, Serialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelAdvisory {
+    pub opus_utilization: f64,
+    pub sonnet_utilization: f64,
+}
+
+/// The points just recorded for the primary organization, so open charts can
+/// append them instead of re-querying their range.
+#[derive(Debug, Clone, Serialize, Type, Event)]
+pub struct HistoryAppended {
+    pub points: Vec<crate::history::UsageHistoryPoint>,
+}
+
+/// Emitted after history is deleted or imported so charts and storage info reload.
+#[derive(Debug, Clone, Serialize, Type, Event)]
+pub struct HistoryChanged;
+
+#[derive(Debug, Clone, Serialize, Type, Event)]
+pub struct CompactModeChanged {
+    pub compact: bool,
+}
+
+/// Why the OS keychain couldn't be read or written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum KeychainProblem {
+    /// No Secret Service provider (gnome-keyring, KWallet) or no D-Bus session.
+    Unavailable,
+    /// The keyring is locked, or its unlock prompt was dismissed.
+    Locked,
+    /// The OS refused this app access to the keychain.
+    AccessDenied,
+    /// The stored entry isn't valid credentials.
+    Unreadable,
+    Failed,
+}
+
+/// What the app does instead while the keychain can't be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialFallback {
+    /// Credentials entered now are kept in memory until the app quits.
+    MemoryOnly,
+    /// The keychain works; saving credentials again replaces the bad entry.
+    ReplaceOnSave,
+}
+
+/// Emitted when a keychain access fails (at startup, unlock or save), and
+/// returned by `get_keychain_status` until credentials are saved successfully.
+#[derive(Debug, Clone, PartialEq, Serialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct KeychainIssue {
+    pub problem: KeychainProblem,
+    pub fallback: CredentialFallback,
+    /// The platform error, redacted.
+    pub detail: String,
+}
+
+/// Why usage looks like it's no longer being updated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Type)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleDataReason {
+    /// Unchanged for an hour while Claude Code sessions were running.
+    ActiveSessions,
+    /// A window still reports usage well after its reset time.
+    ResetPassed,
+}
+
+/// Emitted once when fetches keep succeeding with identical usage that should
+/// have changed, e.g. because the token serves cached data or belongs to
+/// another account. Cleared by the next change in usage.
+#[derive(Debug, Clone, PartialEq, Serialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleDataSuspected {
+    pub provider: ProviderKind,
+    pub reason: StaleDataReason,
+    /// Label of the window past its reset, for `ResetPassed`.
+    pub window: Option<String>,
+    /// When usage last changed, as far as this run knows (RFC3339).
+    pub unchanged_since: String,
+}
+
+/// Emitted when the refresh loop panicked; it is respawned after a short delay.
+#[derive(Debug, Clone, Serialize, Type, Event)]
+#[serde(rename_all = "camelCase")]
+pub struct RefreshLoopRestarted {
+    /// Panic message, redacted.
+    pub message: String,
+    /// Restarts since the app started.
+    pub restarts: u32,
+}
+
+/// Emitted when the app locks (by hand or after the auto-lock timeout) or unlocks.
+#[derive(Debug, Clone, Serialize, Type, Event)]
+pub struct AppLockChanged {
+    pub locked: bool,
+}
+
+/// Outcome of the most recent usage fetch, so a newly opened window can render
+/// before the next `usage-updated` event.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct LastFetchResult {
+    pub provider: ProviderKind,
+    pub success: bool,
+    pub error: Option<ErrorPayload>,
+    /// When the fetch finished (RFC3339).
+    pub finished_at: String,
+    /// None when the request failed before a response arrived.
+    pub http_status: Option<u16>,
+    pub duration_ms: u64,
+    pub usage: Option<UsageSnapshot>,
+}
+
+/// What `refresh_now` did.
+#[derive(Debug, Clone, PartialEq, Serialize, Type)]
+#[serde(tag = "status", rename_all = "camelCase")]
+pub enum ManualRefresh {
+    /// Fetched; the result arrived as `usage-updated` or `usage-error`.
+    Refreshed,
+    /// Backing off after a rate limit; the refresh loop fetches when it ends.
+    RateLimited {
+        #[serde(rename = "retryInSeconds")]
+        retry_in_seconds: u64,
+    },
+}
+
+/// Age of the displayed usage, from `get_staleness`.
+#[derive(Debug, Clone, Serialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub struct Staleness {
+    /// When usage was last updated successfully (RFC3339); None before the first update.
+    pub last_success_at: Option<String>,
+    pub age_seconds: Option<i64>,
+    /// The last update is more than twice the refresh interval old.
+    pub stale: bool,
 }
 
 pub struct AppState {
@@ -218,6 +806,26 @@ pub struct AppState {
     pub restart_tx: watch::Sender<()>,
     pub notification_settings: Mutex<NotificationSettings>,
     pub notification_state: Mutex<NotificationState>,
+    pub preferences: Mutex<Preferences>,
+    pub last_fetch: Mutex<Option<LastFetchResult>>,
+    /// Latest usage of each additional organization, in configured order.
+    pub organization_usage: Mutex<Vec<OrganizationUsage>>,
+    /// Held while additional organizations are fetched, so overlapping
+    /// refreshes don't fetch and save them twice.
+    pub organization_fetch: Mutex<()>,
+    pub spike_filter: Mutex<crate::auto_refresh::SpikeFilter>,
+    /// When usage was last applied to the tray, notifications and history.
+    pub last_success_at: Mutex<Option<chrono::DateTime<chrono::Utc>>>,
+    /// The usage applied then, so the tray can keep showing it while fetches fail.
+    pub last_applied_usage: Mutex<Option<UsageSnapshot>>,
+    /// Set by the refresh loop whenever its schedule changes.
+    pub paused_reason: Mutex<Option<PausedReason>>,
+    /// When the refresh loop's rate-limit backoff ends; None when not backing off.
+    pub backoff_until: Mutex<Option<chrono::DateTime<chrono::Utc>>>,
+    /// Provider APIs; the demo client in demo mode, a mock in tests.
+    pub usage_client: Arc<dyn UsageClient>,
+    #[cfg(feature = "transcript-watcher")]
+    pub transcript_watcher: Mutex<Option<notify::RecommendedWatcher>>,
     #[cfg(target_os = "macos")]
     pub wake_observer: Mutex<Option<Retained<crate::wake_detection::WakeObserver>>>,
 }
@@ -226,6 +834,29 @@ pub struct AppState {
 mod tests {
     use super::*;
 
+    #[test]
+    fn grades_by_window_cutoffs() {
+        let mut config = SeverityConfig::default();
+        assert_eq!(config.severity("five_hour", 69.9), Severity::Normal);
+        assert_eq!(config.severity("five_hour", 70.0), Severity::Warning);
+        assert_eq!(config.severity("five_hour", 90.0), Severity::Critical);
+
+        config.windows.insert(
+            "seven_day".to_string(),
+            SeverityThresholds {
+                warning: 50.0,
+                critical: 75.0,
+            },
+        );
+        assert_eq!(config.severity("seven_day", 80.0), Severity::Critical);
+        assert_eq!(config.severity("five_hour", 80.0), Severity::Warning);
+
+        let parsed: SeverityConfig =
+            serde_json::from_str(r#"{"windows":{"seven_day":{"warning":60,"critical":80}}}"#)
+                .unwrap();
+        assert_eq!(parsed.default, DEFAULT_SEVERITY_THRESHOLDS);
+    }
+
     #[test]
     fn deserializes_legacy_notification_settings() {
         let json = r#"{
@@ -0,0 +1,176 @@
+//! Importing Other Tools' Exports
+//!
+//! Reads the JSON printed by `ccusage daily --json` and stores each day's
+//! per-model token counts in `local_token_history`, so token charts and cost
+//! estimates cover the time before this app was installed. ccusage reads the
+//! same Claude Code transcripts as `sync_local_usage`, so only days before the
+//! app's own transcript history are taken (see `history::import_local_usage`).
+
+use crate::error::AppError;
+use crate::history::IMPORTED_MESSAGE_PREFIX;
+use crate::local_usage::{LocalUsageEntry, TokenCounts};
+use chrono::{DateTime, Local, NaiveDate, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::path::Path;
+
+/// Project name for imported rows; ccusage's daily report isn't per project.
+const IMPORTED_PROJECT: &str = "(imported)";
+
+/// Model name for days exported without a per-model breakdown.
+const UNKNOWN_MODEL: &str = "unknown";
+
+#[derive(Debug, Clone, Serialize, Deserialize, Type, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct UsageImportSummary {
+    /// Rows stored by this import.
+    pub imported: usize,
+    /// Rows left out because they overlap the app's own history or were
+    /// already imported.
+    pub skipped: usize,
+}
+
+#[derive(Debug, Deserialize)]
+struct CcusageDaily {
+    daily: Vec<CcusageDay>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CcusageDay {
+    /// Local date, `YYYY-MM-DD`.
+    date: String,
+    #[serde(flatten)]
+    tokens: CcusageTokens,
+    #[serde(default)]
+    model_breakdowns: Vec<CcusageModel>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CcusageModel {
+    model_name: String,
+    #[serde(flatten)]
+    tokens: CcusageTokens,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct CcusageTokens {
+    input_tokens: u64,
+    output_tokens: u64,
+    cache_creation_tokens: u64,
+    cache_read_tokens: u64,
+}
+
+impl From<&CcusageTokens> for TokenCounts {
+    fn from(tokens: &CcusageTokens) -> Self {
+        Self {
+            input_tokens: tokens.input_tokens,
+            output_tokens: tokens.output_tokens,
+            cache_creation_tokens: tokens.cache_creation_tokens,
+            cache_read_tokens: tokens.cache_read_tokens,
+        }
+    }
+}
+
+/// Import a `ccusage daily --json` export.
+pub fn import_usage_file(path: &Path) -> Result<UsageImportSummary, AppError> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| AppError::Storage(format!("Failed to read {}: {e}", path.display())))?;
+    let entries = parse_ccusage_daily(&contents)?;
+    let imported = crate::history::import_local_usage(&entries)?;
+    Ok(UsageImportSummary {
+        imported,
+        skipped: entries.len() - imported,
+    })
+}
+
+/// One entry per day and model, timestamped at the start of the local day.
+fn parse_ccusage_daily(json: &str) -> Result<Vec<LocalUsageEntry>, AppError> {
+    let export: CcusageDaily = serde_json::from_str(json)
+        .map_err(|e| AppError::Storage(format!("Not a `ccusage daily --json` export: {e}")))?;
+
+    let mut entries = Vec::new();
+    for day in export.daily {
+        let timestamp = day_start(&day.date).ok_or_else(|| {
+            AppError::Storage(format!("Invalid date in ccusage export: {}", day.date))
+        })?;
+        let models: Vec<(&str, &CcusageTokens)> = if day.model_breakdowns.is_empty() {
+            vec![(UNKNOWN_MODEL, &day.tokens)]
+        } else {
+            day.model_breakdowns
+                .iter()
+                .map(|model| (model.model_name.as_str(), &model.tokens))
+                .collect()
+        };
+
+        for (model, tokens) in models {
+            entries.push(LocalUsageEntry {
+                message_key: format!("{IMPORTED_MESSAGE_PREFIX}ccusage:{}:{model}", day.date),
+                timestamp,
+                project: IMPORTED_PROJECT.to_string(),
+                model: model.to_string(),
+                tokens: tokens.into(),
+            });
+        }
+    }
+    Ok(entries)
+}
+
+fn day_start(date: &str) -> Option<DateTime<Utc>> {
+    let midnight = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .ok()?
+        .and_hms_opt(0, 0, 0)?;
+    Local
+        .from_local_datetime(&midnight)
+        .earliest()
+        .map(|start| start.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_ccusage_days_to_model_entries() {
+        let json = r#"{
+            "daily": [
+                {
+                    "date": "2026-03-01",
+                    "inputTokens": 300,
+                    "outputTokens": 30,
+                    "cacheCreationTokens": 0,
+                    "cacheReadTokens": 3000,
+                    "totalTokens": 3330,
+                    "totalCost": 1.25,
+                    "modelsUsed": ["claude-opus-4", "claude-sonnet-4"],
+                    "modelBreakdowns": [
+                        { "modelName": "claude-opus-4", "inputTokens": 100, "outputTokens": 10, "cacheCreationTokens": 0, "cacheReadTokens": 1000, "cost": 1.0 },
+                        { "modelName": "claude-sonnet-4", "inputTokens": 200, "outputTokens": 20, "cacheCreationTokens": 0, "cacheReadTokens": 2000, "cost": 0.25 }
+                    ]
+                },
+                { "date": "2026-03-02", "inputTokens": 5, "outputTokens": 1 }
+            ],
+            "totals": { "inputTokens": 305 }
+        }"#;
+
+        let entries = parse_ccusage_daily(json).unwrap();
+        assert_eq!(entries.len(), 3);
+        assert_eq!(
+            entries[0].message_key,
+            "import:ccusage:2026-03-01:claude-opus-4"
+        );
+        assert_eq!(entries[0].tokens.cache_read_tokens, 1000);
+        assert_eq!(entries[1].model, "claude-sonnet-4");
+        assert_eq!(entries[2].model, UNKNOWN_MODEL);
+        assert_eq!(entries[2].tokens.total(), 6);
+        assert_eq!(entries[2].timestamp, day_start("2026-03-02").unwrap());
+    }
+
+    #[test]
+    fn rejects_other_json() {
+        assert!(parse_ccusage_daily(r#"{"sessions": []}"#).is_err());
+        assert!(parse_ccusage_daily(r#"{"daily": [{"date": "March 1"}]}"#).is_err());
+    }
+}
@@ -1,4 +1,34 @@
 use crate::error::AppError;
+use crate::history::HistoryTable;
+use crate::types::{ModelAdvisorySettings, NotificationSettings, SeverityConfig};
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// Refresh and polling intervals: at least a minute, so the loop can't spin,
+/// and at most a day, so it still fires.
+pub const INTERVAL_MINUTES: RangeInclusive<u32> = 1..=24 * 60;
+
+/// History retention: a day to ten years.
+pub const RETENTION_DAYS: RangeInclusive<u32> = 1..=3650;
+
+/// Idle suspension: an hour to a week without activity.
+pub const IDLE_HOURS: RangeInclusive<u32> = 1..=7 * 24;
+
+/// Random delay before the first fetch after launch or a wake: up to 10
+/// minutes, 0 fetching at once.
+pub const STARTUP_JITTER_SECS: RangeInclusive<u32> = 0..=10 * 60;
+
+/// Wait after the top of the hour before the hourly fetch: up to 15 minutes.
+pub const HOURLY_REFRESH_GAP_SECS: RangeInclusive<u32> = 0..=15 * 60;
+
+/// Random delay added to the hourly fetch: up to 5 minutes.
+pub const HOURLY_REFRESH_JITTER_SECS: RangeInclusive<u32> = 0..=5 * 60;
+
+/// Warning ahead of a known session expiry: up to 90 days, 0 turning it off.
+pub const TOKEN_EXPIRY_WARNING_DAYS: RangeInclusive<u32> = 0..=90;
+
+/// Utilization thresholds, in percent.
+pub const THRESHOLD_PERCENT: RangeInclusive<u32> = 1..=100;
 
 /// Validate session token format to prevent HTTP header injection.
 /// Allows alphanumeric characters, hyphens, underscores, periods, and base64 chars (+, /, =).
@@ -43,6 +73,127 @@ pub fn validate_org_id(org_id: &str) -> Result<(), AppError> {
     Ok(())
 }
 
+/// Validate a session expiry captured with the token. Must be RFC3339.
+pub fn validate_session_expiry(expires_at: &str) -> Result<(), AppError> {
+    chrono::DateTime::parse_from_rfc3339(expires_at)
+        .map(|_| ())
+        .map_err(|_| AppError::MissingConfig("invalid session expiry".to_string()))
+}
+
+fn validate_range(setting: &str, value: u32, range: &RangeInclusive<u32>) -> Result<(), AppError> {
+    if range.contains(&value) {
+        Ok(())
+    } else {
+        Err(AppError::InvalidSetting {
+            setting: setting.to_string(),
+            reason: format!(
+                "{value} is not between {} and {}",
+                range.start(),
+                range.end()
+            ),
+        })
+    }
+}
+
+/// Validate a refresh or polling interval in minutes.
+pub fn validate_interval_minutes(setting: &str, minutes: u32) -> Result<(), AppError> {
+    validate_range(setting, minutes, &INTERVAL_MINUTES)
+}
+
+/// Validate a history retention in days.
+pub fn validate_retention_days(setting: &str, days: u32) -> Result<(), AppError> {
+    validate_range(setting, days, &RETENTION_DAYS)
+}
+
+/// Validate the hours without activity before polling is suspended.
+pub fn validate_idle_hours(setting: &str, hours: u32) -> Result<(), AppError> {
+    validate_range(setting, hours, &IDLE_HOURS)
+}
+
+/// Validate the upper bound of the startup jitter in seconds.
+pub fn validate_startup_jitter(setting: &str, secs: u32) -> Result<(), AppError> {
+    validate_range(setting, secs, &STARTUP_JITTER_SECS)
+}
+
+/// Validate the hourly refresh gap in seconds.
+pub fn validate_hourly_refresh_gap(setting: &str, secs: u32) -> Result<(), AppError> {
+    validate_range(setting, secs, &HOURLY_REFRESH_GAP_SECS)
+}
+
+/// Validate the upper bound of the hourly refresh jitter in seconds.
+pub fn validate_hourly_refresh_jitter(setting: &str, secs: u32) -> Result<(), AppError> {
+    validate_range(setting, secs, &HOURLY_REFRESH_JITTER_SECS)
+}
+
+/// Validate the days of warning before a session expires.
+pub fn validate_token_expiry_warning_days(setting: &str, days: u32) -> Result<(), AppError> {
+    validate_range(setting, days, &TOKEN_EXPIRY_WARNING_DAYS)
+}
+
+/// Validate a utilization threshold in percent.
+pub fn validate_threshold(setting: &str, percent: u32) -> Result<(), AppError> {
+    validate_range(setting, percent, &THRESHOLD_PERCENT)
+}
+
+/// Validate the per-table retention preference.
+pub fn validate_retention_policy(policy: &BTreeMap<HistoryTable, u32>) -> Result<(), AppError> {
+    policy.iter().try_for_each(|(table, days)| {
+        validate_retention_days(&format!("history_retention.{table:?}"), *days)
+    })
+}
+
+/// Validate every rule's thresholds and intervals.
+pub fn validate_notification_settings(settings: &NotificationSettings) -> Result<(), AppError> {
+    settings.rules.iter().try_for_each(|(window, rule)| {
+        let setting = |field: &str| format!("{window}.{field}");
+        validate_threshold(&setting("interval_percent"), rule.interval_percent)?;
+        rule.thresholds
+            .iter()
+            .try_for_each(|percent| validate_threshold(&setting("thresholds"), *percent))?;
+        validate_threshold(&setting("reminder_threshold"), rule.reminder_threshold)?;
+        validate_interval_minutes(
+            &setting("reminder_interval_minutes"),
+            rule.reminder_interval_minutes,
+        )?;
+        rule.time_remaining_minutes.iter().try_for_each(|minutes| {
+            validate_interval_minutes(&setting("time_remaining_minutes"), *minutes)
+        })
+    })
+}
+
+/// Validate the model advisory cutoffs.
+pub fn validate_model_advisory(settings: &ModelAdvisorySettings) -> Result<(), AppError> {
+    validate_threshold("opus_threshold", settings.opus_threshold)?;
+    validate_threshold("sonnet_max", settings.sonnet_max)
+}
+
+/// Validate severity cutoffs: within 0-100, with warning not above critical.
+pub fn validate_severity_config(config: &SeverityConfig) -> Result<(), AppError> {
+    let in_range = |value: f64| (0.0..=100.0).contains(&value);
+    if !in_range(config.time_sensitive) {
+        return Err(AppError::InvalidSetting {
+            setting: "severity.time_sensitive".to_string(),
+            reason: "cutoff must be within 0-100".to_string(),
+        });
+    }
+    std::iter::once(("default", &config.default))
+        .chain(config.windows.iter().map(|(key, t)| (key.as_str(), t)))
+        .try_for_each(|(key, thresholds)| {
+            if in_range(thresholds.warning)
+                && in_range(thresholds.critical)
+                && thresholds.warning <= thresholds.critical
+            {
+                Ok(())
+            } else {
+                Err(AppError::InvalidSetting {
+                    setting: format!("severity.{key}"),
+                    reason: "cutoffs must be within 0-100 with warning not above critical"
+                        .to_string(),
+                })
+            }
+        })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -158,4 +309,99 @@ mod tests {
             assert!(validate_org_id("org with spaces").is_err());
         }
     }
+
+    mod validate_session_expiry {
+        use super::*;
+
+        #[test]
+        fn accepts_rfc3339() {
+            assert!(validate_session_expiry("2026-05-13T10:00:00Z").is_ok());
+            assert!(validate_session_expiry("2026-05-13T12:00:00+02:00").is_ok());
+        }
+
+        #[test]
+        fn rejects_other_formats() {
+            assert!(validate_session_expiry("Wed, 13 May 2026 10:00:00 GMT").is_err());
+            assert!(validate_session_expiry("").is_err());
+        }
+    }
+
+    mod validate_numeric_settings {
+        use super::*;
+        use crate::types::NotificationRule;
+
+        #[test]
+        fn enforces_interval_retention_and_threshold_bounds() {
+            assert!(validate_interval_minutes("interval_minutes", 0).is_err());
+            assert!(validate_interval_minutes("interval_minutes", 1).is_ok());
+            assert!(validate_interval_minutes("interval_minutes", 1440).is_ok());
+            assert!(validate_interval_minutes("interval_minutes", 1441).is_err());
+            assert!(validate_retention_days("retention_days", 0).is_err());
+            assert!(validate_retention_days("retention_days", 90).is_ok());
+            assert!(validate_idle_hours("idle_suspend_hours", 0).is_err());
+            assert!(validate_idle_hours("idle_suspend_hours", 168).is_ok());
+            assert!(validate_idle_hours("idle_suspend_hours", 169).is_err());
+            assert!(validate_startup_jitter("jitter_secs", 0).is_ok());
+            assert!(validate_startup_jitter("jitter_secs", 601).is_err());
+            assert!(validate_hourly_refresh_gap("gap_secs", 900).is_ok());
+            assert!(validate_hourly_refresh_gap("gap_secs", 901).is_err());
+            assert!(validate_hourly_refresh_jitter("jitter_secs", 301).is_err());
+            assert!(validate_token_expiry_warning_days("days", 0).is_ok());
+            assert!(validate_token_expiry_warning_days("days", 91).is_err());
+            assert!(validate_threshold("opus_threshold", 0).is_err());
+            assert!(validate_threshold("opus_threshold", 100).is_ok());
+            assert!(validate_threshold("opus_threshold", 101).is_err());
+        }
+
+        #[test]
+        fn names_the_offending_setting() {
+            let mut settings = NotificationSettings::default();
+            settings
+                .rules
+                .insert("five_hour".to_string(), NotificationRule::default());
+            assert!(validate_notification_settings(&settings).is_ok());
+
+            settings.rules.get_mut("five_hour").unwrap().thresholds = vec![80, 150];
+            match validate_notification_settings(&settings) {
+                Err(AppError::InvalidSetting { setting, .. }) => {
+                    assert_eq!(setting, "five_hour.thresholds")
+                }
+                other => panic!("expected InvalidSetting, got {other:?}"),
+            }
+
+            let policy = BTreeMap::from([(HistoryTable::MemberUsage, 0)]);
+            assert!(validate_retention_policy(&policy).is_err());
+        }
+    }
+
+    mod validate_severity_config {
+        use super::*;
+        use crate::types::SeverityThresholds;
+
+        #[test]
+        fn rejects_inverted_or_out_of_range_cutoffs() {
+            let mut config = SeverityConfig::default();
+            assert!(validate_severity_config(&config).is_ok());
+
+            let cutoffs = |warning, critical| SeverityThresholds { warning, critical };
+            config
+                .windows
+                .insert("seven_day".to_string(), cutoffs(80.0, 60.0));
+            assert!(validate_severity_config(&config).is_err());
+            config
+                .windows
+                .insert("seven_day".to_string(), cutoffs(60.0, 120.0));
+            assert!(validate_severity_config(&config).is_err());
+            config
+                .windows
+                .insert("seven_day".to_string(), cutoffs(f64::NAN, 80.0));
+            assert!(validate_severity_config(&config).is_err());
+
+            let config = SeverityConfig {
+                time_sensitive: 101.0,
+                ..SeverityConfig::default()
+            };
+            assert!(validate_severity_config(&config).is_err());
+        }
+    }
 }
@@ -87,6 +87,7 @@ impl Drop for WakeObserver {
 pub fn start_wake_monitor(restart_tx: watch::Sender<()>) -> Retained<WakeObserver> {
     WakeObserver::new(move || {
         log::info!("System resume or unlock detected, triggering refresh");
+        crate::auto_refresh::note_wake();
         let _ = restart_tx.send(());
     })
 }
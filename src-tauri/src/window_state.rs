@@ -0,0 +1,300 @@
+//! Window placement on Windows/Linux.
+//!
+//! By default the window opens next to the tray icon, on the monitor that
+//! contains it and clamped to that monitor's work area. With
+//! `Preferences.window_placement = remember`, its last position and size are
+//! saved on hide and restored on open, keyed by monitor layout so that
+//! docking or undocking a display doesn't restore it off-screen.
+//!
+//! A pinned window (`Preferences.window_pinned`) stays open when it loses focus.
+//! Compact mode (all platforms) shrinks the window to a strip of usage bars.
+
+use crate::tray::CompactModeMenuItem;
+use crate::types::CompactModeChanged;
+use serde::{Deserialize, Serialize};
+use specta::Type;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(not(target_os = "macos"))]
+use std::collections::BTreeMap;
+#[cfg(not(target_os = "macos"))]
+use tauri::{PhysicalPosition, PhysicalRect, PhysicalSize, Rect, Runtime, WebviewWindow};
+#[cfg(not(target_os = "macos"))]
+use tauri_plugin_store::StoreExt;
+
+#[cfg(not(target_os = "macos"))]
+const GEOMETRY_STORE_KEY: &str = "window_geometry";
+
+/// Logical window sizes; the full size matches `tauri.conf.json`.
+const FULL_SIZE: (f64, f64) = (400.0, 450.0);
+const COMPACT_SIZE: (f64, f64) = (400.0, 120.0);
+
+/// Where the window opens.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, Type)]
+#[serde(rename_all = "camelCase")]
+pub enum WindowPlacement {
+    /// Centre on the tray icon every time.
+    #[default]
+    FollowTray,
+    /// Reopen where it was last left.
+    Remember,
+}
+
+static REMEMBER: AtomicBool = AtomicBool::new(false);
+static PINNED: AtomicBool = AtomicBool::new(false);
+
+/// Apply a placement preference. Called at startup and when preferences change.
+pub fn set_window_placement(placement: WindowPlacement) {
+    REMEMBER.store(placement == WindowPlacement::Remember, Ordering::Relaxed);
+}
+
+/// Keep the window open when it loses focus (e.g. on a second screen).
+pub fn set_pinned(pinned: bool) {
+    PINNED.store(pinned, Ordering::Relaxed);
+}
+
+pub fn is_pinned() -> bool {
+    PINNED.load(Ordering::Relaxed)
+}
+
+/// Resize the main window for compact mode, sync the tray checkbox and tell
+/// the frontend to switch layouts.
+pub fn set_compact<R: tauri::Runtime>(app: &tauri::AppHandle<R>, compact: bool) {
+    use tauri::Manager;
+    use tauri_specta::Event;
+
+    let (width, height) = if compact { COMPACT_SIZE } else { FULL_SIZE };
+    if let Some(window) = app.get_webview_window("main") {
+        let _ = window.set_size(tauri::LogicalSize::new(width, height));
+    }
+    if let Some(item) = app.try_state::<CompactModeMenuItem<R>>() {
+        let _ = item.0.set_checked(compact);
+    }
+    let _ = CompactModeChanged { compact }.emit(app);
+}
+
+#[cfg(not(target_os = "macos"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct WindowGeometry {
+    x: i32,
+    y: i32,
+    width: u32,
+    height: u32,
+}
+
+/// Identify the current set of monitors by name, position and size.
+#[cfg(not(target_os = "macos"))]
+fn monitor_layout_key<R: Runtime>(window: &WebviewWindow<R>) -> Option<String> {
+    let mut monitors: Vec<String> = window
+        .available_monitors()
+        .ok()?
+        .iter()
+        .map(|monitor| {
+            layout_entry(
+                monitor.name().map(String::as_str),
+                *monitor.position(),
+                *monitor.size(),
+            )
+        })
+        .collect();
+    if monitors.is_empty() {
+        return None;
+    }
+    monitors.sort();
+    Some(monitors.join(";"))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn layout_entry(
+    name: Option<&str>,
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+) -> String {
+    format!(
+        "{}@{},{} {}x{}",
+        name.unwrap_or("unknown"),
+        position.x,
+        position.y,
+        size.width,
+        size.height
+    )
+}
+
+#[cfg(not(target_os = "macos"))]
+fn load_geometries<R: Runtime>(window: &WebviewWindow<R>) -> BTreeMap<String, WindowGeometry> {
+    window
+        .store("settings.json")
+        .ok()
+        .and_then(|store| store.get(GEOMETRY_STORE_KEY))
+        .and_then(|value| serde_json::from_value(value).ok())
+        .unwrap_or_default()
+}
+
+/// Save the window's position and size for the current monitor layout.
+/// Does nothing unless the user chose to remember the position.
+#[cfg(not(target_os = "macos"))]
+pub fn save_geometry<R: Runtime>(window: &WebviewWindow<R>) {
+    if !REMEMBER.load(Ordering::Relaxed) || !window.is_visible().unwrap_or(false) {
+        return;
+    }
+    let (Some(layout), Ok(position), Ok(size)) = (
+        monitor_layout_key(window),
+        window.outer_position(),
+        window.outer_size(),
+    ) else {
+        return;
+    };
+
+    let mut geometries = load_geometries(window);
+    geometries.insert(
+        layout,
+        WindowGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        },
+    );
+
+    match (
+        window.store("settings.json"),
+        serde_json::to_value(&geometries),
+    ) {
+        (Ok(store), Ok(value)) => store.set(GEOMETRY_STORE_KEY, value),
+        _ => log::warn!("Failed to save window geometry"),
+    }
+}
+
+/// Move the window to its saved geometry for the current monitor layout.
+/// Returns false when following the tray or nothing was saved, so the caller
+/// falls back to tray positioning.
+#[cfg(not(target_os = "macos"))]
+pub fn restore_geometry<R: Runtime>(window: &WebviewWindow<R>) -> bool {
+    if !REMEMBER.load(Ordering::Relaxed) {
+        return false;
+    }
+    let Some(geometry) =
+        monitor_layout_key(window).and_then(|layout| load_geometries(window).remove(&layout))
+    else {
+        return false;
+    };
+
+    let _ = window.set_size(PhysicalSize::new(geometry.width, geometry.height));
+    window
+        .set_position(PhysicalPosition::new(geometry.x, geometry.y))
+        .is_ok()
+}
+
+/// Place the window by the tray icon on the monitor under the click, inside
+/// its work area. Returns false when the monitor can't be determined, so the
+/// caller can fall back to the positioner plugin.
+#[cfg(not(target_os = "macos"))]
+pub fn move_to_tray<R: Runtime>(
+    window: &WebviewWindow<R>,
+    click: PhysicalPosition<f64>,
+    tray_rect: &Rect,
+) -> bool {
+    let (Ok(Some(monitor)), Ok(window_size)) = (
+        window.monitor_from_point(click.x, click.y),
+        window.outer_size(),
+    ) else {
+        return false;
+    };
+
+    let scale = monitor.scale_factor();
+    let position = tray_window_position(
+        tray_rect.position.to_physical(scale),
+        tray_rect.size.to_physical(scale),
+        window_size,
+        monitor.work_area(),
+    );
+    window.set_position(position).is_ok()
+}
+
+/// Centre horizontally on the tray icon, open away from the taskbar edge the
+/// icon sits on, and keep the whole window inside the work area.
+#[cfg(not(target_os = "macos"))]
+fn tray_window_position(
+    tray_position: PhysicalPosition<f64>,
+    tray_size: PhysicalSize<f64>,
+    window_size: PhysicalSize<u32>,
+    work_area: &PhysicalRect<i32, u32>,
+) -> PhysicalPosition<i32> {
+    let area_x = work_area.position.x as f64;
+    let area_y = work_area.position.y as f64;
+    let area_width = work_area.size.width as f64;
+    let area_height = work_area.size.height as f64;
+    let width = window_size.width as f64;
+    let height = window_size.height as f64;
+
+    let x = tray_position.x + tray_size.width / 2.0 - width / 2.0;
+    let tray_in_bottom_half = tray_position.y + tray_size.height / 2.0 > area_y + area_height / 2.0;
+    let y = if tray_in_bottom_half {
+        tray_position.y - height
+    } else {
+        tray_position.y + tray_size.height
+    };
+
+    let clamp =
+        |value: f64, min: f64, extent: f64, size: f64| value.min(min + extent - size).max(min);
+    PhysicalPosition::new(
+        clamp(x, area_x, area_width, width).round() as i32,
+        clamp(y, area_y, area_height, height).round() as i32,
+    )
+}
+
+#[cfg(all(test, not(target_os = "macos")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn layout_entry_describes_monitor() {
+        assert_eq!(
+            layout_entry(
+                Some("DP-1"),
+                PhysicalPosition::new(-1920, 0),
+                PhysicalSize::new(1920, 1080)
+            ),
+            "DP-1@-1920,0 1920x1080"
+        );
+        assert_eq!(
+            layout_entry(
+                None,
+                PhysicalPosition::new(0, 0),
+                PhysicalSize::new(800, 600)
+            ),
+            "unknown@0,0 800x600"
+        );
+    }
+
+    fn work_area(x: i32, y: i32, width: u32, height: u32) -> PhysicalRect<i32, u32> {
+        PhysicalRect {
+            position: PhysicalPosition::new(x, y),
+            size: PhysicalSize::new(width, height),
+        }
+    }
+
+    #[test]
+    fn opens_above_bottom_taskbar_and_clamps_to_screen_edge() {
+        // Tray icon in the bottom-right corner of a 1920x1040 work area
+        let position = tray_window_position(
+            PhysicalPosition::new(1880.0, 1045.0),
+            PhysicalSize::new(24.0, 24.0),
+            PhysicalSize::new(400, 450),
+            &work_area(0, 0, 1920, 1040),
+        );
+        assert_eq!(position, PhysicalPosition::new(1520, 590));
+    }
+
+    #[test]
+    fn opens_below_top_panel_on_secondary_monitor() {
+        // Second monitor to the left, panel at the top
+        let position = tray_window_position(
+            PhysicalPosition::new(-1000.0, 0.0),
+            PhysicalSize::new(24.0, 28.0),
+            PhysicalSize::new(400, 450),
+            &work_area(-1920, 28, 1920, 1052),
+        );
+        assert_eq!(position, PhysicalPosition::new(-1188, 28));
+    }
+}
@@ -0,0 +1,70 @@
+//! Windows Usage Toasts
+//!
+//! Usage alerts are posted as WinRT toasts directly so they can carry a progress
+//! bar filled to the highest alerted window's utilization and an "Open" button
+//! that brings the main window back. Uses the same app id as the notification
+//! plugin, so toasts group with the app's other notifications.
+
+use crate::i18n::{Text, t};
+use tauri::Manager;
+use tauri_winrt_notification::{Duration, Progress, Toast};
+
+/// Post a usage toast. Returns false when it couldn't be shown, so the caller
+/// can fall back to the notification plugin.
+pub fn show<R: tauri::Runtime>(
+    app: &tauri::AppHandle<R>,
+    title: &str,
+    body: &str,
+    label: &str,
+    utilization: f64,
+) -> bool {
+    let app_id = if tauri::is_dev() {
+        Toast::POWERSHELL_APP_ID.to_string()
+    } else {
+        app.config().identifier.clone()
+    };
+    let progress = Progress {
+        tag: "usage".to_string(),
+        title: label.to_string(),
+        status: String::new(),
+        value: progress_value(utilization),
+        value_string: format!("{utilization:.0}%"),
+    };
+
+    let app = app.clone();
+    Toast::new(&app_id)
+        .title(title)
+        .text1(body)
+        .progress(&progress)
+        .duration(Duration::Short)
+        .add_button(t(Text::Open), "open")
+        .on_activated(move |_action| {
+            // Clicking the toast or its button opens the app
+            if let Some(window) = app.get_webview_window("main") {
+                let _ = window.unminimize();
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+            Ok(())
+        })
+        .show()
+        .inspect_err(|e| log::warn!("Failed to show usage toast: {e}"))
+        .is_ok()
+}
+
+/// Fraction of the progress bar to fill.
+fn progress_value(utilization: f64) -> f32 {
+    (utilization / 100.0).clamp(0.0, 1.0) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_progress_to_bar() {
+        assert_eq!(progress_value(50.0), 0.5);
+        assert_eq!(progress_value(130.0), 1.0);
+        assert_eq!(progress_value(-5.0), 0.0);
+    }
+}
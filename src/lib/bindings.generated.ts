@@ -1,27 +1,426 @@
 // This file has been generated by Tauri Specta. Do not edit this file manually.
 
 import { invoke as __TAURI_INVOKE } from "@tauri-apps/api/core";
+import * as __TAURI_EVENT from "@tauri-apps/api/event";
 
 /** Commands */
 export const commands = {
-	getUsage: (provider: ProviderKind, orgId: string | null, sessionToken: string | null, ollamaSessionToken: string | null) => typedError<UsageSnapshot, string>(__TAURI_INVOKE("get_usage", { provider, orgId, sessionToken, ollamaSessionToken })),
+	setStartupJitter: (jitterSecs: number) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_startup_jitter", { jitterSecs })),
+	getStaleness: () => typedError<Staleness, null>(__TAURI_INVOKE("get_staleness")),
+	generateReport: (range: string, format: ReportFormat, path: string) => typedError<null, ErrorPayload>(__TAURI_INVOKE("generate_report", { range, format, path })),
+	getUsage: (provider: ProviderKind, orgId: string | null, sessionToken: string | null, ollamaSessionToken: string | null) => typedError<UsageSnapshot, ErrorPayload>(__TAURI_INVOKE("get_usage", { provider, orgId, sessionToken, ollamaSessionToken })),
 	getDefaultSettings: () => __TAURI_INVOKE<Settings>("get_default_settings"),
-	saveCredentials: (orgId: string, sessionToken: string) => typedError<null, string>(__TAURI_INVOKE("save_credentials", { orgId, sessionToken })),
-	clearCredentials: () => typedError<null, string>(__TAURI_INVOKE("clear_credentials")),
-	saveOllamaCredentials: (sessionToken: string) => typedError<null, string>(__TAURI_INVOKE("save_ollama_credentials", { sessionToken })),
-	clearOllamaCredentials: () => typedError<null, string>(__TAURI_INVOKE("clear_ollama_credentials")),
+	saveCredentials: (orgId: string, sessionToken: string, sessionExpiresAt: string | null) => typedError<null, ErrorPayload>(__TAURI_INVOKE("save_credentials", { orgId, sessionToken, sessionExpiresAt })),
+	clearCredentials: () => typedError<null, ErrorPayload>(__TAURI_INVOKE("clear_credentials")),
+	saveOllamaCredentials: (sessionToken: string) => typedError<null, ErrorPayload>(__TAURI_INVOKE("save_ollama_credentials", { sessionToken })),
+	clearOllamaCredentials: () => typedError<null, ErrorPayload>(__TAURI_INVOKE("clear_ollama_credentials")),
+	getKeychainStatus: () => __TAURI_INVOKE<KeychainIssue | null>("get_keychain_status"),
+	getAppLockStatus: () => typedError<AppLockStatus, null>(__TAURI_INVOKE("get_app_lock_status")),
+	setAppPassphrase: (passphrase: string | null, currentPassphrase: string | null) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_app_passphrase", { passphrase, currentPassphrase })),
+	setAutoLockTimeout: (minutes: number | null) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_auto_lock_timeout", { minutes })),
+	lockApp: () => typedError<null, ErrorPayload>(__TAURI_INVOKE("lock_app")),
+	unlockApp: (passphrase: string) => typedError<null, ErrorPayload>(__TAURI_INVOKE("unlock_app", { passphrase })),
 	getProviderStatuses: () => typedError<ProviderStatus[], null>(__TAURI_INVOKE("get_provider_statuses")),
 	setActiveProvider: (provider: ProviderKind) => typedError<null, null>(__TAURI_INVOKE("set_active_provider", { provider })),
-	setAutoRefresh: (enabled: boolean, intervalMinutes: number) => typedError<null, null>(__TAURI_INVOKE("set_auto_refresh", { enabled, intervalMinutes })),
+	setAutoRefresh: (enabled: boolean, intervalMinutes: number) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_auto_refresh", { enabled, intervalMinutes })),
 	setHourlyRefresh: (enabled: boolean) => typedError<null, null>(__TAURI_INVOKE("set_hourly_refresh", { enabled })),
-	refreshNow: () => typedError<null, null>(__TAURI_INVOKE("refresh_now")),
-	setNotificationSettings: (settings: NotificationSettings) => typedError<null, null>(__TAURI_INVOKE("set_notification_settings", { settings })),
-	getUsageHistoryByRange: (provider: ProviderKind, range: string) => typedError<UsageHistoryPoint[], string>(__TAURI_INVOKE("get_usage_history_by_range", { provider, range })),
+	setHourlyRefreshTiming: (gapSecs: number, jitterSecs: number) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_hourly_refresh_timing", { gapSecs, jitterSecs })),
+	refreshNow: () => typedError<ManualRefresh, null>(__TAURI_INVOKE("refresh_now")),
+	setNotificationSettings: (settings: NotificationSettings) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_notification_settings", { settings })),
+	getUsageHistoryByRange: (provider: ProviderKind, range: string, metrics: string[]) => typedError<UsageHistoryPoint[], string>(__TAURI_INVOKE("get_usage_history_by_range", { provider, range, metrics })),
 	getUsageStats: (provider: ProviderKind, range: string) => typedError<UsageStats, string>(__TAURI_INVOKE("get_usage_stats", { provider, range })),
+	getTimeToThreshold: (provider: ProviderKind, range: string, threshold: number) => typedError<TimeToThreshold, string>(__TAURI_INVOKE("get_time_to_threshold", { provider, range, threshold })),
+	getUsageHistogram: (provider: ProviderKind, range: string, metric: string) => typedError<UsageHistogram, string>(__TAURI_INVOKE("get_usage_histogram", { provider, range, metric })),
+	getChartSeries: (provider: ProviderKind, range: string, metrics: string[]) => typedError<ChartSeries[], string>(__TAURI_INVOKE("get_chart_series", { provider, range, metrics })),
+	getCombinedHistory: (provider: ProviderKind, range: string, metrics: string[]) => typedError<CombinedHistory, string>(__TAURI_INVOKE("get_combined_history", { provider, range, metrics })),
+	getProjectedLimitTime: (provider: ProviderKind) => typedError<LimitProjection[], string>(__TAURI_INVOKE("get_projected_limit_time", { provider })),
+	getMemberUsage: (range: string) => typedError<MemberUsageSummary[], string>(__TAURI_INVOKE("get_member_usage", { range })),
 	cleanupHistory: (retentionDays: number) => typedError<number, string>(__TAURI_INVOKE("cleanup_history", { retentionDays })),
+	getHistoryDbInfo: () => typedError<HistoryDbInfo, string>(__TAURI_INVOKE("get_history_db_info")),
+	clearHistory: (scope: ClearScope, confirmation: string) => typedError<number, string>(__TAURI_INVOKE("clear_history", { scope, confirmation })),
+	snoozeUntilReset: () => typedError<string | null, ErrorPayload>(__TAURI_INVOKE("snooze_until_reset")),
+	simulateUsage: (usage: UsageSnapshot) => typedError<null, string>(__TAURI_INVOKE("simulate_usage", { usage })),
+	startResponseCapture: (path: string) => typedError<null, ErrorPayload>(__TAURI_INVOKE("start_response_capture", { path })),
+	stopResponseCapture: () => __TAURI_INVOKE<string | null>("stop_response_capture"),
+	replayResponseCapture: (path: string, speed: number) => typedError<number, string>(__TAURI_INVOKE("replay_response_capture", { path, speed })),
+	getLastFetchResult: () => typedError<LastFetchResult | null, null>(__TAURI_INVOKE("get_last_fetch_result")),
+	getPreferences: () => typedError<Preferences, null>(__TAURI_INVOKE("get_preferences")),
+	setPreferences: (preferences: Preferences) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_preferences", { preferences })),
+	getProjectUsage: (range: string) => typedError<ProjectUsage[], ErrorPayload>(__TAURI_INVOKE("get_project_usage", { range })),
+	getModelUsage: (range: string) => typedError<ModelUsage[], ErrorPayload>(__TAURI_INVOKE("get_model_usage", { range })),
+	getCostEstimate: (range: string) => typedError<CostEstimate, ErrorPayload>(__TAURI_INVOKE("get_cost_estimate", { range })),
+	getActiveSessions: () => typedError<ActiveSessions, ErrorPayload>(__TAURI_INVOKE("get_active_sessions")),
+	setActiveSessionInterval: (intervalMinutes: number | null) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_active_session_interval", { intervalMinutes })),
+	setMemberUsageEnabled: (enabled: boolean) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_member_usage_enabled", { enabled })),
+	setModelAdvisory: (settings: ModelAdvisorySettings) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_model_advisory", { settings })),
+	setWeeklySummaryEnabled: (enabled: boolean) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_weekly_summary_enabled", { enabled })),
+	setUsageBudgets: (budgets: UsageBudget[]) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_usage_budgets", { budgets })),
+	getBudgetProgress: () => typedError<BudgetProgress[], ErrorPayload>(__TAURI_INVOKE("get_budget_progress")),
+	setNearResetPolling: (minutes: number | null) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_near_reset_polling", { minutes })),
+	setTokenExpiryWarning: (days: number) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_token_expiry_warning", { days })),
+	setIdleSuspend: (hours: number | null) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_idle_suspend", { hours })),
+	setHistorySampleInterval: (minutes: number | null) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_history_sample_interval", { minutes })),
+	setSkipUnchangedSamples: (enabled: boolean) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_skip_unchanged_samples", { enabled })),
+	setHistorySyncFolder: (folder: string | null) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_history_sync_folder", { folder })),
+	syncHistoryNow: () => typedError<number, string>(__TAURI_INVOKE("sync_history_now")),
+	importUsageData: (path: string) => typedError<UsageImportSummary, string>(__TAURI_INVOKE("import_usage_data", { path })),
+	setAutostart: (enabled: boolean, startHidden: boolean) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_autostart", { enabled, startHidden })),
+	listClaudeOrganizations: (sessionToken: string) => typedError<Organization[], ErrorPayload>(__TAURI_INVOKE("list_claude_organizations", { sessionToken })),
+	setAdditionalOrganizations: (organizations: Organization[]) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_additional_organizations", { organizations })),
+	switchOrganization: (orgId: string) => typedError<null, ErrorPayload>(__TAURI_INVOKE("switch_organization", { orgId })),
+	getOrganizationUsage: () => typedError<OrganizationUsage[], null>(__TAURI_INVOKE("get_organization_usage")),
+	setWindowPinned: (pinned: boolean) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_window_pinned", { pinned })),
+	setCompactMode: (compact: boolean) => typedError<null, ErrorPayload>(__TAURI_INVOKE("set_compact_mode", { compact })),
+};
+
+/** Events */
+export const events = {
+	usageUpdated: makeEvent<UsageUpdated>("usage-updated"),
+	usageError: makeEvent<UsageError>("usage-error"),
+	activeSessionsChanged: makeEvent<ActiveSessionsChanged>("active-sessions-changed"),
+	localUsageChanged: makeEvent<LocalUsageChanged>("local-usage-changed"),
+	rateLimitUpdated: makeEvent<RateLimitUpdated>("rate-limit-updated"),
+	checkForUpdates: makeEvent<CheckForUpdates>("check-for-updates"),
+	modelAdvisory: makeEvent<ModelAdvisory>("model-advisory"),
+	compactModeChanged: makeEvent<CompactModeChanged>("compact-mode-changed"),
+	historyAppended: makeEvent<HistoryAppended>("history-appended"),
+	historyChanged: makeEvent<HistoryChanged>("history-changed"),
+	appLockChanged: makeEvent<AppLockChanged>("app-lock-changed"),
+	keychainIssue: makeEvent<KeychainIssue>("keychain-issue"),
+	staleDataSuspected: makeEvent<StaleDataSuspected>("stale-data-suspected"),
+	refreshLoopRestarted: makeEvent<RefreshLoopRestarted>("refresh-loop-restarted"),
 };
 
 /* Types */
+export type ActiveSession = {
+	sessionId: string,
+	project: string,
+	lastActivity: string,
+};
+
+export type ActiveSessions = {
+	count: number,
+	sessions: ActiveSession[],
+};
+
+export type ActiveSessionsChanged = ActiveSessions;
+
+/**
+ * Emitted when the app locks (by hand or after the auto-lock timeout) or unlocks.
+ */
+export type AppLockChanged = {
+	locked: boolean,
+};
+
+export type AppLockStatus = {
+	/**
+	 * A passphrase is set.
+	 */
+	enabled: boolean,
+	locked: boolean,
+	autoLockMinutes: number | null,
+};
+
+export type BudgetProgress = {
+	budget: UsageBudget,
+	label: string,
+	utilization: number,
+	/**
+	 * Utilization the plan allows by now.
+	 */
+	planned: number,
+	/**
+	 * Utilization above the plan; negative while under it.
+	 */
+	aheadBy: number,
+	status: BudgetStatus,
+	/**
+	 * When the target applies (RFC3339).
+	 */
+	deadlineAt: string,
+	/**
+	 * Reset of the period this progress belongs to (RFC3339).
+	 */
+	resetsAt: string,
+};
+
+export type BudgetStatus = "onTrack" | "ahead" | "exceeded";
+
+export type BudgetWeekday = "monday" | "tuesday" | "wednesday" | "thursday" | "friday" | "saturday" | "sunday";
+
+export type ChartPoint = {
+	/**
+	 * Unix epoch milliseconds.
+	 */
+	x: number,
+	/**
+	 * Utilization; null breaks the line where no data was recorded.
+	 */
+	y: number | null,
+};
+
+/**
+ * One window's line, ready to plot.
+ */
+export type ChartSeries = {
+	key: string,
+	label: string,
+	points: ChartPoint[],
+};
+
+export type CheckForUpdates = null;
+
+/**
+ * Which history `clear_history` deletes.
+ */
+export type ClearScope = { scope: "all" } | { scope: "olderThan"; days: number } | { scope: "metric"; provider: ProviderKind; key: string };
+
+/**
+ * Utilization and local token history over the same range, for overlay charts.
+ */
+export type CombinedHistory = {
+	utilization: UsageHistoryPoint[],
+	tokens: TokenHistoryPoint[],
+	tokenBucketMinutes: number,
+};
+
+export type CompactModeChanged = {
+	compact: boolean,
+};
+
+export type CostBucket = {
+	/**
+	 * Local calendar date the bucket starts on (`YYYY-MM-DD`).
+	 */
+	periodStart: string,
+	costUsd: number,
+	tokens: TokenCounts,
+};
+
+export type CostEstimate = {
+	totalCostUsd: number,
+	daily: CostBucket[],
+	weekly: CostBucket[],
+	/**
+	 * Models with no known or overridden price; their tokens are excluded from costs.
+	 */
+	unpricedModels: string[],
+};
+
+/**
+ * What the app does instead while the keychain can't be used.
+ */
+export type CredentialFallback = "memory_only" | "replace_on_save";
+
+/**
+ * Machine-readable error category, so the UI can offer specific remediation.
+ */
+export type ErrorCode = "network" | "invalid_token" | "rate_limited" | "server" | "missing_config" | "storage" | "organization_not_found" | "forbidden" | "blocked" | "locked" | "wrong_passphrase" | "invalid_setting";
+
+/**
+ * Wire format of `AppError`: `{ code, message }`.
+ */
+export type ErrorPayload = {
+	code: ErrorCode,
+	message: string,
+};
+
+/**
+ * How far to trust a projection, from the samples behind it.
+ */
+export type ForecastConfidence = "low" | "medium" | "high";
+
+/**
+ * Usage consumed in one local hour of one weekday, summed over a range.
+ */
+export type HistogramBucket = {
+	/**
+	 * 0 = Monday ... 6 = Sunday.
+	 */
+	weekday: number,
+	/**
+	 * 0-23.
+	 */
+	hour: number,
+	/**
+	 * Percentage points the window rose by, counting from 0 after resets.
+	 */
+	consumption: number,
+	samples: number,
+};
+
+/**
+ * The points just recorded for the primary organization, so open charts can
+ * append them instead of re-querying their range.
+ */
+export type HistoryAppended = {
+	points: UsageHistoryPoint[],
+};
+
+/**
+ * Emitted after history is deleted or imported so charts and storage info reload.
+ */
+export type HistoryChanged = null;
+
+/**
+ * Size and contents of the history database, for the settings storage panel.
+ */
+export type HistoryDbInfo = {
+	fileSizeBytes: number,
+	usageRowCount: number,
+	tokenRowCount: number,
+	/**
+	 * Oldest and newest utilization samples (RFC3339).
+	 */
+	oldestTimestamp: string | null,
+	newestTimestamp: string | null,
+	journalMode: string,
+};
+
+/**
+ * A history table that grows with time, for retention.
+ */
+export type HistoryTable = "usage" | "localTokens" | "memberUsage";
+
+/**
+ * Emitted when a keychain access fails (at startup, unlock or save), and
+ * returned by `get_keychain_status` until credentials are saved successfully.
+ */
+export type KeychainIssue = {
+	problem: KeychainProblem,
+	fallback: CredentialFallback,
+	/**
+	 * The platform error, redacted.
+	 */
+	detail: string,
+};
+
+/**
+ * Why the OS keychain couldn't be read or written.
+ */
+export type KeychainProblem = "unavailable" | "locked" | "access_denied" | "unreadable" | "failed";
+
+/**
+ * Language preference as stored in settings.
+ */
+export type Language = "system" | "en" | "de" | "es" | "fr" | "ja";
+
+/**
+ * Outcome of the most recent usage fetch, so a newly opened window can render
+ * before the next `usage-updated` event.
+ */
+export type LastFetchResult = {
+	provider: ProviderKind,
+	success: boolean,
+	error: ErrorPayload | null,
+	/**
+	 * When the fetch finished (RFC3339).
+	 */
+	finishedAt: string,
+	/**
+	 * None when the request failed before a response arrived.
+	 */
+	httpStatus: number | null,
+	durationMs: number,
+	usage: UsageSnapshot | null,
+};
+
+/**
+ * When a window would reach 100% at its current burn rate.
+ */
+export type LimitProjection = {
+	key: string,
+	label: string,
+	utilization: number,
+	/**
+	 * Percentage points per hour over the last hour, when known.
+	 */
+	burnRate: number | null,
+	/**
+	 * Projected time of reaching 100% (RFC3339); None when the pace is safe.
+	 */
+	projectedAt: string | null,
+	/**
+	 * Samples since the latest reset within the last hour.
+	 */
+	sampleCount: number,
+	/**
+	 * How well a straight line fits those samples (0-1); None with fewer
+	 * than three samples.
+	 */
+	rSquared: number | null,
+	confidence: ForecastConfidence,
+};
+
+export type LocalUsageChanged = null;
+
+/**
+ * What `refresh_now` did.
+ */
+export type ManualRefresh = { status: "refreshed" } | { status: "rateLimited"; retryInSeconds: number };
+
+/**
+ * A member's latest and peak utilization of one window over a range.
+ */
+export type MemberUsageSummary = {
+	memberId: string,
+	name: string | null,
+	email: string | null,
+	windowKey: string,
+	label: string,
+	utilization: number,
+	peakUtilization: number,
+	resetsAt: string | null,
+	/**
+	 * When the latest sample was recorded (RFC3339).
+	 */
+	timestamp: string,
+};
+
+/**
+ * Opus's weekly window is running high while Sonnet's has headroom.
+ */
+export type ModelAdvisory = {
+	opusUtilization: number,
+	sonnetUtilization: number,
+};
+
+/**
+ * Suggest Sonnet while Opus's weekly window runs high and Sonnet's has room.
+ */
+export type ModelAdvisorySettings = {
+	enabled: boolean,
+	/**
+	 * Opus 7-day utilization at or above which to advise.
+	 */
+	opusThreshold: number,
+	/**
+	 * Sonnet 7-day utilization at or below which it counts as having headroom.
+	 */
+	sonnetMax: number,
+};
+
+/**
+ * Prices in USD per million tokens.
+ */
+export type ModelPricing = {
+	input: number,
+	output: number,
+	cache_write: number,
+	cache_read: number,
+};
+
+export type ModelUsage = {
+	model: string,
+	/**
+	 * Model family (`opus`, `sonnet`, `haiku`, or `other`) for matching API windows.
+	 */
+	family: string,
+	tokens: TokenCounts,
+	messageCount: number,
+	estimatedCostUsd: number,
+};
+
 export type NotificationRule = {
 	interval_enabled: boolean,
 	interval_percent: number,
@@ -29,6 +428,16 @@ export type NotificationRule = {
 	thresholds: number[],
 	time_remaining_enabled: boolean,
 	time_remaining_minutes: number[],
+	/**
+	 * Repeat the alert while utilization stays at or above `reminder_threshold`.
+	 */
+	reminder_enabled: boolean,
+	reminder_threshold: number,
+	reminder_interval_minutes: number,
+	/**
+	 * Notify when utilization falls back under a fired threshold.
+	 */
+	recovery_enabled: boolean,
 };
 
 export type NotificationSettings = {
@@ -36,6 +445,125 @@ export type NotificationSettings = {
 	rules: { [key in string]: NotificationRule },
 };
 
+/**
+ * A Claude organization the session token can access.
+ */
+export type Organization = {
+	id: string,
+	name: string,
+	plan: string | null,
+};
+
+/**
+ * Latest usage of an organization monitored alongside the primary one.
+ */
+export type OrganizationUsage = {
+	organization: Organization,
+	usage: UsageSnapshot,
+};
+
+/**
+ * Why scheduled fetches don't run on the regular interval, so consumers can
+ * say why no countdown is shown.
+ */
+export type PausedReason = "user_pause" | "locked" | "no_credentials" | "auth_expired" | "backoff" | "idle";
+
+/**
+ * Backend-owned preferences persisted under the `preferences` store key.
+ */
+export type Preferences = {
+	show_cost_in_tray: boolean,
+	/**
+	 * Add the first window's burn rate over the last hour to the tray tooltip.
+	 */
+	show_burn_rate_in_tray: boolean,
+	/**
+	 * Per-million-token prices keyed by a model id substring (e.g. `"sonnet"`).
+	 */
+	pricing_overrides: { [key in string]: ModelPricing },
+	/**
+	 * Opt-in: write crash files (and upload them when Sentry is configured).
+	 */
+	crash_reports_enabled: boolean,
+	/**
+	 * Start in the tray when launched at login; otherwise the window is shown.
+	 */
+	start_hidden_at_login: boolean,
+	/**
+	 * Language for notifications and tray labels.
+	 */
+	language: Language,
+	/**
+	 * 12/24-hour clock for times in the tray and notifications.
+	 */
+	time_format: TimeFormat,
+	/**
+	 * User-Agent for provider requests.
+	 */
+	user_agent: UserAgentMode,
+	/**
+	 * Windows/Linux: open at the tray icon or where the window was last left.
+	 */
+	window_placement: WindowPlacement,
+	/**
+	 * Windows/Linux: don't hide the window when it loses focus.
+	 */
+	window_pinned: boolean,
+	/**
+	 * Don't flash the tray icon when usage turns critical.
+	 */
+	reduce_motion: boolean,
+	/**
+	 * Shrink the window to a minimal strip of usage bars.
+	 */
+	compact_mode: boolean,
+	/**
+	 * macOS: let critical alerts (see `severity`) break through Focus modes.
+	 */
+	time_sensitive_alerts: boolean,
+	/**
+	 * Hold back a fetch whose utilization jumps implausibly until the next
+	 * fetch, so one-off API glitches stay out of history and alerts.
+	 */
+	reject_usage_spikes: boolean,
+	/**
+	 * Keep history and notification state in memory only; nothing new is
+	 * written to disk while on.
+	 */
+	incognito: boolean,
+	/**
+	 * Per-organization lines or one worst-case line in the tray tooltip.
+	 */
+	tray_aggregation: TrayAggregation,
+	/**
+	 * Custom tray tooltip, e.g. `{five_hour}% ({five_hour_resets_in})`; None
+	 * keeps the default layout.
+	 */
+	tray_tooltip_format: string | null,
+	/**
+	 * Show synthetic usage instead of fetching it (see `demo.rs`). Read at
+	 * launch, like the `--demo` flag.
+	 */
+	demo_mode: boolean,
+	/**
+	 * Days to keep per history table (0 keeps everything); tables left out
+	 * use `config.toml`'s `retention_days` or their default.
+	 */
+	history_retention: Partial<{ [key in HistoryTable]: number }>,
+	/**
+	 * Warning and critical utilization cutoffs.
+	 */
+	severity: SeverityConfig,
+};
+
+export type ProjectUsage = {
+	project: string,
+	tokens: TokenCounts,
+	messageCount: number,
+	estimatedCostUsd: number,
+	lastActivity: string | null,
+};
+
 export type ProviderKind = "claude" | "codex" | "ollama";
 
 export type ProviderStatus = {
@@ -45,11 +573,211 @@ export type ProviderStatus = {
 	message: string | null,
 };
 
+/**
+ * Request quota reported by the provider's rate-limit response headers.
+ */
+export type RateLimitInfo = {
+	limit: number | null,
+	remaining: number | null,
+	/**
+	 * When the quota resets (RFC3339).
+	 */
+	resetAt: string | null,
+	/**
+	 * Server-requested delay from `Retry-After`.
+	 */
+	retryAfterSeconds: number | null,
+};
+
+/**
+ * Emitted after each fetch that reported rate-limit headers, and whenever the
+ * backoff changes, so the UI can explain why refreshes are delayed.
+ */
+export type RateLimitUpdated = {
+	provider: ProviderKind,
+	rateLimit: RateLimitInfo | null,
+	/**
+	 * Seconds until the next attempt while backing off, 0 when not backing off.
+	 */
+	backoffSeconds: number,
+};
+
+/**
+ * Emitted when the refresh loop panicked; it is respawned after a short delay.
+ */
+export type RefreshLoopRestarted = {
+	/**
+	 * Panic message, redacted.
+	 */
+	message: string,
+	/**
+	 * Restarts since the app started.
+	 */
+	restarts: number,
+};
+
+export type ReportFormat = "markdown" | "html";
+
 export type Settings = {
 	active_provider: ProviderKind,
 	refresh_interval_minutes: number,
 };
 
+/**
+ * The one place warning and critical cutoffs are configured. The tray flash,
+ * notification urgency, the shell prompt's colors and the frontend's bars all
+ * grade usage with it.
+ */
+export type SeverityConfig = {
+	/**
+	 * Cutoffs for windows without their own.
+	 */
+	default: SeverityThresholds,
+	/**
+	 * Cutoffs by window key (e.g. `seven_day`), overriding `default`.
+	 */
+	windows: { [key in string]: SeverityThresholds },
+	/**
+	 * macOS: utilization at which a critical alert breaks through Focus,
+	 * when `Preferences.time_sensitive_alerts` is on.
+	 */
+	time_sensitive: number,
+};
+
+/**
+ * Utilization cutoffs of one window; each applies at or above its value.
+ */
+export type SeverityThresholds = {
+	warning: number,
+	critical: number,
+};
+
+/**
+ * Why usage looks like it's no longer being updated.
+ */
+export type StaleDataReason = "active_sessions" | "reset_passed";
+
+/**
+ * Emitted once when fetches keep succeeding with identical usage that should
+ * have changed, e.g. because the token serves cached data or belongs to
+ * another account. Cleared by the next change in usage.
+ */
+export type StaleDataSuspected = {
+	provider: ProviderKind,
+	reason: StaleDataReason,
+	/**
+	 * Label of the window past its reset, for `ResetPassed`.
+	 */
+	window: string | null,
+	/**
+	 * When usage last changed, as far as this run knows (RFC3339).
+	 */
+	unchangedSince: string,
+};
+
+/**
+ * Age of the displayed usage, from `get_staleness`.
+ */
+export type Staleness = {
+	/**
+	 * When usage was last updated successfully (RFC3339); None before the first update.
+	 */
+	lastSuccessAt: string | null,
+	ageSeconds: number | null,
+	/**
+	 * The last update is more than twice the refresh interval old.
+	 */
+	stale: boolean,
+};
+
+export type ThresholdCrossing = {
+	/**
+	 * Start of the period (RFC3339).
+	 */
+	startedAt: string,
+	/**
+	 * Minutes from the start until the first sample at or above the threshold.
+	 */
+	minutes: number,
+};
+
+/**
+ * Clock style preference for times rendered by the backend.
+ */
+export type TimeFormat = "system" | "twelveHour" | "twentyFourHour";
+
+/**
+ * How long 5-hour periods took to reach a utilization threshold.
+ */
+export type TimeToThreshold = {
+	threshold: number,
+	/**
+	 * Mean of `periods`; None when no period reached the threshold.
+	 */
+	averageMinutes: number | null,
+	/**
+	 * Periods in the range, whether or not they reached the threshold.
+	 */
+	periodCount: number,
+	/**
+	 * Periods that reached it, oldest first.
+	 */
+	periods: ThresholdCrossing[],
+};
+
+export type TokenCounts = {
+	inputTokens: number,
+	outputTokens: number,
+	cacheCreationTokens: number,
+	cacheReadTokens: number,
+};
+
+export type TokenHistoryPoint = {
+	/**
+	 * Start of the bucket (`YYYY-MM-DD HH:MM:SS`, UTC).
+	 */
+	timestamp: string,
+	tokens: TokenCounts,
+	messageCount: number,
+};
+
+/**
+ * How additional organizations show up in the tray tooltip.
+ */
+export type TrayAggregation = "perOrganization" | "worstCase";
+
+export type UsageBudget = {
+	provider: ProviderKind,
+	/**
+	 * Window key, e.g. `seven_day`.
+	 */
+	windowKey: string,
+	/**
+	 * Utilization to stay under by the deadline (1-100).
+	 */
+	targetPercent: number,
+	/**
+	 * Local weekday whose end is the deadline; None means the window's reset.
+	 */
+	deadline: BudgetWeekday | null,
+};
+
+export type UsageError = {
+	provider: ProviderKind,
+	error: string,
+	code: ErrorCode,
+	monitoringEnabled: boolean,
+	pausedReason: PausedReason | null,
+};
+
+/**
+ * When a window was used; hours without samples are left out.
+ */
+export type UsageHistogram = {
+	metric: string,
+	buckets: HistogramBucket[],
+};
+
 export type UsageHistoryPoint = {
 	id: number,
 	provider: ProviderKind,
@@ -58,6 +786,19 @@ export type UsageHistoryPoint = {
 	label: string,
 	utilization: number,
 	resetsAt: string | null,
+	resetsAtEpochMs: number | null,
+};
+
+export type UsageImportSummary = {
+	/**
+	 * Rows stored by this import.
+	 */
+	imported: number,
+	/**
+	 * Rows left out because they overlap the app's own history or were
+	 * already imported.
+	 */
+	skipped: number,
 };
 
 export type UsageSnapshot = {
@@ -65,12 +806,37 @@ export type UsageSnapshot = {
 	windows: UsageWindow[],
 	accountEmail: string | null,
 	planType: string | null,
+	rateLimit: RateLimitInfo | null,
 };
 
 export type UsageStats = {
 	windows: WindowStats[],
 	recordCount: number,
 	periodHours: number,
+	/**
+	 * 5-hour periods in the range whose peak reached `CAPPED_UTILIZATION`.
+	 */
+	cappedFiveHourWindows: number,
+};
+
+export type UsageUpdated = {
+	usage: UsageSnapshot,
+	nextRefreshAt: number | null,
+	/**
+	 * Utilization change per window key since the previous successful fetch of
+	 * the same provider; windows without a previous value are left out.
+	 */
+	deltas: { [key in string]: number },
+	/**
+	 * When usage was last updated successfully (RFC3339). Older than `usage`
+	 * only while a spike is held back.
+	 */
+	lastSuccessAt: string | null,
+	/**
+	 * False while scheduled fetching is off (see `PausedReason::stops_monitoring`).
+	 */
+	monitoringEnabled: boolean,
+	pausedReason: PausedReason | null,
 };
 
 export type UsageWindow = {
@@ -78,9 +844,23 @@ export type UsageWindow = {
 	label: string,
 	utilization: number,
 	resetsAt: string | null,
+	/**
+	 * `resets_at` as milliseconds since the Unix epoch, parsed once here.
+	 */
+	resetsAtEpochMs: number | null,
 	windowDurationSeconds: number | null,
 };
 
+/**
+ * User-Agent sent with provider requests.
+ */
+export type UserAgentMode = "app" | "browser";
+
+/**
+ * Where the window opens.
+ */
+export type WindowPlacement = "followTray" | "remember";
+
 export type WindowStats = {
 	key: string,
 	label: string,
@@ -99,3 +879,11 @@ async function typedError<T, E>(result: Promise<T>): Promise<{ status: "ok"; dat
     }
 }
 
+function makeEvent<T>(name: string) {
+    return {
+        listen: (cb: __TAURI_EVENT.EventCallback<T>) => __TAURI_EVENT.listen<T>(name, cb),
+        once: (cb: __TAURI_EVENT.EventCallback<T>) => __TAURI_EVENT.once<T>(name, cb),
+        emit: (payload: T) => __TAURI_EVENT.emit(name, payload),
+    };
+}
+
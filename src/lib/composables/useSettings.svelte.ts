@@ -115,8 +115,10 @@ export function useSettings(callbacks: SettingsCallbacks = {}) {
       "notification_settings",
     );
     const savedRetention = await store.get<number>("data_retention_days");
+    // Carries the interval from config.toml / env when a deployment sets one
+    const defaults = await commands.getDefaultSettings();
 
-    refreshIntervalMinutes = savedInterval ?? 5;
+    refreshIntervalMinutes = savedInterval ?? defaults.refresh_interval_minutes;
     autoRefreshEnabled = savedAutoRefresh ?? true;
     hourlyRefreshEnabled = savedHourlyRefresh ?? false;
     notificationSettings = normalizeNotificationSettings(savedNotificationSettings);
@@ -174,9 +176,9 @@ export function useSettings(callbacks: SettingsCallbacks = {}) {
     loading = true;
     error = null;
 
-    const result = await commands.saveCredentials(orgIdInput, tokenInput);
+    const result = await commands.saveCredentials(orgIdInput, tokenInput, null);
     if (result.status === "error") {
-      error = result.error;
+      error = result.error.message;
       loading = false;
       onError?.(error);
       return;
@@ -196,7 +198,7 @@ export function useSettings(callbacks: SettingsCallbacks = {}) {
 
     const result = await commands.saveOllamaCredentials(ollamaTokenInput);
     if (result.status === "error") {
-      error = result.error;
+      error = result.error.message;
       loading = false;
       onError?.(error);
       return;
@@ -212,7 +214,7 @@ export function useSettings(callbacks: SettingsCallbacks = {}) {
   async function logoutOllama() {
     const result = await commands.clearOllamaCredentials();
     if (result.status === "error") {
-      onError?.(result.error);
+      onError?.(result.error.message);
       return;
     }
 
@@ -228,7 +230,7 @@ export function useSettings(callbacks: SettingsCallbacks = {}) {
       await store.set("notification_settings", newSettings);
       const result = await commands.setNotificationSettings(newSettings);
       if (result.status === "error") {
-        throw new Error(result.error ?? "Failed to save notification settings");
+        throw new Error(result.error.message);
       }
       onSuccess?.("Notification settings saved");
     } catch (e) {
@@ -249,7 +251,7 @@ export function useSettings(callbacks: SettingsCallbacks = {}) {
       await store.set("refresh_interval_minutes", intervalMinutes);
       const result = await commands.setAutoRefresh(enabled, intervalMinutes);
       if (result.status === "error") {
-        throw new Error(result.error ?? "Failed to save settings");
+        throw new Error(result.error.message);
       }
       onSuccess?.("Settings saved");
     } catch (e) {
@@ -313,7 +315,7 @@ export function useSettings(callbacks: SettingsCallbacks = {}) {
   async function logout() {
     const result = await commands.clearCredentials();
     if (result.status === "error") {
-      onError?.(result.error);
+      onError?.(result.error.message);
       return;
     }
 
@@ -333,7 +335,7 @@ export function useSettings(callbacks: SettingsCallbacks = {}) {
     if (results.some((r) => r.status === "error")) {
       const errResult = results.find((r) => r.status === "error");
       if (errResult && errResult.status === "error") {
-        onError?.(errResult.error);
+        onError?.(errResult.error.message);
       }
       return;
     }
@@ -2,9 +2,10 @@
  * Usage data composable - manages usage data fetching, events, and countdown timer
  */
 
-import { listen, type UnlistenFn } from "@tauri-apps/api/event";
-import { commands } from "$lib/bindings.generated";
+import type { UnlistenFn } from "@tauri-apps/api/event";
+import { commands, events } from "$lib/bindings.generated";
 import type { UsageSnapshot } from "$lib/types";
+import { formatCountdown } from "$lib/utils";
 
 const RECOVERY_GRACE_MS = 15_000;
 const RECOVERY_STALE_MS = 5 * 60_000;
@@ -163,22 +164,19 @@ export function useUsageData(callbacks: UsageDataCallbacks) {
 
   async function setupEventListeners() {
     unlistenFns.push(
-      await listen<{ usage: UsageSnapshot; nextRefreshAt: number | null }>(
-        "usage-updated",
-        (event) => {
-          usageData = event.payload.usage;
-          lastUpdateAt = Date.now();
-          nextRefreshAt = event.payload.nextRefreshAt;
-          secondsSinceLastUpdate = 0;
-          updateTimers();
-          callbacks.setError(null);
-          callbacks.setLoading(false);
-        },
-      ),
+      await events.usageUpdated.listen((event) => {
+        usageData = event.payload.usage;
+        lastUpdateAt = Date.now();
+        nextRefreshAt = event.payload.nextRefreshAt;
+        secondsSinceLastUpdate = 0;
+        updateTimers();
+        callbacks.setError(null);
+        callbacks.setLoading(false);
+      }),
     );
 
     unlistenFns.push(
-      await listen<{ provider: string; error: string }>("usage-error", (event) => {
+      await events.usageError.listen((event) => {
         callbacks.setError(event.payload.error);
         callbacks.setLoading(false);
       }),
@@ -198,6 +196,13 @@ export function useUsageData(callbacks: UsageDataCallbacks) {
       if (result.status === "error") {
         throw new Error(result.error ?? "Failed to refresh");
       }
+      if (result.data.status === "rateLimited") {
+        // No usage event follows; the refresh loop fetches once the backoff ends.
+        callbacks.setError(
+          `Rate limited. Retrying in ${formatCountdown(result.data.retryInSeconds)}.`,
+        );
+        callbacks.setLoading(false);
+      }
     } catch (e) {
       console.error("Failed to trigger refresh:", e);
       callbacks.setError(e instanceof Error ? e.message : "Failed to refresh");
@@ -9,8 +9,13 @@ export function initHistoryStorage(): void {
   // Database is initialized by Rust backend.
 }
 
-export async function getUsageHistoryByRange(provider: ProviderKind, range: TimeRange) {
-  const result = await commands.getUsageHistoryByRange(provider, range);
+/** `metrics` limits the samples to those window keys; empty returns every window. */
+export async function getUsageHistoryByRange(
+  provider: ProviderKind,
+  range: TimeRange,
+  metrics: string[] = [],
+) {
+  const result = await commands.getUsageHistoryByRange(provider, range, metrics);
   if (result.status === "error") {
     throw new Error(result.error);
   }
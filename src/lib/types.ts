@@ -18,16 +18,6 @@ import type {
   UsageWindow,
 } from "./bindings.generated";
 
-export interface UsageUpdateEvent {
-  usage: import("./bindings.generated").UsageSnapshot;
-  nextRefreshAt: number | null;
-}
-
-export interface UsageErrorEvent {
-  provider: import("./bindings.generated").ProviderKind;
-  error: string;
-}
-
 export interface NotificationState {
   last_notified: Record<string, number>;
   fired_thresholds: string[];
@@ -113,6 +103,10 @@ export function getDefaultNotificationRule(): NotificationRule {
     thresholds: [80, 90],
     time_remaining_enabled: false,
     time_remaining_minutes: [30, 60],
+    reminder_enabled: false,
+    reminder_threshold: 90,
+    reminder_interval_minutes: 30,
+    recovery_enabled: false,
   };
 }
 